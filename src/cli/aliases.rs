@@ -0,0 +1,99 @@
+//! Shorthand aliases for provider CLIs
+//!
+//! Users often type abbreviations like `ic` for `ibmcloud`. Aliases are
+//! expanded before a command is validated or executed.
+
+use std::collections::HashMap;
+
+/// Built-in aliases as (alias, command) pairs
+const BUILTIN_ALIASES: &[(&str, &str)] = &[
+    ("ic", "ibmcloud"),
+    ("k", "kubectl"),
+    ("gc", "gcloud"),
+];
+
+/// Table mapping a leading alias to the full CLI name
+#[derive(Debug, Clone, Default)]
+pub struct CommandAliases {
+    aliases: HashMap<String, String>,
+}
+
+impl CommandAliases {
+    /// Create an empty alias table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create the table of built-in aliases
+    pub fn builtin() -> Self {
+        let mut aliases = Self::new();
+        for (alias, command) in BUILTIN_ALIASES {
+            aliases.add(alias, command);
+        }
+        aliases
+    }
+
+    /// Register an alias, replacing any existing one with the same name
+    pub fn add(&mut self, alias: &str, command: &str) {
+        self.aliases.insert(alias.to_string(), command.to_string());
+    }
+
+    /// Replace a leading alias with its full command
+    ///
+    /// Only the first word is expanded; commands that do not start with an
+    /// alias are returned unchanged.
+    pub fn expand(&self, command: &str) -> String {
+        let command = command.trim();
+        let (first, rest) = command.split_once(char::is_whitespace).unwrap_or((command, ""));
+
+        match self.aliases.get(first) {
+            Some(expanded) if rest.is_empty() => expanded.clone(),
+            Some(expanded) => format!("{} {}", expanded, rest.trim_start()),
+            None => command.to_string(),
+        }
+    }
+}
+
+/// Parse an `ALIAS=COMMAND` pair from the command line
+pub fn parse_alias(spec: &str) -> Result<(String, String), String> {
+    match spec.split_once('=') {
+        Some((alias, command)) if !alias.trim().is_empty() && !command.trim().is_empty() => {
+            Ok((alias.trim().to_string(), command.trim().to_string()))
+        }
+        _ => Err(format!("expected ALIAS=COMMAND, got '{}'", spec)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_aliases() {
+        let mut aliases = CommandAliases::builtin();
+        aliases.add("tf", "terraform");
+
+        assert_eq!(aliases.expand("ic resource groups"), "ibmcloud resource groups");
+        assert_eq!(aliases.expand("k get pods -n default"), "kubectl get pods -n default");
+        assert_eq!(aliases.expand("tf plan"), "terraform plan");
+        assert_eq!(aliases.expand("ic"), "ibmcloud");
+    }
+
+    #[test]
+    fn test_full_commands_are_untouched() {
+        let aliases = CommandAliases::builtin();
+
+        assert_eq!(aliases.expand("ibmcloud resource groups"), "ibmcloud resource groups");
+        assert_eq!(aliases.expand("aws s3 ls"), "aws s3 ls");
+        // Only the leading word is an alias
+        assert_eq!(aliases.expand("kubectl get k"), "kubectl get k");
+        assert_eq!(aliases.expand("icx list"), "icx list");
+    }
+
+    #[test]
+    fn test_parse_alias() {
+        assert_eq!(parse_alias("ic=ibmcloud"), Ok(("ic".to_string(), "ibmcloud".to_string())));
+        assert!(parse_alias("ic").is_err());
+        assert!(parse_alias("=ibmcloud").is_err());
+    }
+}
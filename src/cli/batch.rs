@@ -0,0 +1,195 @@
+//! Batch translation of queries read from a file or stdin
+
+use serde::Serialize;
+use std::io::Read;
+use crate::core::{CloudProviderType, LLMProvider, RAGEngine, Result};
+use super::CommandTranslator;
+use super::ui::{render_table, use_color};
+
+/// Output format for translations and command results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Human readable text, passed through unchanged
+    #[value(alias = "plain")]
+    Text,
+    /// Machine readable JSON
+    Json,
+    /// YAML
+    Yaml,
+    /// Boxed table for arrays of flat objects
+    Table,
+}
+
+/// Outcome of translating one query in a batch
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchResult {
+    pub query: String,
+    pub command: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<String>,
+}
+
+/// Read batch queries from a file, or from stdin when `path` is `-`
+///
+/// Blank lines and lines starting with `#` are skipped.
+pub fn read_batch_queries(path: &str) -> Result<Vec<String>> {
+    let content = if path == "-" {
+        let mut content = String::new();
+        std::io::stdin().read_to_string(&mut content)?;
+        content
+    } else {
+        std::fs::read_to_string(path)?
+    };
+
+    Ok(parse_batch_queries(&content))
+}
+
+/// Split batch input into queries
+fn parse_batch_queries(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Translate every query for `provider`, recording failures instead of stopping
+///
+/// Queries go through `translate_for_provider`, like a single `-c` query, so
+/// templates and provider-scoped documentation apply.
+pub async fn translate_batch<L: LLMProvider, R: RAGEngine>(
+    translator: &CommandTranslator<L, R>,
+    queries: &[String],
+    provider: CloudProviderType,
+) -> Vec<BatchResult> {
+    let mut results = Vec::with_capacity(queries.len());
+
+    for query in queries {
+        let result = match translator.translate_for_provider(query, provider).await {
+            Ok(command) => BatchResult {
+                query: query.clone(),
+                command: Some(command),
+                error: None,
+                error_code: None,
+            },
+            Err(e) => BatchResult {
+                query: query.clone(),
+                command: None,
+                error: Some(e.to_string()),
+                error_code: Some(e.error_code().to_string()),
+            },
+        };
+        results.push(result);
+    }
+
+    results
+}
+
+/// Print batch results in the requested format
+pub fn print_batch_results(results: &[BatchResult], format: OutputFormat) -> Result<()> {
+    print!("{}", format_batch_results(results, format, use_color())?);
+    Ok(())
+}
+
+/// Render batch results in the requested format, ending with a newline
+///
+/// Only table borders are ever colored, and only when `color` is set.
+fn format_batch_results(results: &[BatchResult], format: OutputFormat, color: bool) -> Result<String> {
+    match format {
+        OutputFormat::Json => {
+            let json = serde_json::to_string_pretty(results)
+                .map_err(|e| crate::core::Error::Serialization(e.to_string()))?;
+            Ok(format!("{}\n", json))
+        }
+        OutputFormat::Yaml => {
+            let yaml = serde_yaml::to_string(results)
+                .map_err(|e| crate::core::Error::Serialization(e.to_string()))?;
+            Ok(yaml)
+        }
+        OutputFormat::Table => {
+            let rows = serde_json::to_value(results)
+                .map_err(|e| crate::core::Error::Serialization(e.to_string()))?;
+            match rows.as_array().and_then(|rows| render_table(rows, color)) {
+                Some(table) => Ok(format!("{}\n", table)),
+                None => format_batch_results(results, OutputFormat::Text, color),
+            }
+        }
+        OutputFormat::Text => Ok(results
+            .iter()
+            .map(|result| match (&result.command, &result.error) {
+                (Some(command), _) => format!("{}\n", command),
+                (None, Some(error)) => format!("# error: {}\n", error),
+                (None, None) => "\n".to_string(),
+            })
+            .collect()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Error;
+    use crate::cli::test_support::{MockLLM, TestRag};
+
+    #[test]
+    fn test_parse_batch_queries() {
+        let queries = parse_batch_queries("list buckets\n\n# comment\n  show clusters  \n");
+        assert_eq!(queries, vec!["list buckets", "show clusters"]);
+    }
+
+    #[tokio::test]
+    async fn test_translate_batch_continues_past_failures() {
+        let llm = MockLLM::new(|prompt| {
+            if prompt.contains("gibberish") {
+                Err(Error::LLMProvider("cannot translate".to_string()))
+            } else {
+                Ok("aws s3 ls".to_string())
+            }
+        });
+        let translator: CommandTranslator<MockLLM, TestRag> = CommandTranslator::new(llm);
+
+        let queries = parse_batch_queries("list buckets\ngibberish\nlist my buckets\n");
+        let results = translate_batch(&translator, &queries, CloudProviderType::AWS).await;
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results.iter().filter(|r| r.error.is_some()).count(), 1);
+        assert!(results[1].command.is_none());
+        assert_eq!(results[1].error_code.as_deref(), Some("translation_failed"));
+        assert_eq!(results[0].command.as_deref(), Some("aws s3 ls"));
+    }
+
+    #[tokio::test]
+    async fn test_translate_batch_uses_the_given_provider() {
+        let translator: CommandTranslator<MockLLM, TestRag> = CommandTranslator::new(MockLLM::replying("aws s3 ls"));
+        let queries = parse_batch_queries("list buckets
+");
+
+        let results = translate_batch(&translator, &queries, CloudProviderType::GCP).await;
+        assert_eq!(results[0].command.as_deref(), Some("gcloud storage buckets list"));
+    }
+
+    #[tokio::test]
+    async fn test_uncolored_output_has_no_escape_sequences() {
+        let llm = MockLLM::new(|prompt| {
+            if prompt.contains("gibberish") {
+                Err(Error::LLMProvider("cannot translate".to_string()))
+            } else {
+                Ok("aws s3 ls".to_string())
+            }
+        });
+        let translator: CommandTranslator<MockLLM, TestRag> = CommandTranslator::new(llm);
+        let results = translate_batch(&translator, &parse_batch_queries("list buckets\ngibberish\n"), CloudProviderType::AWS).await;
+
+        let json = format_batch_results(&results, OutputFormat::Json, false).unwrap();
+        assert!(!json.contains('\x1b'), "{:?}", json);
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed[0]["command"], "aws s3 ls");
+
+        let table = format_batch_results(&results, OutputFormat::Table, false).unwrap();
+        assert!(table.contains("aws s3 ls"));
+        assert!(!table.contains('\x1b'), "{:?}", table);
+    }
+}
@@ -0,0 +1,171 @@
+//! Bounded capture of command output
+//!
+//! Output is read up to a byte limit and the rest is drained and counted, so
+//! a command that prints gigabytes cannot exhaust memory and cannot block on
+//! a full pipe either.
+
+use std::io::{self, Read};
+use std::process::{Child, ExitStatus};
+use std::thread;
+use super::CommandResult;
+
+/// Default limit on captured bytes per stream (10 MiB)
+pub const DEFAULT_MAX_CAPTURE_BYTES: usize = 10 * 1024 * 1024;
+
+/// Bytes read from one output stream
+#[derive(Debug, Default)]
+pub struct CapturedOutput {
+    bytes: Vec<u8>,
+    /// Bytes read past the limit and discarded
+    dropped: u64,
+}
+
+impl CapturedOutput {
+    /// Read `reader` to the end, keeping at most `limit` bytes
+    ///
+    /// When the limit splits a UTF-8 character, the partial character is
+    /// dropped too so truncation alone never makes text look binary.
+    pub fn read(mut reader: impl Read, limit: usize) -> io::Result<Self> {
+        let mut bytes = Vec::new();
+        (&mut reader).take(limit as u64).read_to_end(&mut bytes)?;
+        let mut dropped = io::copy(&mut reader, &mut io::sink())?;
+
+        if dropped > 0
+            && let Err(e) = std::str::from_utf8(&bytes)
+            && e.error_len().is_none()
+        {
+            dropped += (bytes.len() - e.valid_up_to()) as u64;
+            bytes.truncate(e.valid_up_to());
+        }
+
+        Ok(Self { bytes, dropped })
+    }
+
+    /// Whether output past the limit was discarded
+    pub fn is_truncated(&self) -> bool {
+        self.dropped > 0
+    }
+
+    /// Captured output as text, with a marker appended when it was truncated
+    ///
+    /// Invalid UTF-8 is replaced with U+FFFD; see [`CapturedOutput::into_raw`].
+    pub fn to_text(&self) -> String {
+        let mut text = self.text();
+        if let Some(marker) = self.marker() {
+            if !text.is_empty() && !text.ends_with('\n') {
+                text.push('\n');
+            }
+            text.push_str(&marker);
+        }
+        text
+    }
+
+    /// Captured output as text, without a truncation marker
+    fn text(&self) -> String {
+        String::from_utf8_lossy(&self.bytes).into_owned()
+    }
+
+    /// The line to show after the output when it was truncated
+    fn marker(&self) -> Option<String> {
+        self.is_truncated().then(|| truncation_marker(self.bytes.len(), self.dropped))
+    }
+
+    /// The captured bytes if they are not valid UTF-8, so nothing is lost
+    /// to lossy decoding
+    pub fn into_raw(self) -> Option<Vec<u8>> {
+        std::str::from_utf8(&self.bytes).is_err().then_some(self.bytes)
+    }
+}
+
+/// Line appended to truncated output
+fn truncation_marker(kept: usize, dropped: u64) -> String {
+    format!("[output truncated: showing the first {} bytes, {} more bytes omitted]\n", kept, dropped)
+}
+
+/// Wait for `child`, capturing at most `limit` bytes of its stdout and of its stderr
+///
+/// Stderr is read on a separate thread so neither pipe can fill up and
+/// stall the child.
+pub fn capture_child(mut child: Child, limit: usize) -> io::Result<(ExitStatus, CapturedOutput, CapturedOutput)> {
+    let stderr = child.stderr.take();
+    let stderr_reader = thread::spawn(move || match stderr {
+        Some(stderr) => CapturedOutput::read(stderr, limit),
+        None => Ok(CapturedOutput::default()),
+    });
+
+    let stdout = match child.stdout.take() {
+        Some(stdout) => CapturedOutput::read(stdout, limit)?,
+        None => CapturedOutput::default(),
+    };
+    let status = child.wait()?;
+    let stderr = stderr_reader
+        .join()
+        .map_err(|_| io::Error::other("stderr reader panicked"))??;
+
+    Ok((status, stdout, stderr))
+}
+
+/// Build a command result from captured streams
+///
+/// Stdout is kept without its truncation marker so it can still be parsed
+/// and repaired; the marker is only added when the output is displayed.
+pub fn command_result(success: bool, stdout: CapturedOutput, stderr: CapturedOutput) -> CommandResult {
+    CommandResult {
+        success,
+        stdout: stdout.text(),
+        stderr: stderr.to_text(),
+        truncation_marker: stdout.marker(),
+        raw_stdout: stdout.into_raw(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_within_limit_is_untouched() {
+        let captured = CapturedOutput::read("aws s3 ls\n".as_bytes(), 10).unwrap();
+        assert!(!captured.is_truncated());
+        assert_eq!(captured.to_text(), "aws s3 ls\n");
+        assert!(captured.into_raw().is_none());
+    }
+
+    #[test]
+    fn test_truncates_at_limit_with_marker() {
+        let output = "x".repeat(25);
+        let captured = CapturedOutput::read(output.as_bytes(), 10).unwrap();
+        assert!(captured.is_truncated());
+        assert_eq!(
+            captured.to_text(),
+            format!("{}\n[output truncated: showing the first 10 bytes, 15 more bytes omitted]\n", "x".repeat(10))
+        );
+    }
+
+    #[test]
+    fn test_result_keeps_marker_out_of_stdout() {
+        let captured = CapturedOutput::read("[{\"a\": 1}, {\"b\"".as_bytes(), 12).unwrap();
+        let result = command_result(true, captured, CapturedOutput::default());
+        assert_eq!(result.stdout, "[{\"a\": 1}, {");
+        assert_eq!(
+            result.truncation_marker.as_deref(),
+            Some("[output truncated: showing the first 12 bytes, 3 more bytes omitted]\n")
+        );
+    }
+
+    #[test]
+    fn test_truncation_does_not_split_characters() {
+        // "é" is two bytes, so a 3-byte limit would cut the second one in half
+        let captured = CapturedOutput::read("aéé".as_bytes(), 4).unwrap();
+        assert!(captured.to_text().starts_with("aé\n[output truncated: showing the first 3 bytes, 2 more"));
+        assert!(captured.into_raw().is_none());
+    }
+
+    #[test]
+    fn test_binary_output_keeps_raw_bytes() {
+        let bytes = [b'{', 0xff, 0xfe, b'}'];
+        let captured = CapturedOutput::read(&bytes[..], 1024).unwrap();
+        assert_eq!(captured.to_text(), "{\u{fffd}\u{fffd}}");
+        assert_eq!(captured.into_raw(), Some(bytes.to_vec()));
+    }
+}
@@ -0,0 +1,125 @@
+//! Per-provider command cheatsheets
+//!
+//! Built from what providers already know: `list_services` gives the
+//! sections and their descriptions, `get_command_patterns` the commands
+//! listed under each.
+
+use colored::*;
+use crate::core::CloudProvider;
+
+/// How a cheatsheet is printed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum CheatsheetFormat {
+    /// Terminal text
+    #[default]
+    Text,
+    /// Markdown, for pasting into wikis
+    Markdown,
+}
+
+/// One service and its example commands
+#[derive(Debug, Clone, PartialEq)]
+struct CheatsheetSection {
+    service: String,
+    description: String,
+    commands: Vec<String>,
+}
+
+/// Group the provider's command patterns by service
+///
+/// Sections follow `list_services`; patterns for services it does not list
+/// end up in a trailing "other" section.
+fn cheatsheet_sections(provider: &dyn CloudProvider) -> Vec<CheatsheetSection> {
+    let cli = provider.provider_type().cli_command();
+    let mut sections: Vec<CheatsheetSection> = provider
+        .list_services()
+        .into_iter()
+        .map(|(service, description)| CheatsheetSection { service, description, commands: Vec::new() })
+        .collect();
+    let mut other = Vec::new();
+
+    for pattern in provider.get_command_patterns() {
+        let mut tokens = pattern.split_whitespace();
+        let service = match (tokens.next(), tokens.next()) {
+            (Some(first), Some(service)) if first == cli => service,
+            _ => "",
+        };
+        match sections.iter_mut().find(|section| section.service == service) {
+            Some(section) => section.commands.push(pattern),
+            None => other.push(pattern),
+        }
+    }
+
+    if !other.is_empty() {
+        sections.push(CheatsheetSection {
+            service: "other".to_string(),
+            description: String::new(),
+            commands: other,
+        });
+    }
+    sections
+}
+
+/// Render the cheatsheet for one provider
+pub fn render_cheatsheet(provider: &dyn CloudProvider, format: CheatsheetFormat) -> String {
+    let title = format!("{} cheatsheet", provider.provider_type().display_name());
+    let mut out = String::new();
+
+    match format {
+        CheatsheetFormat::Markdown => {
+            out.push_str(&format!("# {}\n", title));
+            for section in cheatsheet_sections(provider) {
+                out.push_str(&format!("\n## {}\n\n", section.service));
+                if !section.description.is_empty() {
+                    out.push_str(&format!("{}\n\n", section.description));
+                }
+                for command in &section.commands {
+                    out.push_str(&format!("- `{}`\n", command));
+                }
+            }
+        }
+        CheatsheetFormat::Text => {
+            out.push_str(&format!("{}\n", title.bold()));
+            for section in cheatsheet_sections(provider) {
+                if section.description.is_empty() {
+                    out.push_str(&format!("\n{}\n", section.service.green().bold()));
+                } else {
+                    out.push_str(&format!("\n{} - {}\n", section.service.green().bold(), section.description));
+                }
+                for command in &section.commands {
+                    out.push_str(&format!("  {}\n", command));
+                }
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::GitHubProvider;
+
+    #[test]
+    fn test_sections_follow_services() {
+        let sections = cheatsheet_sections(&GitHubProvider::new());
+        let pr = sections.iter().find(|section| section.service == "pr").unwrap();
+        assert!(pr.description.contains("pull requests"));
+        assert_eq!(pr.commands, vec!["gh pr list"]);
+        // Services without a pattern are still listed
+        assert!(sections.iter().any(|section| section.service == "secret" && section.commands.is_empty()));
+        assert!(sections.iter().all(|section| section.service != "other"));
+    }
+
+    #[test]
+    fn test_markdown_cheatsheet() {
+        let markdown = render_cheatsheet(&GitHubProvider::new(), CheatsheetFormat::Markdown);
+        assert!(markdown.starts_with("# GitHub cheatsheet\n"), "{}", markdown);
+        for header in ["## pr\n", "## workflow\n", "## release\n"] {
+            assert!(markdown.contains(header), "{} missing", header);
+        }
+        assert!(markdown.contains("- `gh pr list`\n"));
+        assert!(markdown.contains("- `gh workflow list`\n"));
+        assert!(!markdown.contains('\x1b'));
+    }
+}
@@ -0,0 +1,283 @@
+//! Following IBM Code Engine build runs
+//!
+//! Build runs are polled with `ibmcloud ce buildrun get --output json` until
+//! they succeed, fail or a timeout passes, printing new log lines as they
+//! appear.
+
+use serde::Deserialize;
+use std::fmt;
+use std::time::{Duration, Instant};
+use crate::core::{run_command_with_retry, CLI_RETRIES, CLI_BACKOFF};
+use super::{CliError, CliResult};
+
+/// How long to follow a build run before giving up
+pub const DEFAULT_BUILD_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+
+/// Pause between status checks
+pub const BUILD_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Where a build run is in its lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildRunStatus {
+    Pending,
+    Running,
+    Succeeded,
+    Failed,
+    /// Still unfinished when polling stopped
+    TimedOut,
+}
+
+impl BuildRunStatus {
+    /// Whether the build run will not change status any more
+    pub fn is_finished(self) -> bool {
+        matches!(self, BuildRunStatus::Succeeded | BuildRunStatus::Failed)
+    }
+}
+
+impl fmt::Display for BuildRunStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self {
+            BuildRunStatus::Pending => "pending",
+            BuildRunStatus::Running => "running",
+            BuildRunStatus::Succeeded => "succeeded",
+            BuildRunStatus::Failed => "failed",
+            BuildRunStatus::TimedOut => "timed out",
+        };
+        f.write_str(status)
+    }
+}
+
+/// Shape of `ibmcloud ce buildrun get --output json`, as far as it is used
+#[derive(Debug, Deserialize)]
+struct BuildRunOutput {
+    #[serde(default)]
+    status: Option<BuildRunOutputStatus>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BuildRunOutputStatus {
+    #[serde(default)]
+    conditions: Vec<BuildRunCondition>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BuildRunCondition {
+    #[serde(rename = "type")]
+    kind: String,
+    status: String,
+    #[serde(default)]
+    reason: Option<String>,
+    #[serde(default)]
+    message: Option<String>,
+}
+
+/// Status of a build run at one point in time
+#[derive(Debug, Clone, PartialEq)]
+pub struct BuildRunState {
+    pub status: BuildRunStatus,
+    /// Why the build run failed, when it did
+    pub failure_reason: Option<String>,
+}
+
+impl BuildRunState {
+    /// Parse the output of `ibmcloud ce buildrun get --output json`
+    ///
+    /// The `Succeeded` condition decides the status: `True` succeeded,
+    /// `False` failed, and `Unknown` is still running unless its reason is
+    /// `Pending`. Without that condition the run has not started yet.
+    pub fn parse(json: &str) -> CliResult<Self> {
+        let output: BuildRunOutput = serde_json::from_str(json)
+            .map_err(|e| CliError::ExecutionFailed(format!("unexpected build run output: {}", e)))?;
+        let condition = output
+            .status
+            .into_iter()
+            .flat_map(|status| status.conditions)
+            .find(|condition| condition.kind == "Succeeded");
+
+        let Some(condition) = condition else {
+            return Ok(Self { status: BuildRunStatus::Pending, failure_reason: None });
+        };
+        let status = match condition.status.as_str() {
+            "True" => BuildRunStatus::Succeeded,
+            "False" => BuildRunStatus::Failed,
+            _ if condition.reason.as_deref() == Some("Pending") => BuildRunStatus::Pending,
+            _ => BuildRunStatus::Running,
+        };
+        let failure_reason = (status == BuildRunStatus::Failed).then(|| {
+            match (condition.reason, condition.message) {
+                (Some(reason), Some(message)) => format!("{}: {}", reason, message),
+                (reason, message) => reason.or(message).unwrap_or_else(|| "unknown".to_string()),
+            }
+        });
+        Ok(Self { status, failure_reason })
+    }
+}
+
+/// Final outcome of following a build run
+#[derive(Debug, Clone, PartialEq)]
+pub struct BuildRunResult {
+    pub name: String,
+    pub status: BuildRunStatus,
+    pub failure_reason: Option<String>,
+    /// Time spent following the build run
+    pub elapsed: Duration,
+}
+
+/// Source of build run status and logs
+pub trait BuildRuns {
+    /// JSON status of the build run
+    fn status(&self, name: &str) -> CliResult<String>;
+
+    /// Logs of the build run so far
+    fn logs(&self, name: &str) -> CliResult<String>;
+}
+
+/// Build runs read through the `ibmcloud ce` CLI
+pub struct CodeEngineCli;
+
+impl CodeEngineCli {
+    fn run(args: &[&str]) -> CliResult<String> {
+        let output = run_command_with_retry("ibmcloud", args, CLI_RETRIES, CLI_BACKOFF)?;
+        if !output.status.success() {
+            return Err(CliError::ExecutionFailed(String::from_utf8_lossy(&output.stderr).trim().to_string()));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+impl BuildRuns for CodeEngineCli {
+    fn status(&self, name: &str) -> CliResult<String> {
+        Self::run(&["ce", "buildrun", "get", "--name", name, "--output", "json"])
+    }
+
+    fn logs(&self, name: &str) -> CliResult<String> {
+        Self::run(&["ce", "buildrun", "logs", "--name", name])
+    }
+}
+
+/// Follow a build run until it finishes or `timeout` passes
+///
+/// Each new log line is passed to `on_log` once. Logs that cannot be read
+/// yet, as before the build pod starts, are skipped; a status that cannot
+/// be read is an error.
+pub async fn poll_build_status(
+    build_runs: &impl BuildRuns,
+    name: &str,
+    timeout: Duration,
+    interval: Duration,
+    mut on_log: impl FnMut(&str),
+) -> CliResult<BuildRunResult> {
+    let started = Instant::now();
+    let mut lines_seen = 0;
+
+    loop {
+        let state = BuildRunState::parse(&build_runs.status(name)?)?;
+        if let Ok(logs) = build_runs.logs(name) {
+            let lines: Vec<&str> = logs.lines().collect();
+            for line in lines.iter().skip(lines_seen) {
+                on_log(line);
+            }
+            lines_seen = lines_seen.max(lines.len());
+        }
+
+        let finished = state.status.is_finished();
+        if finished || started.elapsed() >= timeout {
+            return Ok(BuildRunResult {
+                name: name.to_string(),
+                status: if finished { state.status } else { BuildRunStatus::TimedOut },
+                failure_reason: state.failure_reason,
+                elapsed: started.elapsed(),
+            });
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    const RUNNING: &str = r#"{"metadata": {"name": "web-run-1"}, "status": {"conditions": [{"type": "Succeeded", "status": "Unknown", "reason": "Running"}]}}"#;
+    const SUCCEEDED: &str = r#"{"status": {"conditions": [{"type": "Succeeded", "status": "True", "reason": "Succeeded"}]}}"#;
+    const FAILED: &str = r#"{"status": {"conditions": [{"type": "Succeeded", "status": "False", "reason": "BuildRunTimeout", "message": "step-build exceeded 600s"}]}}"#;
+
+    /// Replays status outputs in order, repeating the last one, with growing logs
+    struct ScriptedBuildRuns {
+        statuses: Vec<&'static str>,
+        polls: Mutex<usize>,
+    }
+
+    impl ScriptedBuildRuns {
+        fn new(statuses: Vec<&'static str>) -> Self {
+            Self { statuses, polls: Mutex::new(0) }
+        }
+    }
+
+    impl BuildRuns for ScriptedBuildRuns {
+        fn status(&self, _name: &str) -> CliResult<String> {
+            let mut polls = self.polls.lock().unwrap();
+            let status = self.statuses[(*polls).min(self.statuses.len() - 1)];
+            *polls += 1;
+            Ok(status.to_string())
+        }
+
+        fn logs(&self, _name: &str) -> CliResult<String> {
+            let polls = *self.polls.lock().unwrap();
+            Ok((1..=polls).map(|step| format!("step {}\n", step)).collect())
+        }
+    }
+
+    #[test]
+    fn test_parse_status() {
+        assert_eq!(BuildRunState::parse(RUNNING).unwrap().status, BuildRunStatus::Running);
+        assert_eq!(BuildRunState::parse(SUCCEEDED).unwrap().status, BuildRunStatus::Succeeded);
+
+        let failed = BuildRunState::parse(FAILED).unwrap();
+        assert_eq!(failed.status, BuildRunStatus::Failed);
+        assert_eq!(failed.failure_reason.as_deref(), Some("BuildRunTimeout: step-build exceeded 600s"));
+
+        let pending = r#"{"status": {"conditions": [{"type": "Succeeded", "status": "Unknown", "reason": "Pending"}]}}"#;
+        assert_eq!(BuildRunState::parse(pending).unwrap().status, BuildRunStatus::Pending);
+        assert_eq!(BuildRunState::parse(r#"{"metadata": {}}"#).unwrap().status, BuildRunStatus::Pending);
+        assert!(BuildRunState::parse("Not found").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_poll_until_success_streams_each_log_line_once() {
+        let build_runs = ScriptedBuildRuns::new(vec![RUNNING, RUNNING, SUCCEEDED]);
+        let mut logged = Vec::new();
+        let result = poll_build_status(&build_runs, "web-run-1", Duration::from_secs(60), Duration::ZERO, |line| {
+            logged.push(line.to_string())
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(result.status, BuildRunStatus::Succeeded);
+        assert_eq!(result.failure_reason, None);
+        assert_eq!(logged, vec!["step 1", "step 2", "step 3"]);
+    }
+
+    #[tokio::test]
+    async fn test_poll_stops_on_failure() {
+        let build_runs = ScriptedBuildRuns::new(vec![RUNNING, FAILED, SUCCEEDED]);
+        let result = poll_build_status(&build_runs, "web-run-1", Duration::from_secs(60), Duration::ZERO, |_| {})
+            .await
+            .unwrap();
+
+        assert_eq!(result.status, BuildRunStatus::Failed);
+        assert!(result.failure_reason.unwrap().starts_with("BuildRunTimeout"));
+        assert_eq!(*build_runs.polls.lock().unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_poll_times_out() {
+        let build_runs = ScriptedBuildRuns::new(vec![RUNNING]);
+        let result = poll_build_status(&build_runs, "web-run-1", Duration::from_millis(20), Duration::from_millis(5), |_| {})
+            .await
+            .unwrap();
+
+        assert_eq!(result.status, BuildRunStatus::TimedOut);
+        assert!(*build_runs.polls.lock().unwrap() > 1);
+    }
+}
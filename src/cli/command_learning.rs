@@ -6,6 +6,7 @@ use std::path::Path;
 use tokio::fs;
 use chrono::Utc;
 use crate::core::{Error, Result, CommandLearning};
+use super::redact::{redact_secrets, REDACTED};
 
 /// Type of correction made by the user
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +18,22 @@ pub enum CorrectionType {
     Other,
 }
 
+/// Minimum word overlap for a learned command to be suggested
+const SUGGESTION_SIMILARITY: f32 = 0.5;
+
+/// Whether `command` would be changed by redaction, and so cannot be stored as is
+fn holds_secret(command: &str) -> bool {
+    redact_secrets(command) != command
+}
+
+/// Whether a learned command can be offered and run
+///
+/// Older versions stored commands with secrets masked, which would now run
+/// with a literal `[REDACTED]` argument.
+fn is_runnable(learning: &CommandLearning) -> bool {
+    !learning.correct_command.contains(REDACTED)
+}
+
 /// Command learning engine
 pub struct CommandLearningEngine {
     corrections: HashMap<String, CommandLearning>,
@@ -32,10 +49,10 @@ impl CommandLearningEngine {
         };
 
         // Try to load existing corrections
-        if Path::new(file_path).exists() {
-            if let Err(e) = engine.load_sync() {
-                eprintln!("Warning: Failed to load corrections: {}", e);
-            }
+        if Path::new(file_path).exists()
+            && let Err(e) = engine.load_sync()
+        {
+            eprintln!("Warning: Failed to load corrections: {}", e);
         }
 
         Ok(engine)
@@ -44,7 +61,7 @@ impl CommandLearningEngine {
     /// Load corrections synchronously (for initialization)
     fn load_sync(&mut self) -> Result<()> {
         let content = std::fs::read_to_string(&self.file_path)
-            .map_err(|e| Error::Io(e))?;
+            .map_err(Error::Io)?;
 
         let corrections: Vec<CommandLearning> = serde_json::from_str(&content)
             .map_err(|e| Error::Serialization(e.to_string()))?;
@@ -60,7 +77,7 @@ impl CommandLearningEngine {
     pub async fn load(&mut self) -> Result<()> {
         let content = fs::read_to_string(&self.file_path)
             .await
-            .map_err(|e| Error::Io(e))?;
+            .map_err(Error::Io)?;
 
         let corrections: Vec<CommandLearning> = serde_json::from_str(&content)
             .map_err(|e| Error::Serialization(e.to_string()))?;
@@ -80,23 +97,34 @@ impl CommandLearningEngine {
 
         fs::write(&self.file_path, json)
             .await
-            .map_err(|e| Error::Io(e))?;
+            .map_err(Error::Io)?;
 
         Ok(())
     }
 
     /// Add a correction
+    ///
+    /// A command holding a secret is not stored: it would have to be saved
+    /// redacted, and a redacted command no longer runs.
     pub async fn add_correction(
         &mut self,
         query: String,
         correct_command: String,
         error_pattern: Option<String>,
     ) -> Result<()> {
+        if holds_secret(&correct_command) {
+            return Ok(());
+        }
+
+        // Never persist secrets pasted into queries
+        let query = redact_secrets(&query);
         let learning = CommandLearning {
             query: query.clone(),
             correct_command,
-            error_pattern,
+            error_pattern: error_pattern.map(|pattern| redact_secrets(&pattern)),
             timestamp: Utc::now().timestamp(),
+            success_count: 0,
+            failure_count: 0,
         };
 
         self.corrections.insert(query, learning);
@@ -105,14 +133,142 @@ impl CommandLearningEngine {
         Ok(())
     }
 
+    /// Record a suggested command the user edited before running it
+    ///
+    /// Nothing is recorded when the edit left the command unchanged apart from
+    /// surrounding whitespace, or holds a secret. Returns whether a correction
+    /// was stored.
+    pub async fn record_edit(&mut self, query: &str, suggested: &str, edited: &str) -> Result<bool> {
+        let suggested = suggested.trim();
+        let edited = edited.trim();
+        if edited.is_empty() || edited == suggested || holds_secret(edited) {
+            return Ok(false);
+        }
+
+        self.add_correction(
+            query.to_string(),
+            edited.to_string(),
+            Some(format!("Edited suggestion: {}", suggested)),
+        ).await?;
+        Ok(true)
+    }
+
+    /// Record a translated command that ran successfully for a query
+    ///
+    /// Repeated successes of the same command raise its `success_count`; a
+    /// different command replaces the previous entry. The command is stored
+    /// exactly as it ran, so one holding a secret is not recorded.
+    pub async fn record_success(&mut self, query: &str, command: &str) -> Result<()> {
+        if holds_secret(command) {
+            return Ok(());
+        }
+        let query = redact_secrets(query);
+        let command = command.to_string();
+        let now = Utc::now().timestamp();
+
+        match self.corrections.get_mut(&query) {
+            Some(learning) if learning.correct_command == command => {
+                learning.success_count += 1;
+                learning.timestamp = now;
+            }
+            _ => {
+                self.corrections.insert(query.clone(), CommandLearning {
+                    query,
+                    correct_command: command,
+                    error_pattern: None,
+                    timestamp: now,
+                    success_count: 1,
+                    failure_count: 0,
+                });
+            }
+        }
+
+        self.save().await
+    }
+
+    /// Record that the learned command for a query failed when run
+    ///
+    /// Only counts against an entry whose command is `command`; returns
+    /// whether one was updated.
+    pub async fn record_failure(&mut self, query: &str, command: &str) -> Result<bool> {
+        match self.corrections.get_mut(&redact_secrets(query)) {
+            Some(learning) if learning.correct_command == command => {
+                learning.failure_count += 1;
+                self.save().await?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Remove the correction at `index` in `get_all_corrections` order
+    pub async fn remove_correction(&mut self, index: usize) -> Result<CommandLearning> {
+        let total = self.corrections.len();
+        let query = self
+            .get_all_corrections()
+            .get(index)
+            .map(|learning| learning.query.clone())
+            .ok_or_else(|| Error::InvalidInput(format!(
+                "no correction at index {} ({} stored)",
+                index, total
+            )))?;
+
+        let removed = self.corrections.remove(&query).expect("query was just listed");
+        self.save().await?;
+        Ok(removed)
+    }
+
+    /// Remove every correction whose success rate is below `min_success_rate`
+    ///
+    /// Corrections that never ran have no success rate and are kept.
+    /// Returns the number removed.
+    pub async fn prune(&mut self, min_success_rate: f32) -> Result<usize> {
+        let before = self.corrections.len();
+        self.corrections.retain(|_, learning| {
+            learning.success_rate().is_none_or(|rate| rate >= min_success_rate)
+        });
+
+        let removed = before - self.corrections.len();
+        if removed > 0 {
+            self.save().await?;
+        }
+        Ok(removed)
+    }
+
+    /// Suggest learned commands for a query, best match first
+    ///
+    /// Ties in similarity are broken by how often the command succeeded.
+    pub fn get_suggestions(&self, query: &str, limit: usize) -> Vec<&CommandLearning> {
+        let query_lower = redact_secrets(query).to_lowercase();
+        let mut results: Vec<(&CommandLearning, f32)> = self
+            .corrections
+            .values()
+            .filter(|learning| is_runnable(learning))
+            .map(|learning| {
+                let similarity = self.calculate_similarity(&query_lower, &learning.query.to_lowercase());
+                (learning, similarity)
+            })
+            .filter(|(_, score)| *score >= SUGGESTION_SIMILARITY)
+            .collect();
+
+        results.sort_by(|a, b| {
+            b.1.partial_cmp(&a.1)
+                .unwrap()
+                .then_with(|| b.0.success_count.cmp(&a.0.success_count))
+        });
+        results.into_iter().take(limit).map(|(learning, _)| learning).collect()
+    }
+
     /// Get a learned command for a query
     pub fn get_learned_command(&self, query: &str) -> Option<&CommandLearning> {
-        self.corrections.get(query)
+        self.corrections.get(&redact_secrets(query)).filter(|learning| is_runnable(learning))
     }
 
-    /// Get all corrections
+    /// Get all corrections, ordered by query so indices stay stable between runs
     pub fn get_all_corrections(&self) -> Vec<&CommandLearning> {
-        self.corrections.values().collect()
+        let mut corrections: Vec<&CommandLearning> = self.corrections.values().collect();
+        corrections.sort_by(|a, b| a.query.cmp(&b.query));
+        corrections
     }
 
     /// Find similar corrections based on query similarity
@@ -121,6 +277,7 @@ impl CommandLearningEngine {
         let mut results: Vec<(&CommandLearning, f32)> = self
             .corrections
             .values()
+            .filter(|learning| is_runnable(learning))
             .map(|learning| {
                 let similarity = self.calculate_similarity(&query_lower, &learning.query.to_lowercase());
                 (learning, similarity)
@@ -176,4 +333,189 @@ mod tests {
         let learned = engine.get_learned_command("list databases");
         assert!(learned.is_some());
     }
+
+    #[tokio::test]
+    async fn test_record_edit_stores_correction() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().to_str().unwrap();
+
+        let mut engine = CommandLearningEngine::new(path).unwrap();
+        let recorded = engine
+            .record_edit("list my vms", "az vm list", "az vm list --output table")
+            .await
+            .unwrap();
+
+        assert!(recorded);
+        let learned = engine.get_learned_command("list my vms").unwrap();
+        assert_eq!(learned.correct_command, "az vm list --output table");
+        assert_eq!(learned.error_pattern.as_deref(), Some("Edited suggestion: az vm list"));
+    }
+
+    #[tokio::test]
+    async fn test_record_edit_ignores_unchanged_command() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().to_str().unwrap();
+
+        let mut engine = CommandLearningEngine::new(path).unwrap();
+        let recorded = engine
+            .record_edit("list my vms", "az vm list", "  az vm list ")
+            .await
+            .unwrap();
+
+        assert!(!recorded);
+        assert!(engine.get_learned_command("list my vms").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_record_success_is_suggested() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().to_str().unwrap();
+
+        let mut engine = CommandLearningEngine::new(path).unwrap();
+        engine.record_success("list my buckets", "aws s3 ls").await.unwrap();
+        engine.record_success("list my buckets", "aws s3 ls").await.unwrap();
+
+        let learned = engine.get_learned_command("list my buckets").unwrap();
+        assert_eq!(learned.correct_command, "aws s3 ls");
+        assert_eq!(learned.success_count, 2);
+
+        let suggestions = engine.get_suggestions("list my buckets", 3);
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].correct_command, "aws s3 ls");
+
+        // Persisted successes survive a reload
+        let reloaded = CommandLearningEngine::new(path).unwrap();
+        assert_eq!(reloaded.get_suggestions("list my buckets", 3)[0].success_count, 2);
+    }
+
+    #[tokio::test]
+    async fn test_record_success_keeps_the_command_as_run() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().to_str().unwrap();
+
+        let mut engine = CommandLearningEngine::new(path).unwrap();
+        let copy = "aws s3 cp s3://MyBucket2024/reports/QuarterlyData/file.csv .";
+        engine.record_success("download the quarterly report", copy).await.unwrap();
+        assert_eq!(engine.get_learned_command("download the quarterly report").unwrap().correct_command, copy);
+
+        engine.record_success("log in to vcenter", "govc session.login --password hunter2").await.unwrap();
+        assert!(engine.get_learned_command("log in to vcenter").is_none());
+        assert!(!std::fs::read_to_string(path).unwrap().contains("hunter2"));
+    }
+
+    #[tokio::test]
+    async fn test_suggestions_prefer_successful_commands() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().to_str().unwrap();
+
+        let mut engine = CommandLearningEngine::new(path).unwrap();
+        engine
+            .add_correction("show clusters".to_string(), "ibmcloud ks clusters".to_string(), None)
+            .await
+            .unwrap();
+        engine.record_success("show clusters please", "ibmcloud ks cluster ls").await.unwrap();
+
+        let suggestions = engine.get_suggestions("show clusters", 2);
+        assert_eq!(suggestions.len(), 2);
+        assert_eq!(suggestions[0].correct_command, "ibmcloud ks cluster ls");
+        assert!(engine.get_suggestions("delete volumes", 2).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_add_correction_redacts_secrets() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().to_str().unwrap();
+
+        let mut engine = CommandLearningEngine::new(path).unwrap();
+
+        engine
+            .add_correction(
+                "login with password=hunter2".to_string(),
+                "govc session.login -u admin".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+        let learned = engine.get_learned_command("login with password=hunter2").unwrap();
+        assert!(!learned.query.contains("hunter2"));
+        assert_eq!(learned.correct_command, "govc session.login -u admin");
+
+        // A command holding a secret would only come back redacted, so it is not learned
+        engine
+            .add_correction(
+                "log in to vcenter".to_string(),
+                "govc session.login --password hunter2".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(engine.get_learned_command("log in to vcenter").is_none());
+        assert!(!std::fs::read_to_string(path).unwrap().contains("hunter2"));
+    }
+
+    #[test]
+    fn test_redacted_commands_are_never_offered() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let stored = serde_json::json!([{
+            "query": "log in to vcenter",
+            "correct_command": "govc session.login --password [REDACTED]",
+            "error_pattern": null,
+            "timestamp": 0,
+            "success_count": 3,
+            "failure_count": 0
+        }]);
+        std::fs::write(temp_file.path(), stored.to_string()).unwrap();
+
+        let engine = CommandLearningEngine::new(temp_file.path().to_str().unwrap()).unwrap();
+        assert_eq!(engine.get_all_corrections().len(), 1);
+        assert!(engine.get_learned_command("log in to vcenter").is_none());
+        assert!(engine.get_suggestions("log in to vcenter", 3).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_prune_removes_low_success_entries() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().to_str().unwrap();
+
+        let mut engine = CommandLearningEngine::new(path).unwrap();
+        engine.record_success("list buckets", "aws s3 ls").await.unwrap();
+        engine.record_success("list vms", "az vm lst").await.unwrap();
+        assert!(engine.record_failure("list vms", "az vm lst").await.unwrap());
+        assert!(engine.record_failure("list vms", "az vm lst").await.unwrap());
+        assert!(!engine.record_failure("list vms", "az vm list").await.unwrap());
+        engine
+            .add_correction("show groups".to_string(), "ibmcloud resource groups".to_string(), None)
+            .await
+            .unwrap();
+
+        assert_eq!(engine.prune(0.5).await.unwrap(), 1);
+        assert!(engine.get_learned_command("list vms").is_none());
+        assert!(engine.get_learned_command("list buckets").is_some());
+        // Never run, so no success rate to judge
+        assert!(engine.get_learned_command("show groups").is_some());
+
+        let reloaded = CommandLearningEngine::new(path).unwrap();
+        assert!(reloaded.get_learned_command("list vms").is_none());
+        assert_eq!(reloaded.get_all_corrections().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_remove_correction_by_index() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().to_str().unwrap();
+
+        let mut engine = CommandLearningEngine::new(path).unwrap();
+        engine.record_success("list vms", "az vm list").await.unwrap();
+        engine.record_success("list buckets", "aws s3 ls").await.unwrap();
+
+        // Ordered by query: "list buckets" comes first
+        let removed = engine.remove_correction(0).await.unwrap();
+        assert_eq!(removed.query, "list buckets");
+        assert!(engine.remove_correction(5).await.is_err());
+
+        let reloaded = CommandLearningEngine::new(path).unwrap();
+        let remaining = reloaded.get_all_corrections();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].query, "list vms");
+    }
 }
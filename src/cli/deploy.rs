@@ -0,0 +1,288 @@
+//! Deployment plans for "deploy my app" requests
+//!
+//! A [`DeploymentTarget`] turns a [`DeploymentSpec`] into the shell steps
+//! that build and deploy an app on one provider. Plans are shown in full and
+//! confirmed before they run; nothing here calls the cloud by itself.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use crate::core::{CloudProviderType, shell_quote};
+use super::{CliError, CliResult};
+use super::dockerfile::generate_dockerfile;
+
+/// Port the deployed app listens on unless told otherwise
+pub const DEFAULT_APP_PORT: u16 = 8080;
+
+/// Environment variable naming the IAM role App Runner uses to pull from ECR
+pub const AWS_DEPLOY_ROLE_ENV: &str = "AWS_DEPLOY_ROLE_ARN";
+
+/// What to deploy and where
+#[derive(Debug, Clone)]
+pub struct DeploymentSpec {
+    /// App name, lowercase letters, digits and dashes
+    pub name: String,
+    pub source_dir: PathBuf,
+    pub port: u16,
+    pub region: Option<String>,
+    /// IAM role for targets that need one to pull images
+    pub role_arn: Option<String>,
+}
+
+impl DeploymentSpec {
+    /// Spec for the app in `source_dir`, named after the directory
+    ///
+    /// The AWS region and role come from `AWS_REGION` (or
+    /// `AWS_DEFAULT_REGION`) and [`AWS_DEPLOY_ROLE_ENV`].
+    pub fn from_dir(source_dir: &Path) -> Self {
+        let dir_name = source_dir
+            .canonicalize()
+            .ok()
+            .and_then(|dir| dir.file_name().map(|name| name.to_string_lossy().into_owned()))
+            .unwrap_or_default();
+        Self {
+            name: app_name(&dir_name),
+            source_dir: source_dir.to_path_buf(),
+            port: DEFAULT_APP_PORT,
+            region: std::env::var("AWS_REGION").or_else(|_| std::env::var("AWS_DEFAULT_REGION")).ok(),
+            role_arn: std::env::var(AWS_DEPLOY_ROLE_ENV).ok(),
+        }
+    }
+}
+
+/// Turn a directory name into a name every target accepts
+fn app_name(dir_name: &str) -> String {
+    let name: String = dir_name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let name = name.split('-').filter(|part| !part.is_empty()).collect::<Vec<_>>().join("-");
+    if name.is_empty() { "app".to_string() } else { name }
+}
+
+/// Shell steps that deploy an app, run in order in one shell
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeploymentPlan {
+    /// Human-readable target, e.g. "IBM Code Engine"
+    pub target: &'static str,
+    pub steps: Vec<String>,
+}
+
+impl DeploymentPlan {
+    /// The steps as a script; shell variables set by one step are seen by the next
+    pub fn script(&self) -> String {
+        self.steps.join("\n")
+    }
+
+    /// Run the steps, stopping at the first failure
+    ///
+    /// Output streams straight to the terminal since builds can be long.
+    pub fn run(&self) -> CliResult<()> {
+        let status = Command::new("sh").arg("-ec").arg(self.script()).status()?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(CliError::ExecutionFailed(format!("deployment to {} failed ({})", self.target, status)))
+        }
+    }
+}
+
+/// A place apps can be deployed to
+pub trait DeploymentTarget {
+    /// Human-readable target name
+    fn name(&self) -> &'static str;
+
+    /// Provider whose CLI the plan uses
+    fn provider(&self) -> CloudProviderType;
+
+    /// Assemble the steps that deploy `spec`
+    fn plan(&self, spec: &DeploymentSpec) -> CliResult<DeploymentPlan>;
+}
+
+/// IBM Code Engine, building straight from source
+pub struct CodeEngineTarget;
+
+impl DeploymentTarget for CodeEngineTarget {
+    fn name(&self) -> &'static str {
+        "IBM Code Engine"
+    }
+
+    fn provider(&self) -> CloudProviderType {
+        CloudProviderType::IBMCloud
+    }
+
+    fn plan(&self, spec: &DeploymentSpec) -> CliResult<DeploymentPlan> {
+        Ok(DeploymentPlan {
+            target: self.name(),
+            steps: vec![format!(
+                "ibmcloud ce application create --name {} --build-source {} --port {}",
+                spec.name,
+                shell_quote(&spec.source_dir.to_string_lossy()),
+                spec.port
+            )],
+        })
+    }
+}
+
+/// AWS App Runner, from an image built locally and pushed to ECR
+pub struct AppRunnerTarget;
+
+impl DeploymentTarget for AppRunnerTarget {
+    fn name(&self) -> &'static str {
+        "AWS App Runner"
+    }
+
+    fn provider(&self) -> CloudProviderType {
+        CloudProviderType::AWS
+    }
+
+    fn plan(&self, spec: &DeploymentSpec) -> CliResult<DeploymentPlan> {
+        let region = spec
+            .region
+            .as_deref()
+            .ok_or_else(|| CliError::ValidationFailed("an AWS region is required; set AWS_REGION".to_string()))?;
+        let role_arn = spec.role_arn.as_deref().ok_or_else(|| {
+            CliError::ValidationFailed(format!("App Runner needs a role to pull from ECR; set {}", AWS_DEPLOY_ROLE_ENV))
+        })?;
+
+        let region = &shell_quote(region);
+        let name = &spec.name;
+        let image = format!("$REGISTRY/{}:latest", name);
+        let source_dir = shell_quote(&spec.source_dir.to_string_lossy());
+        // Projects without a Dockerfile get a generated one on stdin, leaving the source untouched
+        let build = if spec.source_dir.join("Dockerfile").is_file() {
+            format!("docker build -t {} {}", image, source_dir)
+        } else {
+            format!(
+                "docker build -t {} -f - {} <<'DOCKERFILE'\n{}DOCKERFILE",
+                image,
+                source_dir,
+                generate_dockerfile(&spec.source_dir, spec.port)
+            )
+        };
+        // The role is quoted outside the double-quoted JSON so the shell never expands it
+        let source_configuration = format!(
+            r#"{{\"ImageRepository\":{{\"ImageIdentifier\":\"{}\",\"ImageRepositoryType\":\"ECR\",\"ImageConfiguration\":{{\"Port\":\"{}\"}}}},\"AuthenticationConfiguration\":{{\"AccessRoleArn\":\""{}"\"}}}}"#,
+            image, spec.port, shell_quote(role_arn)
+        );
+
+        Ok(DeploymentPlan {
+            target: self.name(),
+            steps: vec![
+                "ACCOUNT=$(aws sts get-caller-identity --query Account --output text)".to_string(),
+                format!("REGISTRY=$ACCOUNT.dkr.ecr.{}.amazonaws.com", region),
+                format!(
+                    "aws ecr describe-repositories --repository-names {name} --region {region} >/dev/null 2>&1 || aws ecr create-repository --repository-name {name} --region {region}"
+                ),
+                format!("aws ecr get-login-password --region {} | docker login --username AWS --password-stdin $REGISTRY", region),
+                build,
+                format!("docker push {}", image),
+                format!(
+                    "aws apprunner create-service --service-name {} --region {} --source-configuration \"{}\"",
+                    name, region, source_configuration
+                ),
+            ],
+        })
+    }
+}
+
+/// Deployment target for a provider, if it has one
+pub fn deployment_target(provider: CloudProviderType) -> Option<Box<dyn DeploymentTarget>> {
+    match provider {
+        CloudProviderType::IBMCloud => Some(Box::new(CodeEngineTarget)),
+        CloudProviderType::AWS => Some(Box::new(AppRunnerTarget)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(source_dir: &Path) -> DeploymentSpec {
+        DeploymentSpec {
+            name: "web".to_string(),
+            source_dir: source_dir.to_path_buf(),
+            port: 3000,
+            region: Some("us-east-1".to_string()),
+            role_arn: Some("arn:aws:iam::123456789012:role/apprunner-ecr".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_target_follows_provider() {
+        assert_eq!(deployment_target(CloudProviderType::IBMCloud).unwrap().name(), "IBM Code Engine");
+        let aws = deployment_target(CloudProviderType::AWS).unwrap();
+        assert_eq!(aws.name(), "AWS App Runner");
+        assert_eq!(aws.provider(), CloudProviderType::AWS);
+        assert!(deployment_target(CloudProviderType::GCP).is_none());
+        assert!(deployment_target(CloudProviderType::VMware).is_none());
+    }
+
+    #[test]
+    fn test_code_engine_plan() {
+        let plan = CodeEngineTarget.plan(&spec(Path::new("./my app"))).unwrap();
+        assert_eq!(
+            plan.steps,
+            vec!["ibmcloud ce application create --name web --build-source './my app' --port 3000"]
+        );
+    }
+
+    #[test]
+    fn test_app_runner_plan() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("Dockerfile"), "FROM scratch\n").unwrap();
+        let plan = AppRunnerTarget.plan(&spec(dir.path())).unwrap();
+
+        assert_eq!(plan.steps[1], "REGISTRY=$ACCOUNT.dkr.ecr.us-east-1.amazonaws.com");
+        assert!(plan.steps[3].ends_with("docker login --username AWS --password-stdin $REGISTRY"));
+        assert_eq!(plan.steps[5], "docker push $REGISTRY/web:latest");
+        let create = &plan.steps[6];
+        assert!(create.starts_with("aws apprunner create-service --service-name web --region us-east-1 --source-configuration \""));
+        assert!(create.contains(r#"\"ImageIdentifier\":\"$REGISTRY/web:latest\""#));
+        assert!(create.contains(r#"\"Port\":\"3000\""#));
+        assert!(create.contains("role/apprunner-ecr"));
+    }
+
+    #[test]
+    fn test_app_runner_plan_quotes_region_and_role() {
+        let dir = tempfile::tempdir().unwrap();
+        let spec = DeploymentSpec {
+            region: Some("us-east-1; touch /tmp/x".to_string()),
+            role_arn: Some("arn:aws:iam::1:role/$(reboot)".to_string()),
+            ..spec(dir.path())
+        };
+        let plan = AppRunnerTarget.plan(&spec).unwrap();
+
+        assert_eq!(plan.steps[1], "REGISTRY=$ACCOUNT.dkr.ecr.'us-east-1; touch /tmp/x'.amazonaws.com");
+        assert!(plan.steps[2].contains("--region 'us-east-1; touch /tmp/x' "));
+        assert!(plan.steps[6].contains(r#"\"AccessRoleArn\":\""'arn:aws:iam::1:role/$(reboot)'"\""#), "{}", plan.steps[6]);
+    }
+
+    #[test]
+    fn test_app_runner_generates_missing_dockerfile() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("go.mod"), "module example.com/web\n").unwrap();
+        let plan = AppRunnerTarget.plan(&spec(dir.path())).unwrap();
+
+        let build = &plan.steps[4];
+        assert!(build.starts_with("docker build -t $REGISTRY/web:latest -f - "));
+        assert!(build.contains("<<'DOCKERFILE'\nFROM golang:1.22 AS build\n"));
+        assert!(build.ends_with("CMD [\"/server\"]\nDOCKERFILE"));
+    }
+
+    #[test]
+    fn test_app_runner_plan_needs_region_and_role() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = |spec: DeploymentSpec| matches!(AppRunnerTarget.plan(&spec), Err(CliError::ValidationFailed(_)));
+
+        assert!(missing(DeploymentSpec { region: None, ..spec(dir.path()) }));
+        assert!(missing(DeploymentSpec { role_arn: None, ..spec(dir.path()) }));
+    }
+
+    #[test]
+    fn test_app_name_from_directory() {
+        assert_eq!(app_name("My_Web App"), "my-web-app");
+        assert_eq!(app_name("--"), "app");
+    }
+}
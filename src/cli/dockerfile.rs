@@ -0,0 +1,152 @@
+//! Dockerfile generation for projects that do not ship one
+//!
+//! The project language is detected from marker files and a multi-stage
+//! Dockerfile is generated for it, falling back to Node.
+
+use std::path::Path;
+
+/// Language of a project, as far as building an image is concerned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectKind {
+    Node,
+    Python,
+    Go,
+    Rust,
+}
+
+/// Marker files, checked in order, and the language each one signals
+const PROJECT_MARKERS: &[(&str, ProjectKind)] = &[
+    ("requirements.txt", ProjectKind::Python),
+    ("pyproject.toml", ProjectKind::Python),
+    ("go.mod", ProjectKind::Go),
+    ("Cargo.toml", ProjectKind::Rust),
+    ("package.json", ProjectKind::Node),
+];
+
+impl ProjectKind {
+    /// Detect the language of the project in `dir`; Node when nothing matches
+    pub fn detect(dir: &Path) -> Self {
+        PROJECT_MARKERS
+            .iter()
+            .find(|(marker, _)| dir.join(marker).is_file())
+            .map(|(_, kind)| *kind)
+            .unwrap_or(ProjectKind::Node)
+    }
+}
+
+/// Generate a Dockerfile for the project in `dir`, serving on `port`
+pub fn generate_dockerfile(dir: &Path, port: u16) -> String {
+    match ProjectKind::detect(dir) {
+        ProjectKind::Node => format!(
+            "FROM node:20-slim AS build\n\
+             WORKDIR /app\n\
+             COPY . .\n\
+             RUN if [ -f package.json ]; then npm install --omit=dev; fi\n\
+             \n\
+             FROM node:20-slim\n\
+             WORKDIR /app\n\
+             COPY --from=build /app .\n\
+             ENV PORT={port}\n\
+             EXPOSE {port}\n\
+             CMD [\"npm\", \"start\"]\n"
+        ),
+        ProjectKind::Python => format!(
+            "FROM python:3.12-slim AS build\n\
+             WORKDIR /app\n\
+             COPY . .\n\
+             RUN if [ -f requirements.txt ]; then pip install --no-cache-dir --prefix=/install -r requirements.txt; \
+             else pip install --no-cache-dir --prefix=/install .; fi\n\
+             \n\
+             FROM python:3.12-slim\n\
+             WORKDIR /app\n\
+             COPY --from=build /install /usr/local\n\
+             COPY . .\n\
+             ENV PORT={port}\n\
+             EXPOSE {port}\n\
+             CMD [\"python\", \"main.py\"]\n"
+        ),
+        ProjectKind::Go => format!(
+            "FROM golang:1.22 AS build\n\
+             WORKDIR /src\n\
+             COPY . .\n\
+             RUN CGO_ENABLED=0 go build -o /server .\n\
+             \n\
+             FROM gcr.io/distroless/static-debian12\n\
+             COPY --from=build /server /server\n\
+             ENV PORT={port}\n\
+             EXPOSE {port}\n\
+             CMD [\"/server\"]\n"
+        ),
+        ProjectKind::Rust => format!(
+            "FROM rust:1-slim AS build\n\
+             WORKDIR /src\n\
+             COPY . .\n\
+             RUN cargo install --path . --root /out\n\
+             \n\
+             FROM debian:bookworm-slim\n\
+             COPY --from=build /out/bin/{binary} /usr/local/bin/server\n\
+             ENV PORT={port}\n\
+             EXPOSE {port}\n\
+             CMD [\"server\"]\n",
+            binary = cargo_package_name(dir).unwrap_or_else(|| "app".to_string())
+        ),
+    }
+}
+
+/// `name` from the `[package]` table of the project's Cargo.toml
+fn cargo_package_name(dir: &Path) -> Option<String> {
+    let manifest = std::fs::read_to_string(dir.join("Cargo.toml")).ok()?;
+    let package = manifest.split("[package]").nth(1)?;
+    package
+        .lines()
+        .take_while(|line| !line.trim_start().starts_with('['))
+        .find_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            (key.trim() == "name").then(|| value.trim().trim_matches('"').to_string())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(markers: &[(&str, &str)]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for (file, content) in markers {
+            std::fs::write(dir.path().join(file), content).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_marker_files_select_language() {
+        let cases = [
+            ("requirements.txt", ProjectKind::Python, "FROM python:3.12-slim AS build"),
+            ("pyproject.toml", ProjectKind::Python, "FROM python:3.12-slim AS build"),
+            ("go.mod", ProjectKind::Go, "FROM golang:1.22 AS build"),
+            ("Cargo.toml", ProjectKind::Rust, "FROM rust:1-slim AS build"),
+            ("package.json", ProjectKind::Node, "FROM node:20-slim AS build"),
+        ];
+        for (marker, kind, first_line) in cases {
+            let dir = project(&[(marker, "")]);
+            assert_eq!(ProjectKind::detect(dir.path()), kind, "{}", marker);
+
+            let dockerfile = generate_dockerfile(dir.path(), 8080);
+            assert!(dockerfile.starts_with(first_line), "{}", marker);
+            assert!(dockerfile.contains("EXPOSE 8080\n"));
+        }
+    }
+
+    #[test]
+    fn test_node_is_the_fallback() {
+        let dir = project(&[("index.html", "<html></html>")]);
+        assert_eq!(ProjectKind::detect(dir.path()), ProjectKind::Node);
+        assert!(generate_dockerfile(dir.path(), 3000).contains("CMD [\"npm\", \"start\"]"));
+    }
+
+    #[test]
+    fn test_rust_dockerfile_uses_package_name() {
+        let dir = project(&[("Cargo.toml", "[package]\nname = \"web-api\"\nversion = \"0.1.0\"\n\n[dependencies]\nname = \"not-this\"\n")]);
+        assert!(generate_dockerfile(dir.path(), 8080).contains("COPY --from=build /out/bin/web-api /usr/local/bin/server"));
+    }
+}
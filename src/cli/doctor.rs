@@ -0,0 +1,178 @@
+//! Environment diagnostics for the `doctor` subcommand
+
+use colored::*;
+use crate::core::CloudProvider;
+
+/// A single diagnostic check
+#[derive(Debug, Clone)]
+pub struct DoctorCheck {
+    /// Short name of the check
+    pub name: String,
+    /// Whether the check passed
+    pub passed: bool,
+    /// Whether a failure should make the report unhealthy
+    pub critical: bool,
+    /// Human readable detail
+    pub detail: String,
+}
+
+/// Aggregated result of all diagnostic checks
+#[derive(Debug, Clone)]
+pub struct DoctorReport {
+    pub checks: Vec<DoctorCheck>,
+}
+
+impl DoctorReport {
+    /// True when no critical check failed
+    pub fn is_healthy(&self) -> bool {
+        self.checks.iter().all(|check| check.passed || !check.critical)
+    }
+
+    /// Print the report as a checklist
+    pub fn print(&self) {
+        println!("{}", "AnyCLI doctor:".bold());
+        for check in &self.checks {
+            let marker = if check.passed {
+                "✅".to_string()
+            } else if check.critical {
+                "❌".to_string()
+            } else {
+                "⚠️ ".to_string()
+            };
+            println!("  {} {} - {}", marker, check.name, check.detail);
+        }
+    }
+}
+
+/// Run all diagnostic checks for the given provider
+///
+/// `llm_credentials_present` and `rag_document_count` are gathered by the
+/// caller so the aggregation can be tested without touching the environment.
+pub async fn run_doctor(
+    provider: &dyn CloudProvider,
+    llm_credentials_present: bool,
+    rag_document_count: Option<usize>,
+) -> DoctorReport {
+    let cli = provider.provider_type().cli_command();
+    let mut checks = Vec::new();
+
+    let installed = provider.is_cli_installed().await.unwrap_or(false);
+    checks.push(DoctorCheck {
+        name: "CLI installed".to_string(),
+        passed: installed,
+        critical: true,
+        detail: if installed {
+            format!("'{}' found on PATH", cli)
+        } else {
+            format!("'{}' not found on PATH", cli)
+        },
+    });
+
+    // Authentication can only be checked when the CLI exists
+    let authenticated = installed && provider.is_authenticated().await.unwrap_or(false);
+    checks.push(DoctorCheck {
+        name: "Authenticated".to_string(),
+        passed: authenticated,
+        critical: true,
+        detail: if authenticated {
+            format!("{} session is active", provider.provider_type())
+        } else {
+            format!("not logged in to {}", provider.provider_type())
+        },
+    });
+
+    checks.push(DoctorCheck {
+        name: "LLM credentials".to_string(),
+        passed: llm_credentials_present,
+        critical: true,
+        detail: if llm_credentials_present {
+            "WatsonX API key and project ID found".to_string()
+        } else {
+            "set WATSONX_API_KEY and WATSONX_PROJECT_ID".to_string()
+        },
+    });
+
+    let rag_ready = rag_document_count.is_some_and(|count| count > 0);
+    checks.push(DoctorCheck {
+        name: "RAG store".to_string(),
+        passed: rag_ready,
+        critical: false,
+        detail: match rag_document_count {
+            Some(count) => format!("{} documents indexed", count),
+            None => "RAG store failed to initialize".to_string(),
+        },
+    });
+
+    DoctorReport { checks }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use crate::core::{CloudProviderType, CommandSafety, Result};
+
+    struct MockProvider {
+        installed: bool,
+        authenticated: bool,
+    }
+
+    #[async_trait]
+    impl CloudProvider for MockProvider {
+        fn provider_type(&self) -> CloudProviderType {
+            CloudProviderType::AWS
+        }
+
+        async fn is_cli_installed(&self) -> Result<bool> {
+            Ok(self.installed)
+        }
+
+        async fn is_authenticated(&self) -> Result<bool> {
+            Ok(self.authenticated)
+        }
+
+        fn get_rag_context(&self) -> String {
+            String::new()
+        }
+
+        fn validate(&self, command: &str) -> Result<CommandSafety> {
+            Ok(CommandSafety::classify(command))
+        }
+
+        fn get_command_patterns(&self) -> Vec<String> {
+            Vec::new()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_doctor_all_checks_pass() {
+        let provider = MockProvider { installed: true, authenticated: true };
+        let report = run_doctor(&provider, true, Some(6)).await;
+        assert!(report.is_healthy());
+        assert!(report.checks.iter().all(|c| c.passed));
+    }
+
+    #[tokio::test]
+    async fn test_doctor_missing_cli_is_critical() {
+        let provider = MockProvider { installed: false, authenticated: true };
+        let report = run_doctor(&provider, true, Some(6)).await;
+        assert!(!report.is_healthy());
+        // Authentication is not attempted without the CLI
+        assert!(!report.checks[1].passed);
+    }
+
+    #[tokio::test]
+    async fn test_doctor_missing_credentials_is_critical() {
+        let provider = MockProvider { installed: true, authenticated: true };
+        let report = run_doctor(&provider, false, Some(6)).await;
+        assert!(!report.is_healthy());
+    }
+
+    #[tokio::test]
+    async fn test_doctor_empty_rag_is_warning_only() {
+        let provider = MockProvider { installed: true, authenticated: true };
+        let report = run_doctor(&provider, true, None).await;
+        assert!(report.is_healthy());
+        assert!(!report.checks[3].passed);
+    }
+}
@@ -0,0 +1,118 @@
+//! Structured error types for the CLI layer
+
+use thiserror::Error;
+use crate::core::{CloudProviderType, Error as CoreError};
+
+/// Result type alias for the translate and execute paths
+pub type CliResult<T> = std::result::Result<T, CliError>;
+
+/// Failures surfaced by the CLI that callers may want to handle programmatically
+#[derive(Error, Debug)]
+pub enum CliError {
+    #[error("Unknown cloud provider: {0}{hint}", hint = did_you_mean(.0))]
+    UnknownProvider(String),
+
+    #[error("Translation failed: {0}")]
+    TranslationFailed(String),
+
+    #[error("Command validation failed: {0}")]
+    ValidationFailed(String),
+
+    #[error("Not authenticated: {0}")]
+    NotAuthenticated(String),
+
+    #[error("Timed out: {0}")]
+    Timeout(String),
+
+    #[error("Execution failed: {0}")]
+    ExecutionFailed(String),
+}
+
+/// "; did you mean 'x'?" when `provider` looks like a typo of a known name
+fn did_you_mean(provider: &str) -> String {
+    CloudProviderType::suggest_name(provider)
+        .map(|name| format!("; did you mean '{}'?", name))
+        .unwrap_or_default()
+}
+
+impl CliError {
+    /// Stable machine-readable code for JSON output
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            CliError::UnknownProvider(_) => "unknown_provider",
+            CliError::TranslationFailed(_) => "translation_failed",
+            CliError::ValidationFailed(_) => "validation_failed",
+            CliError::NotAuthenticated(_) => "not_authenticated",
+            CliError::Timeout(_) => "timeout",
+            CliError::ExecutionFailed(_) => "execution_failed",
+        }
+    }
+}
+
+impl From<CoreError> for CliError {
+    fn from(err: CoreError) -> Self {
+        match err {
+            CoreError::Timeout(msg) => CliError::Timeout(msg),
+            CoreError::Authentication(msg) => CliError::NotAuthenticated(msg),
+            CoreError::InvalidInput(msg) => CliError::ValidationFailed(msg),
+            CoreError::Io(e) => CliError::ExecutionFailed(e.to_string()),
+            other => CliError::TranslationFailed(other.to_string()),
+        }
+    }
+}
+
+impl From<std::io::Error> for CliError {
+    fn from(err: std::io::Error) -> Self {
+        CliError::ExecutionFailed(err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_core_error_mapping() {
+        assert!(matches!(
+            CliError::from(CoreError::Timeout("slow".to_string())),
+            CliError::Timeout(_)
+        ));
+        assert!(matches!(
+            CliError::from(CoreError::Authentication("no token".to_string())),
+            CliError::NotAuthenticated(_)
+        ));
+        assert!(matches!(
+            CliError::from(CoreError::InvalidInput("bad".to_string())),
+            CliError::ValidationFailed(_)
+        ));
+        assert!(matches!(
+            CliError::from(CoreError::LLMProvider("boom".to_string())),
+            CliError::TranslationFailed(_)
+        ));
+    }
+
+    #[test]
+    fn test_io_error_is_execution_failure() {
+        let err = CliError::from(std::io::Error::new(std::io::ErrorKind::NotFound, "sh missing"));
+        assert!(matches!(err, CliError::ExecutionFailed(_)));
+        assert_eq!(err.error_code(), "execution_failed");
+    }
+
+    #[test]
+    fn test_error_codes_are_stable() {
+        assert_eq!(CliError::UnknownProvider("x".to_string()).error_code(), "unknown_provider");
+        assert_eq!(CliError::Timeout("x".to_string()).error_code(), "timeout");
+        assert_eq!(
+            CliError::UnknownProvider("oracle".to_string()).to_string(),
+            "Unknown cloud provider: oracle"
+        );
+    }
+
+    #[test]
+    fn test_unknown_provider_suggests_close_name() {
+        assert_eq!(
+            CliError::UnknownProvider("azre".to_string()).to_string(),
+            "Unknown cloud provider: azre; did you mean 'azure'?"
+        );
+    }
+}
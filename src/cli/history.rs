@@ -0,0 +1,185 @@
+//! Persistent history of executed commands
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tokio::fs;
+use chrono::{DateTime, Utc};
+use crate::core::{CloudProviderType, Error, Result};
+use super::redact_secrets;
+
+/// Number of records kept before the oldest are dropped
+pub const DEFAULT_HISTORY_LIMIT: usize = 1000;
+
+/// One executed command
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryRecord {
+    /// Unix timestamp of the execution
+    pub timestamp: i64,
+    pub provider: Option<CloudProviderType>,
+    /// What the user typed; the command itself for direct `exec`
+    pub query: String,
+    pub command: String,
+    pub success: bool,
+}
+
+impl HistoryRecord {
+    /// Record a command executed just now
+    pub fn now(provider: Option<CloudProviderType>, query: &str, command: &str, success: bool) -> Self {
+        Self {
+            timestamp: Utc::now().timestamp(),
+            provider,
+            query: query.to_string(),
+            command: command.to_string(),
+            success,
+        }
+    }
+}
+
+/// History records persisted as a JSON array, oldest first
+pub struct CommandHistory {
+    records: Vec<HistoryRecord>,
+    file_path: String,
+    limit: usize,
+}
+
+impl CommandHistory {
+    /// Open the history stored at `file_path`, if any
+    pub fn new(file_path: &str) -> Result<Self> {
+        let mut history = Self {
+            records: Vec::new(),
+            file_path: file_path.to_string(),
+            limit: DEFAULT_HISTORY_LIMIT,
+        };
+
+        if Path::new(file_path).exists()
+            && let Err(e) = history.load_sync()
+        {
+            eprintln!("Warning: Failed to load history: {}", e);
+        }
+
+        Ok(history)
+    }
+
+    /// Keep at most `limit` records, dropping the oldest first
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self.trim();
+        self
+    }
+
+    fn load_sync(&mut self) -> Result<()> {
+        let content = std::fs::read_to_string(&self.file_path)
+            .map_err(Error::Io)?;
+
+        self.records = serde_json::from_str(&content)
+            .map_err(|e| Error::Serialization(e.to_string()))?;
+        self.trim();
+        Ok(())
+    }
+
+    /// Save the history to file
+    pub async fn save(&self) -> Result<()> {
+        let json = serde_json::to_string_pretty(&self.records)
+            .map_err(|e| Error::Serialization(e.to_string()))?;
+
+        fs::write(&self.file_path, json)
+            .await
+            .map_err(Error::Io)?;
+
+        Ok(())
+    }
+
+    /// Append a record and save, redacting secrets first
+    pub async fn record(&mut self, mut record: HistoryRecord) -> Result<()> {
+        record.query = redact_secrets(&record.query);
+        record.command = redact_secrets(&record.command);
+        self.records.push(record);
+        self.trim();
+        self.save().await
+    }
+
+    /// All records, oldest first
+    pub fn records(&self) -> &[HistoryRecord] {
+        &self.records
+    }
+
+    /// The most recent `count` records, oldest first
+    pub fn recent(&self, count: usize) -> &[HistoryRecord] {
+        &self.records[self.records.len().saturating_sub(count)..]
+    }
+
+    /// Executed commands one per line, oldest first, for shell reuse
+    pub fn export_plain(&self) -> String {
+        self.records
+            .iter()
+            .map(|record| record.command.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn trim(&mut self) {
+        if self.records.len() > self.limit {
+            let excess = self.records.len() - self.limit;
+            self.records.drain(..excess);
+        }
+    }
+}
+
+/// Format a record's timestamp for display in local time
+pub fn format_timestamp(timestamp: i64) -> String {
+    DateTime::<Utc>::from_timestamp(timestamp, 0)
+        .map(|time| time.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|| timestamp.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(command: &str, timestamp: i64) -> HistoryRecord {
+        HistoryRecord {
+            timestamp,
+            provider: Some(CloudProviderType::AWS),
+            query: format!("query for {}", command),
+            command: command.to_string(),
+            success: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_records_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.json");
+        let path = path.to_str().unwrap();
+
+        let mut history = CommandHistory::new(path).unwrap();
+        history.record(record("aws s3 ls", 100)).await.unwrap();
+        history.record(HistoryRecord {
+            success: false,
+            provider: None,
+            ..record("ibmcloud login --apikey=abc123", 200)
+        }).await.unwrap();
+
+        let reloaded = CommandHistory::new(path).unwrap();
+        assert_eq!(reloaded.records(), history.records());
+        assert_eq!(reloaded.records()[0].provider, Some(CloudProviderType::AWS));
+        assert!(!reloaded.records()[1].success);
+        assert!(!reloaded.records()[1].command.contains("abc123"));
+        assert_eq!(reloaded.export_plain().lines().next(), Some("aws s3 ls"));
+    }
+
+    #[tokio::test]
+    async fn test_limit_drops_oldest_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.json");
+
+        let mut history = CommandHistory::new(path.to_str().unwrap()).unwrap().with_limit(2);
+        for (i, command) in ["az vm list", "az group list", "az aks list"].iter().enumerate() {
+            history.record(record(command, i as i64)).await.unwrap();
+        }
+
+        assert_eq!(history.export_plain(), "az group list\naz aks list");
+        assert_eq!(history.recent(1)[0].command, "az aks list");
+        assert_eq!(history.recent(10).len(), 2);
+    }
+}
@@ -0,0 +1,321 @@
+//! Indexing of user-supplied documentation for the `index` subcommand
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use crate::core::{Document, DocumentIndexer, Error, IndexingResult, Result};
+use super::{CliError, CliResult, Progress};
+
+/// File extensions picked up when indexing a directory
+const INDEXABLE_EXTENSIONS: &[&str] = &["md", "txt", "rst", "html", "htm"];
+
+/// Where the documentation to index comes from
+#[derive(Debug, Clone, PartialEq)]
+pub enum IndexSource {
+    /// A web page
+    Url(String),
+    /// A local file or directory
+    Path(PathBuf),
+    /// Raw text
+    Text(String),
+}
+
+impl IndexSource {
+    /// Build a source from CLI arguments, requiring exactly one of them
+    pub fn from_args(url: Option<String>, file: Option<PathBuf>, text: Option<String>) -> CliResult<Self> {
+        match (url, file, text) {
+            (Some(url), None, None) => Ok(IndexSource::Url(url)),
+            (None, Some(path), None) => Ok(IndexSource::Path(path)),
+            (None, None, Some(text)) if !text.trim().is_empty() => Ok(IndexSource::Text(text)),
+            (None, None, Some(_)) => Err(CliError::ValidationFailed(
+                "--text must not be empty".to_string(),
+            )),
+            (None, None, None) => Err(CliError::ValidationFailed(
+                "one of --url, --file or --text is required".to_string(),
+            )),
+            _ => Err(CliError::ValidationFailed(
+                "only one of --url, --file or --text may be given".to_string(),
+            )),
+        }
+    }
+}
+
+/// Files of a directory run that finished indexing, persisted so an
+/// interrupted run can resume
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct IndexCheckpoint {
+    completed: BTreeSet<String>,
+    #[serde(skip)]
+    file_path: PathBuf,
+}
+
+impl IndexCheckpoint {
+    /// Load the checkpoint at `file_path`, or start an empty one
+    pub fn load(file_path: &Path) -> Result<Self> {
+        let mut checkpoint = if file_path.exists() {
+            let content = std::fs::read_to_string(file_path)?;
+            serde_json::from_str(&content).map_err(|e| Error::Serialization(e.to_string()))?
+        } else {
+            Self::default()
+        };
+        checkpoint.file_path = file_path.to_path_buf();
+        Ok(checkpoint)
+    }
+
+    /// Number of sources already completed
+    pub fn completed_count(&self) -> usize {
+        self.completed.len()
+    }
+
+    pub fn is_completed(&self, source: &str) -> bool {
+        self.completed.contains(source)
+    }
+
+    /// Record a completed source and save the checkpoint
+    pub fn mark_completed(&mut self, source: &str) -> Result<()> {
+        self.completed.insert(source.to_string());
+        let json = serde_json::to_string_pretty(self).map_err(|e| Error::Serialization(e.to_string()))?;
+        std::fs::write(&self.file_path, json)?;
+        Ok(())
+    }
+
+    /// Forget all progress and delete the checkpoint file
+    pub fn clear(&mut self) -> Result<()> {
+        self.completed.clear();
+        if self.file_path.exists() {
+            std::fs::remove_file(&self.file_path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checkpoint state threaded through a resumable directory run
+struct Resume<'a> {
+    checkpoint: &'a mut IndexCheckpoint,
+    persist: &'a dyn Fn() -> Result<()>,
+}
+
+/// Index a source into the store behind `indexer`
+pub async fn index_source<D: DocumentIndexer>(indexer: &D, source: &IndexSource) -> Result<IndexingResult> {
+    index_source_inner(indexer, source, None).await
+}
+
+/// Index a source, skipping directory files that `checkpoint` lists as done
+///
+/// After each file is indexed, `persist` saves the store and the file is
+/// then added to the checkpoint, so a checkpointed file is never missing
+/// from the saved store. Sources other than directories are indexed as usual.
+pub async fn index_source_with_checkpoint<D: DocumentIndexer>(
+    indexer: &D,
+    source: &IndexSource,
+    checkpoint: &mut IndexCheckpoint,
+    persist: impl Fn() -> Result<()>,
+) -> Result<IndexingResult> {
+    let resume = Resume { checkpoint, persist: &persist };
+    index_source_inner(indexer, source, Some(resume)).await
+}
+
+async fn index_source_inner<D: DocumentIndexer>(
+    indexer: &D,
+    source: &IndexSource,
+    mut resume: Option<Resume<'_>>,
+) -> Result<IndexingResult> {
+    match source {
+        IndexSource::Url(url) => {
+            let _spinner = Progress::spinner(&format!("Fetching {}", url));
+            indexer.index_from_url(url).await
+        }
+        IndexSource::Text(text) => {
+            let document = Document {
+                id: format!("custom_{:x}", md5::compute(text.as_bytes())),
+                title: "Custom knowledge".to_string(),
+                content: text.clone(),
+                url: None,
+                metadata: json!({
+                    "source": "custom",
+                    "type": "documentation",
+                }),
+            };
+            indexer.index_document(document).await
+        }
+        IndexSource::Path(path) if path.is_dir() => {
+            let mut result = IndexingResult {
+                documents_indexed: 0,
+                documents_failed: 0,
+                errors: Vec::new(),
+            };
+
+            let mut files: Vec<String> = collect_indexable_files(path)?
+                .iter()
+                .map(|file| file.to_string_lossy().to_string())
+                .collect();
+            if let Some(ref resume) = resume {
+                files.retain(|file| !resume.checkpoint.is_completed(file));
+            }
+            let progress = Progress::bar(files.len() as u64, "Indexing");
+
+            for file in files {
+                progress.set_message(&file);
+                let outcome = indexer.index_from_file(&file).await;
+                progress.inc();
+
+                match outcome {
+                    Ok(file_result) => {
+                        let complete = file_result.documents_failed == 0;
+                        result.documents_indexed += file_result.documents_indexed;
+                        result.documents_failed += file_result.documents_failed;
+                        result.errors.extend(file_result.errors);
+
+                        if let Some(ref mut resume) = resume
+                            && complete
+                        {
+                            (resume.persist)()?;
+                            resume.checkpoint.mark_completed(&file)?;
+                        }
+                    }
+                    Err(e) => {
+                        result.documents_failed += 1;
+                        result.errors.push(format!("Failed to index {}: {}", file, e));
+                    }
+                }
+            }
+
+            progress.finish();
+            Ok(result)
+        }
+        IndexSource::Path(path) => indexer.index_from_file(&path.to_string_lossy()).await,
+    }
+}
+
+/// Recursively collect documentation files under a directory
+fn collect_indexable_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+
+    while let Some(current) = pending.pop() {
+        for entry in std::fs::read_dir(&current)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| INDEXABLE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+            {
+                files.push(path);
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::VectorStore;
+    use crate::rag::{LocalDocumentIndexer, LocalVectorStore};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_from_args_requires_exactly_one_source() {
+        assert!(matches!(
+            IndexSource::from_args(None, None, None),
+            Err(CliError::ValidationFailed(_))
+        ));
+        assert!(matches!(
+            IndexSource::from_args(Some("https://example.com".to_string()), None, Some("text".to_string())),
+            Err(CliError::ValidationFailed(_))
+        ));
+        assert!(matches!(
+            IndexSource::from_args(None, None, Some("   ".to_string())),
+            Err(CliError::ValidationFailed(_))
+        ));
+        assert_eq!(
+            IndexSource::from_args(None, Some(PathBuf::from("docs")), None).unwrap(),
+            IndexSource::Path(PathBuf::from("docs"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_index_text_increases_doc_count() {
+        let mut store = LocalVectorStore::new();
+        store.connect().await.unwrap();
+        let store = Arc::new(store);
+        let indexer = LocalDocumentIndexer::new(store.clone());
+
+        let before = store.count().await.unwrap();
+        let source = IndexSource::Text("Use 'aws s3 sync' to mirror a directory to a bucket".to_string());
+        let result = index_source(&indexer, &source).await.unwrap();
+
+        assert!(result.documents_indexed > 0);
+        assert_eq!(store.count().await.unwrap(), before + result.documents_indexed);
+    }
+
+    #[tokio::test]
+    async fn test_index_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("guide.md"), "govc ls lists inventory").unwrap();
+        std::fs::write(dir.path().join("image.png"), "not text").unwrap();
+
+        let mut store = LocalVectorStore::new();
+        store.connect().await.unwrap();
+        let store = Arc::new(store);
+        let indexer = LocalDocumentIndexer::new(store.clone());
+
+        let source = IndexSource::Path(dir.path().to_path_buf());
+        let result = index_source(&indexer, &source).await.unwrap();
+        assert_eq!(result.documents_indexed, 1);
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_skips_completed_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let docs = dir.path().join("docs");
+        std::fs::create_dir(&docs).unwrap();
+        let files: Vec<String> = ["a.md", "b.md", "c.md", "d.md"]
+            .iter()
+            .map(|name| {
+                let file = docs.join(name);
+                std::fs::write(&file, format!("notes from {}", name)).unwrap();
+                file.to_string_lossy().to_string()
+            })
+            .collect();
+
+        // An earlier run finished the first three files before being interrupted
+        let checkpoint_path = dir.path().join("checkpoint.json");
+        let mut checkpoint = IndexCheckpoint::load(&checkpoint_path).unwrap();
+        for file in &files[..3] {
+            checkpoint.mark_completed(file).unwrap();
+        }
+
+        let mut store = LocalVectorStore::new();
+        store.connect().await.unwrap();
+        let store = Arc::new(store);
+        let indexer = LocalDocumentIndexer::new(store.clone());
+
+        let mut checkpoint = IndexCheckpoint::load(&checkpoint_path).unwrap();
+        assert_eq!(checkpoint.completed_count(), 3);
+        let persisted = std::cell::Cell::new(0);
+        let source = IndexSource::Path(docs.clone());
+        let result = index_source_with_checkpoint(&indexer, &source, &mut checkpoint, || {
+            persisted.set(persisted.get() + 1);
+            Ok(())
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(result.documents_indexed, 1);
+        assert_eq!(persisted.get(), 1);
+        assert_eq!(store.count().await.unwrap(), 1);
+        assert!(IndexCheckpoint::load(&checkpoint_path).unwrap().is_completed(&files[3]));
+
+        // Clearing, as `--force` does, indexes everything again
+        checkpoint.clear().unwrap();
+        assert!(!checkpoint_path.exists());
+        let result = index_source_with_checkpoint(&indexer, &source, &mut checkpoint, || Ok(())).await.unwrap();
+        assert_eq!(result.documents_indexed, 4);
+    }
+}
@@ -0,0 +1,132 @@
+//! Coarse classification of what a query asks for
+//!
+//! Unlike template intents, which only recognise short imperative queries,
+//! every query gets an intent here so prompts and warnings can adapt.
+
+/// Minimum confidence before an intent changes the prompt or the UI
+pub const INTENT_MIN_CONFIDENCE: f32 = 0.5;
+
+/// Kind of request a query makes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Intent {
+    List,
+    Create,
+    Delete,
+    Describe,
+    Deploy,
+    Auth,
+    Help,
+    Unknown,
+}
+
+/// Words that signal each intent
+const INTENT_KEYWORDS: &[(Intent, &[&str])] = &[
+    (Intent::Delete, &["delete", "remove", "destroy", "terminate", "drop", "purge", "deprovision", "uninstall", "kill"]),
+    (Intent::Deploy, &["deploy", "redeploy", "rollout"]),
+    (Intent::Create, &["create", "make", "provision", "launch", "add", "new", "spin", "start", "run"]),
+    (Intent::Describe, &["describe", "inspect", "details", "detail", "info", "status"]),
+    (Intent::List, &["list", "show", "get", "display", "find", "which", "enumerate", "view"]),
+    (Intent::Auth, &["login", "log", "logout", "authenticate", "auth", "sign", "signin", "sso", "credentials"]),
+    (Intent::Help, &["help", "usage", "explain"]),
+];
+
+impl Intent {
+    fn from_word(word: &str) -> Option<Self> {
+        INTENT_KEYWORDS
+            .iter()
+            .find(|(_, keywords)| keywords.contains(&word))
+            .map(|(intent, _)| *intent)
+    }
+
+    /// Extra instruction for the translation prompt, if the intent needs one
+    pub fn prompt_hint(self) -> Option<&'static str> {
+        match self {
+            Intent::Delete => Some("The user wants to delete resources. Do not add flags that skip confirmation prompts."),
+            Intent::Create => Some("The user wants to create resources. Use the names and regions given in the query."),
+            Intent::Deploy => Some("The user wants to deploy a workload. Prefer the provider's deploy command over creating resources one by one."),
+            Intent::Auth => Some("The user wants to log in or manage credentials. Prefer interactive or SSO login over secrets on the command line."),
+            Intent::List | Intent::Describe | Intent::Help | Intent::Unknown => None,
+        }
+    }
+}
+
+/// Classifies queries by keyword and verb heuristics
+pub struct IntentDetector;
+
+impl IntentDetector {
+    /// Classify a query, returning the intent and a confidence from 0.0 to 1.0
+    ///
+    /// A keyword leading the query is a strong signal; one later in the
+    /// query is weaker, and keywords for other intents lower confidence
+    /// further. `Help` only wins when nothing else matches, since "help me
+    /// delete ..." is a delete request.
+    pub fn classify(query: &str) -> (Intent, f32) {
+        let query = query.to_lowercase();
+        let matches: Vec<(usize, Intent)> = query
+            .split(|c: char| !c.is_alphanumeric() && c != '-')
+            .filter(|word| !word.is_empty())
+            .enumerate()
+            .filter_map(|(position, word)| Intent::from_word(word).map(|intent| (position, intent)))
+            .collect();
+
+        let Some(&(position, intent)) = matches
+            .iter()
+            .find(|(_, intent)| *intent != Intent::Help)
+            .or_else(|| matches.first())
+        else {
+            return (Intent::Unknown, 0.0);
+        };
+
+        let mut confidence: f32 = if position == 0 { 0.9 } else { 0.6 };
+        if matches.iter().any(|(_, other)| *other != intent && *other != Intent::Help) {
+            confidence -= 0.2;
+        }
+        (intent, confidence)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_each_intent() {
+        let cases = [
+            ("list all ec2 instances", Intent::List),
+            ("show my resource groups", Intent::List),
+            ("create a bucket named logs in us-east-1", Intent::Create),
+            ("spin up a vm in westeurope", Intent::Create),
+            ("delete the staging cluster", Intent::Delete),
+            ("terminate instance i-0abc", Intent::Delete),
+            ("describe the prod database", Intent::Describe),
+            ("deploy my app to code engine", Intent::Deploy),
+            ("publish a release for v1.2", Intent::Unknown),
+            ("log in with sso", Intent::Auth),
+            ("help", Intent::Help),
+            ("frobnicate the widgets", Intent::Unknown),
+        ];
+        for (query, expected) in cases {
+            assert_eq!(IntentDetector::classify(query).0, expected, "{}", query);
+        }
+    }
+
+    #[test]
+    fn test_confidence_reflects_position_and_conflicts() {
+        assert_eq!(IntentDetector::classify("delete bucket logs"), (Intent::Delete, 0.9));
+        assert_eq!(IntentDetector::classify("please delete bucket logs"), (Intent::Delete, 0.6));
+
+        let (intent, confidence) = IntentDetector::classify("list buckets and delete the empty ones");
+        assert_eq!(intent, Intent::List);
+        assert!(confidence < 0.9);
+
+        // Help yields to a concrete request
+        assert_eq!(IntentDetector::classify("help me remove a user").0, Intent::Delete);
+        assert_eq!(IntentDetector::classify("how do I frobnicate"), (Intent::Unknown, 0.0));
+    }
+
+    #[test]
+    fn test_prompt_hints() {
+        assert!(Intent::Delete.prompt_hint().unwrap().contains("confirmation"));
+        assert_eq!(Intent::List.prompt_hint(), None);
+    }
+}
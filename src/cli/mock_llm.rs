@@ -0,0 +1,114 @@
+//! LLM with canned replies, for the `self-test` subcommand and tests
+//!
+//! Replies come from a closure over the prompt, so one double covers a
+//! fixed answer, prompt-dependent answers and failures.
+
+use async_trait::async_trait;
+use std::sync::Mutex;
+use std::time::Duration;
+use crate::core::{GenerationAttempt, GenerationConfig, GenerationResult, LLMProvider, Result, RetryConfig};
+
+type Responder = Box<dyn Fn(&str) -> Result<String> + Send + Sync>;
+
+/// LLM whose output is computed from the prompt by a closure
+pub struct MockLLM {
+    respond: Responder,
+    delay: Duration,
+    configs: Mutex<Vec<GenerationConfig>>,
+}
+
+impl MockLLM {
+    /// Create a mock that answers each prompt with `respond`
+    pub fn new(respond: impl Fn(&str) -> Result<String> + Send + Sync + 'static) -> Self {
+        Self {
+            respond: Box::new(respond),
+            delay: Duration::ZERO,
+            configs: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Create a mock that always replies with the same text
+    pub fn replying(text: &str) -> Self {
+        let text = text.to_string();
+        Self::new(move |_| Ok(text.clone()))
+    }
+}
+
+#[cfg(test)]
+impl MockLLM {
+    /// Configs passed to each generation so far, oldest first
+    pub fn configs(&self) -> Vec<GenerationConfig> {
+        self.configs.lock().unwrap().clone()
+    }
+
+    /// Make every generation take `delay` before answering
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Create a mock that always fails with the error built by `make_error`
+    pub fn failing(make_error: fn() -> crate::core::Error) -> Self {
+        Self::new(move |_| Err(make_error()))
+    }
+}
+
+#[async_trait]
+impl LLMProvider for MockLLM {
+    async fn connect(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn generate(&self, prompt: &str) -> Result<GenerationResult> {
+        self.generate_with_config(prompt, &GenerationConfig::default()).await
+    }
+
+    async fn generate_with_config(
+        &self,
+        prompt: &str,
+        config: &GenerationConfig,
+    ) -> Result<GenerationResult> {
+        self.configs.lock().unwrap().push(config.clone());
+        if !self.delay.is_zero() {
+            tokio::time::sleep(self.delay).await;
+        }
+        Ok(GenerationResult {
+            text: (self.respond)(prompt)?,
+            model_id: config.model_id.clone(),
+            tokens_used: None,
+            quality_score: None,
+        })
+    }
+
+    async fn generate_with_feedback(
+        &self,
+        base_prompt: &str,
+        config: &GenerationConfig,
+        _previous_failures: &[String],
+        _retry_config: Option<RetryConfig>,
+    ) -> Result<GenerationAttempt> {
+        let result = self.generate_with_config(base_prompt, config).await?;
+        Ok(GenerationAttempt {
+            prompt: base_prompt.to_string(),
+            result: result.text,
+            quality_score: 1.0,
+            attempt_number: 1,
+        })
+    }
+
+    async fn generate_stream(
+        &self,
+        prompt: &str,
+        config: &GenerationConfig,
+    ) -> Result<GenerationResult> {
+        self.generate_with_config(prompt, config).await
+    }
+
+    fn assess_quality(&self, _text: &str, _prompt: &str) -> f32 {
+        1.0
+    }
+
+    fn model_id(&self) -> &str {
+        "mock"
+    }
+}
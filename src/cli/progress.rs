@@ -0,0 +1,110 @@
+//! Progress bars and spinners for long-running work
+//!
+//! Animations are drawn only when stdout is a terminal, so piped output stays
+//! clean.
+
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::io::{self, IsTerminal};
+use std::time::Duration;
+
+/// How often a spinner redraws
+const SPINNER_TICK: Duration = Duration::from_millis(100);
+
+/// A progress bar or spinner that is hidden when output is not a terminal
+///
+/// Unfinished progress is cleared when dropped.
+pub struct Progress {
+    bar: ProgressBar,
+}
+
+impl Progress {
+    /// Create a determinate bar over `len` steps
+    pub fn bar(len: u64, message: &str) -> Self {
+        Self::bar_with(len, message, io::stdout().is_terminal())
+    }
+
+    /// Create a spinner for work of unknown length
+    pub fn spinner(message: &str) -> Self {
+        Self::spinner_with(message, io::stdout().is_terminal())
+    }
+
+    fn bar_with(len: u64, message: &str, enabled: bool) -> Self {
+        let bar = if enabled {
+            let bar = ProgressBar::with_draw_target(Some(len), ProgressDrawTarget::stdout());
+            if let Ok(style) = ProgressStyle::with_template("{bar:30.cyan/blue} {pos}/{len} {msg}") {
+                bar.set_style(style);
+            }
+            bar
+        } else {
+            ProgressBar::hidden()
+        };
+        bar.set_message(message.to_string());
+        Self { bar }
+    }
+
+    fn spinner_with(message: &str, enabled: bool) -> Self {
+        let bar = if enabled {
+            let bar = ProgressBar::with_draw_target(None, ProgressDrawTarget::stdout());
+            if let Ok(style) = ProgressStyle::with_template("{spinner:.cyan} {msg}") {
+                bar.set_style(style);
+            }
+            bar.enable_steady_tick(SPINNER_TICK);
+            bar
+        } else {
+            ProgressBar::hidden()
+        };
+        bar.set_message(message.to_string());
+        Self { bar }
+    }
+
+    /// Whether anything is drawn
+    pub fn is_enabled(&self) -> bool {
+        !self.bar.is_hidden()
+    }
+
+    /// Replace the message shown next to the bar
+    pub fn set_message(&self, message: &str) {
+        self.bar.set_message(message.to_string());
+    }
+
+    /// Advance a determinate bar by one step
+    pub fn inc(&self) {
+        self.bar.inc(1);
+    }
+
+    /// Remove the bar from the terminal
+    pub fn finish(&self) {
+        self.bar.finish_and_clear();
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        if !self.bar.is_finished() {
+            self.bar.finish_and_clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bar_is_noop_without_terminal() {
+        let progress = Progress::bar_with(3, "Indexing", false);
+        assert!(!progress.is_enabled());
+
+        progress.inc();
+        progress.set_message("docs/guide.md");
+        progress.finish();
+        assert!(!progress.is_enabled());
+    }
+
+    #[test]
+    fn test_spinner_is_noop_without_terminal() {
+        let progress = Progress::spinner_with("Translating", false);
+        assert!(!progress.is_enabled());
+        drop(progress);
+    }
+}
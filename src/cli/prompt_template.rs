@@ -0,0 +1,177 @@
+//! Translation prompt templates
+//!
+//! The translation prompt is a template with named placeholders, so it can
+//! be tuned per model from a file instead of recompiling. Placeholders are
+//! filled in a single pass; braces that are not a known placeholder, as in
+//! JSON examples, are kept as written.
+//!
+//! Every prompt that asks for a command uses the template: translations,
+//! alternatives, plan steps and ports. Output summaries and recovery advice
+//! ask for prose rather than a command, so they keep their own prompts.
+
+use std::fs;
+use std::path::Path;
+use super::{CliError, CliResult};
+
+/// Placeholders a template may use
+const PLACEHOLDERS: &[&str] = &["provider", "context", "query", "examples"];
+
+/// Placeholders every template must use
+const REQUIRED_PLACEHOLDERS: &[&str] = &["query"];
+
+/// The built-in translation prompt
+pub const DEFAULT_PROMPT_TEMPLATE: &str = "You are an expert in the {provider} CLI. Translate the following natural language query into a valid {provider} CLI command.\n\
+Only output the command itself, nothing else.\n\
+\n\
+{context}{examples}Query: {query}\n\
+Command:";
+
+/// Values substituted into a [`PromptTemplate`]
+#[derive(Debug, Clone, Default)]
+pub struct PromptValues<'a> {
+    /// Provider the query is scoped to, e.g. "AWS", or "cloud" when unscoped
+    pub provider: &'a str,
+    /// Intent hints, the IBM Cloud target, session context and task
+    /// instructions such as plan steps so far, each ending in a newline
+    pub context: &'a str,
+    /// Earlier commands of this session, for follow-up queries
+    pub examples: &'a str,
+    pub query: &'a str,
+}
+
+/// A translation prompt with `{provider}`, `{context}`, `{examples}` and `{query}` placeholders
+#[derive(Debug, Clone, PartialEq)]
+pub struct PromptTemplate {
+    text: String,
+}
+
+impl PromptTemplate {
+    /// Parse a template, rejecting unknown placeholders and missing required ones
+    pub fn new(text: &str) -> CliResult<Self> {
+        let used = placeholders(text);
+        if let Some(unknown) = used.iter().find(|name| !PLACEHOLDERS.contains(name)) {
+            return Err(CliError::ValidationFailed(format!(
+                "unknown prompt placeholder {{{}}}; expected one of {}",
+                unknown,
+                PLACEHOLDERS.iter().map(|name| format!("{{{}}}", name)).collect::<Vec<_>>().join(", ")
+            )));
+        }
+        if let Some(missing) = REQUIRED_PLACEHOLDERS.iter().find(|name| !used.contains(name)) {
+            return Err(CliError::ValidationFailed(format!("prompt template is missing {{{}}}", missing)));
+        }
+        Ok(Self { text: text.to_string() })
+    }
+
+    /// Load a template from a file
+    pub fn load(path: &Path) -> CliResult<Self> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| CliError::ValidationFailed(format!("cannot read prompt template {}: {}", path.display(), e)))?;
+        Self::new(&text)
+    }
+
+    /// Fill in the placeholders
+    pub fn render(&self, values: &PromptValues) -> String {
+        let mut rendered = String::with_capacity(self.text.len() + values.query.len() + values.context.len());
+        let mut rest = self.text.as_str();
+
+        while let Some(start) = rest.find('{') {
+            rendered.push_str(&rest[..start]);
+            let after = &rest[start + 1..];
+            let value = after.find('}').and_then(|end| {
+                let value = match &after[..end] {
+                    "provider" => values.provider,
+                    "context" => values.context,
+                    "examples" => values.examples,
+                    "query" => values.query,
+                    _ => return None,
+                };
+                Some((value, end))
+            });
+            match value {
+                Some((value, end)) => {
+                    rendered.push_str(value);
+                    rest = &after[end + 1..];
+                }
+                None => {
+                    rendered.push('{');
+                    rest = after;
+                }
+            }
+        }
+        rendered.push_str(rest);
+        rendered
+    }
+}
+
+impl Default for PromptTemplate {
+    fn default() -> Self {
+        Self { text: DEFAULT_PROMPT_TEMPLATE.to_string() }
+    }
+}
+
+/// Names in `{name}` placeholders, where names are letters and underscores
+fn placeholders(text: &str) -> Vec<&str> {
+    text.split('{')
+        .skip(1)
+        .filter_map(|part| part.split_once('}').map(|(name, _)| name))
+        .filter(|name| !name.is_empty() && name.chars().all(|c| c.is_ascii_alphabetic() || c == '_'))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values() -> PromptValues<'static> {
+        PromptValues {
+            provider: "AWS",
+            context: "Target: us-east-1\n",
+            examples: "Recent commands:\n- aws s3 ls\n",
+            query: "list buckets",
+        }
+    }
+
+    #[test]
+    fn test_render_all_placeholders() {
+        let template = PromptTemplate::new("{provider} expert.\n{context}{examples}Q: {query} {\"json\": true}").unwrap();
+        assert_eq!(
+            template.render(&values()),
+            "AWS expert.\nTarget: us-east-1\nRecent commands:\n- aws s3 ls\nQ: list buckets {\"json\": true}"
+        );
+    }
+
+    #[test]
+    fn test_values_are_not_expanded_again() {
+        let template = PromptTemplate::new("Query: {query} / {context}").unwrap();
+        let values = PromptValues { query: "{context}", context: "ctx", ..Default::default() };
+        assert_eq!(template.render(&values), "Query: {context} / ctx");
+    }
+
+    #[test]
+    fn test_default_template() {
+        let prompt = PromptTemplate::default().render(&values());
+        assert!(prompt.starts_with("You are an expert in the AWS CLI. Translate the following natural language query into a valid AWS CLI command.\n"));
+        assert!(prompt.ends_with("\n\nTarget: us-east-1\nRecent commands:\n- aws s3 ls\nQuery: list buckets\nCommand:"));
+        assert_eq!(PromptTemplate::new(DEFAULT_PROMPT_TEMPLATE).unwrap(), PromptTemplate::default());
+    }
+
+    #[test]
+    fn test_rejects_template_without_query() {
+        let err = PromptTemplate::new("You are a {provider} expert.\n{context}Command:").unwrap_err();
+        assert!(matches!(err, CliError::ValidationFailed(ref message) if message.contains("{query}")), "{}", err);
+    }
+
+    #[test]
+    fn test_rejects_unknown_placeholder() {
+        assert!(PromptTemplate::new("{query} {examplse}").is_err());
+    }
+
+    #[test]
+    fn test_load_from_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("prompt.txt");
+        fs::write(&path, "Translate for {provider}: {query}").unwrap();
+        assert_eq!(PromptTemplate::load(&path).unwrap().render(&values()), "Translate for AWS: list buckets");
+        assert!(PromptTemplate::load(&dir.path().join("missing.txt")).is_err());
+    }
+}
@@ -95,6 +95,26 @@ impl QualityAnalyzer {
         }
     }
 
+    /// Analyze a command and blend in its learned execution success rate
+    ///
+    /// The static score keeps 70% of the weight so a single failure does not
+    /// hide an otherwise well-formed command.
+    pub fn analyze_with_success_rate(&self, command: &str, success_rate: Option<f32>) -> QualityAnalysis {
+        let mut analysis = self.analyze(command);
+
+        if let Some(rate) = success_rate {
+            analysis.score = analysis.score * 0.7 + rate.clamp(0.0, 1.0) * 0.3;
+            if rate < 0.5 {
+                analysis.issues.push(format!(
+                    "Command has failed in previous runs (success rate {:.0}%)",
+                    rate * 100.0
+                ));
+            }
+        }
+
+        analysis
+    }
+
     /// Check if a command is likely valid
     pub fn is_valid(&self, command: &str) -> bool {
         let analysis = self.analyze(command);
@@ -125,6 +145,20 @@ mod tests {
         assert!(analysis.score < 0.6);
     }
 
+    #[test]
+    fn test_success_rate_lowers_blended_score() {
+        let analyzer = QualityAnalyzer::new();
+        let command = "ibmcloud resource groups";
+
+        let base = analyzer.analyze_with_success_rate(command, None);
+        let reliable = analyzer.analyze_with_success_rate(command, Some(0.9));
+        let failing = analyzer.analyze_with_success_rate(command, Some(0.2));
+
+        assert_eq!(base.score, analyzer.analyze(command).score);
+        assert!(failing.score < reliable.score);
+        assert!(failing.issues.iter().any(|i| i.contains("success rate")));
+    }
+
     #[test]
     fn test_is_valid() {
         let analyzer = QualityAnalyzer::new();
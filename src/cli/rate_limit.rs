@@ -0,0 +1,90 @@
+//! Token-bucket rate limiting for LLM calls
+
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// Token bucket that refills at a fixed requests-per-minute rate
+pub struct RateLimiter {
+    /// Time to refill one token
+    interval: Duration,
+    /// Maximum tokens held, i.e. the allowed burst
+    capacity: f64,
+    state: Mutex<BucketState>,
+}
+
+struct BucketState {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    /// Allow `requests_per_minute` calls, one at a time
+    ///
+    /// A rate of zero is treated as one request per minute.
+    pub fn new(requests_per_minute: u32) -> Self {
+        Self::with_burst(requests_per_minute, 1)
+    }
+
+    /// Allow `requests_per_minute` calls with up to `burst` back to back
+    pub fn with_burst(requests_per_minute: u32, burst: u32) -> Self {
+        let capacity = burst.max(1) as f64;
+        Self {
+            interval: Duration::from_secs(60) / requests_per_minute.max(1),
+            capacity,
+            state: Mutex::new(BucketState {
+                tokens: capacity,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    /// Wait until a token is available and take it
+    pub async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut state = self.state.lock().await;
+                let now = Instant::now();
+                let refilled = now.duration_since(state.last_refill).as_secs_f64() / self.interval.as_secs_f64();
+                state.tokens = (state.tokens + refilled).min(self.capacity);
+                state.last_refill = now;
+
+                if state.tokens >= 1.0 {
+                    state.tokens -= 1.0;
+                    return;
+                }
+                self.interval.mul_f64(1.0 - state.tokens)
+            };
+
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rapid_calls_are_spaced_out() {
+        // 1200 per minute is one every 50ms
+        let limiter = RateLimiter::new(1200);
+        let start = Instant::now();
+        for _ in 0..5 {
+            limiter.acquire().await;
+        }
+
+        // The first call is free, the other four wait ~50ms each
+        assert!(start.elapsed() >= Duration::from_millis(190), "took {:?}", start.elapsed());
+    }
+
+    #[tokio::test]
+    async fn test_burst_is_immediate() {
+        let limiter = RateLimiter::with_burst(60, 3);
+        let start = Instant::now();
+        for _ in 0..3 {
+            limiter.acquire().await;
+        }
+
+        assert!(start.elapsed() < Duration::from_millis(500));
+    }
+}
@@ -0,0 +1,165 @@
+//! Retry suggestions after a failed command
+//!
+//! The recovery answer from `suggest_recovery` mixes advice with commands.
+//! Lines prefixed with `$`, lines inside code fences and lines starting with
+//! a known provider CLI are executable; everything else is advice that is
+//! only shown.
+
+use crate::core::{infer_provider_from_command, ParsedCommand};
+
+/// One entry of a recovery answer
+#[derive(Debug, Clone, PartialEq)]
+pub enum RetrySuggestion {
+    /// A command that can be run as the retry
+    Command(String),
+    /// Explanation to show, not run
+    Advice(String),
+}
+
+impl RetrySuggestion {
+    /// Whether the suggestion can be picked and run
+    pub fn is_executable(&self) -> bool {
+        matches!(self, RetrySuggestion::Command(_))
+    }
+}
+
+/// Split a recovery answer into commands and advice, in order
+///
+/// Repeated commands and the command that just failed are left out.
+pub fn parse_retry_suggestions(response: &str, failed_command: &str) -> Vec<RetrySuggestion> {
+    let mut suggestions = Vec::new();
+    let mut in_fence = false;
+
+    for line in response.lines() {
+        let line = line.trim();
+        if line.starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if line.is_empty() {
+            continue;
+        }
+
+        let command = match line.strip_prefix('$') {
+            Some(command) => Some(command.trim()),
+            None if in_fence || infer_provider_from_command(line).is_some() => Some(line),
+            None => None,
+        };
+        let suggestion = match command {
+            Some(command) if command.is_empty() || command == failed_command.trim() => continue,
+            Some(command) => RetrySuggestion::Command(command.to_string()),
+            None => RetrySuggestion::Advice(line.to_string()),
+        };
+        if !suggestions.contains(&suggestion) {
+            suggestions.push(suggestion);
+        }
+    }
+    suggestions
+}
+
+/// What the user picked from the retry menu
+#[derive(Debug, Clone, PartialEq)]
+pub enum RetryChoice {
+    /// Run the suggested command
+    Run(String),
+    /// Type a command of their own
+    Custom,
+    /// Do not retry
+    Skip,
+}
+
+/// Map a menu answer to a choice
+///
+/// Numbers count executable suggestions only, starting at 1; `c` asks for a
+/// custom command. Anything else, including Enter, skips the retry.
+pub fn retry_choice(suggestions: &[RetrySuggestion], response: &str) -> RetryChoice {
+    let response = response.trim().to_lowercase();
+    if response == "c" || response == "custom" {
+        return RetryChoice::Custom;
+    }
+    response
+        .parse::<usize>()
+        .ok()
+        .and_then(|choice| choice.checked_sub(1))
+        .and_then(|index| executable_commands(suggestions).nth(index))
+        .map(|command| RetryChoice::Run(command.to_string()))
+        .unwrap_or(RetryChoice::Skip)
+}
+
+/// Whether `retry` is another form of `failed`: same CLI and same first subcommand
+///
+/// Only such a retry can stand in for the failed command when it is learned;
+/// a prerequisite such as `ibmcloud login` cannot.
+pub fn is_same_command_kind(failed: &str, retry: &str) -> bool {
+    match (ParsedCommand::parse(failed), ParsedCommand::parse(retry)) {
+        (Ok(failed), Ok(retry)) => failed.binary == retry.binary && failed.subcommands.first() == retry.subcommands.first(),
+        _ => false,
+    }
+}
+
+/// The executable suggestions, in menu order
+pub fn executable_commands(suggestions: &[RetrySuggestion]) -> impl Iterator<Item = &str> {
+    suggestions.iter().filter_map(|suggestion| match suggestion {
+        RetrySuggestion::Command(command) => Some(command.as_str()),
+        RetrySuggestion::Advice(_) => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESPONSE: &str = "Problem: You are not authenticated to IBM Cloud.\n\
+        \n\
+        Fix: Log in to IBM Cloud first:\n\
+        $ ibmcloud login --sso\n\
+        ```bash\n\
+        ibmcloud target -g default\n\
+        ```\n\
+        ibmcloud login --sso\n\
+        $ ibmcloud ks clusters\n\
+        Then retry your original command.";
+
+    fn commands(suggestions: &[RetrySuggestion]) -> Vec<&str> {
+        executable_commands(suggestions).collect()
+    }
+
+    #[test]
+    fn test_classifies_commands_and_advice() {
+        let suggestions = parse_retry_suggestions(RESPONSE, "ibmcloud ks clusters");
+
+        assert_eq!(commands(&suggestions), vec!["ibmcloud login --sso", "ibmcloud target -g default"]);
+        assert_eq!(suggestions[0], RetrySuggestion::Advice("Problem: You are not authenticated to IBM Cloud.".to_string()));
+        assert!(!suggestions[1].is_executable());
+        assert!(suggestions[2].is_executable());
+        assert_eq!(suggestions.last(), Some(&RetrySuggestion::Advice("Then retry your original command.".to_string())));
+    }
+
+    #[test]
+    fn test_advice_only_response() {
+        let suggestions = parse_retry_suggestions("Ask your account owner for the Editor role.", "aws s3 ls");
+        assert_eq!(suggestions.len(), 1);
+        assert!(!suggestions[0].is_executable());
+    }
+
+    #[test]
+    fn test_menu_numbers_only_executable_entries() {
+        let suggestions = parse_retry_suggestions(RESPONSE, "ibmcloud ks clusters");
+
+        assert_eq!(retry_choice(&suggestions, "1"), RetryChoice::Run("ibmcloud login --sso".to_string()));
+        assert_eq!(retry_choice(&suggestions, " 2\n"), RetryChoice::Run("ibmcloud target -g default".to_string()));
+        assert_eq!(retry_choice(&suggestions, "C"), RetryChoice::Custom);
+        for skipped in ["", "0", "3", "login"] {
+            assert_eq!(retry_choice(&suggestions, skipped), RetryChoice::Skip, "{:?}", skipped);
+        }
+    }
+
+    #[test]
+    fn test_same_command_kind() {
+        assert!(is_same_command_kind("ibmcloud ks clusters", "ibmcloud ks cluster ls --provider vpc-gen2"));
+        assert!(is_same_command_kind("aws s3 ls s3://logs", "aws s3 ls s3://logs --region eu-west-1"));
+        assert!(!is_same_command_kind("ibmcloud ks clusters", "ibmcloud login --sso"));
+        assert!(!is_same_command_kind("aws s3 ls", "gcloud storage ls"));
+        assert!(!is_same_command_kind("aws s3 ls", "aws 's3"));
+    }
+}
@@ -0,0 +1,58 @@
+//! Offline translation regression suite
+//!
+//! Each fixture pairs a query with a canned raw model output. The output is
+//! cleaned the same way the WatsonX adapter cleans real generations, then
+//! run through the translator; the resulting command must contain the
+//! expected text and pass the provider's validation.
+
+use serde::Deserialize;
+use crate::core::{CloudProviderType, GenerationConfig};
+use crate::providers::create_provider;
+use crate::watsonx_adapter::clean_response;
+use super::CommandTranslator;
+use super::test_support::{MockLLM, TestRag};
+
+const FIXTURES: &str = include_str!("fixtures/translate_regressions.json");
+
+#[derive(Debug, Deserialize)]
+struct Fixture {
+    name: String,
+    query: String,
+    provider: String,
+    model_output: String,
+    expected_command_contains: String,
+}
+
+#[tokio::test]
+async fn test_translate_regressions() {
+    let fixtures: Vec<Fixture> = serde_json::from_str(FIXTURES).unwrap();
+    let stop_sequences = GenerationConfig::default().stop_sequences;
+    let mut failures = Vec::new();
+
+    for fixture in &fixtures {
+        let provider = CloudProviderType::from_str(&fixture.provider)
+            .unwrap_or_else(|| panic!("{}: unknown provider {}", fixture.name, fixture.provider));
+        let cleaned = clean_response(&fixture.model_output, &stop_sequences);
+        let translator = CommandTranslator::<MockLLM, TestRag>::new(MockLLM::replying(&cleaned));
+
+        let command = match translator.translate_for_provider(&fixture.query, provider).await {
+            Ok(command) => command,
+            Err(e) => {
+                failures.push(format!("{}: translation failed: {}", fixture.name, e));
+                continue;
+            }
+        };
+
+        if !command.contains(&fixture.expected_command_contains) {
+            failures.push(format!(
+                "{}: expected {:?} in {:?}",
+                fixture.name, fixture.expected_command_contains, command
+            ));
+        } else if let Err(e) = create_provider(provider).validate_command(&command) {
+            failures.push(format!("{}: {:?} failed validation: {}", fixture.name, command, e));
+        }
+    }
+
+    assert!(fixtures.len() >= 20, "expected at least 20 fixtures, found {}", fixtures.len());
+    assert!(failures.is_empty(), "{} regression(s):\n{}", failures.len(), failures.join("\n"));
+}
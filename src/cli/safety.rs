@@ -0,0 +1,85 @@
+//! Command safety checks used to gate execution
+
+use crate::core::is_destructive_command;
+
+/// Outcome of the auto-execute gate
+#[derive(Debug, Clone, PartialEq)]
+pub enum AutoExecuteDecision {
+    /// Run without asking
+    Execute,
+    /// Ask the user first, with the reason
+    Confirm(String),
+}
+
+/// Decide whether a translated command may run without confirmation
+///
+/// Auto-execution requires a threshold, a score strictly above it, and a
+/// command that `is_destructive_command` considers safe.
+pub fn auto_execute_decision(command: &str, score: f32, threshold: Option<f32>) -> AutoExecuteDecision {
+    let Some(threshold) = threshold else {
+        return AutoExecuteDecision::Confirm("auto-execute is disabled".to_string());
+    };
+
+    if score <= threshold {
+        return AutoExecuteDecision::Confirm(format!(
+            "score {:.0}% is not above threshold {:.0}%",
+            score * 100.0,
+            threshold * 100.0
+        ));
+    }
+
+    match is_destructive_command(command) {
+        Some(reason) => AutoExecuteDecision::Confirm(format!("command {}", reason)),
+        None => AutoExecuteDecision::Execute,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auto_execute_disabled_without_threshold() {
+        assert!(matches!(
+            auto_execute_decision("ibmcloud resource groups", 1.0, None),
+            AutoExecuteDecision::Confirm(_)
+        ));
+    }
+
+    #[test]
+    fn test_auto_execute_high_score_safe_command() {
+        assert_eq!(
+            auto_execute_decision("ibmcloud resource groups", 0.95, Some(0.9)),
+            AutoExecuteDecision::Execute
+        );
+    }
+
+    #[test]
+    fn test_auto_execute_low_score_safe_command() {
+        assert!(matches!(
+            auto_execute_decision("ibmcloud resource groups", 0.85, Some(0.9)),
+            AutoExecuteDecision::Confirm(_)
+        ));
+        // Equal to the threshold is not enough
+        assert!(matches!(
+            auto_execute_decision("ibmcloud resource groups", 0.9, Some(0.9)),
+            AutoExecuteDecision::Confirm(_)
+        ));
+    }
+
+    #[test]
+    fn test_auto_execute_high_score_destructive_command() {
+        assert!(matches!(
+            auto_execute_decision("aws s3 rm s3://bucket/key", 0.99, Some(0.9)),
+            AutoExecuteDecision::Confirm(_)
+        ));
+    }
+
+    #[test]
+    fn test_auto_execute_low_score_destructive_command() {
+        assert!(matches!(
+            auto_execute_decision("aws s3 rm s3://bucket/key", 0.5, Some(0.9)),
+            AutoExecuteDecision::Confirm(_)
+        ));
+    }
+}
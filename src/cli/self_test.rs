@@ -0,0 +1,45 @@
+//! Offline round trip for the `self-test` subcommand
+//!
+//! A known query is translated with a canned LLM and the result validated
+//! for IBM Cloud, exercising the translation and validation wiring without
+//! any network calls or LLM quota.
+
+use crate::core::{CloudProviderType, LLMProvider, RAGEngine};
+use crate::providers::create_provider;
+use super::{CliError, CliResult, CommandTranslator};
+
+/// Query translated by the self-test
+pub const SELF_TEST_QUERY: &str = "list resource groups";
+
+/// Command the self-test's [`MockLLM`](super::MockLLM) answers with
+pub const SELF_TEST_COMMAND: &str = "ibmcloud resource groups";
+
+/// Translate [`SELF_TEST_QUERY`] and validate the command for IBM Cloud
+///
+/// Returns the validated command.
+pub async fn run_self_test<L: LLMProvider, R: RAGEngine>(translator: &CommandTranslator<L, R>) -> CliResult<String> {
+    let command = translator.translate(SELF_TEST_QUERY).await?;
+    create_provider(CloudProviderType::IBMCloud)
+        .validate_command(&command)
+        .map_err(|e| CliError::ValidationFailed(format!("'{}': {}", command, e)))?;
+    Ok(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::test_support::{MockLLM, TestRag};
+
+    #[tokio::test]
+    async fn test_self_test_passes_with_mock() {
+        let translator = CommandTranslator::<_, TestRag>::new(MockLLM::replying(SELF_TEST_COMMAND));
+        assert_eq!(run_self_test(&translator).await.unwrap(), SELF_TEST_COMMAND);
+    }
+
+    #[tokio::test]
+    async fn test_self_test_fails_when_validation_fails() {
+        let translator = CommandTranslator::<_, TestRag>::new(MockLLM::replying("aws s3 ls"));
+        let err = run_self_test(&translator).await.unwrap_err();
+        assert!(matches!(err, CliError::ValidationFailed(_)));
+    }
+}
@@ -0,0 +1,209 @@
+//! Copying a project's source for deployment
+//!
+//! Files matched by `.dockerignore` or `.gitignore`, plus dependency and
+//! build directories, are left out so uploads and build contexts stay small.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Directories skipped even when no ignore file lists them
+const DEFAULT_IGNORES: &[&str] = &["node_modules", ".git", "target", "dist"];
+
+/// Ignore files read from the project root
+const IGNORE_FILES: &[&str] = &[".dockerignore", ".gitignore"];
+
+/// One ignore pattern
+#[derive(Debug, Clone)]
+struct IgnorePattern {
+    /// Glob with `*` and `?`, without leading or trailing slashes
+    glob: String,
+    /// Only matches relative to the root, as with a leading or inner `/`
+    anchored: bool,
+    /// Only matches directories, as with a trailing `/`
+    dir_only: bool,
+}
+
+impl IgnorePattern {
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim();
+        // Negations are not supported; skipping them errs on copying too much
+        if line.is_empty() || line.starts_with('#') || line.starts_with('!') {
+            return None;
+        }
+
+        let dir_only = line.ends_with('/');
+        let line = line.trim_end_matches('/');
+        let line = line.strip_prefix("**/").unwrap_or(line);
+        let anchored = line.trim_start_matches('/').contains('/') || line.starts_with('/');
+        let glob = line.trim_start_matches('/').to_string();
+        (!glob.is_empty()).then_some(Self { glob, anchored, dir_only })
+    }
+
+    /// Whether the pattern matches `relative`, a `/`-separated path from the root
+    fn matches(&self, relative: &str, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        if self.anchored {
+            glob_match(&self.glob, relative)
+        } else {
+            relative.rsplit('/').next().is_some_and(|name| glob_match(&self.glob, name))
+        }
+    }
+}
+
+/// Match `text` against a glob where `*` is any run of characters except
+/// `/` and `?` is one such character
+fn glob_match(glob: &str, text: &str) -> bool {
+    let glob: Vec<char> = glob.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut g, mut t) = (0, 0);
+    // Position of the last `*` and the text position it was tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match glob.get(g) {
+            Some('*') => {
+                backtrack = Some((g, t));
+                g += 1;
+            }
+            Some('?') if text[t] != '/' => {
+                g += 1;
+                t += 1;
+            }
+            Some(&c) if c == text[t] => {
+                g += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, from)) if text[from] != '/' => {
+                    backtrack = Some((star, from + 1));
+                    g = star + 1;
+                    t = from + 1;
+                }
+                _ => return false,
+            },
+        }
+    }
+    glob[g..].iter().all(|&c| c == '*')
+}
+
+/// Exclusions for one project
+#[derive(Debug, Clone)]
+pub struct IgnoreRules {
+    patterns: Vec<IgnorePattern>,
+}
+
+impl IgnoreRules {
+    /// Default exclusions plus every pattern in the root's ignore files
+    pub fn load(root: &Path) -> Self {
+        let mut patterns: Vec<IgnorePattern> = DEFAULT_IGNORES
+            .iter()
+            .filter_map(|name| IgnorePattern::parse(&format!("{}/", name)))
+            .collect();
+        for file in IGNORE_FILES {
+            if let Ok(content) = fs::read_to_string(root.join(file)) {
+                patterns.extend(content.lines().filter_map(IgnorePattern::parse));
+            }
+        }
+        Self { patterns }
+    }
+
+    /// Whether `relative`, a `/`-separated path from the root, is excluded
+    pub fn is_ignored(&self, relative: &str, is_dir: bool) -> bool {
+        self.patterns.iter().any(|pattern| pattern.matches(relative, is_dir))
+    }
+}
+
+/// Recursively copy `source` into `dest`, skipping ignored paths
+///
+/// Symlinks are skipped. Returns the number of files copied.
+pub fn copy_source_files(source: &Path, dest: &Path) -> io::Result<usize> {
+    let rules = IgnoreRules::load(source);
+    fs::create_dir_all(dest)?;
+    copy_dir(source, dest, "", &rules)
+}
+
+fn copy_dir(dir: &Path, dest: &Path, relative: &str, rules: &IgnoreRules) -> io::Result<usize> {
+    let mut copied = 0;
+    let mut entries: Vec<_> = fs::read_dir(dir)?.collect::<io::Result<_>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let file_type = entry.file_type()?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let path = if relative.is_empty() { name.clone() } else { format!("{}/{}", relative, name) };
+        if file_type.is_symlink() || rules.is_ignored(&path, file_type.is_dir()) {
+            continue;
+        }
+
+        let target = dest.join(&name);
+        if file_type.is_dir() {
+            fs::create_dir_all(&target)?;
+            copied += copy_dir(&entry.path(), &target, &path, rules)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+            copied += 1;
+        }
+    }
+    Ok(copied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, relative: &str, content: &str) {
+        let path = root.join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*.log", "build.log"));
+        assert!(glob_match("cache?", "cache1"));
+        assert!(glob_match("docs/*.md", "docs/guide.md"));
+        assert!(!glob_match("docs/*.md", "docs/api/guide.md"));
+        assert!(!glob_match("*.log", "build.log.txt"));
+    }
+
+    #[test]
+    fn test_copy_skips_ignored_paths() {
+        let project = tempfile::tempdir().unwrap();
+        let root = project.path();
+        write(root, "package.json", "{}");
+        write(root, "src/index.js", "console.log('hi')");
+        write(root, "src/lib/util.js", "");
+        write(root, "public/logo.svg", "<svg/>");
+        write(root, "node_modules/left-pad/index.js", "");
+        write(root, ".git/HEAD", "ref: refs/heads/main");
+        write(root, "dist/bundle.js", "");
+        write(root, "debug.log", "");
+        write(root, "coverage/lcov.info", "");
+        write(root, "src/secrets.env", "");
+        write(root, ".gitignore", "*.log\n# build output\ncoverage/\n");
+        write(root, ".dockerignore", "/src/secrets.env\n!debug.log\n");
+
+        let dest = tempfile::tempdir().unwrap();
+        let copied = copy_source_files(root, dest.path()).unwrap();
+
+        for kept in ["package.json", "src/index.js", "src/lib/util.js", "public/logo.svg", ".gitignore", ".dockerignore"] {
+            assert!(dest.path().join(kept).is_file(), "{} should be copied", kept);
+        }
+        for skipped in ["node_modules", ".git", "dist", "debug.log", "coverage", "src/secrets.env"] {
+            assert!(!dest.path().join(skipped).exists(), "{} should be skipped", skipped);
+        }
+        assert_eq!(copied, 6);
+    }
+
+    #[test]
+    fn test_unanchored_patterns_match_at_any_depth() {
+        let rules = IgnoreRules::load(Path::new("/nonexistent"));
+        assert!(rules.is_ignored("packages/web/node_modules", true));
+        // A file named like an ignored directory is kept
+        assert!(!rules.is_ignored("docs/target", false));
+        assert!(!rules.is_ignored("src/main.rs", false));
+    }
+}
@@ -0,0 +1,203 @@
+//! Intent-keyed command templates for routine requests
+//!
+//! Short imperative queries like "list ec2 instances" map to a fixed command
+//! per provider, so they can be answered without calling the LLM.
+
+use std::collections::HashMap;
+use crate::core::CloudProviderType;
+
+/// Minimum intent confidence before a template is used instead of the LLM
+pub const TEMPLATE_MIN_CONFIDENCE: f32 = 0.8;
+
+/// Queries longer than this are left to the LLM
+const MAX_TEMPLATE_QUERY_WORDS: usize = 6;
+
+/// Filler words dropped when parsing an intent
+const FILLER_WORDS: &[&str] = &["all", "my", "the", "a", "an", "of", "every"];
+
+/// What the user wants to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IntentAction {
+    List,
+    Describe,
+    Delete,
+}
+
+impl IntentAction {
+    fn from_verb(verb: &str) -> Option<Self> {
+        match verb {
+            "list" | "show" | "get" | "display" => Some(IntentAction::List),
+            "describe" | "inspect" => Some(IntentAction::Describe),
+            "delete" | "remove" | "destroy" => Some(IntentAction::Delete),
+            _ => None,
+        }
+    }
+}
+
+/// A parsed query intent
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryIntent {
+    pub action: IntentAction,
+    /// Remaining words after the verb, with filler removed
+    pub words: Vec<String>,
+    /// How sure we are the query is a routine request (0.0 to 1.0)
+    pub confidence: f32,
+}
+
+/// Parse a query into an action and its object
+///
+/// Returns `None` when the query does not start with a known verb. Short
+/// queries get high confidence; long ones likely carry details a template
+/// would drop.
+pub fn parse_intent(query: &str) -> Option<QueryIntent> {
+    let mut tokens = query.split_whitespace();
+    let action = IntentAction::from_verb(&tokens.next()?.to_lowercase())?;
+
+    // Keep the original case so resource names survive into the command
+    let words: Vec<String> = tokens
+        .filter(|word| !FILLER_WORDS.contains(&word.to_lowercase().as_str()))
+        .map(str::to_string)
+        .collect();
+    if words.is_empty() {
+        return None;
+    }
+
+    let confidence = if words.len() < MAX_TEMPLATE_QUERY_WORDS { 0.9 } else { 0.5 };
+    Some(QueryIntent { action, words, confidence })
+}
+
+/// A command template for one resource phrase
+///
+/// `{resource}` in the template is replaced with the single word following
+/// the resource phrase, such as a bucket name.
+#[derive(Debug, Clone)]
+pub struct CommandTemplate {
+    pub resource: String,
+    pub template: String,
+}
+
+/// Templates by provider and intent action
+#[derive(Debug, Clone, Default)]
+pub struct CommandTemplates {
+    templates: HashMap<CloudProviderType, HashMap<IntentAction, Vec<CommandTemplate>>>,
+}
+
+impl CommandTemplates {
+    /// Create an empty template library
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create the library of built-in templates
+    pub fn builtin() -> Self {
+        use CloudProviderType::*;
+        use IntentAction::*;
+
+        let entries: &[(CloudProviderType, IntentAction, &[&str], &str)] = &[
+            (AWS, List, &["ec2 instances", "instances", "ec2"], "aws ec2 describe-instances"),
+            (AWS, List, &["s3 buckets", "buckets"], "aws s3 ls"),
+            (AWS, List, &["lambda functions", "lambdas", "functions"], "aws lambda list-functions"),
+            (AWS, List, &["eks clusters", "clusters"], "aws eks list-clusters"),
+            (AWS, Delete, &["s3 bucket", "bucket"], "aws s3 rb s3://{resource}"),
+            (AWS, Describe, &["instance", "ec2 instance"], "aws ec2 describe-instances --instance-ids {resource}"),
+            (Azure, List, &["vms", "virtual machines"], "az vm list"),
+            (Azure, List, &["resource groups", "groups"], "az group list"),
+            (Azure, List, &["aks clusters", "clusters"], "az aks list"),
+            (Azure, List, &["storage accounts"], "az storage account list"),
+            (Azure, Delete, &["resource group", "group"], "az group delete --name {resource}"),
+            (GCP, List, &["compute instances", "instances", "vms"], "gcloud compute instances list"),
+            (GCP, List, &["buckets", "storage buckets"], "gcloud storage buckets list"),
+            (GCP, List, &["gke clusters", "clusters"], "gcloud container clusters list"),
+            (IBMCloud, List, &["resource groups", "groups"], "ibmcloud resource groups"),
+            (IBMCloud, List, &["clusters", "kubernetes clusters"], "ibmcloud ks clusters"),
+            (IBMCloud, List, &["service instances", "resources"], "ibmcloud resource service-instances"),
+            (VMware, List, &["vms", "virtual machines"], "govc ls /*/vm"),
+            (VMware, List, &["hosts"], "govc ls /*/host"),
+            (VMware, List, &["datastores"], "govc ls /*/datastore"),
+            (DigitalOcean, List, &["droplets"], "doctl compute droplet list"),
+            (DigitalOcean, List, &["kubernetes clusters", "clusters"], "doctl kubernetes cluster list"),
+            (DigitalOcean, List, &["apps"], "doctl apps list"),
+        ];
+
+        let mut templates = Self::new();
+        for (provider, action, resources, template) in entries {
+            for resource in *resources {
+                templates.add(*provider, *action, resource, template);
+            }
+        }
+        templates
+    }
+
+    /// Register a template for a resource phrase
+    pub fn add(&mut self, provider: CloudProviderType, action: IntentAction, resource: &str, template: &str) {
+        self.templates
+            .entry(provider)
+            .or_default()
+            .entry(action)
+            .or_default()
+            .push(CommandTemplate {
+                resource: resource.to_string(),
+                template: template.to_string(),
+            });
+    }
+
+    /// Fill the template matching an intent, if one fits exactly
+    ///
+    /// The resource phrase must match the intent's words. Templates with
+    /// `{resource}` take exactly one extra word; others take none.
+    pub fn fill(&self, provider: CloudProviderType, intent: &QueryIntent) -> Option<String> {
+        let candidates = self.templates.get(&provider)?.get(&intent.action)?;
+
+        candidates.iter().find_map(|candidate| {
+            let resource: Vec<&str> = candidate.resource.split_whitespace().collect();
+            if intent.words.len() < resource.len()
+                || intent.words[..resource.len()]
+                    .iter()
+                    .zip(&resource)
+                    .any(|(word, part)| !word.eq_ignore_ascii_case(part))
+            {
+                return None;
+            }
+
+            let rest = &intent.words[resource.len()..];
+            match (candidate.template.contains("{resource}"), rest) {
+                (true, [name]) => Some(candidate.template.replace("{resource}", name)),
+                (false, []) => Some(candidate.template.clone()),
+                _ => None,
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_intent() {
+        let intent = parse_intent("List all EC2 instances").unwrap();
+        assert_eq!(intent.action, IntentAction::List);
+        assert_eq!(intent.words, vec!["EC2", "instances"]);
+        assert!(intent.confidence >= TEMPLATE_MIN_CONFIDENCE);
+
+        assert!(parse_intent("how do I list instances").is_none());
+        assert!(parse_intent("list").is_none());
+        assert!(parse_intent("show instances in us-east-1 tagged with team equals web").unwrap().confidence < TEMPLATE_MIN_CONFIDENCE);
+    }
+
+    #[test]
+    fn test_fill_templates() {
+        let templates = CommandTemplates::builtin();
+
+        let list = parse_intent("list ec2 instances").unwrap();
+        assert_eq!(templates.fill(CloudProviderType::AWS, &list).as_deref(), Some("aws ec2 describe-instances"));
+        assert_eq!(templates.fill(CloudProviderType::GCP, &list), None);
+
+        let delete = parse_intent("delete bucket My-Logs").unwrap();
+        assert_eq!(templates.fill(CloudProviderType::AWS, &delete).as_deref(), Some("aws s3 rb s3://My-Logs"));
+
+        // Extra words the template cannot express fall through to the LLM
+        let detailed = parse_intent("list ec2 instances running").unwrap();
+        assert_eq!(templates.fill(CloudProviderType::AWS, &detailed), None);
+    }
+}
@@ -0,0 +1,8 @@
+//! Shared test doubles for CLI tests
+
+use crate::rag::{LocalDocumentIndexer, LocalRAGEngine, LocalVectorStore};
+
+pub use super::mock_llm::MockLLM;
+
+/// RAG engine type used when a test does not need RAG
+pub type TestRag = LocalRAGEngine<LocalVectorStore, LocalDocumentIndexer<LocalVectorStore>>;
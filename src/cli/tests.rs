@@ -2,7 +2,7 @@
 
 #[cfg(test)]
 mod snapshot_tests {
-    use super::{QualityAnalyzer, CommandLearningEngine};
+    use crate::cli::{QualityAnalyzer, CommandLearningEngine};
     use insta::assert_yaml_snapshot;
     use tempfile::NamedTempFile;
 
@@ -32,7 +32,7 @@ mod snapshot_tests {
     fn test_quality_analyzer_validation() {
         let analyzer = QualityAnalyzer::new();
 
-        let validations = vec![
+        let validations = [
             ("ibmcloud resource groups", true),
             ("ibmcloud login", true),
             ("error", false),
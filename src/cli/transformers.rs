@@ -0,0 +1,119 @@
+//! Built-in command transformers
+
+use std::collections::HashMap;
+use crate::core::{has_flag, CloudProviderType, CommandTransformer, Result};
+
+/// Appends a flag to every command for a provider that does not already set it
+pub struct FlagInjector {
+    provider: CloudProviderType,
+    flag: String,
+    value: String,
+}
+
+impl FlagInjector {
+    /// Inject `flag value` into commands for `provider`
+    pub fn new(provider: CloudProviderType, flag: &str, value: &str) -> Self {
+        Self {
+            provider,
+            flag: flag.to_string(),
+            value: value.to_string(),
+        }
+    }
+}
+
+impl CommandTransformer for FlagInjector {
+    fn transform(&self, command: &str, provider: CloudProviderType) -> Result<String> {
+        if provider != self.provider || has_flag(command, &self.flag) {
+            return Ok(command.to_string());
+        }
+        Ok(format!("{} {} {}", command.trim_end(), self.flag, self.value))
+    }
+}
+
+/// Replaces region values, e.g. to steer commands away from a retired region
+pub struct RegionMapper {
+    flag: String,
+    regions: HashMap<String, String>,
+}
+
+impl RegionMapper {
+    /// Map values of `--region` using `regions` (from → to)
+    pub fn new(regions: HashMap<String, String>) -> Self {
+        Self::with_flag("--region", regions)
+    }
+
+    /// Map values of a different flag, such as `--location` for Azure
+    pub fn with_flag(flag: &str, regions: HashMap<String, String>) -> Self {
+        Self {
+            flag: flag.to_string(),
+            regions,
+        }
+    }
+
+    fn map(&self, region: &str) -> String {
+        self.regions.get(region).cloned().unwrap_or_else(|| region.to_string())
+    }
+}
+
+impl CommandTransformer for RegionMapper {
+    fn transform(&self, command: &str, _provider: CloudProviderType) -> Result<String> {
+        let prefix = format!("{}=", self.flag);
+        let mut tokens: Vec<String> = Vec::new();
+        let mut map_next = false;
+
+        for token in command.split_whitespace() {
+            if map_next {
+                tokens.push(self.map(token));
+                map_next = false;
+            } else if token == self.flag {
+                tokens.push(token.to_string());
+                map_next = true;
+            } else if let Some(region) = token.strip_prefix(&prefix) {
+                tokens.push(format!("{}{}", prefix, self.map(region)));
+            } else {
+                tokens.push(token.to_string());
+            }
+        }
+
+        Ok(tokens.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flag_injector() {
+        let injector = FlagInjector::new(CloudProviderType::AWS, "--profile", "corp");
+        assert_eq!(
+            injector.transform("aws s3 ls", CloudProviderType::AWS).unwrap(),
+            "aws s3 ls --profile corp"
+        );
+        assert_eq!(
+            injector.transform("aws s3 ls --profile dev", CloudProviderType::AWS).unwrap(),
+            "aws s3 ls --profile dev"
+        );
+        assert_eq!(
+            injector.transform("az vm list", CloudProviderType::Azure).unwrap(),
+            "az vm list"
+        );
+    }
+
+    #[test]
+    fn test_region_mapper() {
+        let mapper = RegionMapper::new(HashMap::from([("us-west-1".to_string(), "us-west-2".to_string())]));
+        assert_eq!(
+            mapper.transform("aws ec2 describe-instances --region us-west-1", CloudProviderType::AWS).unwrap(),
+            "aws ec2 describe-instances --region us-west-2"
+        );
+        assert_eq!(
+            mapper.transform("aws s3 ls --region=us-west-1", CloudProviderType::AWS).unwrap(),
+            "aws s3 ls --region=us-west-2"
+        );
+        assert_eq!(
+            mapper.transform("aws s3 ls --region eu-west-1", CloudProviderType::AWS).unwrap(),
+            "aws s3 ls --region eu-west-1"
+        );
+    }
+}
@@ -1,17 +1,224 @@
 //! Command translator for converting natural language to IBM Cloud CLI commands
 
-use crate::core::{LLMProvider, GenerationConfig, RAGEngine, RAGQuery, Result};
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::{Mutex, RwLock};
+use crate::core::{
+    LLMProvider, GenerationConfig, GenerationResult, RAGEngine, RAGQuery, CloudProvider, CloudProviderType,
+    CommandTransformer, QualityAnalysis, VectorDocument, ParsedCommand, Error, Result, infer_provider_from_command,
+};
+use crate::providers::create_provider;
+use crate::providers::ibmcloud::TargetContext;
+use super::{redact_secrets, CliError, CliResult, CommandResult};
+use super::rate_limit::RateLimiter;
+use super::usage::UsageLog;
+use super::normalize::normalize_query;
+use super::intent::{IntentDetector, INTENT_MIN_CONFIDENCE};
+use super::session::{is_follow_up, SessionMemory};
+use super::templates::{parse_intent, CommandTemplates, TEMPLATE_MIN_CONFIDENCE};
+use super::prompt_template::{PromptTemplate, PromptValues};
+use super::pipeline::Pipeline;
+
+/// Maximum number of completion suggestions returned
+const MAX_COMPLETIONS: usize = 5;
+
+/// Maximum number of words in a completion extracted from documentation
+const MAX_COMPLETION_WORDS: usize = 6;
+
+/// Success rate assumed for a command the first time it is executed
+const INITIAL_SUCCESS_RATE: f32 = 0.5;
+
+/// How far a single execution outcome moves a command's success rate
+const SUCCESS_RATE_STEP: f32 = 0.1;
+
+/// Most candidates `translate_alternatives` asks the LLM for
+const MAX_ALTERNATIVES: usize = 5;
+
+/// Most steps `translate_plan` asks the LLM for
+const MAX_PLAN_STEPS: usize = 8;
+
+/// Maximum characters of session context added to each prompt
+const MAX_SESSION_CONTEXT_CHARS: usize = 2000;
+
+/// Tokens a translated command may use unless `with_max_tokens` overrides it
+const DEFAULT_TRANSLATION_MAX_TOKENS: u32 = 200;
+
+/// Maximum characters of command output sent to the LLM for summarizing
+const MAX_SUMMARY_INPUT_CHARS: usize = 6000;
+
+/// How many chunks each kind of retrieval asks for, and the lowest score it keeps
+///
+/// Scores are the vector store's own similarity scores, so the thresholds
+/// filter on the same scale the store ranks by.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RagRetrievalConfig {
+    /// Chunks retrieved as translation context
+    pub translation_top_k: usize,
+    /// Lowest score of a chunk used as translation context
+    pub min_document_score: f32,
+    /// Chunks scanned for command completions
+    pub completion_top_k: usize,
+    /// Lowest score of a chunk scanned for completions
+    pub completion_min_score: f32,
+    /// Chunks quoted in recovery suggestions
+    pub recovery_top_k: usize,
+    /// Lowest score of a chunk quoted in recovery suggestions
+    pub recovery_min_score: f32,
+}
+
+impl Default for RagRetrievalConfig {
+    fn default() -> Self {
+        Self {
+            translation_top_k: 3,
+            min_document_score: 0.1,
+            completion_top_k: 10,
+            completion_min_score: 0.1,
+            recovery_top_k: 2,
+            recovery_min_score: 0.5,
+        }
+    }
+}
+
+/// Best retrieval score needed before RAG context is added to the prompt
+pub const DEFAULT_RAG_MIN_CONFIDENCE: f32 = 0.5;
+
+/// Shown when retrieval found nothing relevant enough to use
+pub const NO_RELEVANT_DOCS_NOTE: &str = "No relevant documentation found — answering from model knowledge only";
+
+/// Append the provider's configured global flags that a command is missing
+///
+/// Only commands for the provider's own CLI are changed, and only with flags
+/// the provider says the command accepts. A flag counts as present in both
+/// `--flag value` and `--flag=value` form. In a pipeline the flags go on the
+/// provider command, before the first `|`.
+pub fn postprocess_command(command: &str, provider: &dyn CloudProvider) -> String {
+    let (command, rest) = Pipeline::split_first_stage(command.trim());
+    let command = command.trim_end();
+    let parsed = match ParsedCommand::parse(command) {
+        Ok(parsed) if parsed.binary == provider.provider_type().cli_command() => parsed,
+        _ => return join_stages(command.to_string(), rest),
+    };
+
+    let mut processed = command.to_string();
+    for (flag, value) in provider.flags_for_command(command) {
+        if !parsed.has_flag(&flag) {
+            processed.push_str(&format!(" {} {}", flag, value));
+        }
+    }
+    join_stages(processed, rest)
+}
+
+/// Whether two commands differ only in spacing outside quoted arguments
+fn same_command(a: &str, b: &str) -> bool {
+    match (ParsedCommand::parse(a), ParsedCommand::parse(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Put a rewritten provider command back in front of the rest of its pipeline
+fn join_stages(command: String, rest: &str) -> String {
+    if rest.is_empty() {
+        command
+    } else {
+        format!("{} {}", command, rest)
+    }
+}
+
+/// What went into the most recent translation, shown by the `why` command
+#[derive(Debug, Clone, Default)]
+pub struct LastTranslation {
+    /// The query after secret redaction
+    pub query: String,
+    /// The query as used for template matching and retrieval, when
+    /// normalization changed it; the LLM gets the query as typed
+    pub normalized_query: Option<String>,
+    /// Prompt sent to the LLM, or `None` when a template answered
+    pub prompt: Option<String>,
+    /// Documentation used as context, best match first
+    pub sources: Vec<PromptSource>,
+    /// Best retrieval score, when RAG was consulted
+    pub rag_confidence: Option<f32>,
+    /// Whether the retrieved documentation was added to the prompt
+    pub rag_used: bool,
+    /// RAG was consulted but nothing reached the confidence threshold, so
+    /// the command came from model knowledge alone
+    pub no_relevant_docs: bool,
+    /// Retrieved chunks left out for scoring below the confidence threshold
+    pub rag_skipped: usize,
+    pub command: String,
+    /// Quality analysis, once the caller has scored the command
+    pub quality: Option<QualityAnalysis>,
+    /// Learned commands that were surfaced alongside the translation
+    pub learning_hints: Vec<String>,
+}
+
+/// A documentation chunk added to a prompt
+#[derive(Debug, Clone, PartialEq)]
+pub struct PromptSource {
+    /// Title or URL of the chunk
+    pub name: String,
+    /// Retrieval score
+    pub score: f32,
+}
+
+/// A translation prompt and how RAG contributed to it
+struct PreparedPrompt {
+    text: String,
+    sources: Vec<PromptSource>,
+    rag_confidence: Option<f32>,
+    rag_skipped: usize,
+}
+
+/// Describe where a retrieved chunk came from, and how well it matched
+fn document_source(document: &VectorDocument) -> PromptSource {
+    let name = ["url", "title"]
+        .iter()
+        .find_map(|key| document.metadata.get(*key).and_then(|value| value.as_str()))
+        .unwrap_or(&document.id)
+        .to_string();
+    PromptSource { name, score: document.score.unwrap_or(0.0) }
+}
 
 /// Command translator that uses LLM and RAG to translate natural language to CLI commands
 pub struct CommandTranslator<L: LLMProvider, R: RAGEngine> {
     llm: L,
     rag: Option<R>,
+    success_rates: RwLock<HashMap<String, f32>>,
+    templates: CommandTemplates,
+    rate_limiter: Option<RateLimiter>,
+    transformers: Vec<Box<dyn CommandTransformer>>,
+    session_context: Option<String>,
+    last_translation: RwLock<Option<LastTranslation>>,
+    session_memory: RwLock<SessionMemory>,
+    rag_min_confidence: f32,
+    rag_retrieval: RagRetrievalConfig,
+    usage_log: Option<Mutex<UsageLog>>,
+    ibmcloud_target: Option<TargetContext>,
+    max_tokens: u32,
+    prompt_template: PromptTemplate,
 }
 
 impl<L: LLMProvider, R: RAGEngine> CommandTranslator<L, R> {
     /// Create a new command translator
     pub fn new(llm: L) -> Self {
-        Self { llm, rag: None }
+        Self {
+            llm,
+            rag: None,
+            success_rates: RwLock::new(HashMap::new()),
+            templates: CommandTemplates::builtin(),
+            rate_limiter: None,
+            transformers: Vec::new(),
+            session_context: None,
+            last_translation: RwLock::new(None),
+            session_memory: RwLock::new(SessionMemory::default()),
+            rag_min_confidence: DEFAULT_RAG_MIN_CONFIDENCE,
+            rag_retrieval: RagRetrievalConfig::default(),
+            usage_log: None,
+            ibmcloud_target: None,
+            max_tokens: DEFAULT_TRANSLATION_MAX_TOKENS,
+            prompt_template: PromptTemplate::default(),
+        }
     }
 
     /// Create with RAG support
@@ -19,53 +226,574 @@ impl<L: LLMProvider, R: RAGEngine> CommandTranslator<L, R> {
         Self {
             llm,
             rag: Some(rag),
+            success_rates: RwLock::new(HashMap::new()),
+            templates: CommandTemplates::builtin(),
+            rate_limiter: None,
+            transformers: Vec::new(),
+            session_context: None,
+            last_translation: RwLock::new(None),
+            session_memory: RwLock::new(SessionMemory::default()),
+            rag_min_confidence: DEFAULT_RAG_MIN_CONFIDENCE,
+            rag_retrieval: RagRetrievalConfig::default(),
+            usage_log: None,
+            ibmcloud_target: None,
+            max_tokens: DEFAULT_TRANSLATION_MAX_TOKENS,
+            prompt_template: PromptTemplate::default(),
+        }
+    }
+
+    /// Limit LLM calls to `requests_per_minute`, waiting when over the limit
+    pub fn with_rate_limit(mut self, requests_per_minute: u32) -> Self {
+        self.rate_limiter = Some(RateLimiter::new(requests_per_minute));
+        self
+    }
+
+    /// Count every LLM request in `log`, saving it after each one
+    pub fn with_usage_log(mut self, log: UsageLog) -> Self {
+        self.usage_log = Some(Mutex::new(log));
+        self
+    }
+
+    /// Let each translated command use up to `max_tokens` tokens
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = max_tokens;
+        self
+    }
+
+    /// Tell IBM Cloud prompts which account, region and resource group are targeted
+    pub fn with_ibmcloud_target(mut self, target: TargetContext) -> Self {
+        self.ibmcloud_target = Some(target);
+        self
+    }
+
+    /// Only add retrieved chunks scoring at least `threshold` as RAG context
+    ///
+    /// When none does the bare prompt is used, since irrelevant documentation
+    /// can mislead the model.
+    pub fn with_rag_confidence_threshold(mut self, threshold: f32) -> Self {
+        self.rag_min_confidence = threshold;
+        self
+    }
+
+    /// Retrieve chunks with these limits instead of the defaults
+    pub fn with_rag_retrieval(mut self, config: RagRetrievalConfig) -> Self {
+        self.rag_retrieval = config;
+        self
+    }
+
+    /// Add extra context to every translation prompt for this session only
+    ///
+    /// The text is redacted and truncated to `MAX_SESSION_CONTEXT_CHARS`; it
+    /// is never written to the vector store.
+    pub fn with_context(mut self, context: &str) -> Self {
+        let context = redact_secrets(context.trim());
+        let context = match context.char_indices().nth(MAX_SESSION_CONTEXT_CHARS) {
+            Some((end, _)) => context[..end].to_string(),
+            None => context,
+        };
+        self.session_context = Some(context).filter(|context| !context.is_empty());
+        self
+    }
+
+    /// Translate with a custom prompt instead of the built-in one
+    pub fn with_prompt_template(mut self, template: PromptTemplate) -> Self {
+        self.prompt_template = template;
+        self
+    }
+
+    /// Register a transformer applied to every translated command, in order
+    pub fn with_transformer(mut self, transformer: impl CommandTransformer + 'static) -> Self {
+        self.transformers.push(Box::new(transformer));
+        self
+    }
+
+    /// Run the transformer chain over a validated command
+    ///
+    /// Without an explicit provider, it is inferred from the command's CLI;
+    /// commands for no known CLI are left alone. Only the provider command
+    /// of a pipeline is transformed, not the tools it pipes into.
+    fn apply_transformers(&self, command: String, provider: Option<CloudProviderType>) -> CliResult<String> {
+        let Some(provider) = provider.or_else(|| infer_provider_from_command(&command)) else {
+            return Ok(command);
+        };
+        if self.transformers.is_empty() {
+            return Ok(command);
+        }
+
+        let (first, rest) = Pipeline::split_first_stage(&command);
+        let mut first = first.trim_end().to_string();
+        for transformer in &self.transformers {
+            first = transformer.transform(&first, provider)?;
+        }
+        Ok(join_stages(first, rest))
+    }
+
+    /// Shared clean-up of a generated command for `provider`
+    ///
+    /// Adds the provider's global flags, then runs the transformer chain.
+    /// Spacing and quoting inside the command are kept as generated.
+    fn finish_command(&self, command: &str, provider: &dyn CloudProvider) -> CliResult<String> {
+        let command = postprocess_command(command.trim(), provider);
+        self.apply_transformers(command, Some(provider.provider_type()))
+    }
+
+    /// Call the LLM once the rate limiter allows it
+    async fn generate(&self, prompt: &str, config: &GenerationConfig) -> Result<GenerationResult> {
+        if let Some(ref limiter) = self.rate_limiter {
+            limiter.acquire().await;
+        }
+        let result = self.llm.generate_with_config(prompt, config).await?;
+
+        if let Some(ref usage_log) = self.usage_log
+            && let Ok(mut log) = usage_log.lock()
+        {
+            log.record(chrono::Local::now().date_naive(), &result.model_id, result.tokens_used);
+            if let Err(e) = log.save() {
+                eprintln!("Warning: Failed to save usage: {}", e);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Remember an executed command so follow-up queries can refer to it
+    pub fn remember_turn(&self, query: &str, command: &str, result: &CommandResult) {
+        if let Ok(mut memory) = self.session_memory.write() {
+            memory.record(&redact_secrets(query), &redact_secrets(command), result);
+        }
+    }
+
+    /// Recent commands for the prompt, when `query` reads like a follow-up to them
+    fn follow_up_context(&self, query: &str) -> Option<String> {
+        if !is_follow_up(query) {
+            return None;
+        }
+        let memory = self.session_memory.read().ok()?;
+        (!memory.is_empty()).then(|| memory.summary())
+    }
+
+    /// Record the real outcome of executing a command
+    ///
+    /// Each success or failure nudges the command's success rate by 0.1.
+    pub fn update_command_success(&self, command: &str, success: bool) {
+        if let Ok(mut rates) = self.success_rates.write() {
+            let rate = rates.entry(command.trim().to_string()).or_insert(INITIAL_SUCCESS_RATE);
+            let delta = if success { SUCCESS_RATE_STEP } else { -SUCCESS_RATE_STEP };
+            *rate = (*rate + delta).clamp(0.0, 1.0);
+        }
+    }
+
+    /// Get the learned success rate of a command, if it was ever executed
+    pub fn get_command_success_rate(&self, command: &str) -> Option<f32> {
+        self.success_rates
+            .read()
+            .ok()
+            .and_then(|rates| rates.get(command.trim()).copied())
+    }
+
+    /// Get what went into the most recent successful translation
+    pub fn last_translation(&self) -> Option<LastTranslation> {
+        self.last_translation.read().ok().and_then(|last| last.clone())
+    }
+
+    /// Add details known only to the caller, such as quality, to the last translation
+    pub fn update_last_translation(&self, update: impl FnOnce(&mut LastTranslation)) {
+        if let Ok(mut last) = self.last_translation.write()
+            && let Some(last) = last.as_mut()
+        {
+            update(last);
+        }
+    }
+
+    fn record_translation(&self, translation: LastTranslation) {
+        if let Ok(mut last) = self.last_translation.write() {
+            *last = Some(translation);
         }
     }
 
     /// Translate a natural language query to an IBM Cloud CLI command
-    pub async fn translate(&self, query: &str) -> Result<String> {
-        let prompt = self.build_prompt(query).await?;
+    pub async fn translate(&self, query: &str) -> CliResult<String> {
+        self.translate_with_scope(query, None).await
+    }
+
+    /// Translate a query using only documentation indexed for `provider`
+    pub async fn translate_for_provider(&self, query: &str, provider: CloudProviderType) -> CliResult<String> {
+        self.translate_with_scope(query, Some(provider)).await
+    }
+
+    async fn translate_with_scope(&self, query: &str, provider: Option<CloudProviderType>) -> CliResult<String> {
+        let query = redact_secrets(query);
+        // Typos and case hurt matching; the LLM still gets the query as typed,
+        // since names such as bucket IDs are case-sensitive
+        let normalized = normalize_query(&query);
+
+        // Routine requests are answered from templates without the LLM, unless
+        // they refer back to an earlier command the template cannot see
+        if let Some(provider) = provider.filter(|_| self.follow_up_context(&query).is_none())
+            && let Some(command) = parse_intent(&normalized)
+                .filter(|intent| intent.confidence >= TEMPLATE_MIN_CONFIDENCE)
+                .and_then(|intent| self.templates.fill(provider, &intent))
+        {
+            let command = self.apply_transformers(command, Some(provider))?;
+            self.record_translation(LastTranslation {
+                query: query.clone(),
+                command: command.clone(),
+                ..Default::default()
+            });
+            return Ok(command);
+        }
+
+        let prompt = self.build_prompt(&query, provider).await?;
 
         let config = GenerationConfig {
             model_id: self.llm.model_id().to_string(),
-            max_tokens: 200,
+            max_tokens: self.max_tokens,
             ..Default::default()
         };
 
-        let result = self.llm.generate_with_config(&prompt, &config).await?;
-        Ok(result.text)
+        let result = self.generate(&prompt.text, &config).await?;
+        if result.text.trim().is_empty() {
+            return Err(CliError::TranslationFailed("LLM returned an empty command".to_string()));
+        }
+        let command = self.apply_transformers(result.text, provider)?;
+        self.record_translation(LastTranslation {
+            normalized_query: Some(normalized).filter(|normalized| *normalized != query),
+            query,
+            rag_used: !prompt.sources.is_empty(),
+            no_relevant_docs: prompt.rag_confidence.is_some() && prompt.sources.is_empty(),
+            prompt: Some(prompt.text),
+            sources: prompt.sources,
+            rag_confidence: prompt.rag_confidence,
+            rag_skipped: prompt.rag_skipped,
+            command: command.clone(),
+            ..Default::default()
+        });
+        Ok(command)
     }
 
-    /// Build the prompt with optional RAG context
-    async fn build_prompt(&self, query: &str) -> Result<String> {
-        let base_prompt = format!(
-            "You are an IBM Cloud CLI expert. Translate the following natural language query into a valid IBM Cloud CLI command.\n\
-            Only output the command itself, nothing else.\n\
-            \n\
-            Query: {}\n\
-            Command:",
-            query
+    /// Ask for up to `n` distinct commands for a query, best first
+    ///
+    /// The LLM is asked once per candidate, each time told which commands it
+    /// already suggested. Retrieval is limited to `provider`, and candidates
+    /// that fail its validation are dropped; duplicates differing only in
+    /// spacing outside quotes are collapsed. The rest are ranked by the LLM's
+    /// quality assessment.
+    pub async fn translate_alternatives(&self, query: &str, n: usize, provider: CloudProviderType) -> CliResult<Vec<String>> {
+        let query = redact_secrets(query);
+        let cloud_provider = create_provider(provider);
+        let config = GenerationConfig {
+            model_id: self.llm.model_id().to_string(),
+            max_tokens: self.max_tokens,
+            temperature: Some(0.7), // Higher temperature for varied candidates
+            ..Default::default()
+        };
+
+        let mut suggested: Vec<String> = Vec::new();
+        let mut candidates: Vec<String> = Vec::new();
+        for _ in 0..n.min(MAX_ALTERNATIVES) {
+            let avoid = if suggested.is_empty() {
+                String::new()
+            } else {
+                format!(
+                    "Already suggested, give a different command:\n{}\n\n",
+                    suggested.iter().map(|command| format!("- {}", command)).collect::<Vec<_>>().join("\n")
+                )
+            };
+            let prompt = self.build_prompt_with(&query, Some(provider), &avoid).await?;
+
+            let result = self.generate(&prompt.text, &config).await?;
+            let command = result.text.trim().to_string();
+            if command.is_empty() || suggested.iter().any(|seen| same_command(seen, &command)) {
+                continue;
+            }
+            suggested.push(command.clone());
+
+            if cloud_provider.validate(&command).is_ok() {
+                let command = self.finish_command(&command, cloud_provider.as_ref())?;
+                if !candidates.contains(&command) {
+                    candidates.push(command);
+                }
+            }
+        }
+
+        let mut ranked: Vec<(String, f32)> = candidates
+            .into_iter()
+            .map(|command| {
+                let score = self.llm.assess_quality(&command, &query);
+                (command, score)
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+        Ok(ranked.into_iter().map(|(command, _)| command).collect())
+    }
+
+    /// Break a query into the ordered commands that carry it out
+    ///
+    /// The LLM is asked for one step at a time, told the steps so far, until
+    /// it answers `DONE`, repeats a step or `MAX_PLAN_STEPS` is reached. Every
+    /// step must pass the provider's validation; a plan with an invalid step
+    /// is rejected as a whole, since later steps may depend on it.
+    pub async fn translate_plan(&self, query: &str, provider: CloudProviderType) -> CliResult<Vec<String>> {
+        let query = redact_secrets(query);
+        let cloud_provider = create_provider(provider);
+        let config = GenerationConfig {
+            model_id: self.llm.model_id().to_string(),
+            max_tokens: self.max_tokens,
+            ..Default::default()
+        };
+
+        let mut steps: Vec<String> = Vec::new();
+        while steps.len() < MAX_PLAN_STEPS {
+            let so_far = if steps.is_empty() {
+                String::new()
+            } else {
+                format!(
+                    "Commands so far:\n{}\n",
+                    steps.iter().enumerate().map(|(i, step)| format!("{}. {}", i + 1, step)).collect::<Vec<_>>().join("\n")
+                )
+            };
+            let instructions = format!(
+                "The query may take several {} commands, run in order.\n{}\
+                Output only the next command, or DONE when the commands so far complete the query.\n\n",
+                provider.cli_command(),
+                so_far
+            );
+            let prompt = self.build_prompt_with(&query, Some(provider), &instructions).await?;
+
+            let result = self.generate(&prompt.text, &config).await?;
+            let command = result.text.trim();
+            if command.is_empty() || command.eq_ignore_ascii_case("done") {
+                break;
+            }
+            cloud_provider
+                .validate(command)
+                .map_err(|e| CliError::TranslationFailed(format!("step {} ({}) is invalid: {}", steps.len() + 1, command, e)))?;
+            let command = self.finish_command(command, cloud_provider.as_ref())?;
+            if steps.iter().any(|step| same_command(step, &command)) {
+                break;
+            }
+            steps.push(command);
+        }
+
+        if steps.is_empty() {
+            return Err(CliError::TranslationFailed("LLM returned an empty plan".to_string()));
+        }
+        Ok(steps)
+    }
+
+    /// Port `command` from one provider's CLI to the equivalent on another
+    ///
+    /// The translation prompt for the target provider gets the source command
+    /// and both providers' reference context, so the LLM can map services
+    /// across clouds. The result must pass the target provider's validation.
+    pub async fn port_command(&self, command: &str, from: CloudProviderType, to: CloudProviderType) -> CliResult<String> {
+        let source = create_provider(from);
+        let target = create_provider(to);
+        source
+            .validate(command)
+            .map_err(|e| CliError::ValidationFailed(format!("not a {} command: {}", from, e)))?;
+
+        let command = redact_secrets(command);
+        let instructions = format!(
+            "{}\n{}\n{} command to port: {}\n\n",
+            source.get_rag_context(),
+            target.get_rag_context(),
+            from,
+            command
         );
+        let query = format!("do the same as the {} command `{}` on {}", from, command, to);
+        let prompt = self.build_prompt_with(&query, Some(to), &instructions).await?;
+        let config = GenerationConfig {
+            model_id: self.llm.model_id().to_string(),
+            max_tokens: self.max_tokens,
+            ..Default::default()
+        };
 
-        if let Some(ref rag) = self.rag {
-            if rag.is_ready() {
-                let rag_query = RAGQuery {
-                    query: query.to_string(),
-                    top_k: 3,
-                    score_threshold: Some(0.5),
-                    filters: None,
-                };
+        let result = self.generate(&prompt.text, &config).await?;
+        let ported = result.text.trim();
+        if ported.is_empty() {
+            return Err(CliError::TranslationFailed("LLM returned an empty command".to_string()));
+        }
+        target
+            .validate(ported)
+            .map_err(|e| CliError::ValidationFailed(format!("ported command is not valid for {}: {}", to, e)))?;
+        self.finish_command(ported, target.as_ref())
+    }
 
-                return rag.enhance_prompt(&base_prompt, &rag_query).await;
+    /// Build the prompt with optional RAG context
+    ///
+    /// Retrieval and intent detection use the normalized query; the prompt
+    /// quotes `query` as given. With a provider, retrieval is limited to
+    /// chunks tagged for it. Only chunks scoring at least the confidence
+    /// threshold go into the context, so with none left the prompt has no
+    /// context at all; the returned sources are those actually included.
+    async fn build_prompt(&self, query: &str, provider: Option<CloudProviderType>) -> Result<PreparedPrompt> {
+        self.build_prompt_with(query, provider, "").await
+    }
+
+    /// Build the prompt with `instructions` added to its context
+    ///
+    /// Generations are reduced to a single command, so requests for several
+    /// commands ask once per command and use `instructions` to say what came
+    /// before, e.g. the commands already suggested.
+    async fn build_prompt_with(
+        &self,
+        query: &str,
+        provider: Option<CloudProviderType>,
+        instructions: &str,
+    ) -> Result<PreparedPrompt> {
+        let session_context = self
+            .session_context
+            .as_ref()
+            .map(|context| format!("Additional context:\n{}\n\n", context))
+            .unwrap_or_default();
+        let target_context = self
+            .ibmcloud_target
+            .as_ref()
+            .filter(|_| provider.is_none_or(|provider| provider == CloudProviderType::IBMCloud))
+            .map(TargetContext::describe)
+            .filter(|target| !target.is_empty())
+            .map(|target| format!("Current IBM Cloud target:\n{}\n", target))
+            .unwrap_or_default();
+        let follow_up_context = self.follow_up_context(query).unwrap_or_default();
+        let normalized = normalize_query(query);
+        let intent_hint = match IntentDetector::classify(&normalized) {
+            (intent, confidence) if confidence >= INTENT_MIN_CONFIDENCE => intent.prompt_hint(),
+            _ => None,
+        }
+        .map(|hint| format!("{}\n", hint))
+        .unwrap_or_default();
+        let base_prompt = self.prompt_template.render(&PromptValues {
+            provider: provider.map_or("cloud", |provider| provider.display_name()),
+            context: &format!("{}{}{}{}", intent_hint, target_context, session_context, instructions),
+            examples: &follow_up_context,
+            query,
+        });
+
+        if let Some(ref rag) = self.rag
+            && rag.is_ready()
+        {
+            let rag_query = RAGQuery {
+                query: normalized,
+                top_k: self.rag_retrieval.translation_top_k,
+                score_threshold: Some(self.rag_retrieval.min_document_score),
+                filters: provider.map(|provider| vec![("provider".to_string(), provider.id().to_string())]),
+            };
+
+            let rag_result = rag.retrieve(&rag_query).await?;
+            let confidence = rag_result
+                .documents
+                .iter()
+                .filter_map(|document| document.score)
+                .fold(0.0, f32::max);
+            let (included, skipped): (Vec<VectorDocument>, Vec<VectorDocument>) = rag_result
+                .documents
+                .into_iter()
+                .partition(|document| document.score.unwrap_or(0.0) >= self.rag_min_confidence);
+
+            if !included.is_empty() {
+                // Same layout as `enhance_prompt`, keeping the documents for `why`
+                return Ok(PreparedPrompt {
+                    text: format!(
+                        "{}\n---\n\nBased on the above documentation, {}",
+                        rag.build_context(&included), base_prompt
+                    ),
+                    sources: included.iter().map(document_source).collect(),
+                    rag_confidence: Some(confidence),
+                    rag_skipped: skipped.len(),
+                });
             }
+
+            return Ok(PreparedPrompt {
+                text: base_prompt,
+                sources: Vec::new(),
+                rag_confidence: Some(confidence),
+                rag_skipped: skipped.len(),
+            });
         }
 
-        Ok(base_prompt)
+        Ok(PreparedPrompt {
+            text: base_prompt,
+            sources: Vec::new(),
+            rag_confidence: None,
+            rag_skipped: 0,
+        })
+    }
+
+    /// The LLM used for every generation
+    pub fn llm(&self) -> &L {
+        &self.llm
     }
 
     /// Check if RAG is available
     pub fn has_rag(&self) -> bool {
-        self.rag.as_ref().map_or(false, |r| r.is_ready())
+        self.rag.as_ref().is_some_and(|r| r.is_ready())
+    }
+
+    /// Suggest full commands that complete a partially typed command
+    ///
+    /// Candidates come from the provider's known command patterns and from
+    /// commands mentioned in indexed documentation, ranked by relevance.
+    pub async fn suggest_completions(&self, partial: &str) -> Result<Vec<String>> {
+        let partial = partial.trim().to_lowercase();
+        let Some(cli) = partial.split_whitespace().next() else {
+            return Ok(Vec::new());
+        };
+
+        let mut candidates: HashMap<String, f32> = HashMap::new();
+
+        if let Some(provider_type) = CloudProviderType::from_str(cli)
+            && provider_type.cli_command() == cli
+        {
+            for pattern in create_provider(provider_type).get_command_patterns() {
+                if is_completion_of(&pattern, &partial) {
+                    candidates.insert(pattern, 1.0);
+                }
+            }
+        }
+
+        if let Some(ref rag) = self.rag
+            && rag.is_ready()
+        {
+            let rag_query = RAGQuery {
+                query: partial.clone(),
+                top_k: self.rag_retrieval.completion_top_k,
+                score_threshold: Some(self.rag_retrieval.completion_min_score),
+                filters: None,
+            };
+
+            let rag_result = rag.retrieve(&rag_query).await?;
+            let command_regex = Regex::new(&format!(r"\b{}(?:[ \t]+[\w\-./:=<>]+)+", regex::escape(cli)))
+                .map_err(|e| Error::Other(e.to_string()))?;
+
+            for doc in &rag_result.documents {
+                let relevance = doc.score.unwrap_or(0.0);
+                for found in command_regex.find_iter(&doc.content.to_lowercase()) {
+                    let command = found.as_str()
+                        .split_whitespace()
+                        .take(MAX_COMPLETION_WORDS)
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    if is_completion_of(&command, &partial) {
+                        let entry = candidates.entry(command).or_insert(0.0);
+                        *entry = entry.max(relevance);
+                    }
+                }
+            }
+        }
+
+        let mut ranked: Vec<(String, f32)> = candidates.into_iter().collect();
+        ranked.sort_by(|a, b| {
+            b.1.partial_cmp(&a.1)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.0.len().cmp(&b.0.len()))
+                .then_with(|| a.0.cmp(&b.0))
+        });
+
+        Ok(ranked
+            .into_iter()
+            .take(MAX_COMPLETIONS)
+            .map(|(command, _)| command)
+            .collect())
     }
 
     /// Suggest recovery steps for a failed command
@@ -83,27 +811,30 @@ impl<L: LLMProvider, R: RAGEngine> CommandTranslator<L, R> {
         failed_command: &str,
         error_message: &str,
     ) -> Result<String> {
+        let original_query = redact_secrets(original_query);
+        let failed_command = redact_secrets(failed_command);
+        let error_message = redact_secrets(error_message);
+
         // Try to get RAG context for better suggestions
         let mut rag_context = String::new();
-        if let Some(ref rag) = self.rag {
-            if rag.is_ready() {
-                let rag_query = RAGQuery {
-                    query: format!("troubleshooting error: {}", error_message),
-                    top_k: 2,
-                    score_threshold: Some(0.5),
-                    filters: None,
-                };
-                
-                if let Ok(rag_result) = rag.retrieve(&rag_query).await {
-                    if !rag_result.documents.is_empty() {
-                        rag_context = format!("\n\nRELEVANT DOCUMENTATION:\n{}\n", 
-                            rag_result.documents.iter()
-                                .take(2)
-                                .map(|d| format!("- {}", d.content))
-                                .collect::<Vec<_>>()
-                                .join("\n"));
-                    }
-                }
+        if let Some(ref rag) = self.rag
+            && rag.is_ready()
+        {
+            let rag_query = RAGQuery {
+                query: format!("troubleshooting error: {}", error_message),
+                top_k: self.rag_retrieval.recovery_top_k,
+                score_threshold: Some(self.rag_retrieval.recovery_min_score),
+                filters: None,
+            };
+            
+            if let Ok(rag_result) = rag.retrieve(&rag_query).await
+                && !rag_result.documents.is_empty()
+            {
+                rag_context = format!("\n\nRELEVANT DOCUMENTATION:\n{}\n", 
+                    rag_result.documents.iter()
+                        .map(|d| format!("- {}", d.content))
+                        .collect::<Vec<_>>()
+                        .join("\n"));
             }
         }
 
@@ -158,17 +889,830 @@ impl<L: LLMProvider, R: RAGEngine> CommandTranslator<L, R> {
             model_id: self.llm.model_id().to_string(),
             max_tokens: 400,
             temperature: Some(0.3), // Lower temperature for more focused responses
+            raw: true, // Advice and several commands, one per line
             ..Default::default()
         };
 
-        let result = self.llm.generate_with_config(&prompt, &config).await?;
+        let result = self.generate(&prompt, &config).await?;
         Ok(result.text)
     }
+
+    /// Ask the LLM for a short summary of a command's output
+    ///
+    /// Returns `None` without calling the LLM when the command failed or
+    /// printed nothing. Output is redacted and truncated to
+    /// `MAX_SUMMARY_INPUT_CHARS`, and the prompt says when it was cut.
+    pub async fn summarize_output(&self, command: &str, result: &CommandResult) -> Result<Option<String>> {
+        let output = result.stdout.trim();
+        if !result.success || output.is_empty() {
+            return Ok(None);
+        }
+
+        let output = redact_secrets(output);
+        let (output, truncation_note) = match output.char_indices().nth(MAX_SUMMARY_INPUT_CHARS) {
+            Some((end, _)) => (
+                &output[..end],
+                format!(
+                    "\nNOTE: The output was truncated to its first {} of {} characters; say so if it matters.\n",
+                    MAX_SUMMARY_INPUT_CHARS,
+                    output.chars().count()
+                ),
+            ),
+            None => (output.as_str(), String::new()),
+        };
+
+        let prompt = format!(
+            "You are a cloud CLI expert. Summarize the resources in this command output.\n\
+            \n\
+            COMMAND: {}\n\
+            OUTPUT:\n{}\n\
+            {}\
+            \n\
+            Give a concise summary in at most five short lines: how many resources there are, \
+            their notable states, and anything that needs attention. Do not repeat the raw output.",
+            redact_secrets(command),
+            output,
+            truncation_note
+        );
+
+        let config = GenerationConfig {
+            model_id: self.llm.model_id().to_string(),
+            max_tokens: 300,
+            temperature: Some(0.3),
+            raw: true, // A summary spans several lines
+            ..Default::default()
+        };
+
+        let summary = self.generate(&prompt, &config).await?;
+        Ok(Some(summary.text.trim().to_string()))
+    }
+}
+
+/// Whether `candidate` is a longer command starting with `partial`
+fn is_completion_of(candidate: &str, partial: &str) -> bool {
+    candidate.len() > partial.len() && candidate.starts_with(partial)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::rag::{LocalDocumentIndexer, LocalRAGEngine, LocalVectorStore};
+    use crate::cli::test_support::{MockLLM, TestRag};
+
+    fn translator(llm: MockLLM) -> CommandTranslator<MockLLM, TestRag> {
+        CommandTranslator::new(llm)
+    }
+
+    #[tokio::test]
+    async fn test_session_context_appears_in_prompt() {
+        let translator = translator(MockLLM::replying("ibmcloud is buckets"))
+            .with_context("Our team stores logs in the cos-logs-prod bucket.");
+
+        let prompt = translator.build_prompt("list log buckets", None).await.unwrap().text;
+        assert!(prompt.contains("cos-logs-prod"));
+        assert!(prompt.find("cos-logs-prod") < prompt.find("Query: list log buckets"));
+
+        let plain = CommandTranslator::<MockLLM, TestRag>::new(MockLLM::replying("ibmcloud is buckets"));
+        assert!(!plain.build_prompt("list log buckets", None).await.unwrap().text.contains("Additional context"));
+    }
+
+    #[tokio::test]
+    async fn test_ibmcloud_target_appears_in_prompt() {
+        let target = TargetContext {
+            region: Some("eu-de".to_string()),
+            resource_group: Some("team-rg".to_string()),
+            ..Default::default()
+        };
+        let translator = translator(MockLLM::replying("ibmcloud is instances")).with_ibmcloud_target(target);
+
+        let prompt = translator.build_prompt("list instances", Some(CloudProviderType::IBMCloud)).await.unwrap().text;
+        assert!(prompt.contains("Targeted region: eu-de"));
+        assert!(prompt.contains("Targeted resource group: team-rg"));
+
+        let aws_prompt = translator.build_prompt("list instances", Some(CloudProviderType::AWS)).await.unwrap().text;
+        assert!(!aws_prompt.contains("team-rg"));
+    }
+
+    #[tokio::test]
+    async fn test_intent_hint_in_prompt() {
+        let translator = translator(MockLLM::replying("ibmcloud resource group-delete dev"));
+
+        let prompt = translator.build_prompt("delete the dev resource group", None).await.unwrap().text;
+        assert!(prompt.contains("Do not add flags that skip confirmation prompts."));
+
+        let prompt = translator.build_prompt("list resource groups", None).await.unwrap().text;
+        assert!(!prompt.contains("The user wants to"));
+    }
+
+    #[tokio::test]
+    async fn test_custom_prompt_template() {
+        let template = PromptTemplate::new("{provider} command for: {query}").unwrap();
+        let translator = translator(MockLLM::replying("aws ec2 describe-instances")).with_prompt_template(template);
+
+        let prompt = translator.build_prompt("show instances", Some(CloudProviderType::AWS)).await.unwrap().text;
+        assert_eq!(prompt, "AWS command for: show instances");
+        let prompt = translator.build_prompt("show instances", None).await.unwrap().text;
+        assert_eq!(prompt, "cloud command for: show instances");
+    }
+
+    #[tokio::test]
+    async fn test_prompt_keeps_the_query_as_typed() {
+        let translator = translator(MockLLM::new(|prompt| {
+            assert!(prompt.contains("Query: List objects in kubenetes bucket MyLogs-Prod\n"));
+            Ok("ibmcloud cos objects --bucket MyLogs-Prod".to_string())
+        }));
+
+        translator.translate("List objects in kubenetes bucket MyLogs-Prod").await.unwrap();
+        let last = translator.last_translation().unwrap();
+        assert_eq!(last.query, "List objects in kubenetes bucket MyLogs-Prod");
+        assert_eq!(last.normalized_query.as_deref(), Some("list objects in kubernetes bucket mylogs-prod"));
+    }
+
+    #[tokio::test]
+    async fn test_max_tokens_reaches_generation_config() {
+        let default_budget = translator(MockLLM::replying("ibmcloud resource groups"));
+        default_budget.translate("list resource groups").await.unwrap();
+        assert_eq!(default_budget.llm().configs().last().unwrap().max_tokens, DEFAULT_TRANSLATION_MAX_TOKENS);
+
+        let overridden = translator(MockLLM::replying("ibmcloud resource groups")).with_max_tokens(512);
+        overridden.translate("list resource groups").await.unwrap();
+        assert_eq!(overridden.llm().configs().last().unwrap().max_tokens, 512);
+    }
+
+    #[tokio::test]
+    async fn test_translate_alternatives_dedupes_and_drops_invalid() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let replies = ["aws s3 ls", "aws  s3   ls", "rm -rf /tmp/x", "aws s3api list-buckets", "aws s3 ls 'oops"];
+        let calls = AtomicUsize::new(0);
+        let translator = translator(MockLLM::new(move |prompt| {
+            let call = calls.fetch_add(1, Ordering::SeqCst);
+            if call > 0 {
+                assert!(prompt.contains("Already suggested, give a different command:\n- aws s3 ls"));
+            }
+            Ok(replies[call].to_string())
+        }));
+
+        let alternatives = translator.translate_alternatives("list my buckets", 5, CloudProviderType::AWS).await.unwrap();
+        assert_eq!(alternatives, vec!["aws s3 ls", "aws s3api list-buckets"]);
+        assert!(translator.translate_alternatives("list my buckets", 0, CloudProviderType::AWS).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_alternatives_are_tied_to_the_provider() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let replies = [
+            "aws s3 ls",
+            "az storage account list",
+            r#"az storage blob list --account-name logs --query "[?name == 'a  b']""#,
+        ];
+        let calls = AtomicUsize::new(0);
+        let translator = translator(MockLLM::new(move |_| Ok(replies[calls.fetch_add(1, Ordering::SeqCst)].to_string())));
+
+        let alternatives = translator.translate_alternatives("list my storage", 3, CloudProviderType::Azure).await.unwrap();
+        assert_eq!(alternatives.len(), 2);
+        assert!(alternatives.iter().all(|command| command.starts_with("az ")));
+        // Quoted arguments keep their spacing
+        assert!(alternatives.iter().any(|command| command.contains("'a  b'")), "{:?}", alternatives);
+    }
+
+    #[tokio::test]
+    async fn test_alternatives_use_the_translation_prompt() {
+        let translator = translator(MockLLM::new(|prompt| {
+            assert!(prompt.contains("Team buckets start with acme-"));
+            assert!(prompt.contains("Query: List My Buckets\n"));
+            Ok("aws s3 ls".to_string())
+        }))
+        .with_context("Team buckets start with acme-");
+
+        assert_eq!(translator.translate_alternatives("List My Buckets", 1, CloudProviderType::AWS).await.unwrap(), vec!["aws s3 ls"]);
+    }
+
+    #[tokio::test]
+    async fn test_translate_plan_asks_step_by_step() {
+        let translator = translator(MockLLM::new(|prompt| {
+            let reply = if prompt.contains("2. aws s3 cp") {
+                "DONE"
+            } else if prompt.contains("1. aws s3 mb") {
+                "aws s3 cp ./site s3://site-bucket --recursive"
+            } else {
+                "aws s3 mb s3://site-bucket"
+            };
+            Ok(reply.to_string())
+        }));
+
+        let plan = translator.translate_plan("upload my site to a new bucket", CloudProviderType::AWS).await.unwrap();
+        assert_eq!(plan, vec!["aws s3 mb s3://site-bucket", "aws s3 cp ./site s3://site-bucket --recursive"]);
+    }
+
+    #[tokio::test]
+    async fn test_translate_plan_uses_the_translation_prompt() {
+        let translator = translator(MockLLM::new(|prompt| {
+            assert!(prompt.contains("several aws commands"));
+            assert!(prompt.contains("Use the acme- bucket prefix"));
+            assert!(prompt.contains("Query: Upload MySite\n"));
+            Ok("DONE".to_string())
+        }))
+        .with_context("Use the acme- bucket prefix");
+
+        assert!(translator.translate_plan("Upload MySite", CloudProviderType::AWS).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_translate_plan_stops_on_repeat_and_rejects_invalid_steps() {
+        let repeating = translator(MockLLM::replying("aws s3 ls"));
+        assert_eq!(repeating.translate_plan("check my buckets", CloudProviderType::AWS).await.unwrap(), vec!["aws s3 ls"]);
+
+        let invalid = translator(MockLLM::replying("rm -rf /tmp/site"));
+        assert!(matches!(
+            invalid.translate_plan("clean up", CloudProviderType::AWS).await,
+            Err(CliError::TranslationFailed(_))
+        ));
+
+        let empty = translator(MockLLM::replying("DONE"));
+        assert!(empty.translate_plan("nothing", CloudProviderType::AWS).await.is_err());
+
+        let respaced = translator(MockLLM::new(|prompt| {
+            Ok(if prompt.contains("1. aws s3 ls") { "aws  s3   ls" } else { "aws s3 ls" }.to_string())
+        }));
+        assert_eq!(respaced.translate_plan("check my buckets", CloudProviderType::AWS).await.unwrap(), vec!["aws s3 ls"]);
+    }
+
+    #[tokio::test]
+    async fn test_translate_plan_keeps_quoted_arguments() {
+        let step = r#"aws logs filter-log-events --log-group-name app --filter-pattern "ERROR  timeout""#;
+        let translator = translator(MockLLM::new(move |prompt| {
+            Ok(if prompt.contains("1. aws logs") { "DONE" } else { step }.to_string())
+        }));
+
+        let plan = translator.translate_plan("find timeouts", CloudProviderType::AWS).await.unwrap();
+        assert_eq!(plan, vec![step]);
+    }
+
+    #[tokio::test]
+    async fn test_port_command_to_azure() {
+        let translator = translator(MockLLM::new(|prompt| {
+            assert!(prompt.contains("AWS CLI Commands:"));
+            assert!(prompt.contains("Azure CLI Commands:"));
+            assert!(prompt.contains("AWS command to port: aws s3 ls\n"));
+            assert!(prompt.contains("Query: do the same as the AWS command `aws s3 ls` on Microsoft Azure\n"));
+            Ok("az storage account list".to_string())
+        }));
+
+        let ported = translator
+            .port_command("aws s3 ls", CloudProviderType::AWS, CloudProviderType::Azure)
+            .await
+            .unwrap();
+        assert_eq!(ported, "az storage account list");
+        assert_eq!(infer_provider_from_command(&ported), Some(CloudProviderType::Azure));
+    }
+
+    #[tokio::test]
+    async fn test_port_command_keeps_quoted_arguments() {
+        let ported = r#"gcloud logging read "severity>=ERROR  AND  resource.type=gce_instance" --limit 10"#;
+        let translator = translator(MockLLM::replying(ported));
+        let command = translator
+            .port_command(r#"aws logs filter-log-events --log-group-name app --filter-pattern "ERROR""#, CloudProviderType::AWS, CloudProviderType::GCP)
+            .await
+            .unwrap();
+        assert_eq!(command, ported);
+    }
+
+    #[tokio::test]
+    async fn test_port_command_validates_both_sides() {
+        // The LLM answered for the wrong cloud
+        let wrong_target = translator(MockLLM::replying("aws s3api list-buckets"));
+        assert!(matches!(
+            wrong_target.port_command("aws s3 ls", CloudProviderType::AWS, CloudProviderType::Azure).await,
+            Err(CliError::ValidationFailed(_))
+        ));
+
+        let wrong_source = translator(MockLLM::replying("az storage account list"));
+        assert!(matches!(
+            wrong_source.port_command("gcloud storage ls", CloudProviderType::AWS, CloudProviderType::Azure).await,
+            Err(CliError::ValidationFailed(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_follow_up_prompt_includes_recent_commands() {
+        let translator = translator(MockLLM::replying("aws ec2 terminate-instances --instance-ids i-0abc"));
+        let listed = CommandResult {
+            success: true,
+            stdout: "i-0abc running\ni-0def stopped\n".to_string(),
+            stderr: String::new(),
+            truncation_marker: None,
+            raw_stdout: None,
+        };
+        translator.remember_turn("list my ec2 instances", "aws ec2 describe-instances", &listed);
+
+        let prompt = translator.build_prompt("now delete the first one", None).await.unwrap().text;
+        assert!(prompt.contains("Recent commands in this session"));
+        assert!(prompt.contains("Command: aws ec2 describe-instances"));
+        assert!(prompt.contains("i-0abc running i-0def stopped"));
+        assert!(prompt.find("Recent commands") < prompt.find("Query: now delete the first one"));
+
+        let fresh = translator.build_prompt("list lambda functions", None).await.unwrap().text;
+        assert!(!fresh.contains("Recent commands"));
+    }
+
+    #[tokio::test]
+    async fn test_follow_up_skips_templates() {
+        let translator = translator(MockLLM::replying("aws s3 rb s3://logs"));
+        let empty = CommandResult {
+            success: true,
+            stdout: String::new(),
+            stderr: String::new(),
+            truncation_marker: None,
+            raw_stdout: None,
+        };
+        translator.remember_turn("show bucket logs", "aws s3 ls s3://logs", &empty);
+        let command = translator.translate_for_provider("delete it", CloudProviderType::AWS).await.unwrap();
+        assert_eq!(command, "aws s3 rb s3://logs");
+        assert!(translator.last_translation().unwrap().prompt.unwrap().contains("aws s3 ls s3://logs"));
+    }
+
+    #[tokio::test]
+    async fn test_session_context_is_truncated() {
+        let translator = translator(MockLLM::replying("ibmcloud is buckets"))
+            .with_context(&"é".repeat(MAX_SESSION_CONTEXT_CHARS + 500));
+
+        let prompt = translator.build_prompt("list buckets", None).await.unwrap().text;
+        assert_eq!(prompt.matches('é').count(), MAX_SESSION_CONTEXT_CHARS);
+    }
+
+    #[tokio::test]
+    async fn test_last_translation_is_recorded() {
+        let translator = translator(MockLLM::replying("ibmcloud resource groups"));
+        assert!(translator.last_translation().is_none());
+
+        translator.translate("show my groups").await.unwrap();
+        let last = translator.last_translation().unwrap();
+        assert_eq!(last.query, "show my groups");
+        assert_eq!(last.command, "ibmcloud resource groups");
+        assert!(last.prompt.unwrap().contains("Query: show my groups"));
+        assert!(last.sources.is_empty());
+
+        translator.update_last_translation(|last| last.learning_hints.push("ibmcloud resource groups".to_string()));
+        assert_eq!(translator.last_translation().unwrap().learning_hints.len(), 1);
+
+        // Template answers have no prompt
+        translator.translate_for_provider("list ec2 instances", CloudProviderType::AWS).await.unwrap();
+        let last = translator.last_translation().unwrap();
+        assert_eq!(last.command, "aws ec2 describe-instances");
+        assert!(last.prompt.is_none());
+    }
+
+    /// RAG engine with one AWS and one IBM Cloud chunk about buckets
+    async fn bucket_rag() -> TestRag {
+        use crate::core::{DocumentIndexer, Document, IndexingConfig, VectorStore};
+        use std::sync::Arc;
+
+        let mut store = LocalVectorStore::new();
+        store.connect().await.unwrap();
+        let store = Arc::new(store);
+
+        for (provider, id, content) in [
+            (CloudProviderType::AWS, "aws_buckets", "list storage buckets with aws s3 ls"),
+            (CloudProviderType::IBMCloud, "ibm_buckets", "list storage buckets with ibmcloud cos buckets"),
+        ] {
+            let config = IndexingConfig {
+                provider: Some(provider),
+                ..Default::default()
+            };
+            LocalDocumentIndexer::with_config(store.clone(), config)
+                .index_document(Document {
+                    id: id.to_string(),
+                    title: id.to_string(),
+                    content: content.to_string(),
+                    url: None,
+                    metadata: serde_json::json!({}),
+                })
+                .await
+                .unwrap();
+        }
+
+        let indexer = Arc::new(LocalDocumentIndexer::new(store.clone()));
+        let mut rag = LocalRAGEngine::new(store, indexer);
+        rag.initialize().await.unwrap();
+        rag
+    }
+
+    #[tokio::test]
+    async fn test_translate_for_provider_uses_only_provider_chunks() {
+        // Echo the prompt back so the retrieved context is visible
+        let translator = CommandTranslator::with_rag(MockLLM::new(|prompt| Ok(prompt.to_string())), bucket_rag().await);
+        let prompt = translator
+            .translate_for_provider("list storage buckets", CloudProviderType::AWS)
+            .await
+            .unwrap();
+
+        assert!(prompt.contains("aws s3 ls"));
+        assert!(!prompt.contains("ibmcloud cos buckets"));
+    }
+
+    #[tokio::test]
+    async fn test_low_confidence_retrieval_uses_bare_prompt() {
+        let translator = CommandTranslator::with_rag(MockLLM::new(|prompt| Ok(prompt.to_string())), bucket_rag().await);
+
+        // Only "list" matches the AWS chunk, a score of 0.2
+        let prompt = translator
+            .translate_for_provider("list my reactor cores now", CloudProviderType::AWS)
+            .await
+            .unwrap();
+        assert!(!prompt.contains("aws s3 ls"));
+        assert!(!prompt.contains("Based on the above documentation"));
+
+        let last = translator.last_translation().unwrap();
+        assert!(!last.rag_used);
+        assert!(last.no_relevant_docs);
+        assert!((last.rag_confidence.unwrap() - 0.2).abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn test_no_relevant_docs_flag() {
+        let rag_translator = CommandTranslator::with_rag(MockLLM::replying("aws ec2 describe-instances"), bucket_rag().await);
+
+        // Nothing matches at all
+        rag_translator
+            .translate_for_provider("count my reactor cores", CloudProviderType::AWS)
+            .await
+            .unwrap();
+        let last = rag_translator.last_translation().unwrap();
+        assert!(last.no_relevant_docs);
+        assert_eq!(last.rag_confidence, Some(0.0));
+        assert!(last.sources.is_empty());
+
+        // Relevant documentation clears the flag
+        rag_translator
+            .translate_for_provider("list storage buckets", CloudProviderType::AWS)
+            .await
+            .unwrap();
+        assert!(!rag_translator.last_translation().unwrap().no_relevant_docs);
 
-    // Mock implementations for testing would go here
+        // Without RAG there was nothing to find
+        let plain = translator(MockLLM::replying("aws s3 ls"));
+        plain.translate("list buckets please").await.unwrap();
+        assert!(!plain.last_translation().unwrap().no_relevant_docs);
+    }
+
+    #[tokio::test]
+    async fn test_min_document_score_limits_context_chunks() {
+        let sources_with = |min_document_score: f32| async move {
+            let config = RagRetrievalConfig { translation_top_k: 10, min_document_score, ..Default::default() };
+            let translator = CommandTranslator::with_rag(MockLLM::replying("ibmcloud cos buckets"), bucket_rag().await)
+                .with_rag_retrieval(config)
+                .with_rag_confidence_threshold(0.0);
+            translator
+                .translate_for_provider("list storage buckets", CloudProviderType::IBMCloud)
+                .await
+                .unwrap();
+            translator.last_translation().unwrap().sources
+        };
+
+        // The bucket chunk matches fully; basic knowledge chunks only on "list"
+        let loose = sources_with(0.1).await;
+        let strict = sources_with(0.5).await;
+        assert!(loose.len() > 1, "{:?}", loose);
+        assert!(loose.windows(2).all(|pair| pair[0].score >= pair[1].score), "{:?}", loose);
+        assert_eq!(strict.len(), 1);
+        assert_eq!(strict[0].score, 1.0);
+        assert!(sources_with(1.01).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_confident_retrieval_enhances_prompt() {
+        let translator = CommandTranslator::with_rag(MockLLM::new(|prompt| Ok(prompt.to_string())), bucket_rag().await)
+            .with_rag_confidence_threshold(0.1);
+
+        let prompt = translator
+            .translate_for_provider("list my reactor cores now", CloudProviderType::AWS)
+            .await
+            .unwrap();
+        assert!(prompt.contains("Based on the above documentation"));
+        assert!(translator.last_translation().unwrap().rag_used);
+    }
+
+    #[tokio::test]
+    async fn test_low_scoring_chunks_are_left_out_of_confident_context() {
+        let translator = CommandTranslator::with_rag(MockLLM::new(|prompt| Ok(prompt.to_string())), bucket_rag().await)
+            .with_rag_retrieval(RagRetrievalConfig { translation_top_k: 10, min_document_score: 0.1, ..Default::default() })
+            .with_rag_confidence_threshold(0.5);
+
+        translator
+            .translate_for_provider("list storage buckets", CloudProviderType::IBMCloud)
+            .await
+            .unwrap();
+        let last = translator.last_translation().unwrap();
+        assert!(last.rag_used);
+        assert_eq!(last.sources.len(), 1, "{:?}", last.sources);
+        assert!(last.sources.iter().all(|source| source.score >= 0.5));
+        assert!(last.rag_skipped > 0);
+    }
+
+    #[tokio::test]
+    async fn test_usage_log_counts_requests() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("usage.json");
+
+        let translator = translator(MockLLM::replying("aws s3 ls"))
+            .with_usage_log(UsageLog::new(&path).unwrap());
+        translator.translate("list buckets please").await.unwrap();
+        translator.translate("list buckets again").await.unwrap();
+
+        let today = chrono::Local::now().date_naive();
+        assert_eq!(UsageLog::new(&path).unwrap().day(today, "mock").requests, 2);
+    }
+
+    #[tokio::test]
+    async fn test_registered_transformer_rewrites_command() {
+        use crate::cli::transformers::FlagInjector;
+
+        let plain = translator(MockLLM::replying("aws s3 ls"))
+            .with_transformer(FlagInjector::new(CloudProviderType::AWS, "--profile", "corp"));
+        let command = plain.translate("list buckets please").await.unwrap();
+        assert_eq!(command, "aws s3 ls --profile corp");
+
+        let piped = translator(MockLLM::replying("aws s3 ls | grep  logs"))
+            .with_transformer(FlagInjector::new(CloudProviderType::AWS, "--profile", "corp"));
+        let command = piped.translate("list log buckets please").await.unwrap();
+        assert_eq!(command, "aws s3 ls --profile corp | grep  logs");
+    }
+
+    #[tokio::test]
+    async fn test_empty_transformer_chain_is_noop() {
+        let translator = translator(MockLLM::replying("aws s3 ls --region us-west-1"));
+        let command = translator.translate("list buckets please").await.unwrap();
+        assert_eq!(command, "aws s3 ls --region us-west-1");
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_spaces_translations() {
+        // 600 per minute is one call every 100ms
+        let translator = translator(MockLLM::replying("aws s3 ls")).with_rate_limit(600);
+        let start = std::time::Instant::now();
+        for _ in 0..3 {
+            translator.translate("list buckets please").await.unwrap();
+        }
+
+        assert!(start.elapsed() >= std::time::Duration::from_millis(190), "took {:?}", start.elapsed());
+    }
+
+    #[tokio::test]
+    async fn test_template_answers_routine_query_without_llm() {
+        let translator = translator(MockLLM::failing(|| Error::LLMProvider("LLM should not be called".to_string())));
+
+        let command = translator
+            .translate_for_provider("list ec2 instances", CloudProviderType::AWS)
+            .await
+            .unwrap();
+        assert_eq!(command, "aws ec2 describe-instances");
+
+        // Without a template match the LLM is still used
+        assert!(translator
+            .translate_for_provider("list ec2 instances created last week", CloudProviderType::AWS)
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_postprocess_adds_aws_region_and_profile() {
+        use crate::providers::{aws::AWSConfig, AWSProvider};
+
+        let provider = AWSProvider::with_config(AWSConfig {
+            region: Some("eu-west-1".to_string()),
+            profile: Some("dev".to_string()),
+        });
+        assert_eq!(
+            postprocess_command("aws s3 ls", &provider),
+            "aws s3 ls --region eu-west-1 --profile dev"
+        );
+        assert_eq!(
+            postprocess_command("aws ec2 describe-instances --region=us-east-1", &provider),
+            "aws ec2 describe-instances --region=us-east-1 --profile dev"
+        );
+        assert_eq!(
+            postprocess_command("aws s3 ls --profile prod --region us-east-1", &provider),
+            "aws s3 ls --profile prod --region us-east-1"
+        );
+    }
+
+    #[test]
+    fn test_postprocess_flags_go_on_the_provider_command() {
+        use crate::providers::{aws::AWSConfig, AWSProvider};
+
+        let provider = AWSProvider::with_config(AWSConfig { region: None, profile: Some("dev".to_string()) });
+        assert_eq!(
+            postprocess_command("aws s3api list-buckets | jq -r '.Buckets[] | .Name'", &provider),
+            "aws s3api list-buckets --profile dev | jq -r '.Buckets[] | .Name'"
+        );
+    }
+
+    #[test]
+    fn test_postprocess_adds_azure_subscription() {
+        use crate::providers::{azure::AzureConfig, AzureProvider};
+
+        let provider = AzureProvider::with_config(AzureConfig {
+            subscription: Some("sub-123".to_string()),
+            resource_group: None,
+        });
+        assert_eq!(postprocess_command("az vm list", &provider), "az vm list --subscription sub-123");
+        assert_eq!(
+            postprocess_command("az vm list --subscription other", &provider),
+            "az vm list --subscription other"
+        );
+    }
+
+    #[test]
+    fn test_postprocess_adds_azure_resource_group() {
+        use crate::providers::{azure::AzureConfig, AzureProvider};
+
+        let provider = AzureProvider::with_config(AzureConfig {
+            subscription: Some("sub-123".to_string()),
+            resource_group: Some("rg-prod".to_string()),
+        });
+        assert_eq!(
+            postprocess_command("az vm list", &provider),
+            "az vm list --subscription sub-123 --resource-group rg-prod"
+        );
+        // Already present, in long or short form
+        assert_eq!(
+            postprocess_command("az vm list -g rg-dev --subscription=other", &provider),
+            "az vm list -g rg-dev --subscription=other"
+        );
+        // Commands that reject the flags are left alone
+        assert_eq!(postprocess_command("az group list", &provider), "az group list --subscription sub-123");
+        assert_eq!(postprocess_command("az login", &provider), "az login");
+
+        // Nothing is added without configuration
+        assert_eq!(postprocess_command("az vm list", &AzureProvider::new()), "az vm list");
+    }
+
+    #[test]
+    fn test_postprocess_adds_gcp_project() {
+        use crate::providers::{gcp::GCPConfig, GCPProvider};
+
+        let provider = GCPProvider::with_config(GCPConfig {
+            project: Some("my-project".to_string()),
+            region: None,
+        });
+        assert_eq!(
+            postprocess_command("gcloud compute instances list", &provider),
+            "gcloud compute instances list --project my-project"
+        );
+        assert_eq!(
+            postprocess_command("gcloud compute instances list --project=other", &provider),
+            "gcloud compute instances list --project=other"
+        );
+        // Commands for other CLIs are left alone
+        assert_eq!(postprocess_command("aws s3 ls", &provider), "aws s3 ls");
+    }
+
+    #[tokio::test]
+    async fn test_translate_success() {
+        let translator = translator(MockLLM::replying("ibmcloud resource groups"));
+        let command = translator.translate("list resource groups").await.unwrap();
+        assert_eq!(command, "ibmcloud resource groups");
+    }
+
+    #[tokio::test]
+    async fn test_translate_timeout_variant() {
+        let translator = translator(MockLLM::failing(|| Error::Timeout("Request timed out".to_string())));
+        let err = translator.translate("list resource groups").await.unwrap_err();
+        assert!(matches!(err, CliError::Timeout(_)));
+    }
+
+    #[tokio::test]
+    async fn test_translate_llm_failure_variant() {
+        let translator = translator(MockLLM::failing(|| Error::LLMProvider("boom".to_string())));
+        let err = translator.translate("list resource groups").await.unwrap_err();
+        assert!(matches!(err, CliError::TranslationFailed(_)));
+    }
+
+    #[tokio::test]
+    async fn test_translate_empty_output_variant() {
+        let translator = translator(MockLLM::replying("   "));
+        let err = translator.translate("list resource groups").await.unwrap_err();
+        assert!(matches!(err, CliError::TranslationFailed(_)));
+    }
+
+    #[tokio::test]
+    async fn test_suggest_completions_from_seeded_store() {
+        use crate::core::{DocumentIndexer, Document, VectorStore};
+        use std::sync::Arc;
+
+        let mut store = LocalVectorStore::new();
+        store.connect().await.unwrap();
+        let store = Arc::new(store);
+        let indexer = Arc::new(LocalDocumentIndexer::new(store.clone()));
+        indexer
+            .index_document(Document {
+                id: "aws_s3".to_string(),
+                title: "AWS S3".to_string(),
+                content: "List S3 buckets: aws s3 ls\nCopy a file: aws s3 cp file.txt s3://bucket/".to_string(),
+                url: None,
+                metadata: serde_json::json!({}),
+            })
+            .await
+            .unwrap();
+
+        let mut rag = LocalRAGEngine::new(store, indexer);
+        rag.initialize().await.unwrap();
+
+        let translator = CommandTranslator::with_rag(MockLLM::replying(""), rag);
+        let suggestions = translator.suggest_completions("aws s3").await.unwrap();
+
+        assert!(suggestions.len() <= 5);
+        assert!(suggestions.contains(&"aws s3 ls".to_string()));
+        assert!(suggestions.iter().all(|s| s.starts_with("aws s3")));
+    }
+
+    #[tokio::test]
+    async fn test_suggest_completions_empty_partial() {
+        let translator = translator(MockLLM::replying(""));
+        assert!(translator.suggest_completions("  ").await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_success_rate_drops_after_failures() {
+        let translator = translator(MockLLM::replying(""));
+        let command = "ibmcloud resource groups";
+        assert_eq!(translator.get_command_success_rate(command), None);
+
+        translator.update_command_success(command, true);
+        let after_success = translator.get_command_success_rate(command).unwrap();
+
+        translator.update_command_success(command, false);
+        translator.update_command_success(command, false);
+        let after_failures = translator.get_command_success_rate(command).unwrap();
+
+        assert!(after_failures < after_success);
+        assert!(after_failures >= 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_translate_auth_failure_variant() {
+        let translator = translator(MockLLM::failing(|| Error::Authentication("expired".to_string())));
+        let err = translator.translate("list resource groups").await.unwrap_err();
+        assert!(matches!(err, CliError::NotAuthenticated(_)));
+    }
+
+    fn command_result(success: bool, stdout: &str) -> CommandResult {
+        CommandResult {
+            success,
+            stdout: stdout.to_string(),
+            stderr: String::new(),
+            truncation_marker: None,
+            raw_stdout: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_summarize_output_sends_output() {
+        let translator = translator(MockLLM::new(|prompt| {
+            assert!(prompt.contains("COMMAND: aws ec2 describe-instances"));
+            assert!(prompt.contains("i-0abc running"));
+            assert!(!prompt.contains("truncated"));
+            Ok("  2 instances, 1 stopped  \n".to_string())
+        }));
+
+        let result = command_result(true, "i-0abc running\ni-0def stopped\n");
+        let summary = translator.summarize_output("aws ec2 describe-instances", &result).await.unwrap();
+        assert_eq!(summary.as_deref(), Some("2 instances, 1 stopped"));
+        assert!(translator.llm().configs().iter().all(|config| config.raw));
+    }
+
+    #[tokio::test]
+    async fn test_recovery_keeps_every_suggested_command() {
+        let translator = translator(MockLLM::replying("Problem: not logged in.\n$ ibmcloud login\n$ ibmcloud ks clusters"));
+        let response = translator.suggest_recovery("list clusters", "ibmcloud ks clusters", "Not logged in").await.unwrap();
+
+        let suggestions = crate::cli::parse_retry_suggestions(&response, "ibmcloud ks cluster ls");
+        assert_eq!(suggestions.iter().filter(|suggestion| suggestion.is_executable()).count(), 2);
+        assert!(translator.llm().configs().iter().all(|config| config.raw));
+    }
+
+    #[tokio::test]
+    async fn test_summarize_output_truncates_long_output() {
+        let translator = translator(MockLLM::new(|prompt| {
+            assert_eq!(prompt.matches('é').count(), MAX_SUMMARY_INPUT_CHARS);
+            assert!(prompt.contains(&format!("first {} of {} characters", MAX_SUMMARY_INPUT_CHARS, MAX_SUMMARY_INPUT_CHARS + 10)));
+            Ok("many".to_string())
+        }));
+
+        let result = command_result(true, &"é".repeat(MAX_SUMMARY_INPUT_CHARS + 10));
+        assert!(translator.summarize_output("az vm list", &result).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_summarize_output_skipped_for_failed_command() {
+        // Calling the LLM would fail the test
+        let translator = translator(MockLLM::failing(|| Error::Other("LLM called".to_string())));
+
+        let failed = command_result(false, "partial output");
+        assert_eq!(translator.summarize_output("az vm list", &failed).await.unwrap(), None);
+        let empty = command_result(true, "  \n");
+        assert_eq!(translator.summarize_output("az vm list", &empty).await.unwrap(), None);
+    }
 }
@@ -2,17 +2,42 @@
 
 use colored::*;
 use crossterm::{
-    event::{self, Event, KeyCode},
+    event::{self, Event, KeyCode, KeyModifiers},
     terminal::{disable_raw_mode, enable_raw_mode, size},
 };
-use std::io::{self, Write, IsTerminal};
-use std::process::Command;
-use crate::core::{Result, CloudProviderType};
-use super::CommandLearningEngine;
+use std::io::{self, BufRead, Write, IsTerminal};
+use std::process::{Command, Stdio};
+use crate::core::{Result, CloudProvider, CloudProviderType, CommandRunner, CommandSafety, has_flag, infer_provider_from_command, is_destructive_command, is_interactive_command};
+use crate::providers::{create_provider, IBMCloudProvider};
+use std::sync::{Arc, OnceLock};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::sync::Notify;
+use super::{redact_secrets, CliError, CliResult, LastTranslation, OutputFormat, Pipeline};
+use super::capture::{capture_child, command_result};
+use super::plan::PlanQuestion;
+use super::recovery::{retry_choice, RetryChoice, RetrySuggestion};
 use anyrepair::Repair;
 
+/// Environment variable that turns the startup banner off
+pub const NO_BANNER_ENV: &str = "ANYCLI_NO_BANNER";
+
+/// Banner title unless `--banner-title` sets another
+pub const DEFAULT_BANNER_TITLE: &str = "AnyCLI - Cloud Universal CLI";
+
+/// Whether the startup banner should be shown
+///
+/// It is left out when `--no-banner` is passed, when [`NO_BANNER_ENV`] is set
+/// to anything but an empty string, `0` or `false`, and when stdout is not a
+/// terminal, as in scripts and CI logs.
+pub fn should_display_banner(no_banner: bool, no_banner_env: Option<&str>, stdout_is_terminal: bool) -> bool {
+    let env_disabled = no_banner_env
+        .map(|value| !matches!(value.trim().to_lowercase().as_str(), "" | "0" | "false"))
+        .unwrap_or(false);
+    !no_banner && !env_disabled && stdout_is_terminal
+}
+
 /// Display startup banner with Carbon Design System inspired styling
-pub fn display_banner() {
+pub fn display_banner(title: &str) {
     let terminal_width = size().map(|(w, _)| w as usize).unwrap_or(80);
     let banner_width = std::cmp::min(67, terminal_width.saturating_sub(4));
 
@@ -26,8 +51,8 @@ pub fn display_banner() {
 
     let title_line = format!(
         "│  {}{}│",
-        "AnyCLI - Cloud Universal CLI".blue().bold(),
-        " ".repeat(banner_width - 31)
+        title.blue().bold(),
+        " ".repeat(banner_width.saturating_sub(title.chars().count() + 3))
     );
     println!("{}", title_line);
 
@@ -70,85 +95,169 @@ pub fn display_banner() {
 }
 
 /// Handle input with command history navigation
-pub async fn handle_input_with_history(history: &mut Vec<String>) -> Result<String> {
+///
+/// A line ending in `\` continues on the next line, and lines between `"""`
+/// fences are read as one query. The joined query replaces its fragments in
+/// history.
+///
+/// Returns `None` once piped stdin reaches end of input.
+pub async fn handle_input_with_history(history: &mut Vec<String>) -> Result<Option<String>> {
+    read_query(history, "").await
+}
+
+/// Read a possibly multiline query whose first line starts out as `initial`
+async fn read_query(history: &mut Vec<String>, initial: &str) -> Result<Option<String>> {
+    let history_len = history.len();
+    let mut multiline = MultilineInput::default();
+
+    loop {
+        let (prompt, initial) = if multiline.is_pending() { ("...>", "") } else { ("cuc>", initial) };
+        let Some(line) = read_input(history, initial, prompt).await? else {
+            // End of input submits whatever was accumulated
+            return Ok(multiline.finish());
+        };
+        if let Some(query) = multiline.push(&line) {
+            history.truncate(history_len);
+            push_history(history, &query);
+            return Ok(Some(query));
+        }
+    }
+}
+
+/// Let the user edit a suggested command before running it
+///
+/// The prompt starts with the suggestion and the cursor after it; history,
+/// Ctrl-R search and line continuations work as for queries. Returns an
+/// empty string when the edit is cancelled with Esc. Piped input keeps the
+/// suggestion when the line is empty.
+pub async fn edit_command(history: &mut Vec<String>, suggestion: &str) -> Result<String> {
+    Ok(read_query(history, suggestion).await?.unwrap_or_default())
+}
+
+/// Read the next line of piped input, or `None` at end of input
+///
+/// An empty line yields `initial`.
+pub fn read_piped_line(reader: &mut impl BufRead, history: &mut Vec<String>, initial: &str) -> Result<Option<String>> {
+    let mut input = String::new();
+    if reader.read_line(&mut input)? == 0 {
+        return Ok(None);
+    }
+
+    let mut input = input.trim().to_string();
+    if input.is_empty() {
+        input = initial.to_string();
+    }
+    push_history(history, &input);
+    Ok(Some(input))
+}
+
+/// Read a line with history navigation, starting from `initial`
+///
+/// `prompt` is shown before the line, e.g. `cuc>`.
+async fn read_input(history: &mut Vec<String>, initial: &str, prompt: &str) -> Result<Option<String>> {
     // Check if stdin is a terminal (interactive) or piped
     if !io::stdin().is_terminal() {
-        // Handle piped input - read from stdin directly
-        let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
-        let input = input.trim().to_string();
-        if !input.is_empty() {
-            history.push(input.clone());
-        }
-        return Ok(input);
+        return read_piped_line(&mut io::stdin().lock(), history, initial);
     }
 
+    let prompt = prompt.green().bold();
     enable_raw_mode()?;
-    let mut input = String::new();
-    let mut history_index: Option<usize> = None;
-    let mut cursor_pos = 0;
+    let mut buffer = LineBuffer::prefilled(initial);
+    let mut cursor = HistoryCursor::new(history);
+    // Active Ctrl-R search: the typed query and which match is shown
+    let mut search: Option<(String, usize)> = None;
 
-    print!("{} ", "cuc>".green().bold());
+    print!("{} {}", prompt, buffer.text());
     io::stdout().flush()?;
 
     loop {
         if let Event::Key(key_event) = event::read()? {
+            let ctrl_r = key_event.code == KeyCode::Char('r')
+                && key_event.modifiers.contains(KeyModifiers::CONTROL);
+
+            if let Some((query, match_index)) = search.as_mut() {
+                match key_event.code {
+                    _ if ctrl_r => *match_index += 1,
+                    KeyCode::Char(c) => {
+                        query.push(c);
+                        *match_index = 0;
+                    }
+                    KeyCode::Backspace => {
+                        query.pop();
+                        *match_index = 0;
+                    }
+                    KeyCode::Esc => {
+                        search = None;
+                        print!("\r{}\r{} {}", " ".repeat(80), prompt, buffer.text());
+                        io::stdout().flush()?;
+                        continue;
+                    }
+                    _ => {
+                        // Any other key accepts the current match and resumes editing
+                        let matches = search_history(history, query);
+                        if let Some(found) = matches.get(*match_index) {
+                            buffer = LineBuffer::prefilled(found);
+                        }
+                        search = None;
+                        cursor = HistoryCursor::new(history);
+                        print!("\r{}\r{} {}", " ".repeat(80), prompt, buffer.text());
+                        io::stdout().flush()?;
+                        if key_event.code != KeyCode::Enter {
+                            continue;
+                        }
+                    }
+                }
+
+                if let Some((query, match_index)) = search.as_mut() {
+                    let matches = search_history(history, query);
+                    // Wrap around so repeated Ctrl-R cycles through the matches
+                    if *match_index >= matches.len() {
+                        *match_index = 0;
+                    }
+                    let shown = matches.get(*match_index).copied().unwrap_or("");
+                    print!("\r{}\r(reverse-i-search)`{}': {}", " ".repeat(80), query, shown);
+                    io::stdout().flush()?;
+                    continue;
+                }
+            } else if ctrl_r {
+                search = Some((String::new(), 0));
+                print!("\r{}\r(reverse-i-search)`': ", " ".repeat(80));
+                io::stdout().flush()?;
+                continue;
+            }
+
             match key_event.code {
                 KeyCode::Enter => {
                     disable_raw_mode()?;
                     println!();
-                    if !input.is_empty() {
-                        history.push(input.clone());
-                    }
-                    return Ok(input);
+                    push_history(history, buffer.text());
+                    return Ok(Some(buffer.into_text()));
                 }
                 KeyCode::Char(c) => {
-                    input.insert(cursor_pos, c);
-                    cursor_pos += 1;
-                    print!("\r{} {}", "cuc>".green().bold(), input);
+                    buffer.insert(c);
+                    print!("\r{} {}", prompt, buffer.text());
                     io::stdout().flush()?;
                 }
-                KeyCode::Backspace => {
-                    if cursor_pos > 0 {
-                        input.remove(cursor_pos - 1);
-                        cursor_pos -= 1;
-                        print!("\r{} {}  \r{} {}", "cuc>".green().bold(), input, "cuc>".green().bold(), input);
-                        io::stdout().flush()?;
-                    }
-                }
-                KeyCode::Up => {
-                    if !history.is_empty() {
-                        let new_index = match history_index {
-                            None => history.len() - 1,
-                            Some(idx) if idx > 0 => idx - 1,
-                            Some(idx) => idx,
-                        };
-                        history_index = Some(new_index);
-                        input = history[new_index].clone();
-                        cursor_pos = input.len();
-                        print!("\r{} {}  \r{} {}", "cuc>".green().bold(), " ".repeat(50), "cuc>".green().bold(), input);
-                        io::stdout().flush()?;
-                    }
+                KeyCode::Backspace if buffer.backspace() => {
+                    print!("\r{} {}  \r{} {}", prompt, buffer.text(), prompt, buffer.text());
+                    io::stdout().flush()?;
                 }
-                KeyCode::Down => {
-                    if let Some(idx) = history_index {
-                        if idx < history.len() - 1 {
-                            let new_index = idx + 1;
-                            history_index = Some(new_index);
-                            input = history[new_index].clone();
-                        } else {
-                            history_index = None;
-                            input.clear();
-                        }
-                        cursor_pos = input.len();
-                        print!("\r{} {}  \r{} {}", "cuc>".green().bold(), " ".repeat(50), "cuc>".green().bold(), input);
+                KeyCode::Up | KeyCode::Down => {
+                    let recalled = if key_event.code == KeyCode::Up {
+                        cursor.up(history, buffer.text())
+                    } else {
+                        cursor.down(history)
+                    };
+                    if let Some(line) = recalled {
+                        buffer = LineBuffer::prefilled(&line);
+                        print!("\r{} {}  \r{} {}", prompt, " ".repeat(50), prompt, buffer.text());
                         io::stdout().flush()?;
                     }
                 }
                 KeyCode::Esc => {
                     disable_raw_mode()?;
                     println!();
-                    return Ok(String::new());
+                    return Ok(Some(String::new()));
                 }
                 _ => {}
             }
@@ -156,11 +265,348 @@ pub async fn handle_input_with_history(history: &mut Vec<String>) -> Result<Stri
     }
 }
 
+/// Fence that starts and ends a multiline query
+const MULTILINE_FENCE: &str = "\"\"\"";
+
+/// Accumulates the lines of a multiline query
+///
+/// Lines are trimmed and joined with single spaces; blank lines are dropped.
+#[derive(Debug, Default)]
+pub struct MultilineInput {
+    lines: Vec<String>,
+    fenced: bool,
+}
+
+impl MultilineInput {
+    /// Whether earlier lines are waiting for the query to be terminated
+    pub fn is_pending(&self) -> bool {
+        self.fenced || !self.lines.is_empty()
+    }
+
+    /// Add a line; returns the joined query once it is complete
+    pub fn push(&mut self, line: &str) -> Option<String> {
+        let mut line = line.trim();
+
+        if !self.is_pending()
+            && let Some(rest) = line.strip_prefix(MULTILINE_FENCE)
+        {
+            self.fenced = true;
+            line = rest.trim_start();
+        }
+
+        if self.fenced {
+            if let Some(last) = line.strip_suffix(MULTILINE_FENCE) {
+                self.add(last);
+                return Some(self.take_query());
+            }
+            self.add(line);
+            return None;
+        }
+
+        match line.strip_suffix('\\') {
+            Some(continued) => {
+                self.add(continued);
+                None
+            }
+            None => {
+                self.add(line);
+                Some(self.take_query())
+            }
+        }
+    }
+
+    /// End the query early, e.g. at end of input; `None` if nothing was read
+    pub fn finish(&mut self) -> Option<String> {
+        Some(self.take_query()).filter(|query| !query.is_empty())
+    }
+
+    fn take_query(&mut self) -> String {
+        self.fenced = false;
+        std::mem::take(&mut self.lines).join(" ")
+    }
+
+    fn add(&mut self, line: &str) {
+        let line = line.trim();
+        if !line.is_empty() {
+            self.lines.push(line.to_string());
+        }
+    }
+}
+
+/// Append an entry to history, skipping blanks and consecutive duplicates
+pub fn push_history(history: &mut Vec<String>, entry: &str) {
+    if entry.is_empty() || history.last().is_some_and(|last| last == entry) {
+        return;
+    }
+    history.push(entry.to_string());
+}
+
+/// Line being typed at the prompt
+///
+/// The cursor is a byte offset that always sits on a character boundary.
+#[derive(Debug, Default, PartialEq)]
+struct LineBuffer {
+    text: String,
+    cursor: usize,
+}
+
+impl LineBuffer {
+    /// Buffer holding `text` with the cursor after its last character
+    fn prefilled(text: &str) -> Self {
+        Self { text: text.to_string(), cursor: text.len() }
+    }
+
+    fn text(&self) -> &str {
+        &self.text
+    }
+
+    fn into_text(self) -> String {
+        self.text
+    }
+
+    /// Type `c` at the cursor
+    fn insert(&mut self, c: char) {
+        self.text.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+    }
+
+    /// Delete the character before the cursor; `false` at the start of the line
+    fn backspace(&mut self) -> bool {
+        let Some(c) = self.text[..self.cursor].chars().next_back() else {
+            return false;
+        };
+        self.cursor -= c.len_utf8();
+        self.text.remove(self.cursor);
+        true
+    }
+}
+
+/// Position while browsing history with the arrow keys
+///
+/// Positions run from 0, the oldest entry, to `history.len()`, the line being
+/// edited. Leaving that line with Up keeps what was typed as a draft, and
+/// Down past the newest entry brings it back.
+#[derive(Debug)]
+struct HistoryCursor {
+    position: usize,
+    draft: String,
+}
+
+impl HistoryCursor {
+    /// Start on the line being edited
+    fn new(history: &[String]) -> Self {
+        Self { position: history.len(), draft: String::new() }
+    }
+
+    /// Move to the next older entry; `None` at the oldest one or with no history
+    fn up(&mut self, history: &[String], current: &str) -> Option<String> {
+        if history.is_empty() || self.position == 0 {
+            return None;
+        }
+        if self.position >= history.len() {
+            self.draft = current.to_string();
+        }
+        self.position = self.position.min(history.len()) - 1;
+        Some(history[self.position].clone())
+    }
+
+    /// Move to the next newer entry, or back to the draft; `None` while editing
+    fn down(&mut self, history: &[String]) -> Option<String> {
+        if self.position >= history.len() {
+            return None;
+        }
+        self.position += 1;
+        Some(history.get(self.position).cloned().unwrap_or_else(|| std::mem::take(&mut self.draft)))
+    }
+}
+
+/// Find history entries containing `query`, most recent first
+pub fn search_history<'a>(history: &'a [String], query: &str) -> Vec<&'a str> {
+    let mut matches: Vec<&str> = Vec::new();
+    for entry in history.iter().rev() {
+        if entry.contains(query) && !matches.contains(&entry.as_str()) {
+            matches.push(entry);
+        }
+    }
+    matches
+}
+
+/// Run `future` unless `cancel` completes first
+///
+/// Returns `None` when cancelled. The terminal is taken out of raw mode on
+/// cancel so the prompt is usable again.
+pub async fn run_cancellable<T>(
+    future: impl std::future::Future<Output = T>,
+    cancel: impl std::future::Future<Output = ()>,
+) -> Option<T> {
+    tokio::select! {
+        output = future => Some(output),
+        _ = cancel => {
+            let _ = disable_raw_mode();
+            None
+        }
+    }
+}
+
+/// Run `future` until it finishes or the user presses Ctrl-C
+///
+/// Outside such waits Ctrl-C still ends the process, with exit status 130
+/// as the default handler would.
+pub async fn run_until_ctrl_c<T>(future: impl std::future::Future<Output = T>) -> Option<T> {
+    ctrl_c_scopes().run(future).await
+}
+
+/// Waits that Ctrl-C currently cancels
+#[derive(Default)]
+struct CtrlCScopes {
+    active: AtomicUsize,
+    cancel: Notify,
+}
+
+impl CtrlCScopes {
+    /// Hand a Ctrl-C to the active waits; `false` when there are none
+    fn deliver(&self) -> bool {
+        if self.active.load(Ordering::SeqCst) == 0 {
+            return false;
+        }
+        self.cancel.notify_waiters();
+        true
+    }
+
+    /// Run `future` as an active wait until it finishes or is cancelled
+    async fn run<T>(&self, future: impl std::future::Future<Output = T>) -> Option<T> {
+        let cancelled = self.cancel.notified();
+        self.active.fetch_add(1, Ordering::SeqCst);
+        let _active = ActiveScope(&self.active);
+        run_cancellable(future, cancelled).await
+    }
+}
+
+/// Counts a wait as active until dropped, however it ends
+struct ActiveScope<'a>(&'a AtomicUsize);
+
+impl Drop for ActiveScope<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// The process-wide Ctrl-C scopes, listening for SIGINT from the first use on
+///
+/// Tokio cannot remove its SIGINT handler once installed, so a single
+/// listener stays for the rest of the process and exits itself when no
+/// wait takes the signal. If the handler cannot be installed, the default
+/// behavior stays and nothing is cancelled.
+fn ctrl_c_scopes() -> &'static CtrlCScopes {
+    static SCOPES: OnceLock<CtrlCScopes> = OnceLock::new();
+    SCOPES.get_or_init(|| {
+        tokio::spawn(async {
+            while tokio::signal::ctrl_c().await.is_ok() {
+                if !ctrl_c_scopes().deliver() {
+                    let _ = disable_raw_mode();
+                    std::process::exit(130);
+                }
+            }
+        });
+        CtrlCScopes::default()
+    })
+}
+
+/// Characters of a RAG source name shown before it is cut off
+pub const DEFAULT_SOURCE_WIDTH: usize = 60;
+
+/// Shorten a source name to at most `width` characters, ending in `…` when cut
+///
+/// Only the display is shortened; the full name stays in the translation record.
+pub fn truncate_source_name(source: &str, width: usize) -> String {
+    if source.chars().count() <= width {
+        return source.to_string();
+    }
+    let mut truncated: String = source.chars().take(width.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// Describe what went into the last translation, for the `why` command
+///
+/// Secrets in the prompt are redacted before display, and source names are
+/// cut to `source_width` characters.
+pub fn format_last_translation(last: Option<&LastTranslation>, source_width: usize) -> String {
+    let Some(last) = last else {
+        return format!("{} Nothing to explain yet - translate a query first, then type 'why'", "ℹ️".cyan());
+    };
+
+    let mut lines = vec![
+        format!("{} {}", "Query:".bold(), last.query),
+    ];
+    if let Some(normalized) = &last.normalized_query {
+        lines.push(format!("{} {}", "Normalized:".bold(), normalized));
+    }
+    lines.push(format!("{} {}", "Command:".bold(), last.command));
+
+    match &last.prompt {
+        Some(prompt) => {
+            lines.push(format!("{}", "Prompt:".bold()));
+            lines.extend(redact_secrets(prompt).lines().map(|line| format!("  {}", line.dimmed())));
+        }
+        None => lines.push(format!("{} built-in template (no LLM call)", "Prompt:".bold())),
+    }
+
+    if let Some(decision) = format_rag_decision(last) {
+        lines.push(format!("{} {}", "RAG confidence:".bold(), decision));
+    }
+
+    if last.sources.is_empty() {
+        lines.push(format!("{} none", "Sources:".bold()));
+    } else {
+        lines.push(format!("{}", "Sources:".bold()));
+        lines.extend(last.sources.iter().enumerate().map(|(rank, source)| {
+            format!("  {}. {} ({:.0}%)", rank + 1, truncate_source_name(&source.name, source_width), source.score * 100.0)
+        }));
+    }
+
+    if let Some(quality) = &last.quality {
+        lines.push(format!("{} {:.0}%", "Quality:".bold(), quality.score * 100.0));
+        lines.extend(quality.issues.iter().map(|issue| format!("  • {}", issue)));
+    }
+
+    if !last.learning_hints.is_empty() {
+        lines.push(format!("{}", "Learned from earlier sessions:".bold()));
+        lines.extend(last.learning_hints.iter().map(|hint| format!("  • {}", hint)));
+    }
+
+    lines.join("\n")
+}
+
+/// Whether retrieved documentation went into the prompt, `None` when RAG was not consulted
+pub fn format_rag_decision(last: &LastTranslation) -> Option<String> {
+    let confidence = last.rag_confidence?;
+    let decision = if last.rag_used {
+        format!("{} chunks used as context", last.sources.len())
+    } else {
+        "too low, answered from model knowledge only".to_string()
+    };
+    let skipped = match last.rag_skipped {
+        0 => String::new(),
+        skipped => format!(", {} below the threshold left out", skipped),
+    };
+    Some(format!("{:.0}% ({}{})", confidence * 100.0, decision, skipped))
+}
+
 /// Display help message
 pub fn print_help() {
     println!("{}", "Available commands:".bold());
     println!("  {} - Type natural language queries to translate to cloud commands", "query".green());
     println!("  {} - Execute a command directly", "exec <command>".green());
+    println!("  {} - Suggest completions for a partial command", "complete <partial>".green());
+    println!("  {} - Pick from several candidate commands for a query", "alternatives <query>".green());
+    println!("  {} - Rewrite a command for another provider, e.g. port azure aws s3 ls", "port <provider> <command>".green());
+    println!("  {} - Translate a multi-step request into a plan and run it step by step", "plan <query>".green());
+    println!("  {} - Follow an IBM Code Engine build run and its logs until it finishes", "buildrun <name>".green());
+    println!("  {} - Continue a query on the next line, or wrap several lines in \"\"\"", "\\ at line end".green());
+    println!("  {} - Search history for a substring (repeat to cycle)", "Ctrl-R".green());
+    println!("  {} - Cancel a running translation", "Ctrl-C".green());
+    println!("  {} - Show the prompt, sources and quality behind the last translation", "why".green());
     println!("  {} - Show this help message", "help".green());
     println!("  {} - Exit the application", "exit/quit".green());
     println!();
@@ -170,9 +616,157 @@ pub fn print_help() {
     println!("  exec ibmcloud target --cf");
 }
 
+/// Answer assumed when the user just presses Enter at a confirmation prompt
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ConfirmDefault {
+    Yes,
+    No,
+}
+
+impl ConfirmDefault {
+    /// Default for `command`: no for destructive commands, yes otherwise,
+    /// unless `forced` overrides it
+    pub fn for_command(command: &str, forced: Option<ConfirmDefault>) -> Self {
+        forced.unwrap_or_else(|| {
+            if is_destructive_command(command).is_some() {
+                ConfirmDefault::No
+            } else {
+                ConfirmDefault::Yes
+            }
+        })
+    }
+
+    /// Prompt choices with the default capitalized, e.g. `Y/n`
+    fn choices(self) -> &'static str {
+        match self {
+            ConfirmDefault::Yes => "Y/n",
+            ConfirmDefault::No => "y/N",
+        }
+    }
+}
+
 /// Confirm command execution with user
-pub async fn confirm_execution(_command: &str) -> Result<bool> {
-    print!("{} Execute this command? [Y/n]: ", "❓".cyan());
+///
+/// An empty answer means yes unless the command is destructive; `forced`
+/// overrides that default.
+pub async fn confirm_execution(command: &str, forced: Option<ConfirmDefault>) -> Result<bool> {
+    let default = ConfirmDefault::for_command(command, forced);
+    print!("{} Execute this command? [{}]: ", "❓".cyan(), default.choices());
+    io::stdout().flush()?;
+
+    let mut response = String::new();
+    io::stdin().read_line(&mut response)?;
+    let response = response.trim().to_lowercase();
+
+    Ok((response.is_empty() && default == ConfirmDefault::Yes) || response == "y" || response == "yes")
+}
+
+/// Confirm a translated command, offering to edit it first
+///
+/// Returns the command to run, which may have been edited, or `None` when
+/// the user declines or cancels the edit. An empty answer follows the same
+/// default as [`confirm_execution`].
+pub async fn confirm_or_edit(
+    command: &str,
+    history: &mut Vec<String>,
+    forced: Option<ConfirmDefault>,
+) -> Result<Option<String>> {
+    let default = ConfirmDefault::for_command(command, forced);
+    print!("{} Execute this command? [{}/e(dit)]: ", "❓".cyan(), default.choices());
+    io::stdout().flush()?;
+
+    let mut response = String::new();
+    io::stdin().read_line(&mut response)?;
+
+    match response.trim().to_lowercase().as_str() {
+        "" if default == ConfirmDefault::Yes => Ok(Some(command.to_string())),
+        "y" | "yes" => Ok(Some(command.to_string())),
+        "e" | "edit" => {
+            let edited = edit_command(history, command).await?;
+            Ok(Some(edited.trim().to_string()).filter(|edited| !edited.is_empty()))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Ask a plan question, returning the user's yes or no
+///
+/// Enter means yes when approving non-destructive steps or a safe step,
+/// and no for a destructive step or continuing after a failure.
+pub async fn ask_plan_question(question: &PlanQuestion) -> Result<bool> {
+    let (text, default) = match question {
+        PlanQuestion::ApproveSafeSteps { count } => {
+            (format!("Run all {} non-destructive steps without asking again?", count), ConfirmDefault::Yes)
+        }
+        PlanQuestion::ConfirmStep { index, step } if step.destructive => (
+            format!("Step {} is destructive: {} — run it?", index + 1, step.command.red()),
+            ConfirmDefault::No,
+        ),
+        PlanQuestion::ConfirmStep { index, step } => {
+            (format!("Run step {}: {}?", index + 1, step.command.bold()), ConfirmDefault::Yes)
+        }
+        PlanQuestion::ContinueAfterFailure { failed, remaining } => (
+            format!("Step {} failed — continue with the remaining {} steps?", failed + 1, remaining),
+            ConfirmDefault::No,
+        ),
+    };
+    print!("{} {} [{}]: ", "❓".cyan(), text, default.choices());
+    io::stdout().flush()?;
+
+    let mut response = String::new();
+    io::stdin().read_line(&mut response)?;
+    let response = response.trim().to_lowercase();
+
+    Ok((response.is_empty() && default == ConfirmDefault::Yes) || response == "y" || response == "yes")
+}
+
+/// Show candidate commands as a numbered menu and read the user's pick
+///
+/// Returns `None` when the user presses Enter or gives an invalid number.
+pub async fn choose_command(commands: &[String]) -> Result<Option<String>> {
+    for (index, command) in commands.iter().enumerate() {
+        println!("  {} {}", format!("{}.", index + 1).cyan(), command.bold());
+    }
+    print!("{} Run which command? [1-{}, Enter to skip]: ", "❓".cyan(), commands.len());
+    io::stdout().flush()?;
+
+    let mut response = String::new();
+    io::stdin().read_line(&mut response)?;
+
+    Ok(response
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .and_then(|choice| choice.checked_sub(1))
+        .and_then(|index| commands.get(index))
+        .cloned())
+}
+
+/// Ask whether to switch to a detected provider for this query
+///
+/// `matched_keywords` are shown so the user can see why it was detected.
+pub async fn confirm_provider_switch(provider: CloudProviderType, matched_keywords: &[String]) -> Result<bool> {
+    print!(
+        "{} Looks like a {} query (matched: {}) — switch? [Y/n]: ",
+        "🔍".cyan(),
+        provider,
+        matched_keywords.join(", ")
+    );
+    io::stdout().flush()?;
+
+    let mut response = String::new();
+    io::stdin().read_line(&mut response)?;
+    let response = response.trim().to_lowercase();
+
+    Ok(response.is_empty() || response == "y" || response == "yes")
+}
+
+/// Ask whether to continue offline after the LLM connection failed
+///
+/// Enter means yes, since the alternative is quitting.
+pub async fn confirm_offline_mode(error: &CliError) -> Result<bool> {
+    println!("{} {}", "⚠️".yellow(), error);
+    print!("{} Continue in offline mode with template and learned commands only? [Y/n]: ", "❓".cyan());
     io::stdout().flush()?;
 
     let mut response = String::new();
@@ -187,101 +781,372 @@ pub struct CommandResult {
     pub success: bool,
     pub stdout: String,
     pub stderr: String,
+    /// Marker for stdout cut at the capture limit, shown after it but kept
+    /// out of `stdout` so the output can still be parsed and repaired
+    pub truncation_marker: Option<String>,
+    /// Stdout bytes as captured, kept only when they are not valid UTF-8
+    pub raw_stdout: Option<Vec<u8>>,
 }
 
 /// Execute a shell command and return detailed result
-pub async fn execute_command(command: &str) -> Result<CommandResult> {
-    execute_command_with_provider(command, None).await
+///
+/// The provider is inferred from the command's CLI binary, so login checks
+/// and JSON repair still apply.
+pub async fn execute_command(
+    command: &str,
+    format: OutputFormat,
+    max_capture_bytes: usize,
+    runner: &Arc<dyn CommandRunner>,
+) -> CliResult<CommandResult> {
+    execute_command_with_provider(command, infer_provider_from_command(command), format, max_capture_bytes, runner).await
 }
 
 /// Execute a shell command with provider-aware JSON repair
+///
+/// At most `max_capture_bytes` of stdout and of stderr are kept; longer
+/// output ends with a truncation marker. Login checks go through `runner`.
 pub async fn execute_command_with_provider(
     command: &str,
     provider: Option<CloudProviderType>,
-) -> Result<CommandResult> {
+    format: OutputFormat,
+    max_capture_bytes: usize,
+    runner: &Arc<dyn CommandRunner>,
+) -> CliResult<CommandResult> {
     // Check login status for IBM Cloud commands before executing
-    if let Some(p) = provider {
-        if p == CloudProviderType::IBMCloud && command.starts_with("ibmcloud") && !command.contains("login") {
-            if let Err(e) = ensure_ibmcloud_login().await {
-                println!("{} {}: {}", "⚠️".yellow(), "Login required".yellow(), e);
-                println!("{}", "Please run 'ibmcloud login' first".cyan());
-                return Ok(CommandResult {
-                    success: false,
-                    stdout: String::new(),
-                    stderr: format!("Not logged in to IBM Cloud: {}", e),
-                });
-            }
-        }
+    if let Some(blocked) = ibmcloud_login_gate(command, provider, runner.clone()).await {
+        return Ok(blocked);
     }
 
-    println!("{} Executing...", "🚀".yellow());
-
-    let output = if cfg!(target_os = "windows") {
-        Command::new("cmd").args(["/C", command]).output()?
+    // Pipelines run stage by stage without a shell, so validate them up front.
+    // Other operators such as `;` or `&&` are rejected rather than handed to
+    // the shell, where the pipeline allowlist would not apply.
+    let pipeline = if Pipeline::has_shell_operators(command) {
+        Some(Pipeline::parse(command)?)
     } else {
-        Command::new("sh").arg("-c").arg(command).output()?
+        None
     };
 
-    let mut stdout = String::from_utf8_lossy(&output.stdout).to_string();
-    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    // Prompts would hang unseen behind captured output, so hand over the terminal
+    if pipeline.is_none() && is_interactive_command(command) {
+        println!("{} Interactive command: answer its prompts below (output is not captured)", "🚀".yellow());
+        let success = shell_command(command).status()?.success();
+        report_outcome(success);
+        return Ok(CommandResult {
+            success,
+            stdout: String::new(),
+            stderr: String::new(),
+            truncation_marker: None,
+            raw_stdout: None,
+        });
+    }
+
+    // Rendering as JSON, YAML or a table needs JSON from the CLI
+    let command = match provider.filter(|_| format != OutputFormat::Text) {
+        Some(p) => match with_json_output_flag(command, p) {
+            Some(with_flag) => {
+                println!("{} Running: {}", "ℹ️".cyan(), with_flag.dimmed());
+                with_flag
+            }
+            None => command.to_string(),
+        },
+        None => command.to_string(),
+    };
+    let command = command.as_str();
+
+    println!("{} Executing...", "🚀".yellow());
 
-    // Repair JSON output for AWS commands if needed
-    if let Some(p) = provider {
-        if p == CloudProviderType::AWS && command.contains("--output json") && !stdout.is_empty() {
-            stdout = repair_aws_json_output(&stdout)?;
+    let mut result = match pipeline {
+        Some(pipeline) => pipeline.run(max_capture_bytes)?,
+        None => {
+            let child = shell_command(command).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+            let (status, stdout, stderr) = capture_child(child, max_capture_bytes)?;
+            command_result(status.success(), stdout, stderr)
         }
+    };
+
+    repair_json_output(&mut result, command, provider)?;
+    let success = result.success;
+
+    if !result.stdout.is_empty() {
+        println!("{}", render_result(&result, format, use_color()));
+    }
+    if let Some(marker) = &result.truncation_marker {
+        println!("{}", marker.trim_end().dimmed());
     }
 
-    if !stdout.is_empty() {
-        println!("{}", stdout);
+    if !result.stderr.is_empty() {
+        eprintln!("{}", result.stderr.red());
     }
 
-    if !stderr.is_empty() {
-        eprintln!("{}", stderr.red());
+    report_outcome(success);
+    Ok(result)
+}
+
+/// The platform shell running `command`
+fn shell_command(command: &str) -> Command {
+    if cfg!(target_os = "windows") {
+        let mut shell = Command::new("cmd");
+        shell.args(["/C", command]);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c").arg(command);
+        shell
     }
+}
 
-    let success = output.status.success();
+fn report_outcome(success: bool) {
     if success {
         println!("{} Command executed successfully", "✅".green());
     } else {
         println!("{} Command failed", "❌".red());
     }
+}
 
-    Ok(CommandResult {
-        success,
-        stdout,
-        stderr,
-    })
+/// Add the provider's JSON output flag to `command`
+///
+/// The flag comes from the provider's capabilities manifest. It is appended,
+/// except for govc, which only reads flags between the subcommand and its
+/// arguments. Returns `None` when the command already chooses an output
+/// format, is a pipeline, does not reach the cloud (such as `login` or
+/// `version`), or the CLI has no JSON flag.
+pub fn with_json_output_flag(command: &str, provider: CloudProviderType) -> Option<String> {
+    let flag = create_provider(provider).capabilities().json_output_flag?;
+    if Pipeline::is_pipeline(command) || !CommandSafety::classify(command).requires_auth {
+        return None;
+    }
+
+    let flag_name = flag.split(['=', ' ']).next()?;
+    if [flag_name, "--output", "-o", "--format"].iter().any(|name| has_flag(command, name)) {
+        return None;
+    }
+
+    let command = command.trim();
+    if provider == CloudProviderType::VMware {
+        let at = after_words(command, 2)?;
+        return Some(format!("{} {}{}", &command[..at], flag, &command[at..]));
+    }
+    Some(format!("{} {}", command, flag))
 }
 
-/// Check if IBM Cloud CLI is logged in
-pub async fn check_ibmcloud_login() -> Result<bool> {
-    let output = if cfg!(target_os = "windows") {
-        Command::new("cmd")
-            .args(["/C", "ibmcloud account show"])
-            .output()?
-    } else {
-        Command::new("sh")
-            .arg("-c")
-            .arg("ibmcloud account show")
-            .output()?
+/// Byte offset just past the first `n` whitespace-separated words of `command`
+fn after_words(command: &str, n: usize) -> Option<usize> {
+    let mut rest = command;
+    for _ in 0..n {
+        rest = rest.trim_start();
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        if end == 0 {
+            return None;
+        }
+        rest = &rest[end..];
+    }
+    Some(command.len() - rest.len())
+}
+
+/// Whether colored output is allowed
+///
+/// Color is off when `NO_COLOR` is set to a non-empty value (see
+/// https://no-color.org) or stdout is not a terminal, so ANSI codes never
+/// end up in files, pipes or JSON output.
+pub fn use_color() -> bool {
+    color_enabled(std::env::var_os("NO_COLOR").as_deref(), io::stdout().is_terminal())
+}
+
+/// [`use_color`] for the given `NO_COLOR` value and stdout
+fn color_enabled(no_color: Option<&std::ffi::OsStr>, stdout_is_terminal: bool) -> bool {
+    no_color.is_none_or(|value| value.is_empty()) && stdout_is_terminal
+}
+
+/// Turn `colored` output on or off for the whole process, following [`use_color`]
+///
+/// Called once at startup, before anything is printed.
+pub fn configure_color() {
+    colored::control::set_override(use_color());
+}
+
+/// Render a command's stdout in the requested format
+///
+/// JSON, YAML and table output parse stdout as JSON (repairing it first if
+/// needed) and fall back to the raw text when that fails.
+pub fn render_result(result: &CommandResult, format: OutputFormat, color: bool) -> String {
+    if format == OutputFormat::Text {
+        return result.stdout.clone();
+    }
+
+    let Some(value) = parse_json_output(&result.stdout) else {
+        return result.stdout.clone();
     };
-    
-    Ok(output.status.success())
+
+    let rendered = match format {
+        OutputFormat::Json => serde_json::to_string_pretty(&value).ok(),
+        OutputFormat::Yaml => serde_yaml::to_string(&value).ok(),
+        OutputFormat::Table => value.as_array().and_then(|rows| render_table(rows, color)),
+        OutputFormat::Text => None,
+    };
+
+    rendered.unwrap_or_else(|| result.stdout.clone())
+}
+
+/// Parse command output as JSON, repairing it with anyrepair when malformed
+///
+/// Output that does not start like a JSON document is left alone.
+fn parse_json_output(output: &str) -> Option<serde_json::Value> {
+    let trimmed = output.trim();
+    if !trimmed.starts_with('{') && !trimmed.starts_with('[') {
+        return None;
+    }
+
+    if let Ok(value) = serde_json::from_str(trimmed) {
+        return Some(value);
+    }
+
+    let repaired = anyrepair::json::JsonRepairer::new().repair(trimmed).ok()?;
+    serde_json::from_str(&repaired.to_string()).ok()
+}
+
+/// Render an array of flat JSON objects as a boxed table
+///
+/// Returns `None` when any row is not an object or holds nested values.
+pub fn render_table(rows: &[serde_json::Value], color: bool) -> Option<String> {
+    let mut columns: Vec<&str> = Vec::new();
+    for row in rows {
+        for (key, value) in row.as_object()? {
+            if value.is_object() || value.is_array() {
+                return None;
+            }
+            if !columns.contains(&key.as_str()) {
+                columns.push(key);
+            }
+        }
+    }
+    if columns.is_empty() {
+        return None;
+    }
+
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| {
+            columns
+                .iter()
+                .map(|column| match row.get(*column) {
+                    Some(serde_json::Value::String(text)) => text.clone(),
+                    Some(serde_json::Value::Null) | None => String::new(),
+                    Some(other) => other.to_string(),
+                })
+                .collect()
+        })
+        .collect();
+
+    let widths: Vec<usize> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, column)| {
+            cells
+                .iter()
+                .map(|row| row[i].chars().count())
+                .chain(std::iter::once(column.chars().count()))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let border = |left: &str, middle: &str, right: &str| {
+        let segments: Vec<String> = widths.iter().map(|width| "─".repeat(width + 2)).collect();
+        format!("{}{}{}", left, segments.join(middle), right)
+    };
+    let line = |values: Vec<String>| {
+        let padded: Vec<String> = values
+            .into_iter()
+            .zip(&widths)
+            .map(|(value, width)| {
+                let padding = " ".repeat(width - value.chars().count());
+                format!(" {}{} ", value, padding)
+            })
+            .collect();
+        format!("│{}│", padded.join("│"))
+    };
+
+    let header: Vec<String> = columns
+        .iter()
+        .map(|column| {
+            if color {
+                column.bold().to_string()
+            } else {
+                column.to_string()
+            }
+        })
+        .collect();
+
+    let mut table = vec![border("┌", "┬", "┐")];
+    // Pad by the plain column width so color codes don't skew alignment
+    let header_line: Vec<String> = header
+        .into_iter()
+        .zip(&columns)
+        .zip(&widths)
+        .map(|((styled, plain), width)| {
+            format!(" {}{} ", styled, " ".repeat(width - plain.chars().count()))
+        })
+        .collect();
+    table.push(format!("│{}│", header_line.join("│")));
+    table.push(border("├", "┼", "┤"));
+    for row in cells {
+        table.push(line(row));
+    }
+    table.push(border("└", "┴", "┘"));
+
+    Some(table.join("\n"))
+}
+
+/// Result to return instead of running an IBM Cloud command while logged out
+///
+/// Only `ibmcloud` commands other than `ibmcloud login` are checked; the
+/// login status is read through `runner`.
+async fn ibmcloud_login_gate(
+    command: &str,
+    provider: Option<CloudProviderType>,
+    runner: Arc<dyn CommandRunner>,
+) -> Option<CommandResult> {
+    if provider != Some(CloudProviderType::IBMCloud) || !command.starts_with("ibmcloud") || command.contains("login") {
+        return None;
+    }
+    let e = ensure_ibmcloud_login(runner).await.err()?;
+    println!("{} {}: {}", "⚠️".yellow(), "Login required".yellow(), e);
+    println!("{}", "Please run 'ibmcloud login' first".cyan());
+    Some(CommandResult {
+        success: false,
+        stdout: String::new(),
+        stderr: format!("Not logged in to IBM Cloud: {}", e),
+        truncation_marker: None,
+        raw_stdout: None,
+    })
 }
 
 /// Ensure user is logged in to IBM Cloud
-pub async fn ensure_ibmcloud_login() -> Result<()> {
-    if !check_ibmcloud_login().await? {
+pub async fn ensure_ibmcloud_login(runner: Arc<dyn CommandRunner>) -> CliResult<()> {
+    if !IBMCloudProvider::new().with_runner(runner).is_authenticated().await? {
         println!("{}", "🔐 IBM Cloud login required".yellow());
         println!("{}", "Please run: ibmcloud login".cyan());
-        return Err(crate::core::Error::Authentication(
+        return Err(CliError::NotAuthenticated(
             "Not logged in to IBM Cloud".to_string()
         ));
     }
     Ok(())
 }
 
+/// Repair the JSON output of AWS commands run with `--output json`
+///
+/// Output that is not UTF-8 is left alone; its bytes stay in `raw_stdout`.
+fn repair_json_output(result: &mut CommandResult, command: &str, provider: Option<CloudProviderType>) -> Result<()> {
+    if provider == Some(CloudProviderType::AWS)
+        && command.contains("--output json")
+        && !result.stdout.is_empty()
+        && result.raw_stdout.is_none()
+    {
+        result.stdout = repair_aws_json_output(&result.stdout)?;
+    }
+    Ok(())
+}
+
 /// Repair malformed JSON output from AWS CLI commands using anyrepair
 fn repair_aws_json_output(output: &str) -> Result<String> {
     // Try to extract JSON from the output
@@ -322,12 +1187,12 @@ fn repair_aws_json_output(output: &str) -> Result<String> {
                 println!("{}", "🔧 JSON repaired successfully".green());
                 // Replace the original JSON section with repaired version
                 let mut result = output.to_string();
-                if let Some(json_start) = result.find('{') {
-                    if let Some(json_end) = result.rfind('}') {
-                        let before = &result[..json_start];
-                        let after = &result[json_end + 1..];
-                        result = format!("{}{}{}", before, repaired, after);
-                    }
+                if let Some(json_start) = result.find('{')
+                    && let Some(json_end) = result.rfind('}')
+                {
+                    let before = &result[..json_start];
+                    let after = &result[json_end + 1..];
+                    result = format!("{}{}{}", before, repaired, after);
                 }
                 Ok(result)
             } else {
@@ -338,28 +1203,534 @@ fn repair_aws_json_output(output: &str) -> Result<String> {
     }
 }
 
-/// Handle learning from failed commands
-pub async fn handle_learning(
-    query: &str,
-    failed_command: &str,
-    learning_engine: &mut CommandLearningEngine,
-) -> Result<()> {
-    println!("{} Would you like to provide the correct command?", "📝".cyan());
-    print!("Correct command (or press Enter to skip): ");
+/// Show retry suggestions after a failure and read the command to retry with
+///
+/// Advice is listed as bullets; only commands are numbered. The user can
+/// also type their own command. Returns `None` when they skip the retry.
+pub async fn choose_retry(suggestions: &[RetrySuggestion]) -> Result<Option<String>> {
+    let mut number = 0;
+    for suggestion in suggestions {
+        match suggestion {
+            RetrySuggestion::Command(command) => {
+                number += 1;
+                println!("  {} {}", format!("{}.", number).cyan(), command.bold());
+            }
+            RetrySuggestion::Advice(advice) => println!("  {} {}", "•".dimmed(), advice),
+        }
+    }
+    if number > 0 {
+        print!("{} Retry with which command? [1-{}, c(ustom), Enter to skip]: ", "❓".cyan(), number);
+    } else {
+        print!("{} Retry with your own command? [c(ustom), Enter to skip]: ", "❓".cyan());
+    }
     io::stdout().flush()?;
 
-    let mut correction = String::new();
-    io::stdin().read_line(&mut correction)?;
-    let correction = correction.trim();
+    let mut response = String::new();
+    io::stdin().read_line(&mut response)?;
 
-    if !correction.is_empty() {
-        learning_engine.add_correction(
-            query.to_string(),
-            correction.to_string(),
-            Some(format!("Failed command: {}", failed_command)),
-        ).await?;
-        println!("{} Thanks! I'll remember this.", "✅".green());
+    match retry_choice(suggestions, &response) {
+        RetryChoice::Run(command) => Ok(Some(command)),
+        RetryChoice::Skip => Ok(None),
+        RetryChoice::Custom => {
+            print!("Command: ");
+            io::stdout().flush()?;
+            let mut command = String::new();
+            io::stdin().read_line(&mut command)?;
+            Ok(Some(command.trim().to_string()).filter(|command| !command.is_empty()))
+        }
     }
+}
 
-    Ok(())
+/// Ask whether a retry that worked should be learned for `query`
+///
+/// Enter means no: the retry does something other than the failed command,
+/// such as logging in, and would replace the real command if learned.
+pub async fn confirm_learn_retry(query: &str, retry: &str) -> Result<bool> {
+    print!("{} Remember {} as the command for \"{}\"? [{}]: ", "❓".cyan(), retry.bold(), query, ConfirmDefault::No.choices());
+    io::stdout().flush()?;
+
+    let mut response = String::new();
+    io::stdin().read_line(&mut response)?;
+    let response = response.trim().to_lowercase();
+
+    Ok(response == "y" || response == "yes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use crate::core::{MockRunner, QualityAnalysis, RunOutput};
+    use super::super::translator::PromptSource;
+
+    #[test]
+    fn test_why_before_any_translation() {
+        assert!(format_last_translation(None, DEFAULT_SOURCE_WIDTH).contains("Nothing to explain yet"));
+    }
+
+    #[test]
+    fn test_why_redacts_prompt_and_lists_details() {
+        let last = LastTranslation {
+            query: "log in".to_string(),
+            prompt: Some("Context: ibmcloud login --apikey=abc123\nQuery: log in".to_string()),
+            sources: vec![
+                PromptSource { name: "https://cloud.ibm.com/docs/cli".to_string(), score: 0.62 },
+                PromptSource { name: "ibmcloud login".to_string(), score: 0.4 },
+            ],
+            command: "ibmcloud login --sso".to_string(),
+            quality: Some(QualityAnalysis {
+                score: 0.85,
+                issues: vec!["Missing resource group".to_string()],
+                suggestions: Vec::new(),
+            }),
+            learning_hints: Vec::new(),
+            ..Default::default()
+        };
+
+        let output = format_last_translation(Some(&last), DEFAULT_SOURCE_WIDTH);
+        assert!(!output.contains("abc123"));
+        assert!(output.contains("1. https://cloud.ibm.com/docs/cli (62%)"));
+        assert!(output.contains("2. ibmcloud login (40%)"));
+        assert!(output.contains("85%"));
+        assert!(output.contains("Missing resource group"));
+    }
+
+    #[test]
+    fn test_rag_decision() {
+        let mut last = LastTranslation {
+            rag_confidence: Some(0.9),
+            rag_used: true,
+            rag_skipped: 2,
+            sources: vec![PromptSource { name: "ibmcloud cos".to_string(), score: 0.9 }],
+            ..Default::default()
+        };
+        assert_eq!(format_rag_decision(&last).unwrap(), "90% (1 chunks used as context, 2 below the threshold left out)");
+
+        last.rag_used = false;
+        last.rag_confidence = Some(0.2);
+        last.sources.clear();
+        assert_eq!(format_rag_decision(&last).unwrap(), "20% (too low, answered from model knowledge only, 2 below the threshold left out)");
+
+        assert_eq!(format_rag_decision(&LastTranslation::default()), None);
+    }
+
+    #[test]
+    fn test_truncate_source_name() {
+        let long = "https://cloud.ibm.com/docs/codeengine?topic=codeengine-cli#cli-application-create-with-build";
+        let truncated = truncate_source_name(long, 40);
+        assert_eq!(truncated.chars().count(), 40);
+        assert!(truncated.ends_with('…'));
+        assert!(long.starts_with(truncated.trim_end_matches('…')));
+
+        assert_eq!(truncate_source_name("https://cloud.ibm.com/docs/cli", 40), "https://cloud.ibm.com/docs/cli");
+        assert_eq!(truncate_source_name("exactly-ten", 11), "exactly-ten");
+    }
+
+    #[test]
+    fn test_why_truncates_sources_but_keeps_them_whole() {
+        let source = format!("https://docs.example.com/{}", "nested/".repeat(20));
+        let last = LastTranslation {
+            command: "aws s3 ls".to_string(),
+            sources: vec![PromptSource { name: source.clone(), score: 0.5 }],
+            ..Default::default()
+        };
+
+        let output = format_last_translation(Some(&last), 30);
+        assert!(output.contains(&truncate_source_name(&source, 30)));
+        assert!(!output.contains(&source));
+        assert_eq!(last.sources[0].name, source);
+    }
+
+    #[test]
+    fn test_read_piped_lines_until_eof() {
+        let mut reader = Cursor::new("list buckets\n\n  list vms  \nexit");
+        let mut history = Vec::new();
+
+        let mut lines = Vec::new();
+        while let Some(line) = read_piped_line(&mut reader, &mut history, "").unwrap() {
+            lines.push(line);
+        }
+
+        assert_eq!(lines, vec!["list buckets", "", "list vms", "exit"]);
+        assert_eq!(history, vec!["list buckets", "list vms", "exit"]);
+    }
+
+    #[test]
+    fn test_read_piped_line_keeps_initial_on_empty_line() {
+        let mut reader = Cursor::new("\n");
+        let mut history = Vec::new();
+
+        assert_eq!(read_piped_line(&mut reader, &mut history, "aws s3 ls").unwrap().as_deref(), Some("aws s3 ls"));
+        assert_eq!(read_piped_line(&mut reader, &mut history, "aws s3 ls").unwrap(), None);
+    }
+
+    #[test]
+    fn test_push_history_skips_consecutive_duplicates() {
+        let mut history = Vec::new();
+        push_history(&mut history, "aws s3 ls");
+        push_history(&mut history, "aws s3 ls");
+        push_history(&mut history, "");
+        push_history(&mut history, "az vm list");
+        push_history(&mut history, "aws s3 ls");
+
+        assert_eq!(history, vec!["aws s3 ls", "az vm list", "aws s3 ls"]);
+    }
+
+    #[test]
+    fn test_multiline_backslash_continuation() {
+        let mut input = MultilineInput::default();
+        assert_eq!(input.push("list all ec2 instances \\"), None);
+        assert!(input.is_pending());
+        assert_eq!(input.push("  in us-east-1\\"), None);
+        assert_eq!(input.push("with their tags").as_deref(), Some("list all ec2 instances in us-east-1 with their tags"));
+        assert!(!input.is_pending());
+
+        // A plain line is a complete query on its own
+        assert_eq!(input.push("aws s3 ls").as_deref(), Some("aws s3 ls"));
+        assert_eq!(input.push("").as_deref(), Some(""));
+    }
+
+    #[test]
+    fn test_multiline_fence() {
+        let mut input = MultilineInput::default();
+        assert_eq!(input.push("\"\"\""), None);
+        assert_eq!(input.push("create a bucket named logs"), None);
+        assert_eq!(input.push(""), None);
+        assert_eq!(input.push("with versioning enabled \\"), None);
+        assert!(input.is_pending());
+        assert_eq!(input.push("\"\"\"").as_deref(), Some("create a bucket named logs with versioning enabled \\"));
+
+        assert_eq!(input.push("\"\"\"list vms").as_deref(), None);
+        assert_eq!(input.push("in westeurope\"\"\"").as_deref(), Some("list vms in westeurope"));
+    }
+
+    #[test]
+    fn test_multiline_finish_at_end_of_input() {
+        let mut input = MultilineInput::default();
+        assert_eq!(input.finish(), None);
+        input.push("list clusters \\");
+        assert_eq!(input.finish().as_deref(), Some("list clusters"));
+        assert!(!input.is_pending());
+    }
+
+    #[test]
+    fn test_json_output_flag_per_provider() {
+        let cases = [
+            ("ibmcloud resource groups", CloudProviderType::IBMCloud, "ibmcloud resource groups --output json"),
+            ("aws ec2 describe-instances", CloudProviderType::AWS, "aws ec2 describe-instances --output json"),
+            ("gcloud compute instances list", CloudProviderType::GCP, "gcloud compute instances list --format=json"),
+            ("az vm list", CloudProviderType::Azure, "az vm list --output json"),
+            ("govc ls /dc1/vm", CloudProviderType::VMware, "govc ls -json /dc1/vm"),
+            ("govc vm.info -r 'web 01'", CloudProviderType::VMware, "govc vm.info -json -r 'web 01'"),
+            ("govc datastore.info", CloudProviderType::VMware, "govc datastore.info -json"),
+            ("doctl compute droplet list", CloudProviderType::DigitalOcean, "doctl compute droplet list --output json"),
+        ];
+        for (command, provider, expected) in cases {
+            assert_eq!(with_json_output_flag(command, provider).as_deref(), Some(expected), "{}", command);
+        }
+    }
+
+    #[test]
+    fn test_json_output_flag_skipped_when_not_applicable() {
+        // A format is already chosen
+        assert_eq!(with_json_output_flag("az vm list -o table", CloudProviderType::Azure), None);
+        assert_eq!(with_json_output_flag("aws s3api list-buckets --output=text", CloudProviderType::AWS), None);
+        assert_eq!(with_json_output_flag("gcloud projects list --format yaml", CloudProviderType::GCP), None);
+        assert_eq!(with_json_output_flag("govc ls -json", CloudProviderType::VMware), None);
+        // Local commands and pipelines
+        assert_eq!(with_json_output_flag("ibmcloud login --sso", CloudProviderType::IBMCloud), None);
+        assert_eq!(with_json_output_flag("ibmcloud version", CloudProviderType::IBMCloud), None);
+        assert_eq!(with_json_output_flag("aws s3 ls | grep logs", CloudProviderType::AWS), None);
+    }
+
+    #[test]
+    fn test_confirm_default_depends_on_destructiveness() {
+        assert_eq!(ConfirmDefault::for_command("aws s3 ls", None), ConfirmDefault::Yes);
+        assert_eq!(ConfirmDefault::for_command("ibmcloud resource groups", None), ConfirmDefault::Yes);
+        assert_eq!(ConfirmDefault::for_command("aws s3 rb s3://logs", None), ConfirmDefault::No);
+        assert_eq!(ConfirmDefault::for_command("az group delete --name rg1", None), ConfirmDefault::No);
+        assert_eq!(ConfirmDefault::No.choices(), "y/N");
+    }
+
+    #[test]
+    fn test_confirm_default_override_wins() {
+        assert_eq!(ConfirmDefault::for_command("aws s3 ls", Some(ConfirmDefault::No)), ConfirmDefault::No);
+        assert_eq!(
+            ConfirmDefault::for_command("aws s3 rb s3://logs", Some(ConfirmDefault::Yes)),
+            ConfirmDefault::Yes
+        );
+    }
+
+    fn json_result(stdout: &str) -> CommandResult {
+        CommandResult {
+            success: true,
+            stdout: stdout.to_string(),
+            stderr: String::new(),
+            truncation_marker: None,
+            raw_stdout: None,
+        }
+    }
+
+    #[test]
+    fn test_render_json_array_as_table() {
+        let result = json_result(r#"[{"name": "web", "state": "running"}, {"name": "db", "state": "stopped", "cpus": 2}]"#);
+        let table = render_result(&result, OutputFormat::Table, false);
+
+        assert_eq!(
+            table,
+            [
+                "┌──────┬─────────┬──────┐",
+                "│ name │ state   │ cpus │",
+                "├──────┼─────────┼──────┤",
+                "│ web  │ running │      │",
+                "│ db   │ stopped │ 2    │",
+                "└──────┴─────────┴──────┘",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_render_table_falls_back_for_nested_values() {
+        let stdout = r#"[{"name": "web", "tags": ["a"]}]"#;
+        assert_eq!(render_result(&json_result(stdout), OutputFormat::Table, false), stdout);
+    }
+
+    #[test]
+    fn test_render_json_as_yaml() {
+        let result = json_result(r#"{"Buckets": [{"Name": "logs"}], "Owner": {"ID": "abc"}}"#);
+        let yaml = render_result(&result, OutputFormat::Yaml, false);
+        let parsed: serde_yaml::Value = serde_yaml::from_str(&yaml).unwrap();
+
+        assert!(yaml.contains("Name: logs"));
+        assert_eq!(parsed["Owner"]["ID"], serde_yaml::Value::from("abc"));
+    }
+
+    #[test]
+    fn test_render_text_passthrough() {
+        let result = json_result("not json at all");
+        assert_eq!(render_result(&result, OutputFormat::Text, true), "not json at all");
+        assert_eq!(render_result(&result, OutputFormat::Json, true), "not json at all");
+    }
+
+    #[tokio::test]
+    async fn test_run_cancellable_cancels_long_future() {
+        let (trigger, signal) = tokio::sync::oneshot::channel::<()>();
+        let long_running = async {
+            tokio::time::sleep(std::time::Duration::from_secs(30)).await;
+            "translated"
+        };
+
+        trigger.send(()).unwrap();
+        let start = std::time::Instant::now();
+        let outcome = run_cancellable(long_running, async {
+            let _ = signal.await;
+        })
+        .await;
+
+        assert_eq!(outcome, None);
+        assert!(start.elapsed() < std::time::Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_ctrl_c_cancels_only_active_waits() {
+        let scopes = CtrlCScopes::default();
+        assert!(!scopes.deliver());
+
+        let wait = scopes.run(std::future::pending::<()>());
+        tokio::pin!(wait);
+        // Polling once starts the wait
+        assert!(tokio::time::timeout(std::time::Duration::from_millis(10), &mut wait).await.is_err());
+        assert!(scopes.deliver());
+        assert_eq!(wait.await, None);
+
+        // Finished waits no longer take the signal
+        assert!(!scopes.deliver());
+        assert_eq!(scopes.run(async { "aws s3 ls" }).await, Some("aws s3 ls"));
+        assert!(!scopes.deliver());
+    }
+
+    #[tokio::test]
+    async fn test_run_cancellable_returns_output_when_not_cancelled() {
+        let outcome = run_cancellable(async { "aws s3 ls" }, std::future::pending()).await;
+        assert_eq!(outcome, Some("aws s3 ls"));
+    }
+
+    #[test]
+    fn test_search_history_most_recent_first() {
+        let history: Vec<String> = vec![
+            "list s3 buckets".to_string(),
+            "az vm list".to_string(),
+            "show ec2 instances".to_string(),
+            "list lambda functions".to_string(),
+            "list s3 buckets".to_string(),
+        ];
+
+        assert_eq!(
+            search_history(&history, "list"),
+            vec!["list s3 buckets", "list lambda functions", "az vm list"]
+        );
+        assert_eq!(search_history(&history, "ec2"), vec!["show ec2 instances"]);
+        assert!(search_history(&history, "gcloud").is_empty());
+    }
+
+    #[test]
+    fn test_banner_shown_on_terminal_by_default() {
+        assert!(should_display_banner(false, None, true));
+        assert!(should_display_banner(false, Some(""), true));
+        assert!(should_display_banner(false, Some("0"), true));
+        assert!(should_display_banner(false, Some("False"), true));
+    }
+
+    #[test]
+    fn test_banner_suppressed() {
+        assert!(!should_display_banner(false, None, false), "stdout is not a terminal");
+        assert!(!should_display_banner(true, None, true), "--no-banner");
+        assert!(!should_display_banner(false, Some("1"), true));
+        assert!(!should_display_banner(false, Some("yes"), true));
+    }
+
+    #[test]
+    fn test_color_enabled() {
+        use std::ffi::OsStr;
+        assert!(color_enabled(None, true));
+        assert!(color_enabled(Some(OsStr::new("")), true));
+        assert!(!color_enabled(Some(OsStr::new("1")), true));
+        assert!(!color_enabled(None, false), "stdout is not a terminal");
+    }
+
+    #[test]
+    fn test_line_buffer_prefilled_with_cursor_at_end() {
+        let mut buffer = LineBuffer::prefilled("aws s3 ls");
+        assert_eq!(buffer.cursor, "aws s3 ls".len());
+
+        for c in " --recursive".chars() {
+            buffer.insert(c);
+        }
+        assert_eq!(buffer.text(), "aws s3 ls --recursive");
+        assert_eq!(buffer.cursor, buffer.text().len());
+
+        assert_eq!(LineBuffer::prefilled(""), LineBuffer::default());
+    }
+
+    #[test]
+    fn test_line_buffer_backspace_keeps_char_boundaries() {
+        let mut buffer = LineBuffer::prefilled("tag café");
+        assert!(buffer.backspace());
+        assert_eq!(buffer.text(), "tag caf");
+        assert_eq!(buffer.cursor, 7);
+
+        buffer.insert('é');
+        assert_eq!(buffer.into_text(), "tag café");
+
+        let mut empty = LineBuffer::default();
+        assert!(!empty.backspace());
+        assert_eq!(empty.cursor, 0);
+    }
+
+    fn lines(entries: &[&str]) -> Vec<String> {
+        entries.iter().map(|entry| entry.to_string()).collect()
+    }
+
+    #[test]
+    fn test_history_cursor_without_history() {
+        let history = Vec::new();
+        let mut cursor = HistoryCursor::new(&history);
+        assert_eq!(cursor.up(&history, "draft"), None);
+        assert_eq!(cursor.down(&history), None);
+        assert_eq!(cursor.up(&history, "draft"), None);
+    }
+
+    #[test]
+    fn test_history_cursor_single_entry() {
+        let history = lines(&["list buckets"]);
+        let mut cursor = HistoryCursor::new(&history);
+        assert_eq!(cursor.down(&history), None);
+        assert_eq!(cursor.up(&history, "show vms").as_deref(), Some("list buckets"));
+        assert_eq!(cursor.up(&history, "list buckets"), None);
+        assert_eq!(cursor.down(&history).as_deref(), Some("show vms"));
+        assert_eq!(cursor.down(&history), None);
+        assert_eq!(cursor.up(&history, "show vms").as_deref(), Some("list buckets"));
+    }
+
+    #[test]
+    fn test_history_cursor_walks_three_entries() {
+        let history = lines(&["one", "two", "three"]);
+        let mut cursor = HistoryCursor::new(&history);
+
+        let ups: Vec<Option<String>> = (0..4).map(|_| cursor.up(&history, "")).collect();
+        assert_eq!(ups, vec![Some("three".to_string()), Some("two".to_string()), Some("one".to_string()), None]);
+
+        let downs: Vec<Option<String>> = (0..4).map(|_| cursor.down(&history)).collect();
+        assert_eq!(downs, vec![Some("two".to_string()), Some("three".to_string()), Some(String::new()), None]);
+
+        // Typing on a recalled entry and moving away does not replace the draft
+        assert_eq!(cursor.up(&history, "new draft").as_deref(), Some("three"));
+        assert_eq!(cursor.up(&history, "three edited").as_deref(), Some("two"));
+        assert_eq!(cursor.down(&history).as_deref(), Some("three"));
+        assert_eq!(cursor.down(&history).as_deref(), Some("new draft"));
+    }
+
+    fn ibmcloud_session(logged_in: bool) -> Arc<MockRunner> {
+        Arc::new(MockRunner::new(move |_, _| Ok(RunOutput { success: logged_in, ..Default::default() })))
+    }
+
+    #[tokio::test]
+    async fn test_login_gate_blocks_when_logged_out() {
+        let runner = ibmcloud_session(false);
+        let blocked = ibmcloud_login_gate("ibmcloud resource groups", Some(CloudProviderType::IBMCloud), runner.clone())
+            .await
+            .expect("logged-out command should be blocked");
+
+        assert!(!blocked.success);
+        assert!(blocked.stderr.starts_with("Not logged in to IBM Cloud"));
+        assert_eq!(runner.calls(), vec!["ibmcloud target"]);
+        assert!(matches!(ensure_ibmcloud_login(runner).await, Err(CliError::NotAuthenticated(_))));
+    }
+
+    #[tokio::test]
+    async fn test_login_gate_passes_when_logged_in() {
+        let runner = ibmcloud_session(true);
+        assert!(ibmcloud_login_gate("ibmcloud ks clusters", Some(CloudProviderType::IBMCloud), runner.clone()).await.is_none());
+        assert_eq!(runner.calls().len(), 1);
+    }
+
+    #[test]
+    fn test_json_repair_only_for_aws_json_output() {
+        let broken = "{\"Buckets\": [{\"Name\": \"logs\"}";
+        let result = |stdout: &str| CommandResult {
+            success: true,
+            stdout: stdout.to_string(),
+            stderr: String::new(),
+            truncation_marker: None,
+            raw_stdout: None,
+        };
+
+        let mut aws = result(broken);
+        repair_json_output(&mut aws, "aws s3api list-buckets --output json", Some(CloudProviderType::AWS)).unwrap();
+        assert!(serde_json::from_str::<serde_json::Value>(&aws.stdout).is_ok(), "{}", aws.stdout);
+
+        for (command, provider) in [
+            ("aws s3api list-buckets --output text", Some(CloudProviderType::AWS)),
+            ("gcloud storage buckets list --format json", Some(CloudProviderType::GCP)),
+            ("aws s3api list-buckets --output json", None),
+        ] {
+            let mut untouched = result(broken);
+            repair_json_output(&mut untouched, command, provider).unwrap();
+            assert_eq!(untouched.stdout, broken, "{}", command);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_login_gate_skips_other_commands() {
+        let runner = ibmcloud_session(false);
+        for (command, provider) in [
+            ("ibmcloud login --sso", Some(CloudProviderType::IBMCloud)),
+            ("aws s3 ls", Some(CloudProviderType::AWS)),
+            ("ibmcloud resource groups", None),
+        ] {
+            assert!(ibmcloud_login_gate(command, provider, runner.clone()).await.is_none(), "{}", command);
+        }
+        assert!(runner.calls().is_empty());
+    }
 }
@@ -0,0 +1,188 @@
+//! Cumulative LLM usage, persisted across sessions
+//!
+//! Counts are bucketed by local day and model. Token counts only grow when
+//! the LLM reports them in `GenerationResult::tokens_used`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use chrono::{Days, NaiveDate};
+use crate::core::{Error, Result};
+
+/// Date format of the day buckets; ISO dates sort chronologically as strings
+const DAY_FORMAT: &str = "%Y-%m-%d";
+
+/// Where usage is stored: `$XDG_DATA_HOME/anycli/usage.json`, defaulting to
+/// `~/.local/share/anycli/usage.json`
+pub fn default_usage_path() -> PathBuf {
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share")));
+
+    match data_home {
+        Some(dir) => dir.join("anycli").join("usage.json"),
+        None => PathBuf::from("usage.json"),
+    }
+}
+
+/// Requests and tokens counted for one model or period
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageCount {
+    pub requests: u64,
+    pub tokens: u64,
+}
+
+impl UsageCount {
+    fn add(&mut self, other: UsageCount) {
+        self.requests += other.requests;
+        self.tokens += other.tokens;
+    }
+}
+
+/// Usage totals for the `usage` subcommand
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UsageSummary {
+    pub today: UsageCount,
+    /// Today and the six days before it
+    pub last_7_days: UsageCount,
+    pub total: UsageCount,
+    /// All-time totals per model
+    pub by_model: BTreeMap<String, UsageCount>,
+}
+
+/// Per-day, per-model usage stored as JSON
+pub struct UsageLog {
+    days: BTreeMap<String, BTreeMap<String, UsageCount>>,
+    file_path: PathBuf,
+}
+
+impl UsageLog {
+    /// Open the usage log at `file_path`, if any
+    pub fn new(file_path: &Path) -> Result<Self> {
+        let mut log = Self {
+            days: BTreeMap::new(),
+            file_path: file_path.to_path_buf(),
+        };
+
+        if file_path.exists()
+            && let Err(e) = log.load()
+        {
+            eprintln!("Warning: Failed to load usage: {}", e);
+        }
+
+        Ok(log)
+    }
+
+    fn load(&mut self) -> Result<()> {
+        let content = std::fs::read_to_string(&self.file_path)?;
+        self.days = serde_json::from_str(&content)
+            .map_err(|e| Error::Serialization(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Save the log, creating its directory if needed
+    pub fn save(&self) -> Result<()> {
+        if let Some(dir) = self.file_path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(&self.days)
+            .map_err(|e| Error::Serialization(e.to_string()))?;
+        std::fs::write(&self.file_path, json)?;
+        Ok(())
+    }
+
+    /// Count one request to `model` on `day`, with its tokens when known
+    pub fn record(&mut self, day: NaiveDate, model: &str, tokens: Option<u32>) {
+        let count = self
+            .days
+            .entry(day.format(DAY_FORMAT).to_string())
+            .or_default()
+            .entry(model.to_string())
+            .or_default();
+        count.add(UsageCount {
+            requests: 1,
+            tokens: tokens.map_or(0, u64::from),
+        });
+    }
+
+    /// Usage for `model` on `day`
+    pub fn day(&self, day: NaiveDate, model: &str) -> UsageCount {
+        self.days
+            .get(&day.format(DAY_FORMAT).to_string())
+            .and_then(|models| models.get(model))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Aggregate usage relative to `today`
+    pub fn summary(&self, today: NaiveDate) -> UsageSummary {
+        let today_key = today.format(DAY_FORMAT).to_string();
+        let week_start = today
+            .checked_sub_days(Days::new(6))
+            .unwrap_or(today)
+            .format(DAY_FORMAT)
+            .to_string();
+
+        let mut summary = UsageSummary::default();
+        for (day, models) in &self.days {
+            for (model, count) in models {
+                if *day == today_key {
+                    summary.today.add(*count);
+                }
+                if *day >= week_start && *day <= today_key {
+                    summary.last_7_days.add(*count);
+                }
+                summary.total.add(*count);
+                summary.by_model.entry(model.clone()).or_default().add(*count);
+            }
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(day, DAY_FORMAT).unwrap()
+    }
+
+    #[test]
+    fn test_record_into_day_bucket() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("usage.json");
+
+        let mut log = UsageLog::new(&path).unwrap();
+        log.record(date("2026-03-01"), "granite", Some(120));
+        log.record(date("2026-03-01"), "granite", Some(80));
+        log.record(date("2026-03-02"), "granite", None);
+        log.record(date("2026-03-01"), "llama", Some(10));
+        log.save().unwrap();
+
+        let reloaded = UsageLog::new(&path).unwrap();
+        assert_eq!(reloaded.day(date("2026-03-01"), "granite"), UsageCount { requests: 2, tokens: 200 });
+        assert_eq!(reloaded.day(date("2026-03-02"), "granite"), UsageCount { requests: 1, tokens: 0 });
+        assert_eq!(reloaded.day(date("2026-03-01"), "llama"), UsageCount { requests: 1, tokens: 10 });
+        assert_eq!(reloaded.day(date("2026-03-03"), "granite"), UsageCount::default());
+    }
+
+    #[test]
+    fn test_summary_aggregation() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = UsageLog::new(&dir.path().join("usage.json")).unwrap();
+        log.record(date("2026-02-20"), "granite", Some(1000)); // outside the week
+        log.record(date("2026-02-24"), "granite", Some(100)); // six days before
+        log.record(date("2026-03-02"), "granite", Some(10));
+        log.record(date("2026-03-02"), "llama", Some(1));
+        log.record(date("2026-03-03"), "granite", Some(5000)); // after "today"
+
+        let summary = log.summary(date("2026-03-02"));
+        assert_eq!(summary.today, UsageCount { requests: 2, tokens: 11 });
+        assert_eq!(summary.last_7_days, UsageCount { requests: 3, tokens: 111 });
+        assert_eq!(summary.total, UsageCount { requests: 5, tokens: 6111 });
+        assert_eq!(summary.by_model["granite"], UsageCount { requests: 4, tokens: 6110 });
+        assert_eq!(summary.by_model["llama"], UsageCount { requests: 1, tokens: 1 });
+    }
+}
@@ -1,11 +1,39 @@
 //! Cloud provider abstraction for multi-cloud support
 
-use super::{Error, Result};
+use super::{CommandSafety, ParsedCommand, Result};
 use async_trait::async_trait;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+
+/// Names and aliases accepted by [`CloudProviderType::from_str`]
+const PROVIDER_NAMES: &[(&str, CloudProviderType)] = &[
+    ("ibmcloud", CloudProviderType::IBMCloud),
+    ("ibm", CloudProviderType::IBMCloud),
+    ("aws", CloudProviderType::AWS),
+    ("amazon", CloudProviderType::AWS),
+    ("gcp", CloudProviderType::GCP),
+    ("gcloud", CloudProviderType::GCP),
+    ("google", CloudProviderType::GCP),
+    ("azure", CloudProviderType::Azure),
+    ("az", CloudProviderType::Azure),
+    ("microsoft", CloudProviderType::Azure),
+    ("vmware", CloudProviderType::VMware),
+    ("vsphere", CloudProviderType::VMware),
+    ("govc", CloudProviderType::VMware),
+    ("vmc", CloudProviderType::VMware),
+    ("digitalocean", CloudProviderType::DigitalOcean),
+    ("do", CloudProviderType::DigitalOcean),
+    ("doctl", CloudProviderType::DigitalOcean),
+    ("github", CloudProviderType::GitHub),
+    ("gh", CloudProviderType::GitHub),
+    ("terraform", CloudProviderType::Terraform),
+    ("tf", CloudProviderType::Terraform),
+];
 
 /// Supported cloud providers
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum CloudProviderType {
     /// IBM Cloud
     IBMCloud,
@@ -17,6 +45,12 @@ pub enum CloudProviderType {
     Azure,
     /// VMware vSphere/Cloud
     VMware,
+    /// DigitalOcean
+    DigitalOcean,
+    /// GitHub, through the gh CLI
+    GitHub,
+    /// Terraform infrastructure as code
+    Terraform,
 }
 
 impl CloudProviderType {
@@ -28,6 +62,23 @@ impl CloudProviderType {
             CloudProviderType::GCP => "gcloud",
             CloudProviderType::Azure => "az",
             CloudProviderType::VMware => "govc",
+            CloudProviderType::DigitalOcean => "doctl",
+            CloudProviderType::GitHub => "gh",
+            CloudProviderType::Terraform => "terraform",
+        }
+    }
+
+    /// Get the stable identifier used in metadata and file names
+    pub fn id(&self) -> &'static str {
+        match self {
+            CloudProviderType::IBMCloud => "ibmcloud",
+            CloudProviderType::AWS => "aws",
+            CloudProviderType::GCP => "gcp",
+            CloudProviderType::Azure => "azure",
+            CloudProviderType::VMware => "vmware",
+            CloudProviderType::DigitalOcean => "digitalocean",
+            CloudProviderType::GitHub => "github",
+            CloudProviderType::Terraform => "terraform",
         }
     }
 
@@ -39,6 +90,58 @@ impl CloudProviderType {
             CloudProviderType::GCP => "Google Cloud Platform",
             CloudProviderType::Azure => "Microsoft Azure",
             CloudProviderType::VMware => "VMware vSphere",
+            CloudProviderType::DigitalOcean => "DigitalOcean",
+            CloudProviderType::GitHub => "GitHub",
+            CloudProviderType::Terraform => "Terraform",
+        }
+    }
+
+    /// Get the command used to check authentication status
+    pub fn auth_check_command(&self) -> &'static str {
+        match self {
+            CloudProviderType::IBMCloud => "ibmcloud target",
+            CloudProviderType::AWS => "aws sts get-caller-identity",
+            CloudProviderType::GCP => "gcloud auth list",
+            CloudProviderType::Azure => "az account show",
+            CloudProviderType::VMware => "govc about",
+            CloudProviderType::DigitalOcean => "doctl account get",
+            CloudProviderType::GitHub => "gh auth status",
+            CloudProviderType::Terraform => "terraform version",
+        }
+    }
+
+    /// Get the flag that switches the CLI to JSON output, if it has one
+    pub fn json_output_flag(&self) -> Option<&'static str> {
+        match self {
+            CloudProviderType::IBMCloud => Some("--output json"),
+            CloudProviderType::AWS => Some("--output json"),
+            CloudProviderType::GCP => Some("--format=json"),
+            CloudProviderType::Azure => Some("--output json"),
+            CloudProviderType::VMware => Some("-json"),
+            CloudProviderType::DigitalOcean => Some("--output json"),
+            // `--json` needs a field list that differs per command
+            CloudProviderType::GitHub => None,
+            // Only some subcommands take `-json`
+            CloudProviderType::Terraform => None,
+        }
+    }
+
+    /// Subcommands that prompt for input, each with the flags that make it run unattended
+    pub fn interactive_subcommands(&self) -> &'static [(&'static str, &'static [&'static str])] {
+        match self {
+            CloudProviderType::IBMCloud => &[("login", &["--apikey", "--cr-token"])],
+            CloudProviderType::AWS => &[("configure", &[]), ("configure sso", &[])],
+            CloudProviderType::GCP => &[("init", &[]), ("auth login", &["--cred-file"])],
+            CloudProviderType::Azure => &[("login", &["--service-principal", "--identity"])],
+            CloudProviderType::VMware => &[],
+            CloudProviderType::DigitalOcean => &[("auth init", &["--access-token", "-t"])],
+            CloudProviderType::GitHub => &[("auth login", &["--with-token"]), ("auth refresh", &[])],
+            CloudProviderType::Terraform => &[
+                ("apply", &["-auto-approve"]),
+                ("destroy", &["-auto-approve"]),
+                ("login", &[]),
+                ("console", &[]),
+            ],
         }
     }
 
@@ -50,19 +153,32 @@ impl CloudProviderType {
             CloudProviderType::GCP,
             CloudProviderType::Azure,
             CloudProviderType::VMware,
+            CloudProviderType::DigitalOcean,
+            CloudProviderType::GitHub,
+            CloudProviderType::Terraform,
         ]
     }
 
     /// Parse from string
     pub fn from_str(s: &str) -> Option<CloudProviderType> {
-        match s.to_lowercase().as_str() {
-            "ibmcloud" | "ibm" => Some(CloudProviderType::IBMCloud),
-            "aws" | "amazon" => Some(CloudProviderType::AWS),
-            "gcp" | "gcloud" | "google" => Some(CloudProviderType::GCP),
-            "azure" | "az" | "microsoft" => Some(CloudProviderType::Azure),
-            "vmware" | "vsphere" | "govc" | "vmc" => Some(CloudProviderType::VMware),
-            _ => None,
-        }
+        let s = s.to_lowercase();
+        PROVIDER_NAMES
+            .iter()
+            .find(|(name, _)| *name == s)
+            .map(|(_, provider)| *provider)
+    }
+
+    /// Known provider name closest to a misspelled `name`, for "did you mean" hints
+    ///
+    /// Two-letter aliases such as `az` are never suggested, since almost any
+    /// short input is within reach of one.
+    pub fn suggest_name(name: &str) -> Option<&'static str> {
+        let candidates: Vec<&'static str> = PROVIDER_NAMES
+            .iter()
+            .map(|(name, _)| *name)
+            .filter(|name| name.len() > 2)
+            .collect();
+        super::closest_match(name, &candidates)
     }
 }
 
@@ -109,6 +225,47 @@ impl CloudProviderConfig {
     }
 }
 
+/// Machine-readable description of what a provider supports
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProviderCapabilities {
+    /// Provider type
+    pub provider: CloudProviderType,
+    /// CLI binary name
+    pub cli_binary: String,
+    /// Command used to check authentication
+    pub auth_check_command: String,
+    /// Supported top-level services
+    pub services: Vec<String>,
+    /// Whether the CLI can emit JSON
+    pub json_output: bool,
+    /// Flag that enables JSON output
+    pub json_output_flag: Option<String>,
+}
+
+impl ProviderCapabilities {
+    /// Build a manifest for a provider type with the given top-level services
+    ///
+    /// Services are deduplicated while keeping their first-seen order.
+    pub fn new<S: AsRef<str>>(provider: CloudProviderType, services: &[S]) -> Self {
+        let mut unique: Vec<String> = Vec::new();
+        for service in services {
+            let service = service.as_ref().to_string();
+            if !unique.contains(&service) {
+                unique.push(service);
+            }
+        }
+
+        Self {
+            provider,
+            cli_binary: provider.cli_command().to_string(),
+            auth_check_command: provider.auth_check_command().to_string(),
+            services: unique,
+            json_output: provider.json_output_flag().is_some(),
+            json_output_flag: provider.json_output_flag().map(str::to_string),
+        }
+    }
+}
+
 /// Trait for cloud provider-specific operations
 #[async_trait]
 pub trait CloudProvider: Send + Sync {
@@ -124,11 +281,108 @@ pub trait CloudProvider: Send + Sync {
     /// Get provider-specific context for RAG
     fn get_rag_context(&self) -> String;
 
+    /// Validate a command for this provider and classify its risk
+    ///
+    /// Implementations typically finish with `CommandSafety::classify`.
+    fn validate(&self, command: &str) -> Result<CommandSafety>;
+
     /// Validate a command for this provider
-    fn validate_command(&self, command: &str) -> Result<()>;
+    fn validate_command(&self, command: &str) -> Result<()> {
+        self.validate(command).map(|_| ())
+    }
 
     /// Get common command patterns for this provider
     fn get_command_patterns(&self) -> Vec<String>;
+
+    /// Global flags from configuration that generated commands should carry
+    ///
+    /// Returned as `(flag, value)` pairs such as `("--region", "us-east-1")`.
+    fn global_flags(&self) -> Vec<(String, String)> {
+        Vec::new()
+    }
+
+    /// The global flags that `command` accepts
+    ///
+    /// Defaults to all of `global_flags`; providers override this when some
+    /// commands reject a flag.
+    fn flags_for_command(&self, _command: &str) -> Vec<(String, String)> {
+        self.global_flags()
+    }
+
+    /// Check the command's top-level service against the known set
+    ///
+    /// Returns a warning (with a suggestion when one is close) for unknown
+    /// services. Providers without a service list accept everything.
+    fn check_command_service(&self, _command: &str) -> Option<String> {
+        None
+    }
+
+    /// Describe what this provider supports
+    ///
+    /// The default derives the service list from `get_command_patterns`.
+    fn capabilities(&self) -> ProviderCapabilities {
+        let cli = self.provider_type().cli_command();
+        let services: Vec<String> = self
+            .get_command_patterns()
+            .iter()
+            .filter_map(|pattern| {
+                let mut tokens = pattern.split_whitespace();
+                match (tokens.next(), tokens.next()) {
+                    (Some(first), Some(service)) if first == cli => Some(service.to_string()),
+                    _ => None,
+                }
+            })
+            .collect();
+
+        ProviderCapabilities::new(self.provider_type(), &services)
+    }
+
+    /// List top-level services as `(service, description)` pairs
+    ///
+    /// The default parses `- <cli> <service>...: <description>` lines from
+    /// `get_rag_context`, keeping the first description seen per service.
+    fn list_services(&self) -> Vec<(String, String)> {
+        let cli = self.provider_type().cli_command();
+        let mut services: Vec<(String, String)> = Vec::new();
+
+        for line in self.get_rag_context().lines() {
+            let Some(entry) = line.trim().strip_prefix("- ") else {
+                continue;
+            };
+            let Some((command, description)) = entry.split_once(':') else {
+                continue;
+            };
+
+            let mut tokens = command.split_whitespace();
+            if tokens.next() != Some(cli) {
+                continue;
+            }
+            if let Some(service) = tokens.next()
+                && !services.iter().any(|(known, _)| known == service)
+            {
+                services.push((service.to_string(), description.trim().to_string()));
+            }
+        }
+
+        services
+    }
+}
+
+/// Check that the token after `cli` is one of `known_services`
+///
+/// Returns `"unknown service 'X'; did you mean 'Y'?"` on mismatch, or `None`
+/// when the service is known or the command has no service token.
+pub fn check_known_service(command: &str, cli: &str, known_services: &[&str]) -> Option<String> {
+    let parsed = super::ParsedCommand::parse(command).ok().filter(|parsed| parsed.binary == cli)?;
+    let service = parsed.subcommands.first()?.as_str();
+    if known_services.contains(&service) {
+        return None;
+    }
+
+    Some(match super::closest_match(service, known_services) {
+        Some(suggestion) => format!("unknown service '{}'; did you mean '{}'?", service, suggestion),
+        None => format!("unknown service '{}'", service),
+    })
 }
 
 /// Cloud provider detection result
@@ -140,82 +394,176 @@ pub struct ProviderDetectionResult {
     pub confidence: f32,
     /// Reason for detection
     pub reason: String,
+    /// Keywords from the query that triggered the match
+    pub matched_keywords: Vec<String>,
+}
+
+/// Keywords identifying each provider, checked in order
+///
+/// The first provider with any matching keyword wins; keywords only match at
+/// the start of a word. The label is used in the detection reason.
+const PROVIDER_KEYWORDS: &[(CloudProviderType, &str, &[&str])] = &[
+    (CloudProviderType::IBMCloud, "IBM Cloud", &["ibmcloud", "ibm cloud", "watson", "watsonx", "code engine"]),
+    (CloudProviderType::AWS, "AWS", &["ec2", "s3", "lambda", "eks", "aws"]),
+    (CloudProviderType::GCP, "GCP", &["gcloud", "gcp", "compute engine", "gke", "cloud storage"]),
+    (CloudProviderType::Azure, "Azure", &["azure", "az ", "aks", "virtual machine"]),
+    (CloudProviderType::VMware, "VMware", &["vmware", "vsphere", "govc", "esxi", "vcenter", "vmc"]),
+    (CloudProviderType::DigitalOcean, "DigitalOcean", &["digitalocean", "digital ocean", "doctl", "droplet"]),
+    (CloudProviderType::Terraform, "Terraform", &["terraform", "tf plan", "apply infrastructure"]),
+    (CloudProviderType::GitHub, "GitHub", &["github", "pull request", "gh ", "repo", "workflow"]),
+];
+
+/// Whether `keyword` occurs in `text` as a whole word, or its plural
+///
+/// Keeps short keywords such as `gh ` from matching inside `high ` and
+/// `repo` from matching inside `repository`.
+fn contains_keyword(text: &str, keyword: &str) -> bool {
+    let ends_word = keyword.ends_with(|c: char| !c.is_alphanumeric());
+    text.match_indices(keyword).any(|(start, _)| {
+        let rest = &text[start + keyword.len()..];
+        let rest = rest.strip_prefix('s').unwrap_or(rest);
+        text[..start].chars().next_back().is_none_or(|c| !c.is_alphanumeric())
+            && (ends_word || rest.chars().next().is_none_or(|c| !c.is_alphanumeric()))
+    })
+}
+
+/// Region and zone name patterns for each provider, checked in order
+///
+/// Order matters where names overlap: `us-east-1` is read as AWS before the
+/// IBM Cloud zone of the same name.
+static REGION_PATTERNS: LazyLock<Vec<(CloudProviderType, Regex)>> = LazyLock::new(|| {
+    [
+        // us-east-1, eu-central-1, ap-southeast-2a, us-gov-west-1
+        (CloudProviderType::AWS, r"^(us|eu|ap|sa|ca|me|af|il|mx)-(gov-)?(north|south|east|west|central)(east|west)?-\d[a-f]?$"),
+        // us-central1, europe-west4-b
+        (CloudProviderType::GCP, r"^(us|europe|asia|australia|northamerica|southamerica|me|africa)-(north|south|east|west|central)(east|west)?\d+(-[a-f])?$"),
+        // eastus, westus2, northeurope, japaneast
+        (CloudProviderType::Azure, r"^((east|west|central|northcentral|southcentral|westcentral)us\d?|(north|west)europe|uk(south|west)|(japan|korea|canada|australia)(east|west|central|southeast)|(south)?eastasia|(central|south|west)india|francecentral|germanywestcentral|swedencentral|switzerlandnorth|norwayeast|uaenorth|brazilsouth|southafricanorth)$"),
+        // us-south, eu-de, jp-tok-2
+        (CloudProviderType::IBMCloud, r"^(us-south|us-east|eu-gb|eu-de|eu-es|jp-tok|jp-osa|au-syd|ca-tor|br-sao)(-[1-3])?$"),
+        // nyc3, sfo2, fra1
+        (CloudProviderType::DigitalOcean, r"^(nyc|sfo|ams|sgp|lon|fra|tor|blr|syd)\d$"),
+    ]
+    .into_iter()
+    .map(|(provider, pattern)| (provider, Regex::new(pattern).unwrap()))
+    .collect()
+});
+
+/// Find the first region name in the query and the provider it belongs to
+fn detect_region(query_lower: &str) -> Option<(CloudProviderType, String)> {
+    query_lower
+        .split_whitespace()
+        .map(|token| token.trim_matches(|c: char| !c.is_ascii_alphanumeric() && c != '-'))
+        .find_map(|token| {
+            REGION_PATTERNS
+                .iter()
+                .find(|(_, pattern)| pattern.is_match(token))
+                .map(|(provider, _)| (*provider, token.to_string()))
+        })
 }
 
 /// Detect cloud provider from user query
+///
+/// Provider keywords decide the match; a region name belonging to the same
+/// provider raises the confidence. Without keywords, a region name alone
+/// identifies the provider at slightly lower confidence.
 pub fn detect_provider_from_query(query: &str) -> Option<ProviderDetectionResult> {
     let query_lower = query.to_lowercase();
+    let region = detect_region(&query_lower);
+
+    let by_keyword = PROVIDER_KEYWORDS.iter().find_map(|(provider, label, keywords)| {
+        let mut matched_keywords: Vec<String> = keywords
+            .iter()
+            .filter(|keyword| contains_keyword(&query_lower, keyword))
+            .map(|keyword| keyword.trim().to_string())
+            .collect();
+
+        if matched_keywords.is_empty() {
+            return None;
+        }
+
+        let mut confidence = 0.9;
+        let mut reason = format!("Query contains {} specific keywords", label);
+        if let Some((region_provider, ref name)) = region
+            && region_provider == *provider
+        {
+            confidence = 0.95;
+            reason.push_str(&format!(" and the {} region {}", label, name));
+            matched_keywords.push(name.clone());
+        }
+
+        Some(ProviderDetectionResult {
+            provider: *provider,
+            confidence,
+            reason,
+            matched_keywords,
+        })
+    });
+
+    by_keyword.or_else(|| {
+        let (provider, name) = region?;
+        Some(ProviderDetectionResult {
+            provider,
+            confidence: 0.85,
+            reason: format!("Query mentions the {} region {}", provider, name),
+            matched_keywords: vec![name],
+        })
+    })
+}
 
-    // IBM Cloud keywords
-    if query_lower.contains("ibmcloud")
-        || query_lower.contains("ibm cloud")
-        || query_lower.contains("watson")
-        || query_lower.contains("code engine")
-    {
-        return Some(ProviderDetectionResult {
-            provider: CloudProviderType::IBMCloud,
-            confidence: 0.9,
-            reason: "Query contains IBM Cloud specific keywords".to_string(),
-        });
-    }
-
-    // AWS keywords
-    if query_lower.contains("ec2")
-        || query_lower.contains("s3")
-        || query_lower.contains("lambda")
-        || query_lower.contains("eks")
-        || query_lower.contains("aws")
-    {
-        return Some(ProviderDetectionResult {
-            provider: CloudProviderType::AWS,
-            confidence: 0.9,
-            reason: "Query contains AWS specific keywords".to_string(),
-        });
-    }
-
-    // GCP keywords
-    if query_lower.contains("gcloud")
-        || query_lower.contains("gcp")
-        || query_lower.contains("compute engine")
-        || query_lower.contains("gke")
-        || query_lower.contains("cloud storage")
-    {
-        return Some(ProviderDetectionResult {
-            provider: CloudProviderType::GCP,
-            confidence: 0.9,
-            reason: "Query contains GCP specific keywords".to_string(),
-        });
-    }
-
-    // Azure keywords
-    if query_lower.contains("azure")
-        || query_lower.contains("az ")
-        || query_lower.contains("aks")
-        || query_lower.contains("virtual machine")
-    {
-        return Some(ProviderDetectionResult {
-            provider: CloudProviderType::Azure,
-            confidence: 0.9,
-            reason: "Query contains Azure specific keywords".to_string(),
-        });
-    }
-
-    // VMware keywords
-    if query_lower.contains("vmware")
-        || query_lower.contains("vsphere")
-        || query_lower.contains("govc")
-        || query_lower.contains("esxi")
-        || query_lower.contains("vcenter")
-        || query_lower.contains("vmc")
-    {
-        return Some(ProviderDetectionResult {
-            provider: CloudProviderType::VMware,
-            confidence: 0.9,
-            reason: "Query contains VMware specific keywords".to_string(),
-        });
-    }
-
-    None
+/// Infer a command's provider from the CLI binary it runs
+///
+/// Leading `VAR=value` environment assignments are skipped and a path to
+/// the binary is reduced to its file name. Only exact CLI names count, so
+/// `azure ...` or `do ...` are not matched.
+pub fn infer_provider_from_command(command: &str) -> Option<CloudProviderType> {
+    let binary = command
+        .split_whitespace()
+        .find(|token| !token.contains('='))?;
+    let binary = binary.rsplit('/').next().unwrap_or(binary);
+
+    CloudProviderType::all()
+        .into_iter()
+        .find(|provider| provider.cli_command() == binary)
+}
+
+/// Whether `command` is likely to wait for the user to type something
+///
+/// True when its subcommands are exactly one of the provider's
+/// [`interactive_subcommands`](CloudProviderType::interactive_subcommands)
+/// and none of the flags that skip the prompts is given. Such commands
+/// must not run with captured output, or they hang unseen.
+pub fn is_interactive_command(command: &str) -> bool {
+    let Some(provider) = infer_provider_from_command(command) else {
+        return false;
+    };
+    // Environment assignments are not part of the command line proper
+    let words: Vec<&str> = command.split_whitespace().skip_while(|token| token.contains('=')).collect();
+    let Ok(parsed) = ParsedCommand::parse(&words.join(" ")) else {
+        return false;
+    };
+
+    let subcommands = parsed.subcommands.join(" ");
+    provider.interactive_subcommands().iter().any(|(interactive, unattended_flags)| {
+        *interactive == subcommands && !unattended_flags.iter().any(|flag| parsed.has_flag(flag))
+    })
+}
+
+/// Minimum detection confidence before offering to switch providers
+pub const PROVIDER_SWITCH_CONFIDENCE: f32 = 0.8;
+
+/// Decide whether a detection result warrants offering a provider switch
+///
+/// Returns the detected provider when it is confident enough and differs
+/// from the currently active one.
+pub fn provider_switch_candidate(
+    detection: Option<&ProviderDetectionResult>,
+    current: CloudProviderType,
+    min_confidence: f32,
+) -> Option<CloudProviderType> {
+    detection
+        .filter(|d| d.confidence >= min_confidence && d.provider != current)
+        .map(|d| d.provider)
 }
 
 #[cfg(test)]
@@ -229,6 +577,16 @@ mod tests {
         assert_eq!(CloudProviderType::GCP.cli_command(), "gcloud");
         assert_eq!(CloudProviderType::Azure.cli_command(), "az");
         assert_eq!(CloudProviderType::VMware.cli_command(), "govc");
+        assert_eq!(CloudProviderType::DigitalOcean.cli_command(), "doctl");
+        assert_eq!(CloudProviderType::GitHub.cli_command(), "gh");
+        assert_eq!(CloudProviderType::Terraform.cli_command(), "terraform");
+    }
+
+    #[test]
+    fn test_provider_id_round_trips() {
+        for provider in CloudProviderType::all() {
+            assert_eq!(CloudProviderType::from_str(provider.id()), Some(provider));
+        }
     }
 
     #[test]
@@ -257,9 +615,37 @@ mod tests {
             CloudProviderType::from_str("vsphere"),
             Some(CloudProviderType::VMware)
         );
+        assert_eq!(
+            CloudProviderType::from_str("digitalocean"),
+            Some(CloudProviderType::DigitalOcean)
+        );
+        assert_eq!(
+            CloudProviderType::from_str("gh"),
+            Some(CloudProviderType::GitHub)
+        );
+        assert_eq!(
+            CloudProviderType::from_str("tf"),
+            Some(CloudProviderType::Terraform)
+        );
         assert_eq!(CloudProviderType::from_str("unknown"), None);
     }
 
+    #[test]
+    fn test_suggest_provider_name() {
+        assert_eq!(CloudProviderType::suggest_name("azre"), Some("azure"));
+        assert_eq!(CloudProviderType::suggest_name("ibmclod"), Some("ibmcloud"));
+        assert_eq!(CloudProviderType::suggest_name("Terrafrom"), Some("terraform"));
+        assert_eq!(CloudProviderType::suggest_name("digitalocen"), Some("digitalocean"));
+    }
+
+    #[test]
+    fn test_no_provider_suggestion_for_unrelated_names() {
+        assert_eq!(CloudProviderType::suggest_name("oracle"), None);
+        assert_eq!(CloudProviderType::suggest_name("kubernetes"), None);
+        // Would be within two edits of `az`, `do`, `gh` or `tf`
+        assert_eq!(CloudProviderType::suggest_name("xy"), None);
+    }
+
     #[test]
     fn test_detect_provider_from_query() {
         let result = detect_provider_from_query("list my ec2 instances");
@@ -285,6 +671,38 @@ mod tests {
         let result = detect_provider_from_query("show vcenter hosts");
         assert!(result.is_some());
         assert_eq!(result.unwrap().provider, CloudProviderType::VMware);
+
+        let result = detect_provider_from_query("list my droplets");
+        assert!(result.is_some());
+        assert_eq!(result.unwrap().provider, CloudProviderType::DigitalOcean);
+
+        for query in ["list open pull requests", "gh pr list", "clone the api repo", "rerun the failed workflow"] {
+            let result = detect_provider_from_query(query);
+            assert_eq!(result.map(|r| r.provider), Some(CloudProviderType::GitHub), "{}", query);
+        }
+    }
+
+    #[test]
+    fn test_detect_terraform() {
+        for query in ["run terraform init", "tf plan for staging", "apply infrastructure changes"] {
+            let result = detect_provider_from_query(query);
+            assert_eq!(result.map(|r| r.provider), Some(CloudProviderType::Terraform), "{}", query);
+        }
+        // Terraform outranks the repo it lives in
+        assert_eq!(
+            detect_provider_from_query("terraform plan for the infra repo").unwrap().provider,
+            CloudProviderType::Terraform
+        );
+    }
+
+    #[test]
+    fn test_keywords_match_whole_words() {
+        assert!(detect_provider_from_query("show high cpu usage").is_none());
+        assert!(detect_provider_from_query("list repos").is_some());
+        assert!(detect_provider_from_query("fix the laws in the doc").is_none());
+        assert_eq!(detect_provider_from_query("rerun failed workflows").unwrap().provider, CloudProviderType::GitHub);
+        assert!(detect_provider_from_query("describe the image repository").is_none());
+        assert!(detect_provider_from_query("show workflowy notes").is_none());
     }
 
     #[test]
@@ -308,17 +726,23 @@ mod tests {
         assert_eq!(CloudProviderType::GCP.to_string(), "Google Cloud Platform");
         assert_eq!(CloudProviderType::Azure.to_string(), "Microsoft Azure");
         assert_eq!(CloudProviderType::VMware.to_string(), "VMware vSphere");
+        assert_eq!(CloudProviderType::DigitalOcean.to_string(), "DigitalOcean");
+        assert_eq!(CloudProviderType::GitHub.to_string(), "GitHub");
+        assert_eq!(CloudProviderType::Terraform.to_string(), "Terraform");
     }
 
     #[test]
     fn test_provider_type_all() {
         let all = CloudProviderType::all();
-        assert_eq!(all.len(), 5);
+        assert_eq!(all.len(), 8);
         assert!(all.contains(&CloudProviderType::IBMCloud));
         assert!(all.contains(&CloudProviderType::AWS));
         assert!(all.contains(&CloudProviderType::GCP));
         assert!(all.contains(&CloudProviderType::Azure));
         assert!(all.contains(&CloudProviderType::VMware));
+        assert!(all.contains(&CloudProviderType::DigitalOcean));
+        assert!(all.contains(&CloudProviderType::GitHub));
+        assert!(all.contains(&CloudProviderType::Terraform));
     }
 
     #[test]
@@ -345,12 +769,81 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_infer_provider_from_command() {
+        assert_eq!(infer_provider_from_command("az vm list -g rg"), Some(CloudProviderType::Azure));
+        assert_eq!(infer_provider_from_command("gcloud compute instances list"), Some(CloudProviderType::GCP));
+        assert_eq!(infer_provider_from_command("govc vm.info my-vm"), Some(CloudProviderType::VMware));
+        assert_eq!(infer_provider_from_command("  /usr/local/bin/aws s3 ls"), Some(CloudProviderType::AWS));
+        assert_eq!(infer_provider_from_command("AWS_PROFILE=dev aws s3 ls"), Some(CloudProviderType::AWS));
+        assert_eq!(infer_provider_from_command("gh pr list"), Some(CloudProviderType::GitHub));
+        assert_eq!(infer_provider_from_command("terraform plan"), Some(CloudProviderType::Terraform));
+        assert_eq!(infer_provider_from_command("kubectl get pods"), None);
+        assert_eq!(infer_provider_from_command("azure vm list"), None);
+        assert_eq!(infer_provider_from_command(""), None);
+    }
+
+    #[test]
+    fn test_interactive_commands() {
+        for command in [
+            "ibmcloud login",
+            "ibmcloud login --sso -r us-south",
+            "aws configure",
+            "AWS_PROFILE=dev aws configure --profile dev",
+            "aws configure sso",
+            "gcloud init",
+            "gcloud auth login",
+            "az login",
+            "doctl auth init",
+            "gh auth login --hostname github.example.com",
+            "terraform apply",
+            "terraform destroy -target=aws_instance.web",
+        ] {
+            assert!(is_interactive_command(command), "{} should be interactive", command);
+        }
+    }
+
+    #[test]
+    fn test_non_interactive_commands() {
+        for command in [
+            "ibmcloud login --apikey @key.json -r us-south",
+            "ibmcloud resource groups",
+            "aws configure get region",
+            "aws configure list",
+            "aws s3 ls",
+            "gcloud auth login --cred-file=creds.json",
+            "gcloud auth list",
+            "az login --service-principal -u app -p secret --tenant t",
+            "doctl auth init --access-token tok",
+            "gh auth status",
+            "echo $TOKEN | gh auth login --with-token",
+            "terraform apply -auto-approve",
+            "terraform apply tfplan",
+            "terraform plan",
+            "govc about",
+            "kubectl exec -it pod -- sh",
+        ] {
+            assert!(!is_interactive_command(command), "{} should not be interactive", command);
+        }
+    }
+
     #[test]
     fn test_detect_provider_no_match() {
         let result = detect_provider_from_query("some random text");
         assert!(result.is_none());
     }
 
+    #[test]
+    fn test_detection_lists_all_matched_keywords() {
+        let result = detect_provider_from_query("copy s3 objects from aws lambda output").unwrap();
+        assert_eq!(result.provider, CloudProviderType::AWS);
+        assert_eq!(result.matched_keywords, vec!["s3", "lambda", "aws"]);
+        assert_eq!(result.reason, "Query contains AWS specific keywords");
+
+        let result = detect_provider_from_query("list az vms").unwrap();
+        assert_eq!(result.matched_keywords, vec!["az"]);
+    }
+
     #[test]
     fn test_detection_result_confidence() {
         let result = detect_provider_from_query("list ec2 instances").unwrap();
@@ -358,6 +851,42 @@ mod tests {
         assert!(!result.reason.is_empty());
     }
 
+    #[test]
+    fn test_region_names_identify_provider() {
+        let result = detect_provider_from_query("list instances in us-east-1").unwrap();
+        assert_eq!(result.provider, CloudProviderType::AWS);
+        assert_eq!(result.matched_keywords, vec!["us-east-1"]);
+        assert!(result.confidence >= PROVIDER_SWITCH_CONFIDENCE);
+
+        let result = detect_provider_from_query("vms in eastus").unwrap();
+        assert_eq!(result.provider, CloudProviderType::Azure);
+
+        for (query, provider) in [
+            ("create a bucket in us-central1", CloudProviderType::GCP),
+            ("list clusters in europe-west4-b", CloudProviderType::GCP),
+            ("show vpcs in eu-de", CloudProviderType::IBMCloud),
+            ("create a volume in nyc3", CloudProviderType::DigitalOcean),
+            ("deploy to westeurope.", CloudProviderType::Azure),
+        ] {
+            assert_eq!(detect_provider_from_query(query).unwrap().provider, provider, "{}", query);
+        }
+
+        assert!(detect_provider_from_query("list instances in the east").is_none());
+    }
+
+    #[test]
+    fn test_region_boosts_matching_keywords() {
+        let result = detect_provider_from_query("list ec2 instances in eu-west-1").unwrap();
+        assert_eq!(result.provider, CloudProviderType::AWS);
+        assert_eq!(result.confidence, 0.95);
+        assert!(result.matched_keywords.contains(&"eu-west-1".to_string()));
+
+        // Keywords outrank a region from another provider
+        let result = detect_provider_from_query("list ec2 instances in eastus").unwrap();
+        assert_eq!(result.provider, CloudProviderType::AWS);
+        assert_eq!(result.confidence, 0.9);
+    }
+
     #[test]
     fn test_cloud_provider_config_default() {
         let config = CloudProviderConfig::new(CloudProviderType::GCP);
@@ -367,6 +896,50 @@ mod tests {
         assert!(config.enabled);
     }
 
+    #[test]
+    fn test_check_known_service() {
+        let services = ["s3", "ec2", "lambda"];
+        assert_eq!(check_known_service("aws s3 ls", "aws", &services), None);
+        assert_eq!(check_known_service("aws --version", "aws", &services), None);
+        assert_eq!(
+            check_known_service("aws lamda list-functions", "aws", &services),
+            Some("unknown service 'lamda'; did you mean 'lambda'?".to_string())
+        );
+        assert_eq!(
+            check_known_service("aws totally-made-up-service foo", "aws", &services),
+            Some("unknown service 'totally-made-up-service'".to_string())
+        );
+    }
+
+    #[test]
+    fn test_provider_switch_candidate() {
+        let detection = detect_provider_from_query("list my ec2 instances");
+
+        // Confident and different from the active provider
+        assert_eq!(
+            provider_switch_candidate(detection.as_ref(), CloudProviderType::IBMCloud, PROVIDER_SWITCH_CONFIDENCE),
+            Some(CloudProviderType::AWS)
+        );
+
+        // Same as the active provider
+        assert_eq!(
+            provider_switch_candidate(detection.as_ref(), CloudProviderType::AWS, PROVIDER_SWITCH_CONFIDENCE),
+            None
+        );
+
+        // Below the confidence threshold
+        assert_eq!(
+            provider_switch_candidate(detection.as_ref(), CloudProviderType::IBMCloud, 0.95),
+            None
+        );
+
+        // Nothing detected
+        assert_eq!(
+            provider_switch_candidate(None, CloudProviderType::IBMCloud, PROVIDER_SWITCH_CONFIDENCE),
+            None
+        );
+    }
+
     #[test]
     fn test_cloud_provider_config_chaining() {
         let config = CloudProviderConfig::new(CloudProviderType::Azure)
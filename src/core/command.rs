@@ -0,0 +1,241 @@
+//! Structured form of a generated CLI command
+
+use std::collections::HashMap;
+use std::fmt;
+use super::{Error, Result};
+
+/// A command split into binary, subcommands, flags and positional arguments
+///
+/// Parsing has no per-CLI schema, so a bare word right after a flag is taken
+/// as that flag's value; bare words before the first flag are subcommands.
+/// A repeated flag keeps its last value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedCommand {
+    pub binary: String,
+    pub subcommands: Vec<String>,
+    /// Flag name (with dashes) to its value, `None` for boolean flags
+    pub flags: HashMap<String, Option<String>>,
+    pub positionals: Vec<String>,
+    /// Flag names in the order they first appeared, for rendering
+    flag_order: Vec<String>,
+}
+
+impl ParsedCommand {
+    /// Parse a command line, honouring single quotes, double quotes and backslashes
+    pub fn parse(command: &str) -> Result<ParsedCommand> {
+        let mut tokens = split_words(command)?.into_iter().peekable();
+        let binary = tokens
+            .next()
+            .ok_or_else(|| Error::InvalidInput("empty command".to_string()))?;
+
+        let mut parsed = ParsedCommand {
+            binary,
+            subcommands: Vec::new(),
+            flags: HashMap::new(),
+            positionals: Vec::new(),
+            flag_order: Vec::new(),
+        };
+        let mut after_separator = false;
+
+        while let Some(token) = tokens.next() {
+            if after_separator {
+                parsed.positionals.push(token);
+            } else if token == "--" {
+                after_separator = true;
+            } else if is_flag(&token) {
+                let (name, value) = match token.split_once('=') {
+                    Some((name, value)) => (name.to_string(), Some(value.to_string())),
+                    None => {
+                        let value = tokens.next_if(|next| !is_flag(next) && next != "--");
+                        (token, value)
+                    }
+                };
+                if !parsed.flags.contains_key(&name) {
+                    parsed.flag_order.push(name.clone());
+                }
+                parsed.flags.insert(name, value);
+            } else if parsed.flags.is_empty() && parsed.positionals.is_empty() {
+                parsed.subcommands.push(token);
+            } else {
+                parsed.positionals.push(token);
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    /// Whether the command passes `flag`, in either `--flag value` or `--flag=value` form
+    pub fn has_flag(&self, flag: &str) -> bool {
+        self.flags.contains_key(flag)
+    }
+}
+
+impl fmt::Display for ParsedCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts: Vec<String> = vec![shell_quote(&self.binary)];
+        parts.extend(self.subcommands.iter().map(|word| shell_quote(word)));
+
+        for name in &self.flag_order {
+            match self.flags.get(name) {
+                // A value starting with a dash would read back as another flag
+                Some(Some(value)) if value.starts_with('-') => parts.push(format!("{}={}", name, shell_quote(value))),
+                Some(Some(value)) => {
+                    parts.push(name.clone());
+                    parts.push(shell_quote(value));
+                }
+                Some(None) => parts.push(name.clone()),
+                None => {}
+            }
+        }
+
+        if !self.positionals.is_empty() {
+            // Without a separator the first positional would read back as a
+            // subcommand or as the value of a trailing boolean flag
+            let last_flag_has_value = self
+                .flag_order
+                .last()
+                .is_some_and(|name| matches!(self.flags.get(name), Some(Some(_))));
+            if !last_flag_has_value || self.positionals.iter().any(|word| is_flag(word)) {
+                parts.push("--".to_string());
+            }
+            parts.extend(self.positionals.iter().map(|word| shell_quote(word)));
+        }
+
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+/// Split a command line into words the way a POSIX shell would, minus expansion
+pub fn split_words(command: &str) -> Result<Vec<String>> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_token = false;
+    let mut quote: Option<char> = None;
+    let mut chars = command.chars();
+
+    while let Some(c) = chars.next() {
+        match quote {
+            Some(open) if c == open => quote = None,
+            Some('"') if c == '\\' => match chars.next() {
+                Some(escaped @ ('"' | '\\')) => current.push(escaped),
+                Some(other) => {
+                    current.push('\\');
+                    current.push(other);
+                }
+                None => current.push('\\'),
+            },
+            Some(_) => current.push(c),
+            None if c == '\'' || c == '"' => {
+                quote = Some(c);
+                in_token = true;
+            }
+            None if c == '\\' => {
+                current.extend(chars.next());
+                in_token = true;
+            }
+            None if c.is_whitespace() => {
+                if in_token {
+                    tokens.push(std::mem::take(&mut current));
+                    in_token = false;
+                }
+            }
+            None => {
+                current.push(c);
+                in_token = true;
+            }
+        }
+    }
+
+    if let Some(open) = quote {
+        return Err(Error::InvalidInput(format!("unterminated {} quote in command", open)));
+    }
+    if in_token {
+        tokens.push(current);
+    }
+    Ok(tokens)
+}
+
+/// Flags start with a dash; a lone `-`, `--` and negative numbers do not count
+fn is_flag(token: &str) -> bool {
+    let mut chars = token.chars();
+    chars.next() == Some('-')
+        && token != "--"
+        && chars.next().is_some_and(|second| !second.is_ascii_digit())
+}
+
+/// Single-quote a word if the shell would otherwise split or expand it
+pub fn shell_quote(word: &str) -> String {
+    let safe = !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=@,%+".contains(c));
+    if safe {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_structure() {
+        let parsed = ParsedCommand::parse("aws ec2 describe-instances --region us-east-1 --dry-run").unwrap();
+        assert_eq!(parsed.binary, "aws");
+        assert_eq!(parsed.subcommands, vec!["ec2", "describe-instances"]);
+        assert_eq!(parsed.flags.get("--region"), Some(&Some("us-east-1".to_string())));
+        assert_eq!(parsed.flags.get("--dry-run"), Some(&None));
+        assert!(parsed.positionals.is_empty());
+    }
+
+    #[test]
+    fn test_parse_quoted_values() {
+        let parsed = ParsedCommand::parse(
+            r#"az vm create --name "my vm" --tags 'env=dev team=ops' --query=a\ b --note "say \"hi\"""#,
+        )
+        .unwrap();
+        assert_eq!(parsed.flags["--name"].as_deref(), Some("my vm"));
+        assert_eq!(parsed.flags["--tags"].as_deref(), Some("env=dev team=ops"));
+        assert_eq!(parsed.flags["--query"].as_deref(), Some("a b"));
+        assert_eq!(parsed.flags["--note"].as_deref(), Some(r#"say "hi""#));
+
+        assert!(ParsedCommand::parse("aws s3 ls 'unterminated").is_err());
+        assert!(ParsedCommand::parse("   ").is_err());
+    }
+
+    #[test]
+    fn test_parse_boolean_flags_and_positionals() {
+        let parsed = ParsedCommand::parse("gcloud compute ssh --zone=us-east1-b --quiet -- vm-1 -v").unwrap();
+        assert_eq!(parsed.flags["--quiet"], None);
+        assert_eq!(parsed.flags["--zone"].as_deref(), Some("us-east1-b"));
+        assert_eq!(parsed.positionals, vec!["vm-1", "-v"]);
+
+        // Consecutive flags do not swallow each other, and negative numbers are values
+        let parsed = ParsedCommand::parse("ibmcloud is instances -q --offset -5").unwrap();
+        assert_eq!(parsed.flags["-q"], None);
+        assert_eq!(parsed.flags["--offset"].as_deref(), Some("-5"));
+        assert!(parsed.has_flag("-q"));
+        assert!(!parsed.has_flag("--quiet"));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for command in [
+            "aws s3 ls",
+            "aws ec2 describe-instances --region us-east-1 --dry-run",
+            "az vm create --name 'my vm' -g rg",
+            "gcloud compute ssh --zone us-east1-b --quiet -- vm-1 -v",
+            "ibmcloud is instances --offset=-5",
+            "doctl compute droplet list -- web-1",
+        ] {
+            let parsed = ParsedCommand::parse(command).unwrap();
+            assert_eq!(parsed.to_string(), command);
+            assert_eq!(ParsedCommand::parse(&parsed.to_string()).unwrap(), parsed);
+        }
+
+        let quoted = ParsedCommand::parse(r#"aws s3 cp --metadata "it's here" x"#).unwrap();
+        assert_eq!(ParsedCommand::parse(&quoted.to_string()).unwrap(), quoted);
+    }
+}
@@ -3,7 +3,7 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
-use super::Result;
+use super::{CloudProviderType, Result};
 
 /// A document to be indexed
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,20 +23,44 @@ pub struct IndexingResult {
     pub errors: Vec<String>,
 }
 
+/// Sizes used when splitting documents into chunks, in characters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkConfig {
+    /// Preferred chunk length; chunks end at the first whitespace after it
+    pub target_size: usize,
+    /// Hard upper bound on chunk length
+    pub max_size: usize,
+    /// Characters shared between consecutive chunks
+    pub overlap: usize,
+}
+
+impl Default for ChunkConfig {
+    fn default() -> Self {
+        Self {
+            target_size: 800,
+            max_size: 1000,
+            overlap: 200,
+        }
+    }
+}
+
 /// Configuration for document indexing
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IndexingConfig {
-    pub chunk_size: usize,
-    pub chunk_overlap: usize,
+    #[serde(default)]
+    pub chunking: ChunkConfig,
     pub batch_size: usize,
+    /// Provider to tag indexed chunks with, scoping them in retrieval
+    #[serde(default)]
+    pub provider: Option<CloudProviderType>,
 }
 
 impl Default for IndexingConfig {
     fn default() -> Self {
         Self {
-            chunk_size: 1000,
-            chunk_overlap: 200,
+            chunking: ChunkConfig::default(),
             batch_size: 10,
+            provider: None,
         }
     }
 }
@@ -4,7 +4,7 @@ use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
-use super::{Error, Result};
+use super::Result;
 use super::types::{RetryConfig, GenerationAttempt};
 
 /// Configuration for text generation
@@ -17,6 +17,10 @@ pub struct GenerationConfig {
     pub top_k: Option<u32>,
     pub stop_sequences: Vec<String>,
     pub timeout: Duration,
+    /// Return the model's text as generated instead of reducing it to a
+    /// single command, for prose such as summaries
+    #[serde(default)]
+    pub raw: bool,
 }
 
 impl Default for GenerationConfig {
@@ -33,6 +37,7 @@ impl Default for GenerationConfig {
                 "Query:".to_string(),
             ],
             timeout: Duration::from_secs(60),
+            raw: false,
         }
     }
 }
@@ -75,6 +80,22 @@ pub trait LLMProvider: Send + Sync {
         retry_config: Option<RetryConfig>,
     ) -> Result<GenerationAttempt>;
 
+    /// Like `generate_with_feedback`, but also returns every scored attempt in order
+    ///
+    /// The default reports the single attempt `generate_with_feedback` returns.
+    async fn generate_with_feedback_traced(
+        &self,
+        base_prompt: &str,
+        config: &GenerationConfig,
+        previous_failures: &[String],
+        retry_config: Option<RetryConfig>,
+    ) -> Result<(GenerationAttempt, Vec<GenerationAttempt>)> {
+        let attempt = self
+            .generate_with_feedback(base_prompt, config, previous_failures, retry_config)
+            .await?;
+        Ok((attempt.clone(), vec![attempt]))
+    }
+
     /// Generate text with streaming support
     async fn generate_stream(
         &self,
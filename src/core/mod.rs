@@ -7,15 +7,29 @@ pub mod document_indexer;
 pub mod cloud_provider;
 pub mod error;
 pub mod types;
+pub mod suggest;
+pub mod path;
+pub mod transformer;
+pub mod command;
+pub mod safety;
+pub mod process;
 
 pub use error::{Error, Result};
 pub use llm::{LLMProvider, GenerationConfig, GenerationResult};
 pub use rag::{RAGEngine, RAGQuery, RAGResult};
-pub use vector_store::{VectorStore, VectorDocument, SearchResult, SearchConfig};
-pub use document_indexer::{DocumentIndexer, Document, IndexingResult, IndexingConfig};
+pub use vector_store::{VectorStore, VectorDocument, SearchResult, SearchConfig, SimilarityMetric};
+pub use document_indexer::{DocumentIndexer, Document, IndexingResult, IndexingConfig, ChunkConfig};
 pub use cloud_provider::{
-    CloudProvider, CloudProviderType, CloudProviderConfig,
-    ProviderDetectionResult, detect_provider_from_query,
+    CloudProvider, CloudProviderType, ProviderCapabilities, detect_provider_from_query, infer_provider_from_command, is_interactive_command, check_known_service,
+    provider_switch_candidate, PROVIDER_SWITCH_CONFIDENCE,
 };
 pub use types::*;
+pub use suggest::closest_match;
+pub use path::is_binary_on_path;
+pub use transformer::{CommandTransformer, has_flag};
+pub use command::{ParsedCommand, shell_quote, split_words};
+pub use safety::{CommandSafety, CostHint, is_destructive_command};
+pub use process::{run_command_with_retry, CommandRunner, SystemRunner, CLI_RETRIES, CLI_BACKOFF};
+#[cfg(test)]
+pub use process::{MockRunner, RunOutput};
 
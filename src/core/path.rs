@@ -0,0 +1,91 @@
+//! Locating executables without spawning `which`/`where`
+
+use std::env;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+/// Check whether an executable named `name` is on `PATH`
+pub fn is_binary_on_path(name: &str) -> bool {
+    env::var_os("PATH").is_some_and(|path| find_binary_in(name, &path).is_some())
+}
+
+/// Find an executable named `name` in a `PATH`-style list of directories
+///
+/// On Windows the extensions from `PATHEXT` are tried when `name` has none.
+pub fn find_binary_in(name: &str, path: &OsStr) -> Option<PathBuf> {
+    env::split_paths(path)
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .flat_map(|dir| candidates(&dir, name))
+        .find(|candidate| is_executable(candidate))
+}
+
+#[cfg(windows)]
+fn candidates(dir: &Path, name: &str) -> Vec<PathBuf> {
+    if Path::new(name).extension().is_some() {
+        return vec![dir.join(name)];
+    }
+
+    let extensions = env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_string());
+    extensions
+        .split(';')
+        .filter(|ext| !ext.is_empty())
+        .map(|ext| dir.join(format!("{}{}", name, ext)))
+        .collect()
+}
+
+#[cfg(not(windows))]
+fn candidates(dir: &Path, name: &str) -> Vec<PathBuf> {
+    vec![dir.join(name)]
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    path.metadata()
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsString;
+
+    fn path_with(dirs: &[&Path]) -> OsString {
+        env::join_paths(dirs).unwrap()
+    }
+
+    #[test]
+    fn test_finds_fake_executable_on_path() {
+        let empty = tempfile::tempdir().unwrap();
+        let bin = tempfile::tempdir().unwrap();
+        let name = if cfg!(windows) { "fakecli.exe" } else { "fakecli" };
+        let fake = bin.path().join(name);
+        std::fs::write(&fake, "#!/bin/sh\n").unwrap();
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&fake, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+
+        let path = path_with(&[empty.path(), bin.path()]);
+        assert!(find_binary_in("fakecli", &path).is_some());
+        assert_eq!(find_binary_in("missingcli", &path), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_ignores_non_executable_files() {
+        let bin = tempfile::tempdir().unwrap();
+        std::fs::write(bin.path().join("notes"), "plain text").unwrap();
+
+        let path = path_with(&[bin.path()]);
+        assert_eq!(find_binary_in("notes", &path), None);
+    }
+}
@@ -0,0 +1,201 @@
+//! Running provider CLIs with retries
+//!
+//! Reads such as `ibmcloud target --output json` go over the network and can
+//! fail on a transient hiccup, so they are retried with exponential backoff.
+//! Only read-only invocations should go through here; a command the user
+//! asked for runs exactly once.
+//!
+//! Login probes such as `aws sts get-caller-identity` answer with their exit
+//! status, and a logged-out CLI stays logged out, so they run once through a
+//! [`CommandRunner`]. Tests can swap in a scripted runner instead of spawning
+//! real processes.
+
+use async_trait::async_trait;
+use std::io;
+use std::process::{Command, Output};
+use std::thread;
+use std::time::Duration;
+
+/// Retries after the first attempt for provider status checks
+pub const CLI_RETRIES: u32 = 2;
+
+/// Pause before the first retry; doubled before each one after it
+pub const CLI_BACKOFF: Duration = Duration::from_millis(250);
+
+/// Run `cmd` with `args`, retrying up to `retries` times when it fails
+///
+/// See [`output_with_retry`].
+pub fn run_command_with_retry(cmd: &str, args: &[&str], retries: u32, backoff: Duration) -> io::Result<Output> {
+    output_with_retry(Command::new(cmd).args(args), retries, backoff)
+}
+
+/// Run a prepared command, retrying up to `retries` times when it fails
+///
+/// Both a non-zero exit and an error spawning the process count as failures,
+/// except a missing binary, which no retry fixes. The wait before retry `n`
+/// is `backoff * 2^(n-1)`. Returns the last attempt's output, successful or
+/// not, or the spawn error when no attempt started.
+fn output_with_retry(command: &mut Command, retries: u32, backoff: Duration) -> io::Result<Output> {
+    let mut attempt = 0;
+    loop {
+        let result = command.output();
+        let failed = match &result {
+            Ok(output) => !output.status.success(),
+            Err(e) => e.kind() != io::ErrorKind::NotFound,
+        };
+        if !failed || attempt >= retries {
+            return result;
+        }
+        thread::sleep(backoff.saturating_mul(1 << attempt.min(16)));
+        attempt += 1;
+    }
+}
+
+/// Outcome of one process run
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RunOutput {
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl From<Output> for RunOutput {
+    fn from(output: Output) -> Self {
+        Self {
+            success: output.status.success(),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        }
+    }
+}
+
+/// Runs a program to completion and captures its output
+#[async_trait]
+pub trait CommandRunner: Send + Sync {
+    /// Run `program` with `args`, adding `env` to the inherited environment
+    async fn run(&self, program: &str, args: &[&str], env: &[(&str, &str)]) -> io::Result<RunOutput>;
+}
+
+/// Runs real processes once, on a blocking thread so the async runtime is not held up
+#[derive(Debug, Clone, Default)]
+pub struct SystemRunner;
+
+#[async_trait]
+impl CommandRunner for SystemRunner {
+    async fn run(&self, program: &str, args: &[&str], env: &[(&str, &str)]) -> io::Result<RunOutput> {
+        let mut command = Command::new(program);
+        command.args(args).envs(env.iter().copied());
+        tokio::task::spawn_blocking(move || command.output())
+            .await
+            .map_err(io::Error::other)?
+            .map(RunOutput::from)
+    }
+}
+
+/// Closure a `MockRunner` answers each call with
+#[cfg(test)]
+type MockResponse = Box<dyn Fn(&str, &[&str]) -> io::Result<RunOutput> + Send + Sync>;
+
+/// Runner answering from a closure and recording each call as `program args...`
+#[cfg(test)]
+pub struct MockRunner {
+    respond: MockResponse,
+    calls: std::sync::Mutex<Vec<String>>,
+}
+
+#[cfg(test)]
+impl MockRunner {
+    pub fn new(respond: impl Fn(&str, &[&str]) -> io::Result<RunOutput> + Send + Sync + 'static) -> Self {
+        Self { respond: Box::new(respond), calls: Default::default() }
+    }
+
+    /// Every call so far, in order
+    pub fn calls(&self) -> Vec<String> {
+        self.calls.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl CommandRunner for MockRunner {
+    async fn run(&self, program: &str, args: &[&str], _env: &[(&str, &str)]) -> io::Result<RunOutput> {
+        self.calls.lock().unwrap().push(std::iter::once(program).chain(args.iter().copied()).collect::<Vec<_>>().join(" "));
+        (self.respond)(program, args)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    /// Shell script that fails until it has run `succeed_on` times, counting runs in `counter`
+    fn flaky_script(counter: &Path, succeed_on: u32) -> String {
+        format!(
+            "n=$(($(cat '{0}' 2>/dev/null || echo 0) + 1)); echo $n > '{0}'; echo attempt $n; \
+             [ $n -ge {1} ] || {{ echo not yet >&2; exit 1; }}",
+            counter.display(),
+            succeed_on
+        )
+    }
+
+    fn attempts(counter: &Path) -> u32 {
+        std::fs::read_to_string(counter).unwrap().trim().parse().unwrap()
+    }
+
+    #[test]
+    fn test_retries_until_success() {
+        let dir = tempfile::tempdir().unwrap();
+        let counter = dir.path().join("count");
+        let output = run_command_with_retry("sh", &["-c", &flaky_script(&counter, 3)], 3, Duration::ZERO).unwrap();
+
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "attempt 3\n");
+        assert_eq!(attempts(&counter), 3);
+    }
+
+    #[test]
+    fn test_gives_up_after_configured_retries() {
+        let dir = tempfile::tempdir().unwrap();
+        let counter = dir.path().join("count");
+        let output = run_command_with_retry("sh", &["-c", &flaky_script(&counter, 10)], 2, Duration::from_millis(1)).unwrap();
+
+        assert!(!output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stderr), "not yet\n");
+        assert_eq!(attempts(&counter), 3);
+    }
+
+    #[test]
+    fn test_success_is_not_retried() {
+        let dir = tempfile::tempdir().unwrap();
+        let counter = dir.path().join("count");
+        run_command_with_retry("sh", &["-c", &flaky_script(&counter, 1)], 5, Duration::ZERO).unwrap();
+        assert_eq!(attempts(&counter), 1);
+    }
+
+    #[test]
+    fn test_missing_binary_fails_without_retrying() {
+        let error = run_command_with_retry("anycli-no-such-binary", &[], 3, Duration::from_secs(60)).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_system_runner_runs_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let counter = dir.path().join("count");
+        let output = SystemRunner.run("sh", &["-c", &flaky_script(&counter, 2)], &[]).await.unwrap();
+
+        assert!(!output.success);
+        assert_eq!(attempts(&counter), 1);
+    }
+
+    #[tokio::test]
+    async fn test_system_runner_captures_output_and_env() {
+        let output = SystemRunner
+            .run("sh", &["-c", "echo $GREETING; echo oops >&2; exit 3"], &[("GREETING", "hello")])
+            .await
+            .unwrap();
+
+        assert_eq!(output, RunOutput { success: false, stdout: "hello\n".to_string(), stderr: "oops\n".to_string() });
+    }
+}
@@ -0,0 +1,307 @@
+//! Safety and cost classification of provider commands
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use super::{CloudProviderType, ParsedCommand};
+
+/// Verbs that indicate a command modifies or destroys resources irreversibly
+const DESTRUCTIVE_VERBS: &[&str] = &[
+    "delete", "remove", "rm", "rb", "destroy", "terminate", "purge", "drop",
+    "kill", "reset", "truncate", "deprovision", "uninstall", "detach",
+];
+
+/// Flags that bypass the CLI's own safety prompts
+const DANGEROUS_FLAGS: &[&str] = &["--force", "-f", "--yes", "-y", "--recursive", "-auto-approve"];
+
+/// Subcommands destructive for one CLI although their verb is not: CLI, subcommand, reason
+const DESTRUCTIVE_SUBCOMMANDS: &[(&str, &str, &str)] = &[
+    ("terraform", "apply", "'terraform apply' may replace or destroy existing infrastructure"),
+];
+
+/// Verbs that start or grow billable resources
+const COST_INCREASING_VERBS: &[&str] = &[
+    "create", "run", "start", "launch", "deploy", "provision", "mb", "scale",
+    "resize", "allocate", "reserve", "purchase", "buy",
+];
+
+/// Verbs that stop or remove billable resources
+const COST_DECREASING_VERBS: &[&str] = &[
+    "delete", "remove", "rm", "rb", "destroy", "terminate", "deprovision", "stop",
+    "release",
+];
+
+/// Verbs that only read state
+const READ_ONLY_VERBS: &[&str] = &[
+    "list", "ls", "show", "get", "describe", "info", "about", "status", "find",
+    "search", "view", "inspect", "groups", "target", "version",
+];
+
+/// Power flags, as in `govc vm.power -on`, and the verb each stands for
+const POWER_FLAGS: &[(&str, &str)] = &[("-on", "start"), ("-off", "stop")];
+
+/// Commands that work without being logged in
+const NO_AUTH_VERBS: &[&str] = &["login", "logout", "version", "help", "configure", "config", "completion"];
+
+/// Known billable operations: provider, leading subcommands, and what gets billed
+const COST_HINTS: &[(CloudProviderType, &[&str], &str)] = &[
+    (CloudProviderType::AWS, &["ec2", "run-instances"], "launches EC2 instances, billed while they run"),
+    (CloudProviderType::AWS, &["rds", "create-db-instance"], "creates an RDS database instance, billed hourly"),
+    (CloudProviderType::AWS, &["eks", "create-cluster"], "creates an EKS control plane, billed hourly"),
+    (CloudProviderType::AWS, &["s3", "mb"], "creates an S3 bucket; storage and requests are billed"),
+    (CloudProviderType::Azure, &["vm", "create"], "creates a virtual machine, billed while it runs"),
+    (CloudProviderType::Azure, &["aks", "create"], "creates an AKS cluster; its node VMs are billed"),
+    (CloudProviderType::Azure, &["sql", "db", "create"], "creates an Azure SQL database, billed hourly"),
+    (CloudProviderType::GCP, &["compute", "instances", "create"], "creates Compute Engine VMs, billed while they run"),
+    (CloudProviderType::GCP, &["container", "clusters", "create"], "creates a GKE cluster; its nodes are billed"),
+    (CloudProviderType::GCP, &["sql", "instances", "create"], "creates a Cloud SQL instance, billed hourly"),
+    (CloudProviderType::IBMCloud, &["is", "instance-create"], "creates a VPC virtual server, billed hourly"),
+    (CloudProviderType::IBMCloud, &["ks", "cluster", "create"], "creates a Kubernetes cluster; its worker nodes are billed"),
+    (CloudProviderType::IBMCloud, &["resource", "service-instance-create"], "creates a service instance billed by its plan"),
+    (CloudProviderType::DigitalOcean, &["compute", "droplet", "create"], "creates Droplets, billed hourly"),
+    (CloudProviderType::DigitalOcean, &["kubernetes", "cluster", "create"], "creates a Kubernetes cluster; its node Droplets are billed"),
+];
+
+/// Qualitative warning that a command creates or starts billable resources
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CostHint {
+    /// What gets billed, e.g. "creates an EKS control plane, billed hourly"
+    pub message: String,
+}
+
+impl CostHint {
+    /// Look up a cost hint for `command`
+    ///
+    /// Known operations get a specific message; other commands that
+    /// classify as cost-increasing get a generic one. Read-only and
+    /// destructive commands get none.
+    pub fn for_command(provider: CloudProviderType, command: &str) -> Option<Self> {
+        let parsed = ParsedCommand::parse(&command.to_lowercase()).ok()?;
+        if parsed.binary != provider.cli_command() {
+            return None;
+        }
+
+        let known = COST_HINTS.iter().find(|(hint_provider, path, _)| {
+            *hint_provider == provider
+                && parsed.subcommands.len() >= path.len()
+                && parsed.subcommands.iter().zip(path.iter()).all(|(word, expected)| word == expected)
+        });
+        let message = match known {
+            Some((_, _, message)) => message.to_string(),
+            None if CommandSafety::classify(command).may_incur_charges() => {
+                "creates or starts resources that may be billed".to_string()
+            }
+            None => return None,
+        };
+        Some(Self { message })
+    }
+}
+
+impl fmt::Display for CostHint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Cost: this {}", self.message)
+    }
+}
+
+/// Likely effect of a command on the cloud bill
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CostImpact {
+    /// Read-only; no charges
+    None,
+    /// Creates or starts resources that may incur charges
+    Increase,
+    /// Stops or removes resources
+    Decrease,
+    /// The verb was not recognised
+    Unknown,
+}
+
+/// How risky a validated command is, for UIs to warn about
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandSafety {
+    /// Modifies or destroys resources irreversibly, or skips confirmation prompts
+    pub destructive: bool,
+    /// Needs the user to be logged in to the provider
+    pub requires_auth: bool,
+    pub estimated_cost_impact: CostImpact,
+}
+
+impl CommandSafety {
+    /// Classify a command from the verbs in its subcommands
+    ///
+    /// Verbs are matched inside compound words such as `run-instances`,
+    /// `group-create` or `vm.power`; flag values and positionals are
+    /// ignored, except for power flags like govc's `-on`.
+    pub fn classify(command: &str) -> Self {
+        let mut words: Vec<String> = Vec::new();
+        if let Ok(parsed) = ParsedCommand::parse(&command.to_lowercase()) {
+            for subcommand in &parsed.subcommands {
+                words.extend(verb_segments(subcommand).map(str::to_string));
+            }
+            for (flag, verb) in POWER_FLAGS {
+                if parsed.has_flag(flag) {
+                    words.push(verb.to_string());
+                }
+            }
+        }
+        let has_verb = |verbs: &[&str]| words.iter().any(|word| verbs.contains(&word.as_str()));
+
+        let estimated_cost_impact = if has_verb(COST_INCREASING_VERBS) {
+            CostImpact::Increase
+        } else if has_verb(COST_DECREASING_VERBS) {
+            CostImpact::Decrease
+        } else if has_verb(READ_ONLY_VERBS) {
+            CostImpact::None
+        } else {
+            CostImpact::Unknown
+        };
+
+        Self {
+            destructive: is_destructive_command(command).is_some(),
+            requires_auth: !has_verb(NO_AUTH_VERBS),
+            estimated_cost_impact,
+        }
+    }
+
+    /// Whether running the command may add to the bill
+    pub fn may_incur_charges(&self) -> bool {
+        self.estimated_cost_impact == CostImpact::Increase
+    }
+}
+
+/// Split a token into the verb-like words it is made of
+fn verb_segments(token: &str) -> impl Iterator<Item = &str> {
+    token
+        .split(['-', '.', '_', ':'])
+        .filter(|segment| !segment.is_empty())
+}
+
+/// Check whether a command looks destructive
+///
+/// Returns the reason when it does, `None` when it appears safe.
+pub fn is_destructive_command(command: &str) -> Option<String> {
+    let lower = command.to_lowercase();
+
+    if let Ok(parsed) = ParsedCommand::parse(&lower) {
+        let subcommand = parsed.subcommands.first().map(String::as_str);
+        if let Some((_, _, reason)) = DESTRUCTIVE_SUBCOMMANDS
+            .iter()
+            .find(|(cli, name, _)| parsed.binary == *cli && subcommand == Some(*name))
+        {
+            return Some(reason.to_string());
+        }
+    }
+
+    for token in lower.split_whitespace() {
+        if DANGEROUS_FLAGS.contains(&token) {
+            return Some(format!("uses '{}' which skips safety prompts", token));
+        }
+
+        // Match verbs inside compound subcommands like `delete-bucket` or `vm.destroy`
+        if token.starts_with('-') {
+            continue;
+        }
+        if let Some(verb) = verb_segments(token).find(|segment| DESTRUCTIVE_VERBS.contains(segment)) {
+            return Some(format!("contains destructive operation '{}'", verb));
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_destructive_command() {
+        assert!(is_destructive_command("ibmcloud resource group-delete my-group").is_some());
+        assert!(is_destructive_command("aws s3 rb s3://bucket").is_some());
+        assert!(is_destructive_command("govc vm.destroy my-vm").is_some());
+        assert!(is_destructive_command("az group delete --name rg").is_some());
+        assert!(is_destructive_command("ibmcloud ks cluster rm --force").is_some());
+        assert!(is_destructive_command("aws ec2 describe-instances").is_none());
+        assert!(is_destructive_command("ibmcloud resource groups").is_none());
+        assert!(is_destructive_command("terraform apply").unwrap().contains("terraform apply"));
+        assert!(is_destructive_command("terraform destroy -target=aws_instance.web").is_some());
+        assert!(is_destructive_command("terraform plan -out=tfplan").is_none());
+        // Only terraform's apply is special
+        assert!(is_destructive_command("ibmcloud sch workspace apply-plan").is_none());
+    }
+
+    #[test]
+    fn test_classify_create_verbs() {
+        for command in [
+            "aws ec2 run-instances --image-id ami-123",
+            "ibmcloud resource group-create dev",
+            "gcloud compute instances create vm-1",
+            "govc vm.power -on my-vm",
+        ] {
+            let safety = CommandSafety::classify(command);
+            assert_eq!(safety.estimated_cost_impact, CostImpact::Increase, "{}", command);
+            assert!(safety.may_incur_charges());
+            assert!(!safety.destructive, "{}", command);
+            assert!(safety.requires_auth);
+        }
+    }
+
+    #[test]
+    fn test_classify_delete_verbs() {
+        for command in ["az vm delete --name vm-1", "aws s3 rb s3://bucket", "doctl compute droplet delete web-1"] {
+            let safety = CommandSafety::classify(command);
+            assert!(safety.destructive, "{}", command);
+            assert_eq!(safety.estimated_cost_impact, CostImpact::Decrease, "{}", command);
+            assert!(!safety.may_incur_charges());
+        }
+    }
+
+    #[test]
+    fn test_classify_list_verbs() {
+        for command in ["aws ec2 describe-instances", "az vm list", "ibmcloud resource groups", "govc ls /dc/vm"] {
+            let safety = CommandSafety::classify(command);
+            assert!(!safety.destructive, "{}", command);
+            assert_eq!(safety.estimated_cost_impact, CostImpact::None, "{}", command);
+        }
+
+        let login = CommandSafety::classify("ibmcloud login --sso");
+        assert!(!login.requires_auth);
+        assert_eq!(CommandSafety::classify("aws frobnicate").estimated_cost_impact, CostImpact::Unknown);
+    }
+
+    #[test]
+    fn test_cost_hint_for_create_commands() {
+        let hint = CostHint::for_command(CloudProviderType::AWS, "aws ec2 run-instances --image-id ami-123").unwrap();
+        assert_eq!(hint.to_string(), "Cost: this launches EC2 instances, billed while they run");
+
+        for (provider, command) in [
+            (CloudProviderType::Azure, "az vm create --name vm-1 --image Ubuntu2204"),
+            (CloudProviderType::GCP, "gcloud compute instances create vm-1"),
+            (CloudProviderType::IBMCloud, "ibmcloud ks cluster create vpc-gen2 --name dev"),
+            (CloudProviderType::DigitalOcean, "doctl compute droplet create web-1"),
+        ] {
+            let hint = CostHint::for_command(provider, command).unwrap();
+            assert!(!hint.message.starts_with("creates or starts"), "{}", command);
+        }
+
+        // Unlisted creates still get the generic hint
+        let hint = CostHint::for_command(CloudProviderType::AWS, "aws dynamodb create-table --table-name t").unwrap();
+        assert_eq!(hint.message, "creates or starts resources that may be billed");
+    }
+
+    #[test]
+    fn test_no_cost_hint_for_reads_and_deletes() {
+        for (provider, command) in [
+            (CloudProviderType::AWS, "aws ec2 describe-instances"),
+            (CloudProviderType::Azure, "az vm list"),
+            (CloudProviderType::GCP, "gcloud compute instances list"),
+            (CloudProviderType::IBMCloud, "ibmcloud resource groups"),
+            (CloudProviderType::DigitalOcean, "doctl compute droplet get web-1"),
+            (CloudProviderType::Azure, "az vm delete --name vm-1"),
+        ] {
+            assert_eq!(CostHint::for_command(provider, command), None, "{}", command);
+        }
+
+        // A command for another CLI is not judged against this provider
+        assert_eq!(CostHint::for_command(CloudProviderType::Azure, "aws ec2 run-instances"), None);
+    }
+}
@@ -0,0 +1,58 @@
+//! Fuzzy matching helpers for "did you mean" suggestions
+
+/// Levenshtein edit distance between two strings
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == cb { 0 } else { 1 };
+            current[j + 1] = (previous[j + 1] + 1)
+                .min(current[j] + 1)
+                .min(previous[j] + cost);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+/// Find the candidate closest to `input`, if it is close enough to be a likely typo
+pub fn closest_match<'a>(input: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let input = input.to_lowercase();
+    let max_distance = (input.chars().count() / 3).max(2);
+
+    candidates
+        .iter()
+        .map(|candidate| (*candidate, levenshtein(&input, &candidate.to_lowercase())))
+        .filter(|(_, distance)| *distance <= max_distance)
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(candidate, _)| candidate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("", ""), 0);
+        assert_eq!(levenshtein("ec2", "ec2"), 0);
+        assert_eq!(levenshtein("ec3", "ec2"), 1);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_closest_match() {
+        let candidates = ["s3", "ec2", "lambda", "cloudformation"];
+        assert_eq!(closest_match("lamda", &candidates), Some("lambda"));
+        assert_eq!(closest_match("cloudformaton", &candidates), Some("cloudformation"));
+        assert_eq!(closest_match("totally-made-up-service", &candidates), None);
+    }
+}
@@ -0,0 +1,34 @@
+//! Post-translation command transformer trait
+
+use super::{CloudProviderType, ParsedCommand, Result};
+
+/// Rewrites a generated command before it is shown or executed
+///
+/// Transformers run in registration order after the translator has
+/// validated the command; each sees the previous one's output.
+pub trait CommandTransformer: Send + Sync {
+    /// Transform a command for the given provider
+    fn transform(&self, command: &str, provider: CloudProviderType) -> Result<String>;
+}
+
+/// Check whether a command already passes `flag`, as `--flag value` or `--flag=value`
+///
+/// Commands that do not parse (e.g. an unterminated quote) pass no flags.
+pub fn has_flag(command: &str, flag: &str) -> bool {
+    ParsedCommand::parse(command).is_ok_and(|parsed| parsed.has_flag(flag))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_has_flag() {
+        assert!(has_flag("aws s3 ls --profile dev", "--profile"));
+        assert!(has_flag("aws s3 ls --profile=dev", "--profile"));
+        assert!(!has_flag("aws s3 ls --profiles dev", "--profile"));
+        assert!(!has_flag("aws s3 ls", "--profile"));
+        // Quoted values are not mistaken for flags
+        assert!(!has_flag("aws s3 cp --metadata '--profile dev' x", "--profile"));
+    }
+}
@@ -10,6 +10,10 @@ pub struct RetryConfig {
     pub base_timeout: Duration,
     pub enable_progressive_prompts: bool,
     pub quality_threshold: f32,
+    /// Overall budget across all attempts; the best attempt so far is returned once exceeded
+    pub max_total_duration: Duration,
+    /// Upper bound of the random jitter added to each attempt's timeout
+    pub max_jitter: Duration,
 }
 
 impl Default for RetryConfig {
@@ -19,6 +23,8 @@ impl Default for RetryConfig {
             base_timeout: Duration::from_secs(30),
             enable_progressive_prompts: true,
             quality_threshold: 0.7,
+            max_total_duration: Duration::from_secs(120),
+            max_jitter: Duration::from_secs(2),
         }
     }
 }
@@ -39,6 +45,20 @@ pub struct CommandLearning {
     pub correct_command: String,
     pub error_pattern: Option<String>,
     pub timestamp: i64,
+    /// Times this command ran successfully for the query
+    #[serde(default)]
+    pub success_count: u32,
+    /// Times this command failed when run for the query
+    #[serde(default)]
+    pub failure_count: u32,
+}
+
+impl CommandLearning {
+    /// Share of recorded runs that succeeded, `None` if it never ran
+    pub fn success_rate(&self) -> Option<f32> {
+        let runs = self.success_count + self.failure_count;
+        (runs > 0).then(|| self.success_count as f32 / runs as f32)
+    }
 }
 
 /// Quality analysis result
@@ -22,12 +22,60 @@ pub struct SearchResult {
     pub total: usize,
 }
 
+impl SearchResult {
+    /// The documents with their scores and 0-based ranks, best first
+    pub fn ranked(self) -> Vec<RankedDocument> {
+        self.documents
+            .into_iter()
+            .enumerate()
+            .map(|(rank, document)| RankedDocument {
+                score: document.score.unwrap_or(0.0),
+                document,
+                rank,
+            })
+            .collect()
+    }
+}
+
+/// One search hit with its score and position in the results
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RankedDocument {
+    pub document: VectorDocument,
+    pub score: f32,
+    /// 0-based position; rank 0 is the best match
+    pub rank: usize,
+}
+
+/// How vector search scores a document against the query
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SimilarityMetric {
+    /// Angle between the vectors, ignoring magnitude
+    #[default]
+    Cosine,
+    /// Raw dot product, for embedders that encode relevance in magnitude
+    DotProduct,
+    /// Straight-line distance; smaller is closer
+    Euclidean,
+}
+
+impl SimilarityMetric {
+    /// Whether lower scores rank first
+    pub fn ranks_ascending(self) -> bool {
+        matches!(self, SimilarityMetric::Euclidean)
+    }
+}
+
 /// Configuration for vector search
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchConfig {
     pub top_k: usize,
+    /// Minimum score to keep, or maximum distance for `Euclidean`
     pub score_threshold: Option<f32>,
     pub filters: Option<serde_json::Value>,
+    /// Metric used by `search_by_vector`
+    #[serde(default)]
+    pub metric: SimilarityMetric,
 }
 
 impl Default for SearchConfig {
@@ -36,6 +84,7 @@ impl Default for SearchConfig {
             top_k: 5,
             score_threshold: Some(0.7),
             filters: None,
+            metric: SimilarityMetric::default(),
         }
     }
 }
@@ -58,6 +107,11 @@ pub trait VectorStore: Send + Sync {
     /// Search for similar documents
     async fn search(&self, query: &str, config: &SearchConfig) -> Result<SearchResult>;
 
+    /// Like `search`, with each hit's score and rank spelled out
+    async fn search_results(&self, query: &str, config: &SearchConfig) -> Result<Vec<RankedDocument>> {
+        Ok(self.search(query, config).await?.ranked())
+    }
+
     /// Search using a vector embedding
     async fn search_by_vector(&self, vector: Vec<f32>, config: &SearchConfig) -> Result<SearchResult>;
 
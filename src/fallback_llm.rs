@@ -0,0 +1,193 @@
+//! LLM provider that falls back to the next provider in a chain on error
+
+use async_trait::async_trait;
+use std::future::Future;
+use std::sync::Mutex;
+
+use crate::core::{
+    LLMProvider, GenerationConfig, GenerationResult, GenerationAttempt, RetryConfig, Error, Result,
+};
+
+/// Tries each provider in order and returns the first success
+///
+/// Quality assessment and the model ID come from the primary (first)
+/// provider.
+pub struct FallbackLLMProvider {
+    providers: Vec<Box<dyn LLMProvider>>,
+    answered_by: Mutex<Option<usize>>,
+}
+
+impl FallbackLLMProvider {
+    /// Create a chain from providers in priority order
+    pub fn new(providers: Vec<Box<dyn LLMProvider>>) -> Result<Self> {
+        if providers.is_empty() {
+            return Err(Error::Configuration("at least one LLM provider is required".to_string()));
+        }
+        Ok(Self {
+            providers,
+            answered_by: Mutex::new(None),
+        })
+    }
+
+    /// Position in the chain of the provider that answered the last request
+    pub fn answered_by(&self) -> Option<usize> {
+        self.answered_by.lock().ok().and_then(|answered_by| *answered_by)
+    }
+
+    /// Run `call` against each provider until one succeeds
+    ///
+    /// Returns the last provider's error when all of them fail.
+    async fn first_success<'a, T, F, Fut>(&'a self, call: F) -> Result<T>
+    where
+        F: Fn(&'a dyn LLMProvider) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut last_error = None;
+        for (index, provider) in self.providers.iter().enumerate() {
+            match call(provider.as_ref()).await {
+                Ok(value) => {
+                    if let Ok(mut answered_by) = self.answered_by.lock() {
+                        *answered_by = Some(index);
+                    }
+                    return Ok(value);
+                }
+                Err(e) => {
+                    if index + 1 < self.providers.len() {
+                        eprintln!("Warning: LLM provider {} failed, trying the next: {}", index + 1, e);
+                    }
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        if let Ok(mut answered_by) = self.answered_by.lock() {
+            *answered_by = None;
+        }
+        Err(last_error.expect("chain has at least one provider"))
+    }
+}
+
+#[async_trait]
+impl LLMProvider for FallbackLLMProvider {
+    /// Connect every provider; succeeds if at least one connects
+    async fn connect(&mut self) -> Result<()> {
+        let mut last_error = None;
+        let mut connected = false;
+        for provider in &mut self.providers {
+            match provider.connect().await {
+                Ok(()) => connected = true,
+                Err(e) => last_error = Some(e),
+            }
+        }
+        match last_error {
+            Some(e) if !connected => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    async fn generate(&self, prompt: &str) -> Result<GenerationResult> {
+        self.first_success(|provider| provider.generate(prompt)).await
+    }
+
+    async fn generate_with_config(
+        &self,
+        prompt: &str,
+        config: &GenerationConfig,
+    ) -> Result<GenerationResult> {
+        self.first_success(|provider| provider.generate_with_config(prompt, config)).await
+    }
+
+    async fn generate_with_feedback(
+        &self,
+        base_prompt: &str,
+        config: &GenerationConfig,
+        previous_failures: &[String],
+        retry_config: Option<RetryConfig>,
+    ) -> Result<GenerationAttempt> {
+        self.first_success(|provider| {
+            provider.generate_with_feedback(base_prompt, config, previous_failures, retry_config.clone())
+        })
+        .await
+    }
+
+    async fn generate_with_feedback_traced(
+        &self,
+        base_prompt: &str,
+        config: &GenerationConfig,
+        previous_failures: &[String],
+        retry_config: Option<RetryConfig>,
+    ) -> Result<(GenerationAttempt, Vec<GenerationAttempt>)> {
+        self.first_success(|provider| {
+            provider.generate_with_feedback_traced(base_prompt, config, previous_failures, retry_config.clone())
+        })
+        .await
+    }
+
+    async fn generate_stream(
+        &self,
+        prompt: &str,
+        config: &GenerationConfig,
+    ) -> Result<GenerationResult> {
+        self.first_success(|provider| provider.generate_stream(prompt, config)).await
+    }
+
+    fn assess_quality(&self, text: &str, prompt: &str) -> f32 {
+        self.providers[0].assess_quality(text, prompt)
+    }
+
+    fn model_id(&self) -> &str {
+        self.providers[0].model_id()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::test_support::MockLLM;
+
+    #[tokio::test]
+    async fn test_falls_back_to_second_provider() {
+        let chain = FallbackLLMProvider::new(vec![
+            Box::new(MockLLM::failing(|| Error::Network("quota exceeded".to_string()))),
+            Box::new(MockLLM::replying("ibmcloud resource groups")),
+        ])
+        .unwrap();
+
+        let result = chain.generate("list resource groups").await.unwrap();
+        assert_eq!(result.text, "ibmcloud resource groups");
+        assert_eq!(chain.answered_by(), Some(1));
+
+        let attempt = chain
+            .generate_with_feedback("list resource groups", &GenerationConfig::default(), &[], None)
+            .await
+            .unwrap();
+        assert_eq!(attempt.result, "ibmcloud resource groups");
+    }
+
+    #[tokio::test]
+    async fn test_primary_answers_when_healthy() {
+        let chain = FallbackLLMProvider::new(vec![
+            Box::new(MockLLM::replying("aws s3 ls")),
+            Box::new(MockLLM::failing(|| Error::Other("not reached".to_string()))),
+        ])
+        .unwrap();
+
+        assert_eq!(chain.generate("list buckets").await.unwrap().text, "aws s3 ls");
+        assert_eq!(chain.answered_by(), Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_all_providers_failing_returns_last_error() {
+        let chain = FallbackLLMProvider::new(vec![
+            Box::new(MockLLM::failing(|| Error::Network("outage".to_string()))),
+            Box::new(MockLLM::failing(|| Error::Authentication("expired".to_string()))),
+        ])
+        .unwrap();
+
+        let err = chain.generate("list buckets").await.unwrap_err();
+        assert!(matches!(err, Error::Authentication(_)));
+        assert_eq!(chain.answered_by(), None);
+
+        assert!(FallbackLLMProvider::new(Vec::new()).is_err());
+    }
+}
@@ -1,7 +1,14 @@
+// The modules are organized as a library (providers, vector stores,
+// transformers) of which the binary uses only part.
+#![allow(dead_code)]
+
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use colored::*;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 
 // Core modules
 mod core;
@@ -9,16 +16,51 @@ mod cli;
 mod rag;
 mod providers;
 mod watsonx_adapter;
+mod fallback_llm;
+#[cfg(feature = "server")]
+mod server;
 
-use core::{LLMProvider, RAGEngine, VectorStore, CloudProviderType, detect_provider_from_query};
-use watsonx_adapter::create_watsonx_client;
-use rag::{LocalVectorStore, LocalDocumentIndexer, LocalRAGEngine};
+use core::{
+    LLMProvider, RAGEngine, VectorStore, CloudProvider, CloudProviderType, CommandRunner, CostHint, IndexingConfig, SystemRunner,
+    detect_provider_from_query, infer_provider_from_command, provider_switch_candidate, PROVIDER_SWITCH_CONFIDENCE,
+};
+use watsonx_adapter::{create_watsonx_client, create_watsonx_fallback_client, has_watsonx_credentials};
+use fallback_llm::FallbackLLMProvider;
+use rag::{LocalVectorStore, WebDocumentIndexer, LocalRAGEngine};
 use cli::{
-    CommandTranslator, CommandLearningEngine, QualityAnalyzer,
-    display_banner, handle_input_with_history, print_help,
-    confirm_execution, execute_command, execute_command_with_provider, handle_learning,
+    CommandTranslator, RagRetrievalConfig, NO_RELEVANT_DOCS_NOTE, postprocess_command, CommandLearningEngine, QualityAnalyzer,
+    configure_color, display_banner, should_display_banner, DEFAULT_BANNER_TITLE, NO_BANNER_ENV, handle_input_with_history, print_help, run_until_ctrl_c, format_last_translation, format_rag_decision, truncate_source_name, DEFAULT_SOURCE_WIDTH,
+    confirm_execution, confirm_or_edit, confirm_provider_switch, choose_command, execute_command, execute_command_with_provider, choose_retry, confirm_learn_retry, is_same_command_kind, parse_retry_suggestions,
+    run_doctor, CliError, auto_execute_decision, AutoExecuteDecision,
+    IndexCheckpoint, IndexSource, index_source, index_source_with_checkpoint,
+    Progress, OutputFormat, BatchResult, read_batch_queries, translate_batch, print_batch_results,
+    CommandAliases, parse_alias, CommandHistory, HistoryRecord, format_timestamp,
+    UsageLog, default_usage_path, DEFAULT_MAX_CAPTURE_BYTES,
+    MockLLM, SELF_TEST_COMMAND, SELF_TEST_QUERY, run_self_test, ConfirmDefault,
+    Intent, IntentDetector, INTENT_MIN_CONFIDENCE, DeploymentSpec, deployment_target, copy_source_files,
+    BuildRunStatus, CodeEngineCli, poll_build_status, BUILD_POLL_INTERVAL, DEFAULT_BUILD_TIMEOUT,
+    PlanAction, PlanConfirmation, PlanConfirmMode, ask_plan_question, CheatsheetFormat, render_cheatsheet,
+    OfflineLLM, connect_with_timeout, confirm_offline_mode, DEFAULT_CONNECT_TIMEOUT, PromptTemplate,
 };
 
+/// File the local RAG store is persisted to
+const RAG_STORE_FILE: &str = "rag_store.json";
+
+/// File executed commands are recorded in
+const HISTORY_FILE: &str = "command_history.json";
+
+/// File learned corrections are persisted to
+const CORRECTIONS_FILE: &str = "command_corrections.json";
+
+/// Files completed by an interrupted directory `index` run
+const INDEX_CHECKPOINT_FILE: &str = "index_checkpoint.json";
+
+/// Candidates offered by the interactive `alternatives` command
+const DEFAULT_ALTERNATIVES: usize = 3;
+
+/// Shown in verbose mode when the primary LLM endpoint failed
+const FALLBACK_LLM_NOTE: &str = "Answered by the fallback LLM endpoint";
+
 #[derive(Parser)]
 #[command(name = "anycli")]
 #[command(about = "AI-powered Cloud Universal CLI assistant", long_about = None)]
@@ -27,18 +69,213 @@ struct Cli {
     #[arg(short, long)]
     command: Option<String>,
     
-    /// Cloud provider (ibmcloud, aws, gcp, azure)
+    /// Cloud provider (ibmcloud, aws, gcp, azure, vmware, digitalocean, github, terraform)
     #[arg(short, long)]
     provider: Option<String>,
     
     /// List supported cloud providers
     #[arg(long)]
     list_providers: bool,
+
+    /// Skip confirmation for non-destructive commands scoring above this quality (0.0-1.0)
+    #[arg(long, value_name = "SCORE")]
+    auto_execute_threshold: Option<f32>,
+
+    /// Refuse to execute commands that use an unknown provider service
+    #[arg(long)]
+    strict_validation: bool,
+
+    /// Do not offer to switch providers based on the query
+    #[arg(long)]
+    no_auto_detect: bool,
+
+    /// Maximum LLM requests per minute, to stay under provider rate limits
+    #[arg(long, value_name = "RPM")]
+    requests_per_minute: Option<u32>,
+
+    /// Minimum retrieval score (0.0-1.0) before documentation is added to the prompt
+    #[arg(long, value_name = "SCORE")]
+    rag_min_confidence: Option<f32>,
+
+    /// Minimum score (0.0-1.0) of each documentation chunk added to the prompt
+    #[arg(long, value_name = "SCORE")]
+    rag_min_score: Option<f32>,
+
+    /// Documentation page fetched and indexed at startup for this session (repeatable)
+    #[arg(long = "docs-url", value_name = "URL")]
+    docs_urls: Vec<String>,
+
+    /// File whose contents are added to every translation prompt this session, without indexing it
+    #[arg(long, value_name = "PATH")]
+    context_file: Option<PathBuf>,
+
+    /// File with the translation prompt; placeholders are {provider}, {context}, {examples} and the required {query}
+    #[arg(long, value_name = "PATH")]
+    prompt_template: Option<PathBuf>,
+
+    /// How to render command output (text/plain, json, yaml, table)
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+
+    /// Print extra details about each translation, such as when no documentation was relevant
+    #[arg(short, long)]
+    verbose: bool,
+
+    /// Characters of a documentation source name shown by `why` and --verbose before it is cut off
+    #[arg(long, value_name = "CHARS", default_value_t = DEFAULT_SOURCE_WIDTH)]
+    source_width: usize,
+
+    /// Don't show the startup banner (also set by ANYCLI_NO_BANNER=1)
+    #[arg(long)]
+    no_banner: bool,
+
+    /// Title shown in the startup banner
+    #[arg(long, value_name = "TITLE", default_value_t = DEFAULT_BANNER_TITLE.to_string())]
+    banner_title: String,
+
+    /// Maximum tokens the LLM may generate for each translated command
+    #[arg(long, value_name = "TOKENS")]
+    max_tokens: Option<u32>,
+
+    /// After a successful command, print an LLM summary of its output
+    #[arg(long)]
+    summarize: bool,
+
+    /// Maximum bytes of command stdout and stderr to capture; longer output is truncated
+    #[arg(long, value_name = "BYTES", default_value_t = DEFAULT_MAX_CAPTURE_BYTES)]
+    max_output_bytes: usize,
+
+    /// Answer assumed when Enter is pressed at a confirmation prompt; by default yes, or no for destructive commands
+    #[arg(long, value_enum, value_name = "yes|no")]
+    confirm_default: Option<ConfirmDefault>,
+
+    /// How `plan` steps are confirmed: each one, or non-destructive steps together
+    #[arg(long, value_enum, default_value_t = PlanConfirmMode::Batch)]
+    plan_confirm: PlanConfirmMode,
+
+    /// Expand a CLI shorthand before running commands, e.g. ic=ibmcloud (repeatable)
+    #[arg(long = "alias", value_name = "ALIAS=COMMAND", value_parser = parse_alias)]
+    aliases: Vec<(String, String)>,
+
+    /// Skip the LLM: only templates and learned corrections translate queries
+    #[arg(long)]
+    offline: bool,
+
+    /// Seconds to wait for the LLM connection at startup before offering offline mode
+    #[arg(long, value_name = "SECS", default_value_t = DEFAULT_CONNECT_TIMEOUT.as_secs())]
+    connect_timeout: u64,
+
+    #[command(subcommand)]
+    subcommand: Option<Commands>,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// List supported providers and their capabilities
+    Providers {
+        /// Deprecated alias for `--format json`
+        #[arg(long)]
+        json: bool,
+
+        /// List the top-level services of one provider
+        #[arg(long, value_name = "PROVIDER")]
+        services: Option<String>,
+
+        /// Output format; json and yaml emit the capability manifests, or the services with --services
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text, conflicts_with = "json")]
+        format: OutputFormat,
+    },
+
+    /// Print a provider's common commands grouped by service
+    Cheatsheet {
+        /// Provider name, e.g. aws or ibmcloud
+        provider: String,
+        /// Cheatsheet format
+        #[arg(long, value_enum, default_value_t = CheatsheetFormat::Text)]
+        output: CheatsheetFormat,
+    },
+
+    /// Check that the provider CLI, credentials and RAG store are ready
+    Doctor,
+
+    /// Translate a known query with a canned LLM to check the install offline
+    SelfTest,
+
+    /// Serve translations over HTTP: POST /translate and GET /health
+    #[cfg(feature = "server")]
+    Serve {
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        addr: String,
+    },
+
+    /// Index documentation into the RAG store (exactly one source)
+    Index {
+        /// Web page to fetch and index
+        #[arg(long)]
+        url: Option<String>,
+
+        /// Local file or directory to index
+        #[arg(long)]
+        file: Option<PathBuf>,
+
+        /// Raw text to index
+        #[arg(long)]
+        text: Option<String>,
+
+        /// Reindex every file of a directory, ignoring progress saved by an interrupted run
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Translate a query, or every line of a batch file, without executing
+    Translate {
+        /// Natural language query
+        query: Option<String>,
+
+        /// File with one query per line (`-` for stdin)
+        #[arg(long, value_name = "PATH", conflicts_with = "query")]
+        batch: Option<String>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+
+    /// Show LLM requests and tokens for today, the last 7 days and all time
+    Usage,
+
+    /// Show executed commands with their time, provider and outcome
+    History {
+        /// Number of most recent entries to show
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+
+        /// Print only the commands, one per line
+        #[arg(long)]
+        plain: bool,
+    },
+
+    /// Review learned corrections, remove one, or prune unreliable ones
+    Corrections {
+        /// List corrections with their index and success rate (the default)
+        #[arg(long)]
+        list: bool,
+
+        /// Remove the correction at this index, as shown by --list
+        #[arg(long, value_name = "INDEX", conflicts_with = "prune_below")]
+        remove: Option<usize>,
+
+        /// Remove corrections whose success rate is below this (0.0-1.0)
+        #[arg(long, value_name = "RATE")]
+        prune_below: Option<f32>,
+    },
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     dotenvy::dotenv().ok();
+    configure_color();
     let cli = Cli::parse();
 
     // Handle list providers command
@@ -50,51 +287,359 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
+    if let Some(Commands::Providers { json, services, format }) = &cli.subcommand {
+        let format = if *json { OutputFormat::Json } else { *format };
+        if let Some(provider_str) = services {
+            let provider_type = CloudProviderType::from_str(provider_str)
+                .ok_or_else(|| CliError::UnknownProvider(provider_str.clone()))?;
+            let services = providers::create_provider(provider_type).list_services();
+            let entries: Vec<_> = services
+                .iter()
+                .map(|(service, description)| serde_json::json!({
+                    "service": service,
+                    "description": description,
+                }))
+                .collect();
+
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&entries)?),
+                OutputFormat::Yaml => print!("{}", serde_yaml::to_string(&entries)?),
+                OutputFormat::Text | OutputFormat::Table => {
+                    println!("{}", format!("{} services:", provider_type.display_name()).bold());
+                    for (service, description) in services {
+                        println!("  {} - {}", service.green(), description);
+                    }
+                }
+            }
+        } else if format == OutputFormat::Json {
+            println!("{}", serde_json::to_string_pretty(&providers::capability_manifests())?);
+        } else if format == OutputFormat::Yaml {
+            print!("{}", serde_yaml::to_string(&providers::capability_manifests())?);
+        } else {
+            println!("{}", "Supported Cloud Providers:".bold());
+            for provider in CloudProviderType::all() {
+                println!("  {} - {}", provider.cli_command().green(), provider.display_name());
+            }
+        }
+        return Ok(());
+    }
+
+    if let Some(Commands::Cheatsheet { provider, output }) = &cli.subcommand {
+        let provider_type = CloudProviderType::from_str(provider)
+            .ok_or_else(|| CliError::UnknownProvider(provider.clone()))?;
+        print!("{}", render_cheatsheet(providers::create_provider(provider_type).as_ref(), *output));
+        return Ok(());
+    }
+
+    if let Some(Commands::Usage) = &cli.subcommand {
+        let summary = UsageLog::new(&default_usage_path())?.summary(chrono::Local::now().date_naive());
+        for (label, count) in [("Today", summary.today), ("Last 7 days", summary.last_7_days), ("Total", summary.total)] {
+            println!("{:<12} {:>6} requests {:>10} tokens", label.bold(), count.requests, count.tokens);
+        }
+        for (model, count) in &summary.by_model {
+            println!("  {} {} requests, {} tokens", model.cyan(), count.requests, count.tokens);
+        }
+        return Ok(());
+    }
+
+    if let Some(Commands::History { limit, plain }) = &cli.subcommand {
+        let history = CommandHistory::new(HISTORY_FILE)?;
+        if *plain {
+            for record in history.recent(*limit) {
+                println!("{}", record.command);
+            }
+        } else if history.records().is_empty() {
+            println!("{} No commands recorded yet", "ℹ️".cyan());
+        } else {
+            for record in history.recent(*limit) {
+                let status = if record.success { "✓".green() } else { "✗".red() };
+                let provider = record.provider.map(|provider| provider.id()).unwrap_or("-");
+                println!(
+                    "{} {} {:<12} {}  {}",
+                    status,
+                    format_timestamp(record.timestamp).dimmed(),
+                    provider.cyan(),
+                    record.command.bold(),
+                    record.query.dimmed(),
+                );
+            }
+        }
+        return Ok(());
+    }
+
+    if let Some(Commands::Corrections { list, remove, prune_below }) = &cli.subcommand {
+        let mut learning_engine = CommandLearningEngine::new(CORRECTIONS_FILE)?;
+        if let Some(index) = remove {
+            let removed = learning_engine.remove_correction(*index).await?;
+            println!("{} Removed '{}' → {}", "🗑️".yellow(), removed.query, removed.correct_command);
+        }
+        if let Some(min_success_rate) = prune_below {
+            let removed = learning_engine.prune(*min_success_rate).await?;
+            println!("{} Pruned {} corrections below {:.0}% success", "🗑️".yellow(), removed, min_success_rate * 100.0);
+        }
+
+        if *list || (remove.is_none() && prune_below.is_none()) {
+            let corrections = learning_engine.get_all_corrections();
+            if corrections.is_empty() {
+                println!("{} No learned corrections", "ℹ️".cyan());
+            }
+            for (index, learning) in corrections.iter().enumerate() {
+                let rate = learning
+                    .success_rate()
+                    .map(|rate| format!("{:.0}%", rate * 100.0))
+                    .unwrap_or_else(|| "untried".to_string());
+                println!(
+                    "{:>3}  {:<8} {}  {}",
+                    index,
+                    rate.cyan(),
+                    learning.correct_command.bold(),
+                    learning.query.dimmed(),
+                );
+            }
+        }
+        return Ok(());
+    }
+
     // Parse cloud provider if specified
     let default_provider = if let Some(ref provider_str) = cli.provider {
         CloudProviderType::from_str(provider_str)
-            .ok_or_else(|| anyhow::anyhow!("Unknown cloud provider: {}", provider_str))?
+            .ok_or_else(|| CliError::UnknownProvider(provider_str.clone()))?
     } else {
         CloudProviderType::IBMCloud // Default to IBM Cloud for now
     };
 
     println!("{} Default provider: {}", "ℹ️".cyan(), default_provider);
 
-    // Initialize components
-    let watsonx = create_watsonx_client()?;
-
     // Initialize vector store and RAG
     let mut vector_store = LocalVectorStore::new();
     vector_store.connect().await?;
+    if Path::new(RAG_STORE_FILE).exists()
+        && let Err(e) = vector_store.load_from_file(RAG_STORE_FILE)
+    {
+        println!("⚠️  Failed to load {}: {}", RAG_STORE_FILE, e);
+    }
     let vector_store = Arc::new(vector_store);
 
-    let document_indexer = Arc::new(LocalDocumentIndexer::new(vector_store.clone()));
-    let mut rag_engine = LocalRAGEngine::new(vector_store.clone(), document_indexer.clone());
+    // Tag newly indexed chunks with an explicit --provider so retrieval stays
+    // scoped to it; untagged chunks are retrieved for every provider
+    let indexing_config = IndexingConfig {
+        provider: cli.provider.as_ref().map(|_| default_provider),
+        ..Default::default()
+    };
+    let document_indexer = Arc::new(WebDocumentIndexer::with_config(vector_store.clone(), indexing_config));
+    let mut rag_engine = LocalRAGEngine::new(vector_store.clone(), document_indexer.clone())
+        .with_online_sources(cli.docs_urls.clone());
 
     // Initialize RAG engine
     match rag_engine.initialize().await {
         Ok(_) => println!("✅ RAG engine initialized"),
         Err(e) => println!("⚠️  RAG initialization failed: {}. Continuing without RAG.", e),
     }
+    if rag_engine.is_ready() {
+        for failed in rag_engine.failed_sources() {
+            println!("⚠️  Skipped knowledge source: {}", failed);
+        }
+    }
+
+    match &cli.subcommand {
+        Some(Commands::Doctor) => {
+            let provider = providers::create_provider(default_provider);
+            let rag_document_count = if rag_engine.is_ready() {
+                vector_store.count().await.ok()
+            } else {
+                None
+            };
+            let report = run_doctor(provider.as_ref(), has_watsonx_credentials(), rag_document_count).await;
+            report.print();
+            if !report.is_healthy() {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(Commands::SelfTest) => {
+            let translator = CommandTranslator::with_rag(MockLLM::replying(SELF_TEST_COMMAND), rag_engine);
+            match run_self_test(&translator).await {
+                Ok(command) => println!("{} PASS: '{}' -> {}", "✅".green(), SELF_TEST_QUERY, command),
+                Err(e) => {
+                    println!("{} FAIL: '{}': {}", "❌".red(), SELF_TEST_QUERY, e);
+                    std::process::exit(1);
+                }
+            }
+            return Ok(());
+        }
+        Some(Commands::Index { url, file, text, force }) => {
+            let source = IndexSource::from_args(url.clone(), file.clone(), text.clone())?;
+            let result = match source {
+                IndexSource::Path(ref path) if path.is_dir() => {
+                    let mut checkpoint = IndexCheckpoint::load(Path::new(INDEX_CHECKPOINT_FILE))?;
+                    if *force {
+                        checkpoint.clear()?;
+                    } else if checkpoint.completed_count() > 0 {
+                        println!(
+                            "{} Resuming: skipping {} files indexed by an earlier run (use --force to reindex)",
+                            "↩️".cyan(),
+                            checkpoint.completed_count()
+                        );
+                    }
+
+                    let persist = || vector_store.save_to_file(RAG_STORE_FILE);
+                    let result = index_source_with_checkpoint(document_indexer.as_ref(), &source, &mut checkpoint, persist).await?;
+                    // Failed files stay out of the checkpoint and are retried next run
+                    if result.documents_failed == 0 {
+                        checkpoint.clear()?;
+                    }
+                    result
+                }
+                _ => index_source(document_indexer.as_ref(), &source).await?,
+            };
+            vector_store.save_to_file(RAG_STORE_FILE)?;
+
+            println!("{} Indexed {} chunks into {}", "✅".green(), result.documents_indexed, RAG_STORE_FILE);
+            if result.documents_failed > 0 {
+                println!("{} {} chunks failed", "⚠️".yellow(), result.documents_failed);
+                for error in &result.errors {
+                    println!("  {} {}", "•".yellow(), error);
+                }
+            }
+            return Ok(());
+        }
+        Some(Commands::Providers { .. }) | Some(Commands::Cheatsheet { .. }) | Some(Commands::Translate { .. }) | Some(Commands::History { .. }) | Some(Commands::Usage) | Some(Commands::Corrections { .. }) | None => {}
+        #[cfg(feature = "server")]
+        Some(Commands::Serve { .. }) => {}
+    }
+
+    // Curated provider knowledge keeps retrieval useful when documentation
+    // can't be fetched. It is seeded only for translating, after `index` has
+    // returned, so it never ends up in the saved store.
+    let known_providers: Vec<Box<dyn CloudProvider>> = CloudProviderType::all()
+        .into_iter()
+        .map(providers::create_provider)
+        .collect();
+    if let Err(e) = rag_engine.seed_provider_knowledge(&known_providers).await {
+        println!("⚠️  Failed to seed provider knowledge: {}", e);
+    }
+
+    let llm = if cli.offline {
+        println!("{} Offline mode: only template and learned commands are available", "ℹ️".cyan());
+        FallbackLLMProvider::new(vec![Box::new(OfflineLLM)])?
+    } else {
+        // A fallback endpoint, when configured, answers if the primary one fails
+        let mut llm_chain: Vec<Box<dyn LLMProvider>> = vec![Box::new(create_watsonx_client()?)];
+        if let Some(fallback) = create_watsonx_fallback_client()? {
+            llm_chain.push(Box::new(fallback));
+        }
+        let mut llm = FallbackLLMProvider::new(llm_chain)?;
+
+        let spinner = Progress::spinner("Connecting to watsonx.ai...");
+        let connected = connect_with_timeout(&mut llm, Duration::from_secs(cli.connect_timeout)).await;
+        spinner.finish();
+        match connected {
+            Ok(()) => llm,
+            Err(e) if std::io::stdin().is_terminal() && confirm_offline_mode(&e).await? => {
+                FallbackLLMProvider::new(vec![Box::new(OfflineLLM)])?
+            }
+            Err(e) => {
+                eprintln!("{} Use --offline to start without the LLM", "ℹ️".cyan());
+                return Err(e.into());
+            }
+        }
+    };
+
+    let mut translator = CommandTranslator::with_rag(llm, rag_engine)
+        .with_usage_log(UsageLog::new(&default_usage_path())?);
+    if let Some(requests_per_minute) = cli.requests_per_minute {
+        translator = translator.with_rate_limit(requests_per_minute);
+    }
+    if let Some(threshold) = cli.rag_min_confidence {
+        translator = translator.with_rag_confidence_threshold(threshold);
+    }
+    if let Some(min_document_score) = cli.rag_min_score {
+        translator = translator.with_rag_retrieval(RagRetrievalConfig { min_document_score, ..Default::default() });
+    }
+    if let Some(max_tokens) = cli.max_tokens {
+        translator = translator.with_max_tokens(max_tokens);
+    }
+    if let Some(path) = &cli.context_file {
+        let context = std::fs::read_to_string(path)
+            .map_err(|e| CliError::ValidationFailed(format!("cannot read context file {}: {}", path.display(), e)))?;
+        translator = translator.with_context(&context);
+    }
+    if let Some(path) = &cli.prompt_template {
+        translator = translator.with_prompt_template(PromptTemplate::load(path)?);
+    }
+    if default_provider == CloudProviderType::IBMCloud
+        && let Some(target) = providers::ibmcloud::current_target()
+    {
+        translator = translator.with_ibmcloud_target(target);
+    }
+
+    #[cfg(feature = "server")]
+    if let Some(Commands::Serve { addr }) = &cli.subcommand {
+        server::serve(translator, addr).await?;
+        return Ok(());
+    }
 
-    let translator = CommandTranslator::with_rag(watsonx, rag_engine);
-    let mut learning_engine = CommandLearningEngine::new("command_corrections.json")?;
+    let mut learning_engine = CommandLearningEngine::new(CORRECTIONS_FILE)?;
     let quality_analyzer = QualityAnalyzer::new();
 
+    if let Some(Commands::Translate { query, batch, format }) = &cli.subcommand {
+        let queries = match (query, batch) {
+            (_, Some(path)) => read_batch_queries(path)?,
+            (Some(query), None) => vec![query.clone()],
+            (None, None) => return Err(CliError::ValidationFailed(
+                "a query or --batch <path> is required".to_string(),
+            ).into()),
+        };
+
+        let results: Vec<BatchResult> = translate_batch(&translator, &queries, default_provider).await;
+        print_batch_results(&results, *format)?;
+        return Ok(());
+    }
+
     // Handle direct command execution
     if let Some(cmd) = cli.command {
-        let result = translator.translate(&cmd).await?;
+        let result = translator.translate_for_provider(&cmd, default_provider).await?;
+        if cli.verbose && translator.last_translation().is_some_and(|last| last.no_relevant_docs) {
+            eprintln!("{} {}", "ℹ️".cyan(), NO_RELEVANT_DOCS_NOTE);
+        }
+        if let Some(decision) = translator.last_translation().filter(|_| cli.verbose).as_ref().and_then(format_rag_decision) {
+            eprintln!("{} RAG confidence: {}", "ℹ️".cyan(), decision);
+        }
+        if cli.verbose && translator.llm().answered_by().is_some_and(|index| index > 0) {
+            eprintln!("{} {}", "ℹ️".cyan(), FALLBACK_LLM_NOTE);
+        }
         println!("{}", result);
         return Ok(());
     }
 
-    // Interactive mode
-    display_banner();
+    // Interactive mode. Piped stdin is a stream of queries: translate each
+    // line without prompting, since prompts would consume the following queries
+    let interactive = std::io::stdin().is_terminal();
+    let no_banner_env = std::env::var(NO_BANNER_ENV).ok();
+    if interactive && should_display_banner(cli.no_banner, no_banner_env.as_deref(), std::io::stdout().is_terminal()) {
+        display_banner(&cli.banner_title);
+    }
+
+    let mut aliases = CommandAliases::builtin();
+    for (alias, command) in &cli.aliases {
+        aliases.add(alias, command);
+    }
+
+    // Executed commands are persisted; their queries seed arrow-key history
+    let mut command_history = CommandHistory::new(HISTORY_FILE)?;
+    let mut history: Vec<String> = command_history
+        .records()
+        .iter()
+        .map(|record| record.query.clone())
+        .collect();
 
-    let mut history = Vec::new();
+    // Login checks before execution spawn through this
+    let runner: Arc<dyn CommandRunner> = Arc::new(SystemRunner);
 
     loop {
-        let input = handle_input_with_history(&mut history).await?;
+        let Some(input) = handle_input_with_history(&mut history).await? else {
+            break;
+        };
 
         if input.is_empty() {
             continue;
@@ -113,39 +658,353 @@ async fn main() -> Result<()> {
             continue;
         }
 
+        if input_lower == "why" {
+            println!("{}", format_last_translation(translator.last_translation().as_ref(), cli.source_width));
+            continue;
+        }
+
+        if input_lower.starts_with("complete ") {
+            let partial = input[9..].trim();
+            match translator.suggest_completions(partial).await {
+                Ok(suggestions) if !suggestions.is_empty() => {
+                    for suggestion in suggestions {
+                        println!("  {} {}", "→".green(), suggestion);
+                    }
+                }
+                Ok(_) => println!("{} No completions found for '{}'", "ℹ️".cyan(), partial),
+                Err(e) => println!("{} Completion failed: {}", "❌".red(), e),
+            }
+            continue;
+        }
+
+        if input_lower.starts_with("buildrun ") {
+            let name = input["buildrun ".len()..].trim();
+            let followed = run_until_ctrl_c(poll_build_status(&CodeEngineCli, name, DEFAULT_BUILD_TIMEOUT, BUILD_POLL_INTERVAL, |line| {
+                println!("  {}", line.dimmed())
+            }))
+            .await;
+            let Some(followed) = followed else {
+                println!("{} Stopped following build run {}; it keeps running", "⏹️".yellow(), name);
+                continue;
+            };
+            match followed {
+                Ok(result) if result.status == BuildRunStatus::Succeeded => {
+                    println!("{} Build run {} succeeded after {}s", "✅".green(), result.name, result.elapsed.as_secs());
+                }
+                Ok(result) => println!(
+                    "{} Build run {} {}: {}",
+                    "❌".red(),
+                    result.name,
+                    result.status,
+                    result.failure_reason.unwrap_or_else(|| "no reason given".to_string())
+                ),
+                Err(e) => println!("{} Cannot follow build run {}: {}", "❌".red(), name, e),
+            }
+            continue;
+        }
+
+        if input_lower.starts_with("plan ") {
+            let query = input["plan ".len()..].trim();
+            let commands = match translator.translate_plan(query, default_provider).await {
+                Ok(commands) => commands,
+                Err(e) => {
+                    println!("{} Planning failed: {}", "❌".red(), e);
+                    continue;
+                }
+            };
+            let mut plan = PlanConfirmation::new(commands, cli.plan_confirm);
+            println!("{} Plan for {}:", "📋".cyan(), default_provider);
+            for (index, step) in plan.steps().iter().enumerate() {
+                let marker = if step.destructive { "⚠️ destructive".red().to_string() } else { String::new() };
+                println!("  {} {} {}", format!("{}.", index + 1).cyan(), step.command.bold(), marker);
+            }
+            if !interactive {
+                continue;
+            }
+
+            let summary = loop {
+                match plan.next() {
+                    PlanAction::Ask(question) => {
+                        let yes = ask_plan_question(&question).await?;
+                        plan.answer(yes);
+                    }
+                    PlanAction::Run { command, .. } => {
+                        let success = match execute_command(&command, cli.output, cli.max_output_bytes, &runner).await {
+                            Ok(result) => result.success,
+                            Err(e @ CliError::ValidationFailed(_)) => {
+                                println!("{} {}", "❌".red(), e);
+                                false
+                            }
+                            Err(e) => return Err(e.into()),
+                        };
+                        let record = HistoryRecord::now(Some(default_provider), query, &command, success);
+                        if let Err(e) = command_history.record(record).await {
+                            eprintln!("{} Failed to record history: {}", "⚠️".yellow(), e);
+                        }
+                        plan.record_result(success);
+                    }
+                    PlanAction::Finished(summary) => break summary,
+                }
+            };
+            println!(
+                "{} Plan {}: {} succeeded, {} failed, {} skipped",
+                if summary.failed == 0 && !summary.aborted { "✅".green() } else { "⚠️".yellow() },
+                if summary.aborted { "aborted" } else { "finished" },
+                summary.succeeded,
+                summary.failed,
+                summary.skipped
+            );
+            continue;
+        }
+
+        if input_lower.starts_with("port ") {
+            let (target, command) = input["port ".len()..].trim().split_once(' ').unwrap_or_default();
+            let Some(to) = CloudProviderType::from_str(target) else {
+                println!("{} Usage: port <provider> <command>, e.g. port azure aws s3 ls", "ℹ️".cyan());
+                continue;
+            };
+            let Some(from) = infer_provider_from_command(command) else {
+                println!("{} Cannot tell which provider '{}' is for", "❌".red(), command.trim());
+                continue;
+            };
+            let ported = match translator.port_command(command.trim(), from, to).await {
+                Ok(ported) => ported,
+                Err(e) => {
+                    println!("{} Porting failed: {}", "❌".red(), e);
+                    continue;
+                }
+            };
+            println!("{} {}", "→".green(), ported);
+            if let Some(hint) = CostHint::for_command(to, &ported) {
+                println!("{} {}", "💰".yellow(), hint);
+            }
+            if interactive && confirm_execution(&ported, cli.confirm_default).await? {
+                let result = match execute_command(&ported, cli.output, cli.max_output_bytes, &runner).await {
+                    Ok(result) => result,
+                    Err(e @ CliError::ValidationFailed(_)) => {
+                        println!("{} {}", "❌".red(), e);
+                        continue;
+                    }
+                    Err(e) => return Err(e.into()),
+                };
+                if let Err(e) = command_history.record(HistoryRecord::now(Some(to), &input, &ported, result.success)).await {
+                    eprintln!("{} Failed to record history: {}", "⚠️".yellow(), e);
+                }
+            }
+            continue;
+        }
+
+        if input_lower.starts_with("alternatives ") {
+            let query = input["alternatives ".len()..].trim();
+            let alternatives = match translator.translate_alternatives(query, DEFAULT_ALTERNATIVES, default_provider).await {
+                Ok(alternatives) => alternatives,
+                Err(e) => {
+                    println!("{} Translation failed: {}", "❌".red(), e);
+                    continue;
+                }
+            };
+            if alternatives.is_empty() {
+                println!("{} No valid commands found for '{}'", "ℹ️".cyan(), query);
+                continue;
+            }
+            let chosen = if interactive {
+                choose_command(&alternatives).await?
+            } else {
+                for command in &alternatives {
+                    println!("{} {}", "→".green(), command);
+                }
+                None
+            };
+            if let Some(command) = chosen {
+                // The same checks as a single translation before anything runs
+                let provider = default_provider;
+                let command = postprocess_command(&aliases.expand(&command), providers::create_provider(provider).as_ref());
+                println!("{} {}", "→".green(), command.bold());
+                if let Some(hint) = CostHint::for_command(provider, &command) {
+                    println!("{} {}", "💰".yellow(), hint);
+                }
+                if !confirm_execution(&command, cli.confirm_default).await? {
+                    continue;
+                }
+                let result = match execute_command_with_provider(&command, Some(provider), cli.output, cli.max_output_bytes, &runner).await {
+                    Ok(result) => result,
+                    Err(e @ CliError::ValidationFailed(_)) => {
+                        println!("{} {}", "❌".red(), e);
+                        continue;
+                    }
+                    Err(e) => return Err(e.into()),
+                };
+                translator.update_command_success(&command, result.success);
+                if let Err(e) = command_history.record(HistoryRecord::now(Some(provider), query, &command, result.success)).await {
+                    eprintln!("{} Failed to record history: {}", "⚠️".yellow(), e);
+                }
+            }
+            continue;
+        }
+
         if input_lower.starts_with("exec ") {
-            let cmd = input[5..].trim();
-            execute_command(cmd).await?;
+            let cmd = aliases.expand(&input[5..]);
+            let result = match execute_command(&cmd, cli.output, cli.max_output_bytes, &runner).await {
+                Ok(result) => result,
+                // Rejected pipelines leave the session running
+                Err(e @ CliError::ValidationFailed(_)) => {
+                    println!("{} {}", "❌".red(), e);
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+            translator.remember_turn(&cmd, &cmd, &result);
+            let provider = infer_provider_from_command(&cmd);
+            if let Err(e) = command_history.record(HistoryRecord::now(provider, &cmd, &cmd, result.success)).await {
+                eprintln!("{} Failed to record history: {}", "⚠️".yellow(), e);
+            }
             continue;
         }
 
         // Check for learned commands
-        if let Some(learned) = learning_engine.get_learned_command(&input) {
+        if let Some(learned) = learning_engine.get_learned_command(&input).map(|learned| learned.correct_command.clone()) {
             println!("{} Found learned command", "💡".cyan());
-            println!("{} {}", "→".green(), learned.correct_command);
-            
-            if confirm_execution(&learned.correct_command).await? {
-                execute_command(&learned.correct_command).await?;
+            println!("{} {}", "→".green(), learned);
+            if let Some(hint) = infer_provider_from_command(&learned).and_then(|provider| CostHint::for_command(provider, &learned)) {
+                println!("{} {}", "💰".yellow(), hint);
+            }
+
+            if interactive && confirm_execution(&learned, cli.confirm_default).await? {
+                let result = match execute_command(&learned, cli.output, cli.max_output_bytes, &runner).await {
+                    Ok(result) => result,
+                    Err(e @ CliError::ValidationFailed(_)) => {
+                        println!("{} {}", "❌".red(), e);
+                        continue;
+                    }
+                    Err(e) => return Err(e.into()),
+                };
+                let record = HistoryRecord::now(Some(default_provider), &input, &learned, result.success);
+                if let Err(e) = command_history.record(record).await {
+                    eprintln!("{} Failed to record history: {}", "⚠️".yellow(), e);
+                }
+                // Outcomes feed the success rate that `corrections --prune-below` uses
+                let recorded = if result.success {
+                    learning_engine.record_success(&input, &learned).await
+                } else {
+                    learning_engine.record_failure(&input, &learned).await.map(|_| ())
+                };
+                if let Err(e) = recorded {
+                    eprintln!("{} Failed to record correction outcome: {}", "⚠️".yellow(), e);
+                }
             }
             continue;
         }
 
-        // Detect cloud provider from query
-        let detected_provider = detect_provider_from_query(&input);
-        let active_provider = if let Some(detection) = detected_provider {
-            println!("{} Detected provider: {} (confidence: {:.0}%)", 
-                "🔍".cyan(), detection.provider, detection.confidence * 100.0);
-            detection.provider
-        } else {
-            default_provider
-        };
+        // Surface commands that worked for similar queries
+        let learning_hints: Vec<String> = learning_engine
+            .get_suggestions(&input, 1)
+            .into_iter()
+            .map(|suggestion| suggestion.correct_command.clone())
+            .collect();
+        for hint in &learning_hints {
+            println!("{} Similar query worked before: {}", "💡".cyan(), hint.dimmed());
+        }
+
+        let (intent, intent_confidence) = IntentDetector::classify(&input);
+        let intent = Some(intent).filter(|_| intent_confidence >= INTENT_MIN_CONFIDENCE);
+        if intent == Some(Intent::Delete) {
+            println!("{} This looks like a delete request - review the command before running it", "⚠️".yellow());
+        }
+
+        // Detect cloud provider from query and offer to switch for this translation
+        let mut active_provider = default_provider;
+        if !cli.no_auto_detect {
+            let detection = detect_provider_from_query(&input);
+            if let Some(candidate) = provider_switch_candidate(detection.as_ref(), active_provider, PROVIDER_SWITCH_CONFIDENCE) {
+                let matched_keywords = detection.as_ref().map(|d| d.matched_keywords.as_slice()).unwrap_or_default();
+                if interactive && confirm_provider_switch(candidate, matched_keywords).await? {
+                    active_provider = candidate;
+                }
+            }
+        }
+
+        // Deploy requests get a fixed plan for the provider's deployment target
+        if let Some(target) = deployment_target(active_provider).filter(|_| intent == Some(Intent::Deploy)) {
+            let spec = DeploymentSpec::from_dir(Path::new("."));
+            match target.plan(&spec) {
+                Ok(plan) => {
+                    println!("{} Deployment plan for {} ({}):", "📦".cyan(), target.name(), target.provider());
+                    for step in &plan.steps {
+                        println!("  {}", step.bold());
+                    }
+                    if !(interactive && confirm_execution(&plan.script(), cli.confirm_default).await?) {
+                        continue;
+                    }
+
+                    // Build from a copy without ignored files such as node_modules and .git
+                    let staging = match tempfile::tempdir() {
+                        Ok(staging) => staging,
+                        Err(e) => {
+                            println!("{} Cannot stage source files: {}", "❌".red(), e);
+                            continue;
+                        }
+                    };
+                    match copy_source_files(Path::new("."), staging.path()) {
+                        Ok(copied) => println!("{} Staged {} source files for upload", "📁".cyan(), copied),
+                        Err(e) => {
+                            println!("{} Cannot stage source files: {}", "❌".red(), e);
+                            continue;
+                        }
+                    }
+                    let staged = DeploymentSpec { source_dir: staging.path().to_path_buf(), ..spec };
+                    match target.plan(&staged).and_then(|plan| plan.run()) {
+                        Ok(()) => println!("{} Deployed to {}", "✅".green(), target.name()),
+                        Err(e) => println!("{} {}", "❌".red(), e),
+                    }
+                    continue;
+                }
+                Err(e) => println!("{} Cannot deploy to {}: {}. Translating instead.", "⚠️".yellow(), target.name(), e),
+            }
+        }
 
         // Translate natural language to command
-        println!("{} Translating for {}...", "🤖".blue(), active_provider);
-        
-        match translator.translate(&input).await {
+        let spinner = Progress::spinner(&format!("Translating for {}...", active_provider));
+        if !spinner.is_enabled() {
+            println!("{} Translating for {}...", "🤖".blue(), active_provider);
+        }
+        let translation = run_until_ctrl_c(translator.translate_for_provider(&input, active_provider)).await;
+        spinner.finish();
+        let Some(translation) = translation else {
+            println!("{} Translation cancelled", "⏹️".yellow());
+            continue;
+        };
+
+        match translation {
             Ok(command) => {
-                let analysis = quality_analyzer.analyze(&command);
+                if cli.verbose && translator.last_translation().is_some_and(|last| last.no_relevant_docs) {
+                    println!("{} {}", "ℹ️".cyan(), NO_RELEVANT_DOCS_NOTE);
+                }
+                if let Some(decision) = translator.last_translation().filter(|_| cli.verbose).as_ref().and_then(format_rag_decision) {
+                    println!("{} RAG confidence: {}", "ℹ️".cyan(), decision);
+                }
+                if cli.verbose && translator.llm().answered_by().is_some_and(|index| index > 0) {
+                    println!("{} {}", "ℹ️".cyan(), FALLBACK_LLM_NOTE);
+                }
+                if cli.verbose {
+                    for source in translator.last_translation().map(|last| last.sources).unwrap_or_default() {
+                        println!(
+                            "{} Source: {} ({:.0}%)",
+                            "📄".cyan(),
+                            truncate_source_name(&source.name, cli.source_width).dimmed(),
+                            source.score * 100.0
+                        );
+                    }
+                }
+                let provider = providers::create_provider(active_provider);
+                let command = postprocess_command(&aliases.expand(&command), provider.as_ref());
+                let analysis = quality_analyzer.analyze_with_success_rate(
+                    &command,
+                    translator.get_command_success_rate(&command),
+                );
+                translator.update_last_translation(|last| {
+                    last.quality = Some(analysis.clone());
+                    last.learning_hints = learning_hints;
+                });
                 
                 println!("{} {}", "→".green(), command.bold());
                 
@@ -156,10 +1015,80 @@ async fn main() -> Result<()> {
                     }
                 }
 
-                if confirm_execution(&command).await? {
-                    let result = execute_command_with_provider(&command, Some(active_provider)).await?;
-                    
-                    if !result.success {
+                if let Some(warning) = provider.check_command_service(&command) {
+                    if cli.strict_validation {
+                        println!("{} {}", "❌".red(), CliError::ValidationFailed(warning));
+                        continue;
+                    }
+                    println!("{} {}", "⚠️".yellow(), warning);
+                }
+                if let Some(hint) = CostHint::for_command(active_provider, &command) {
+                    println!("{} {}", "💰".yellow(), hint);
+                }
+
+                let approved = match auto_execute_decision(&command, analysis.score, cli.auto_execute_threshold) {
+                    AutoExecuteDecision::Execute => {
+                        println!("{} Quality score {:.0}% - auto-executing", "⚡".cyan(), analysis.score * 100.0);
+                        Some(command.clone())
+                    }
+                    AutoExecuteDecision::Confirm(reason) => {
+                        if cli.auto_execute_threshold.is_some() {
+                            println!("{} Quality score {:.0}% - confirmation required: {}", "ℹ️".cyan(), analysis.score * 100.0, reason);
+                        }
+                        if interactive {
+                            confirm_or_edit(&command, &mut history, cli.confirm_default).await?
+                        } else {
+                            None
+                        }
+                    }
+                };
+
+                if let Some(approved) = approved {
+                    // Edits skipped the translator's checks, so validate them here
+                    if approved != command {
+                        let edited_provider = infer_provider_from_command(&approved).unwrap_or(active_provider);
+                        if let Err(e) = providers::create_provider(edited_provider).validate_command(&approved) {
+                            println!("{} {}", "❌".red(), CliError::ValidationFailed(e.to_string()));
+                            continue;
+                        }
+                    }
+                    // An edited suggestion is an implicit correction worth learning
+                    if learning_engine.record_edit(&input, &command, &approved).await? {
+                        println!("{} Learned your edit for next time", "📝".cyan());
+                    }
+                    let command = aliases.expand(&approved);
+
+                    let result = match execute_command_with_provider(&command, Some(active_provider), cli.output, cli.max_output_bytes, &runner).await {
+                        Ok(result) => result,
+                        Err(e @ CliError::ValidationFailed(_)) => {
+                            println!("{} {}", "❌".red(), e);
+                            continue;
+                        }
+                        Err(e) => return Err(e.into()),
+                    };
+                    translator.update_command_success(&command, result.success);
+                    translator.remember_turn(&input, &command, &result);
+                    let record = HistoryRecord::now(Some(active_provider), &input, &command, result.success);
+                    if let Err(e) = command_history.record(record).await {
+                        eprintln!("{} Failed to record history: {}", "⚠️".yellow(), e);
+                    }
+
+                    if result.success {
+                        if let Err(e) = learning_engine.record_success(&input, &command).await {
+                            eprintln!("{} Failed to record successful command: {}", "⚠️".yellow(), e);
+                        }
+                        if cli.summarize {
+                            match translator.summarize_output(&command, &result).await {
+                                Ok(Some(summary)) => println!("\n{} Summary:\n{}\n", "📋".cyan().bold(), summary),
+                                Ok(None) => {}
+                                Err(e) => eprintln!("{} Failed to summarize output: {}", "⚠️".yellow(), e),
+                            }
+                        }
+                    } else {
+                        if let Err(e) = learning_engine.record_failure(&input, &command).await {
+                            eprintln!("{} Failed to record failed command: {}", "⚠️".yellow(), e);
+                        }
+
                         // Get AI-powered recovery suggestion
                         println!("\n{} Getting AI suggestion for recovery...", "🤖".cyan());
                         
@@ -169,19 +1098,52 @@ async fn main() -> Result<()> {
                             "Command failed with no error message".to_string()
                         };
                         
-                        match translator.suggest_recovery(&input, &command, &error_msg).await {
+                        let suggestions = match translator.suggest_recovery(&input, &command, &error_msg).await {
                             Ok(suggestion) => {
                                 println!("\n{} AI Suggestion:", "💡".green().bold());
-                                println!("{}", suggestion);
-                                println!();
+                                if !interactive {
+                                    println!("{}\n", suggestion);
+                                }
+                                parse_retry_suggestions(&suggestion, &command)
                             }
                             Err(e) => {
                                 eprintln!("{} Failed to get AI suggestion: {}", "⚠️".yellow(), e);
+                                Vec::new()
+                            }
+                        };
+
+                        // Offer to retry with a suggestion or the user's own command
+                        let retry = if interactive { choose_retry(&suggestions).await? } else { None };
+                        if let Some(retry) = retry {
+                            let retry = aliases.expand(&retry);
+                            let retry_provider = infer_provider_from_command(&retry).unwrap_or(active_provider);
+                            if let Err(e) = providers::create_provider(retry_provider).validate_command(&retry) {
+                                println!("{} {}", "❌".red(), CliError::ValidationFailed(e.to_string()));
+                                continue;
+                            }
+                            let result = match execute_command_with_provider(&retry, Some(retry_provider), cli.output, cli.max_output_bytes, &runner).await {
+                                Ok(result) => result,
+                                Err(e @ CliError::ValidationFailed(_)) => {
+                                    println!("{} {}", "❌".red(), e);
+                                    continue;
+                                }
+                                Err(e) => return Err(e.into()),
+                            };
+                            translator.update_command_success(&retry, result.success);
+                            // Only a retry that worked, and that does what the failed
+                            // command meant to do, is worth learning as the correction
+                            let learn = result.success
+                                && (is_same_command_kind(&command, &retry) || confirm_learn_retry(&input, &retry).await?);
+                            if learn {
+                                match learning_engine
+                                    .add_correction(input.clone(), retry, Some(format!("Failed command: {}", command)))
+                                    .await
+                                {
+                                    Ok(()) => println!("{} Thanks! I'll remember this.", "✅".green()),
+                                    Err(e) => eprintln!("{} Failed to learn the correction: {}", "⚠️".yellow(), e),
+                                }
                             }
                         }
-                        
-                        // Still offer manual learning
-                        handle_learning(&input, &command, &mut learning_engine).await?;
                     }
                 }
             }
@@ -1,16 +1,28 @@
 //! AWS provider implementation for CUC
 
 use async_trait::async_trait;
-use crate::core::{CloudProvider, CloudProviderType, Result};
-use std::process::Command;
+use crate::core::{CloudProvider, CloudProviderType, CommandSafety, ParsedCommand, is_binary_on_path, CommandRunner, SystemRunner, ProviderCapabilities, Result, check_known_service};
 
 /// AWS provider
 pub struct AWSProvider {
     config: AWSConfig,
+    strict_validation: bool,
 }
 
+/// Top-level AWS services accepted by `validate_command`
+const AWS_SERVICES: &[&str] = &[
+    "accessanalyzer", "acm", "apigateway", "apigatewayv2", "appsync", "athena",
+    "autoscaling", "backup", "batch", "ce", "cloudformation", "cloudfront",
+    "cloudtrail", "cloudwatch", "codebuild", "codecommit", "codedeploy",
+    "codepipeline", "cognito-idp", "configure", "dynamodb", "ec2", "ecr", "ecs",
+    "efs", "eks", "elasticache", "elb", "elbv2", "emr", "events", "firehose",
+    "glue", "guardduty", "iam", "kinesis", "kms", "lambda", "logs", "organizations",
+    "rds", "redshift", "route53", "s3", "s3api", "secretsmanager", "ses", "sns",
+    "sqs", "ssm", "sso", "stepfunctions", "sts", "wafv2",
+];
+
 /// AWS configuration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct AWSConfig {
     /// AWS region (optional)
     pub region: Option<String>,
@@ -18,11 +30,13 @@ pub struct AWSConfig {
     pub profile: Option<String>,
 }
 
-impl Default for AWSConfig {
-    fn default() -> Self {
+impl AWSConfig {
+    /// Read `AWS_REGION` (or `AWS_DEFAULT_REGION`) and `AWS_PROFILE`, as the aws CLI does
+    pub fn from_env() -> Self {
+        let var = |name| std::env::var(name).ok().filter(|value: &String| !value.trim().is_empty());
         Self {
-            region: None,
-            profile: None,
+            region: var("AWS_REGION").or_else(|| var("AWS_DEFAULT_REGION")),
+            profile: var("AWS_PROFILE"),
         }
     }
 }
@@ -32,12 +46,22 @@ impl AWSProvider {
     pub fn new() -> Self {
         Self {
             config: AWSConfig::default(),
+            strict_validation: false,
         }
     }
 
     /// Create a new AWS provider with configuration
     pub fn with_config(config: AWSConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            strict_validation: false,
+        }
+    }
+
+    /// Reject commands with unknown services instead of only warning
+    pub fn with_strict_validation(mut self, strict: bool) -> Self {
+        self.strict_validation = strict;
+        self
     }
 }
 
@@ -54,20 +78,12 @@ impl CloudProvider for AWSProvider {
     }
 
     async fn is_cli_installed(&self) -> Result<bool> {
-        let output = Command::new("which")
-            .arg("aws")
-            .output();
-        
-        Ok(output.is_ok() && output.unwrap().status.success())
+        Ok(is_binary_on_path("aws"))
     }
 
     async fn is_authenticated(&self) -> Result<bool> {
-        let output = Command::new("aws")
-            .args(["sts", "get-caller-identity"])
-            .output();
-        
-        match output {
-            Ok(result) => Ok(result.status.success()),
+        match SystemRunner.run("aws", &["sts", "get-caller-identity"], &[]).await {
+            Ok(output) => Ok(output.success),
             Err(_) => Ok(false),
         }
     }
@@ -92,13 +108,37 @@ Common patterns:
 "#.to_string()
     }
 
-    fn validate_command(&self, command: &str) -> Result<()> {
-        if !command.starts_with("aws") {
+    fn validate(&self, command: &str) -> Result<CommandSafety> {
+        if ParsedCommand::parse(command)?.binary != "aws" {
             return Err(anyhow::anyhow!(
                 "Invalid AWS command: must start with 'aws'"
             ).into());
         }
-        Ok(())
+        if self.strict_validation
+            && let Some(warning) = self.check_command_service(command)
+        {
+            return Err(anyhow::anyhow!("Invalid AWS command: {}", warning).into());
+        }
+        Ok(CommandSafety::classify(command))
+    }
+
+    fn check_command_service(&self, command: &str) -> Option<String> {
+        check_known_service(command, "aws", AWS_SERVICES)
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities::new(self.provider_type(), AWS_SERVICES)
+    }
+
+    fn global_flags(&self) -> Vec<(String, String)> {
+        let mut flags = Vec::new();
+        if let Some(ref region) = self.config.region {
+            flags.push(("--region".to_string(), region.clone()));
+        }
+        if let Some(ref profile) = self.config.profile {
+            flags.push(("--profile".to_string(), profile.clone()));
+        }
+        flags
     }
 
     fn get_command_patterns(&self) -> Vec<String> {
@@ -129,6 +169,37 @@ mod tests {
         assert!(provider.validate_command("gcloud compute instances list").is_err());
     }
 
+    #[test]
+    fn test_validate_classifies_command() {
+        let provider = AWSProvider::new();
+        assert!(provider.validate("aws ec2 run-instances --image-id ami-123").unwrap().may_incur_charges());
+        assert!(provider.validate("aws s3 rb s3://bucket").unwrap().destructive);
+        assert!(provider.validate("gcloud compute instances create vm-1").is_err());
+    }
+
+    #[test]
+    fn test_validate_known_service() {
+        let provider = AWSProvider::new().with_strict_validation(true);
+        assert!(provider.validate_command("aws ec2 describe-instances").is_ok());
+        assert!(provider.check_command_service("aws s3 ls").is_none());
+    }
+
+    #[test]
+    fn test_validate_service_typo_suggestion() {
+        let provider = AWSProvider::new();
+        let warning = provider.check_command_service("aws lamda list-functions").unwrap();
+        assert!(warning.contains("did you mean 'lambda'"));
+        // Lenient by default
+        assert!(provider.validate_command("aws lamda list-functions").is_ok());
+        assert!(provider.validate_command("aws totally-made-up-service foo").is_ok());
+    }
+
+    #[test]
+    fn test_strict_validation_rejects_unknown_service() {
+        let provider = AWSProvider::new().with_strict_validation(true);
+        assert!(provider.validate_command("aws totally-made-up-service foo").is_err());
+    }
+
     #[test]
     fn test_get_rag_context() {
         let provider = AWSProvider::new();
@@ -1,8 +1,13 @@
 //! Azure provider implementation for CUC
 
 use async_trait::async_trait;
-use crate::core::{CloudProvider, CloudProviderType, Result};
-use std::process::Command;
+use crate::core::{CloudProvider, CloudProviderType, CommandSafety, ParsedCommand, is_binary_on_path, CommandRunner, SystemRunner, Result};
+
+/// Command groups that take neither `--subscription` nor `--resource-group`
+const NO_SCOPE_GROUPS: &[&str] = &["login", "logout", "account", "configure", "extension", "upgrade", "version", "find", "feedback"];
+
+/// Command groups that take `--subscription` but not `--resource-group`
+const NO_RESOURCE_GROUP_GROUPS: &[&str] = &["group", "provider", "ad", "role", "policy", "feature", "tag"];
 
 /// Azure provider
 pub struct AzureProvider {
@@ -10,7 +15,7 @@ pub struct AzureProvider {
 }
 
 /// Azure configuration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct AzureConfig {
     /// Azure subscription (optional)
     pub subscription: Option<String>,
@@ -18,11 +23,13 @@ pub struct AzureConfig {
     pub resource_group: Option<String>,
 }
 
-impl Default for AzureConfig {
-    fn default() -> Self {
+impl AzureConfig {
+    /// Read `AZURE_SUBSCRIPTION_ID` and the az CLI's `AZURE_DEFAULTS_GROUP`
+    pub fn from_env() -> Self {
+        let var = |name| std::env::var(name).ok().filter(|value: &String| !value.trim().is_empty());
         Self {
-            subscription: None,
-            resource_group: None,
+            subscription: var("AZURE_SUBSCRIPTION_ID"),
+            resource_group: var("AZURE_DEFAULTS_GROUP"),
         }
     }
 }
@@ -54,26 +61,24 @@ impl CloudProvider for AzureProvider {
     }
 
     async fn is_cli_installed(&self) -> Result<bool> {
-        let output = Command::new("which")
-            .arg("az")
-            .output();
-        
-        Ok(output.is_ok() && output.unwrap().status.success())
+        Ok(is_binary_on_path("az"))
     }
 
     async fn is_authenticated(&self) -> Result<bool> {
-        let output = Command::new("az")
-            .args(["account", "show"])
-            .output();
-        
-        match output {
-            Ok(result) => Ok(result.status.success()),
+        // With a configured subscription, it must be one the login can access
+        let mut args = vec!["account", "show"];
+        if let Some(ref subscription) = self.config.subscription {
+            args.extend(["--subscription", subscription.as_str()]);
+        }
+
+        match SystemRunner.run("az", &args, &[]).await {
+            Ok(output) => Ok(output.success),
             Err(_) => Ok(false),
         }
     }
 
     fn get_rag_context(&self) -> String {
-        r#"Azure CLI Commands:
+        let context = r#"Azure CLI Commands:
 - az login: Authenticate to Azure
 - az account: Manage subscriptions
 - az vm: Virtual machine management
@@ -90,16 +95,54 @@ Common patterns:
 - List resource groups: az group list
 - Create VM: az vm create
 - Create storage account: az storage account create
-"#.to_string()
+"#;
+
+        let mut context = context.to_string();
+        if let Some(ref subscription) = self.config.subscription {
+            context.push_str(&format!("\nConfigured subscription: {}\n", subscription));
+        }
+        if let Some(ref resource_group) = self.config.resource_group {
+            context.push_str(&format!("Configured resource group: {} (pass --resource-group)\n", resource_group));
+        }
+        context
     }
 
-    fn validate_command(&self, command: &str) -> Result<()> {
-        if !command.starts_with("az ") && command != "az" {
+    fn validate(&self, command: &str) -> Result<CommandSafety> {
+        if ParsedCommand::parse(command)?.binary != "az" {
             return Err(anyhow::anyhow!(
                 "Invalid Azure command: must start with 'az'"
             ).into());
         }
-        Ok(())
+        Ok(CommandSafety::classify(command))
+    }
+
+    fn global_flags(&self) -> Vec<(String, String)> {
+        let mut flags = Vec::new();
+        if let Some(ref subscription) = self.config.subscription {
+            flags.push(("--subscription".to_string(), subscription.clone()));
+        }
+        if let Some(ref resource_group) = self.config.resource_group {
+            flags.push(("--resource-group".to_string(), resource_group.clone()));
+        }
+        flags
+    }
+
+    fn flags_for_command(&self, command: &str) -> Vec<(String, String)> {
+        let Ok(parsed) = ParsedCommand::parse(command) else {
+            return Vec::new();
+        };
+        let group = parsed.subcommands.first().map(String::as_str).unwrap_or("");
+        if group.is_empty() || NO_SCOPE_GROUPS.contains(&group) {
+            return Vec::new();
+        }
+
+        self.global_flags()
+            .into_iter()
+            .filter(|(flag, _)| {
+                flag != "--resource-group"
+                    || !(NO_RESOURCE_GROUP_GROUPS.contains(&group) || parsed.has_flag("-g"))
+            })
+            .collect()
     }
 
     fn get_command_patterns(&self) -> Vec<String> {
@@ -148,6 +191,33 @@ mod tests {
         assert_eq!(provider.config.subscription, config.subscription);
     }
 
+    #[test]
+    fn test_flags_for_command() {
+        let provider = AzureProvider::with_config(AzureConfig {
+            subscription: Some("sub-123".to_string()),
+            resource_group: Some("rg-prod".to_string()),
+        });
+        let flag_names = |command| -> Vec<String> {
+            provider.flags_for_command(command).into_iter().map(|(flag, _)| flag).collect()
+        };
+
+        assert_eq!(flag_names("az vm list"), vec!["--subscription", "--resource-group"]);
+        assert_eq!(flag_names("az vm list -g other"), vec!["--subscription"]);
+        assert_eq!(flag_names("az group list"), vec!["--subscription"]);
+        assert!(flag_names("az account show").is_empty());
+        assert!(AzureProvider::new().flags_for_command("az vm list").is_empty());
+    }
+
+    #[test]
+    fn test_rag_context_mentions_configured_scope() {
+        let provider = AzureProvider::with_config(AzureConfig {
+            subscription: None,
+            resource_group: Some("rg-prod".to_string()),
+        });
+        assert!(provider.get_rag_context().contains("rg-prod"));
+        assert!(!AzureProvider::new().get_rag_context().contains("Configured"));
+    }
+
     #[test]
     fn test_command_patterns() {
         let provider = AzureProvider::new();
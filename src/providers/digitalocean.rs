@@ -0,0 +1,161 @@
+//! DigitalOcean provider implementation for CUC
+
+use async_trait::async_trait;
+use crate::core::{CloudProvider, CloudProviderType, CommandSafety, ParsedCommand, is_binary_on_path, CommandRunner, SystemRunner, Result};
+
+/// DigitalOcean provider
+pub struct DigitalOceanProvider {
+    config: DigitalOceanConfig,
+}
+
+/// DigitalOcean configuration
+#[derive(Debug, Clone, Default)]
+pub struct DigitalOceanConfig {
+    /// doctl auth context (optional)
+    pub context: Option<String>,
+    /// Default region slug (optional)
+    pub region: Option<String>,
+}
+
+impl DigitalOceanProvider {
+    /// Create a new DigitalOcean provider
+    pub fn new() -> Self {
+        Self {
+            config: DigitalOceanConfig::default(),
+        }
+    }
+
+    /// Create a new DigitalOcean provider with configuration
+    pub fn with_config(config: DigitalOceanConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Default for DigitalOceanProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl CloudProvider for DigitalOceanProvider {
+    fn provider_type(&self) -> CloudProviderType {
+        CloudProviderType::DigitalOcean
+    }
+
+    async fn is_cli_installed(&self) -> Result<bool> {
+        Ok(is_binary_on_path("doctl"))
+    }
+
+    async fn is_authenticated(&self) -> Result<bool> {
+        let mut args = vec!["account", "get"];
+        if let Some(ref context) = self.config.context {
+            args.extend(["--context", context.as_str()]);
+        }
+
+        match SystemRunner.run("doctl", &args, &[]).await {
+            Ok(output) => Ok(output.success),
+            Err(_) => Ok(false),
+        }
+    }
+
+    fn get_rag_context(&self) -> String {
+        r#"DigitalOcean doctl CLI Commands:
+- doctl auth init: Authenticate doctl with an API token
+- doctl account get: Show the current account
+- doctl compute droplet: Manage Droplets (virtual machines)
+- doctl compute volume: Manage block storage volumes
+- doctl kubernetes cluster: Manage DigitalOcean Kubernetes (DOKS) clusters
+- doctl apps: Manage App Platform applications
+- doctl databases: Manage managed databases
+- doctl registry: Manage the container registry
+
+Common patterns:
+- List Droplets: doctl compute droplet list
+- Create Droplet: doctl compute droplet create <name> --size s-1vcpu-1gb --image ubuntu-22-04-x64 --region nyc1
+- List Kubernetes clusters: doctl kubernetes cluster list
+- Get cluster credentials: doctl kubernetes cluster kubeconfig save <cluster>
+- List apps: doctl apps list
+"#.to_string()
+    }
+
+    fn validate(&self, command: &str) -> Result<CommandSafety> {
+        if ParsedCommand::parse(command)?.binary != "doctl" {
+            return Err(anyhow::anyhow!(
+                "Invalid DigitalOcean command: must start with 'doctl'"
+            ).into());
+        }
+        Ok(CommandSafety::classify(command))
+    }
+
+    fn get_command_patterns(&self) -> Vec<String> {
+        vec![
+            "doctl compute droplet list".to_string(),
+            "doctl compute volume list".to_string(),
+            "doctl kubernetes cluster list".to_string(),
+            "doctl apps list".to_string(),
+            "doctl databases list".to_string(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_provider_type() {
+        let provider = DigitalOceanProvider::new();
+        assert_eq!(provider.provider_type(), CloudProviderType::DigitalOcean);
+    }
+
+    #[test]
+    fn test_validate_command() {
+        let provider = DigitalOceanProvider::new();
+        assert!(provider.validate_command("doctl compute droplet list").is_ok());
+        assert!(provider.validate_command("doctlx compute droplet list").is_err());
+        assert!(provider.validate_command("aws s3 ls").is_err());
+    }
+
+    #[test]
+    fn test_get_rag_context() {
+        let provider = DigitalOceanProvider::new();
+        let context = provider.get_rag_context();
+        assert!(context.contains("doctl"));
+        assert!(context.contains("droplet"));
+    }
+
+    #[test]
+    fn test_default_provider() {
+        let provider = DigitalOceanProvider::default();
+        assert_eq!(provider.provider_type(), CloudProviderType::DigitalOcean);
+    }
+
+    #[test]
+    fn test_with_config() {
+        let config = DigitalOceanConfig {
+            context: Some("work".to_string()),
+            region: Some("nyc1".to_string()),
+        };
+        let provider = DigitalOceanProvider::with_config(config.clone());
+        assert_eq!(provider.config.context, config.context);
+        assert_eq!(provider.config.region, config.region);
+    }
+
+    #[test]
+    fn test_command_patterns() {
+        let provider = DigitalOceanProvider::new();
+        let patterns = provider.get_command_patterns();
+        assert!(patterns.iter().any(|p| p.contains("droplet")));
+        assert!(patterns.iter().any(|p| p.contains("kubernetes")));
+    }
+
+    #[test]
+    fn test_rag_context_keywords() {
+        let provider = DigitalOceanProvider::new();
+        let context = provider.get_rag_context();
+        assert!(context.contains("kubernetes"));
+        assert!(context.contains("apps"));
+        assert!(context.contains("Droplet"));
+    }
+}
@@ -1,8 +1,7 @@
 //! GCP provider implementation for CUC
 
 use async_trait::async_trait;
-use crate::core::{CloudProvider, CloudProviderType, Result};
-use std::process::Command;
+use crate::core::{CloudProvider, CloudProviderType, CommandSafety, ParsedCommand, is_binary_on_path, CommandRunner, SystemRunner, Result};
 
 /// GCP provider
 pub struct GCPProvider {
@@ -10,7 +9,7 @@ pub struct GCPProvider {
 }
 
 /// GCP configuration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct GCPConfig {
     /// GCP project (optional)
     pub project: Option<String>,
@@ -18,11 +17,13 @@ pub struct GCPConfig {
     pub region: Option<String>,
 }
 
-impl Default for GCPConfig {
-    fn default() -> Self {
+impl GCPConfig {
+    /// Read the gcloud overrides `CLOUDSDK_CORE_PROJECT` and `CLOUDSDK_COMPUTE_REGION`
+    pub fn from_env() -> Self {
+        let var = |name| std::env::var(name).ok().filter(|value: &String| !value.trim().is_empty());
         Self {
-            project: None,
-            region: None,
+            project: var("CLOUDSDK_CORE_PROJECT"),
+            region: var("CLOUDSDK_COMPUTE_REGION"),
         }
     }
 }
@@ -54,20 +55,12 @@ impl CloudProvider for GCPProvider {
     }
 
     async fn is_cli_installed(&self) -> Result<bool> {
-        let output = Command::new("which")
-            .arg("gcloud")
-            .output();
-        
-        Ok(output.is_ok() && output.unwrap().status.success())
+        Ok(is_binary_on_path("gcloud"))
     }
 
     async fn is_authenticated(&self) -> Result<bool> {
-        let output = Command::new("gcloud")
-            .args(["auth", "list"])
-            .output();
-        
-        match output {
-            Ok(result) => Ok(result.status.success()),
+        match SystemRunner.run("gcloud", &["auth", "list"], &[]).await {
+            Ok(output) => Ok(output.success),
             Err(_) => Ok(false),
         }
     }
@@ -92,13 +85,21 @@ Common patterns:
 "#.to_string()
     }
 
-    fn validate_command(&self, command: &str) -> Result<()> {
-        if !command.starts_with("gcloud") {
+    fn validate(&self, command: &str) -> Result<CommandSafety> {
+        if ParsedCommand::parse(command)?.binary != "gcloud" {
             return Err(anyhow::anyhow!(
                 "Invalid GCP command: must start with 'gcloud'"
             ).into());
         }
-        Ok(())
+        Ok(CommandSafety::classify(command))
+    }
+
+    fn global_flags(&self) -> Vec<(String, String)> {
+        self.config
+            .project
+            .iter()
+            .map(|project| ("--project".to_string(), project.clone()))
+            .collect()
     }
 
     fn get_command_patterns(&self) -> Vec<String> {
@@ -0,0 +1,168 @@
+//! GitHub provider implementation for CUC
+
+use async_trait::async_trait;
+use crate::core::{CloudProvider, CloudProviderType, CommandSafety, ParsedCommand, is_binary_on_path, CommandRunner, SystemRunner, Result};
+
+/// GitHub provider, driving the `gh` CLI
+pub struct GitHubProvider {
+    config: GitHubConfig,
+}
+
+/// GitHub configuration
+#[derive(Debug, Clone, Default)]
+pub struct GitHubConfig {
+    /// GitHub Enterprise Server host to check authentication against (optional)
+    pub hostname: Option<String>,
+}
+
+impl GitHubProvider {
+    /// Create a new GitHub provider
+    pub fn new() -> Self {
+        Self {
+            config: GitHubConfig::default(),
+        }
+    }
+
+    /// Create a new GitHub provider with configuration
+    pub fn with_config(config: GitHubConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Default for GitHubProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl CloudProvider for GitHubProvider {
+    fn provider_type(&self) -> CloudProviderType {
+        CloudProviderType::GitHub
+    }
+
+    async fn is_cli_installed(&self) -> Result<bool> {
+        Ok(is_binary_on_path("gh"))
+    }
+
+    async fn is_authenticated(&self) -> Result<bool> {
+        let mut args = vec!["auth", "status"];
+        if let Some(ref hostname) = self.config.hostname {
+            args.extend(["--hostname", hostname.as_str()]);
+        }
+
+        match SystemRunner.run("gh", &args, &[]).await {
+            Ok(output) => Ok(output.success),
+            Err(_) => Ok(false),
+        }
+    }
+
+    fn get_rag_context(&self) -> String {
+        r#"GitHub gh CLI Commands:
+- gh auth login: Authenticate gh with a GitHub host
+- gh repo: Create, clone, fork and view repositories
+- gh pr: Create, review, check out and merge pull requests
+- gh issue: Create, list and close issues
+- gh workflow: List, enable and run GitHub Actions workflows
+- gh run: View and rerun GitHub Actions workflow runs
+- gh release: Create and manage releases and their assets
+- gh secret: Manage GitHub Actions secrets
+
+Common patterns:
+- List open pull requests: gh pr list
+- Create a pull request: gh pr create --title <title> --body <body>
+- Check out a pull request: gh pr checkout <number>
+- Clone a repository: gh repo clone <owner>/<repo>
+- Trigger a workflow: gh workflow run <workflow> --ref <branch>
+- Watch the latest run: gh run watch
+- Create a release: gh release create <tag> --generate-notes
+"#.to_string()
+    }
+
+    fn validate(&self, command: &str) -> Result<CommandSafety> {
+        if ParsedCommand::parse(command)?.binary != "gh" {
+            return Err(anyhow::anyhow!(
+                "Invalid GitHub command: must start with 'gh'"
+            ).into());
+        }
+        Ok(CommandSafety::classify(command))
+    }
+
+    fn get_command_patterns(&self) -> Vec<String> {
+        vec![
+            "gh repo list".to_string(),
+            "gh pr list".to_string(),
+            "gh issue list".to_string(),
+            "gh workflow list".to_string(),
+            "gh run list".to_string(),
+            "gh release list".to_string(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_provider_type() {
+        let provider = GitHubProvider::new();
+        assert_eq!(provider.provider_type(), CloudProviderType::GitHub);
+    }
+
+    #[test]
+    fn test_validate_command() {
+        let provider = GitHubProvider::new();
+        assert!(provider.validate_command("gh pr list --state open").is_ok());
+        assert!(provider.validate_command("ghx pr list").is_err());
+        assert!(provider.validate_command("git push origin main").is_err());
+        assert!(provider.validate_command("aws s3 ls").is_err());
+    }
+
+    #[test]
+    fn test_validate_classifies_risk() {
+        let provider = GitHubProvider::new();
+        assert!(provider.validate("gh repo delete octo/old --yes").unwrap().destructive);
+        assert!(!provider.validate("gh pr view 42").unwrap().destructive);
+    }
+
+    #[test]
+    fn test_get_rag_context() {
+        let provider = GitHubProvider::new();
+        let context = provider.get_rag_context();
+        for group in ["gh repo", "gh pr", "gh workflow", "gh release"] {
+            assert!(context.contains(group), "{} missing", group);
+        }
+    }
+
+    #[test]
+    fn test_default_provider() {
+        let provider = GitHubProvider::default();
+        assert_eq!(provider.provider_type(), CloudProviderType::GitHub);
+    }
+
+    #[test]
+    fn test_with_config() {
+        let config = GitHubConfig {
+            hostname: Some("github.example.com".to_string()),
+        };
+        let provider = GitHubProvider::with_config(config.clone());
+        assert_eq!(provider.config.hostname, config.hostname);
+    }
+
+    #[test]
+    fn test_command_patterns() {
+        let provider = GitHubProvider::new();
+        let patterns = provider.get_command_patterns();
+        assert!(patterns.iter().any(|p| p.contains("pr")));
+        assert!(patterns.iter().any(|p| p.contains("workflow")));
+        assert!(provider.capabilities().services.contains(&"release".to_string()));
+    }
+
+    #[test]
+    fn test_list_services() {
+        let services = GitHubProvider::new().list_services();
+        assert!(services.iter().any(|(name, description)| name == "pr" && description.contains("pull requests")));
+        assert!(services.iter().any(|(name, _)| name == "release"));
+    }
+}
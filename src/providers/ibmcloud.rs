@@ -1,43 +1,144 @@
 //! IBM Cloud provider implementation for CUC
 
 use async_trait::async_trait;
-use crate::core::{CloudProvider, CloudProviderType, Result};
-use std::process::Command;
+use serde::Deserialize;
+use crate::core::{CloudProvider, CloudProviderType, CommandSafety, Error, ParsedCommand, is_binary_on_path, run_command_with_retry, CommandRunner, SystemRunner, CLI_RETRIES, CLI_BACKOFF, ProviderCapabilities, Result, check_known_service};
+use std::sync::{Arc, OnceLock};
 
 /// IBM Cloud provider
 pub struct IBMCloudProvider {
     config: IBMCloudConfig,
+    strict_validation: bool,
+    runner: Arc<dyn CommandRunner>,
 }
 
+/// Top-level IBM Cloud services accepted by `validate_command`
+const IBMCLOUD_COMMANDS: &[&str] = &[
+    "account", "api", "billing", "catalog", "ce", "cf", "cis", "code-engine",
+    "config", "cos", "cr", "dev", "dns", "help", "iam", "is", "ks", "kp", "login",
+    "logout", "oc", "pi", "plugin", "regions", "resource", "schematics", "sl",
+    "sm", "secrets-manager", "target", "tg", "update", "version",
+];
+
 /// IBM Cloud configuration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct IBMCloudConfig {
     /// API endpoint (optional)
     pub api_endpoint: Option<String>,
     /// Region (optional)
     pub region: Option<String>,
+    /// What `ibmcloud target` currently points at, if known
+    pub target: Option<TargetContext>,
 }
 
-impl Default for IBMCloudConfig {
-    fn default() -> Self {
-        Self {
-            api_endpoint: None,
-            region: None,
+/// The account, region and resource group the ibmcloud CLI is targeting
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TargetContext {
+    pub account: Option<String>,
+    pub region: Option<String>,
+    pub resource_group: Option<String>,
+}
+
+/// Shape of `ibmcloud target --output json`; untargeted parts are absent or empty
+#[derive(Deserialize)]
+struct TargetOutput {
+    #[serde(default)]
+    account: Option<NamedTarget>,
+    #[serde(default)]
+    region: Option<NamedTarget>,
+    #[serde(default)]
+    resource_group: Option<NamedTarget>,
+}
+
+#[derive(Deserialize)]
+struct NamedTarget {
+    #[serde(default)]
+    name: Option<String>,
+}
+
+impl TargetContext {
+    /// Parse the output of `ibmcloud target --output json`
+    pub fn parse(json: &str) -> Result<TargetContext> {
+        let output: TargetOutput = serde_json::from_str(json)
+            .map_err(|e| Error::Serialization(e.to_string()))?;
+        let name = |target: Option<NamedTarget>| {
+            target
+                .and_then(|target| target.name)
+                .filter(|name| !name.trim().is_empty())
+        };
+
+        Ok(TargetContext {
+            account: name(output.account),
+            region: name(output.region),
+            resource_group: name(output.resource_group),
+        })
+    }
+
+    /// Prompt lines describing the target, empty when nothing is targeted
+    pub fn describe(&self) -> String {
+        let mut lines = String::new();
+        if let Some(ref account) = self.account {
+            lines.push_str(&format!("Targeted account: {}\n", account));
+        }
+        if let Some(ref region) = self.region {
+            lines.push_str(&format!("Targeted region: {} (only pass -r for a different region)\n", region));
         }
+        if let Some(ref resource_group) = self.resource_group {
+            lines.push_str(&format!("Targeted resource group: {} (only pass -g for a different group)\n", resource_group));
+        }
+        lines
     }
 }
 
+/// Run `ibmcloud target --output json` on first use and cache the result
+///
+/// `None` when the CLI is missing, not logged in, or prints something
+/// unparseable; the lookup is not retried in the same session.
+pub fn current_target() -> Option<TargetContext> {
+    static TARGET: OnceLock<Option<TargetContext>> = OnceLock::new();
+
+    TARGET
+        .get_or_init(|| {
+            if !is_binary_on_path("ibmcloud") {
+                return None;
+            }
+            let output = run_command_with_retry("ibmcloud", &["target", "--output", "json"], CLI_RETRIES, CLI_BACKOFF)
+                .ok()
+                .filter(|output| output.status.success())?;
+            TargetContext::parse(&String::from_utf8_lossy(&output.stdout)).ok()
+        })
+        .clone()
+}
+
 impl IBMCloudProvider {
     /// Create a new IBM Cloud provider
     pub fn new() -> Self {
         Self {
             config: IBMCloudConfig::default(),
+            strict_validation: false,
+            runner: Arc::new(SystemRunner),
         }
     }
 
     /// Create a new IBM Cloud provider with configuration
     pub fn with_config(config: IBMCloudConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            strict_validation: false,
+            runner: Arc::new(SystemRunner),
+        }
+    }
+
+    /// Reject commands with unknown services instead of only warning
+    pub fn with_strict_validation(mut self, strict: bool) -> Self {
+        self.strict_validation = strict;
+        self
+    }
+
+    /// Run status checks through `runner` instead of spawning `ibmcloud` directly
+    pub fn with_runner(mut self, runner: Arc<dyn CommandRunner>) -> Self {
+        self.runner = runner;
+        self
     }
 }
 
@@ -54,26 +155,18 @@ impl CloudProvider for IBMCloudProvider {
     }
 
     async fn is_cli_installed(&self) -> Result<bool> {
-        let output = Command::new("which")
-            .arg("ibmcloud")
-            .output();
-        
-        Ok(output.is_ok() && output.unwrap().status.success())
+        Ok(is_binary_on_path("ibmcloud"))
     }
 
     async fn is_authenticated(&self) -> Result<bool> {
-        let output = Command::new("ibmcloud")
-            .args(["target"])
-            .output();
-        
-        match output {
-            Ok(result) => Ok(result.status.success()),
+        match self.runner.run("ibmcloud", &["target"], &[]).await {
+            Ok(output) => Ok(output.success),
             Err(_) => Ok(false),
         }
     }
 
     fn get_rag_context(&self) -> String {
-        r#"IBM Cloud CLI Commands:
+        let context = r#"IBM Cloud CLI Commands:
 - ibmcloud login: Authenticate to IBM Cloud
 - ibmcloud target: Set or view target account, region, resource group
 - ibmcloud resource: Manage resources (service-instances, groups, etc.)
@@ -88,16 +181,39 @@ Common patterns:
 - Target resource group: ibmcloud target -g <group>
 - List clusters: ibmcloud ks clusters
 - Code Engine apps: ibmcloud ce application list
-"#.to_string()
+"#;
+
+        let mut context = context.to_string();
+        if let Some(ref target) = self.config.target {
+            let target = target.describe();
+            if !target.is_empty() {
+                context.push('\n');
+                context.push_str(&target);
+            }
+        }
+        context
     }
 
-    fn validate_command(&self, command: &str) -> Result<()> {
-        if !command.starts_with("ibmcloud") {
+    fn validate(&self, command: &str) -> Result<CommandSafety> {
+        if ParsedCommand::parse(command)?.binary != "ibmcloud" {
             return Err(anyhow::anyhow!(
                 "Invalid IBM Cloud command: must start with 'ibmcloud'"
             ).into());
         }
-        Ok(())
+        if self.strict_validation
+            && let Some(warning) = self.check_command_service(command)
+        {
+            return Err(anyhow::anyhow!("Invalid IBM Cloud command: {}", warning).into());
+        }
+        Ok(CommandSafety::classify(command))
+    }
+
+    fn check_command_service(&self, command: &str) -> Option<String> {
+        check_known_service(command, "ibmcloud", IBMCLOUD_COMMANDS)
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities::new(self.provider_type(), IBMCLOUD_COMMANDS)
     }
 
     fn get_command_patterns(&self) -> Vec<String> {
@@ -130,6 +246,24 @@ mod tests {
         assert!(provider.validate_command("aws s3 ls").is_err());
     }
 
+    #[test]
+    fn test_validate_known_command() {
+        let provider = IBMCloudProvider::new().with_strict_validation(true);
+        assert!(provider.validate_command("ibmcloud resource groups").is_ok());
+        assert!(provider.validate_command("ibmcloud ks clusters").is_ok());
+    }
+
+    #[test]
+    fn test_validate_command_typo_suggestion() {
+        let provider = IBMCloudProvider::new();
+        let warning = provider.check_command_service("ibmcloud resorce groups").unwrap();
+        assert!(warning.contains("did you mean 'resource'"));
+        assert!(provider.validate_command("ibmcloud resorce groups").is_ok());
+
+        let strict = IBMCloudProvider::new().with_strict_validation(true);
+        assert!(strict.validate_command("ibmcloud resorce groups").is_err());
+    }
+
     #[test]
     fn test_get_rag_context() {
         let provider = IBMCloudProvider::new();
@@ -149,6 +283,7 @@ mod tests {
         let config = IBMCloudConfig {
             api_endpoint: Some("https://cloud.ibm.com".to_string()),
             region: Some("us-south".to_string()),
+            target: None,
         };
         let provider = IBMCloudProvider::with_config(config.clone());
         assert_eq!(provider.config.region, config.region);
@@ -178,4 +313,54 @@ mod tests {
         assert!(context.contains("Kubernetes") || context.contains("ks"));
         assert!(context.contains("Code Engine"));
     }
+
+    const TARGET_JSON: &str = r#"{
+        "api_endpoint": "https://cloud.ibm.com",
+        "region": {"mccp_id": "us-south", "name": "us-south"},
+        "user": {"display_name": "Dev User", "user_email": "dev@example.com"},
+        "account": {"guid": "abc123", "name": "Dev Account", "owner": "dev@example.com"},
+        "resource_group": {"guid": "def456", "name": "team-rg", "state": "ACTIVE", "default": false}
+    }"#;
+
+    #[test]
+    fn test_parse_target_context() {
+        let target = TargetContext::parse(TARGET_JSON).unwrap();
+        assert_eq!(target, TargetContext {
+            account: Some("Dev Account".to_string()),
+            region: Some("us-south".to_string()),
+            resource_group: Some("team-rg".to_string()),
+        });
+
+        // Nothing targeted yet
+        let untargeted = TargetContext::parse(r#"{"api_endpoint": "https://cloud.ibm.com", "region": {}, "resource_group": {"name": ""}}"#).unwrap();
+        assert_eq!(untargeted, TargetContext::default());
+
+        assert!(TargetContext::parse("Not logged in").is_err());
+    }
+
+    #[test]
+    fn test_rag_context_mentions_target() {
+        let provider = IBMCloudProvider::with_config(IBMCloudConfig {
+            target: Some(TargetContext::parse(TARGET_JSON).unwrap()),
+            ..Default::default()
+        });
+        let context = provider.get_rag_context();
+        assert!(context.contains("Targeted region: us-south"));
+        assert!(context.contains("Targeted resource group: team-rg"));
+
+        assert!(!IBMCloudProvider::new().get_rag_context().contains("Targeted"));
+    }
+
+    #[tokio::test]
+    async fn test_is_authenticated_uses_runner() {
+        use crate::core::{MockRunner, RunOutput};
+
+        let logged_in = Arc::new(MockRunner::new(|_, _| Ok(RunOutput { success: true, ..Default::default() })));
+        let provider = IBMCloudProvider::new().with_runner(logged_in.clone());
+        assert!(provider.is_authenticated().await.unwrap());
+        assert_eq!(logged_in.calls(), vec!["ibmcloud target"]);
+
+        let missing = Arc::new(MockRunner::new(|_, _| Err(std::io::ErrorKind::NotFound.into())));
+        assert!(!IBMCloudProvider::new().with_runner(missing).is_authenticated().await.unwrap());
+    }
 }
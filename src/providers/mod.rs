@@ -5,10 +5,104 @@ pub mod azure;
 pub mod gcp;
 pub mod ibmcloud;
 pub mod vmware;
+pub mod digitalocean;
+pub mod github;
+pub mod terraform;
 
 pub use aws::AWSProvider;
 pub use azure::AzureProvider;
 pub use gcp::GCPProvider;
 pub use ibmcloud::IBMCloudProvider;
 pub use vmware::VMwareProvider;
+pub use digitalocean::DigitalOceanProvider;
+pub use github::GitHubProvider;
+pub use terraform::TerraformProvider;
 
+use crate::core::{CloudProvider, CloudProviderType, ProviderCapabilities};
+
+/// Capability manifests of every supported provider, for machine-readable listings
+pub fn capability_manifests() -> Vec<ProviderCapabilities> {
+    CloudProviderType::all()
+        .into_iter()
+        .map(|provider| create_provider(provider).capabilities())
+        .collect()
+}
+
+/// Create the provider implementation for a provider type
+///
+/// Nothing here spawns a CLI; the IBM Cloud target is looked up separately,
+/// only when translating.
+pub fn create_provider(provider_type: CloudProviderType) -> Box<dyn CloudProvider> {
+    match provider_type {
+        CloudProviderType::IBMCloud => Box::new(IBMCloudProvider::new()),
+        CloudProviderType::AWS => Box::new(AWSProvider::with_config(aws::AWSConfig::from_env())),
+        CloudProviderType::GCP => Box::new(GCPProvider::with_config(gcp::GCPConfig::from_env())),
+        CloudProviderType::Azure => Box::new(AzureProvider::with_config(azure::AzureConfig::from_env())),
+        CloudProviderType::VMware => Box::new(VMwareProvider::new()),
+        CloudProviderType::DigitalOcean => Box::new(DigitalOceanProvider::new()),
+        CloudProviderType::GitHub => Box::new(GitHubProvider::new()),
+        CloudProviderType::Terraform => Box::new(TerraformProvider::new()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capability_manifests_serialize() {
+        let manifests = capability_manifests();
+        assert_eq!(manifests.len(), CloudProviderType::all().len());
+
+        let json: Vec<serde_json::Value> = serde_json::from_str(&serde_json::to_string(&manifests).unwrap()).unwrap();
+        let yaml: Vec<serde_json::Value> = serde_yaml::from_str(&serde_yaml::to_string(&manifests).unwrap()).unwrap();
+        assert_eq!(json, yaml);
+        for provider in CloudProviderType::all() {
+            assert!(json.iter().any(|entry| entry["cli_binary"] == provider.cli_command()), "{} missing", provider);
+        }
+    }
+
+    #[test]
+    fn test_capabilities_for_all_providers() {
+        for provider_type in CloudProviderType::all() {
+            let capabilities = create_provider(provider_type).capabilities();
+            assert_eq!(capabilities.provider, provider_type);
+            assert_eq!(capabilities.cli_binary, provider_type.cli_command());
+            assert!(!capabilities.services.is_empty(), "{} has no services", provider_type);
+            assert!(capabilities.auth_check_command.starts_with(provider_type.cli_command()));
+        }
+    }
+
+    #[test]
+    fn test_list_services_for_all_providers() {
+        for provider_type in CloudProviderType::all() {
+            let services = create_provider(provider_type).list_services();
+            assert!(!services.is_empty(), "{} has no services", provider_type);
+
+            let mut names: Vec<&str> = services.iter().map(|(name, _)| name.as_str()).collect();
+            names.sort();
+            names.dedup();
+            assert_eq!(names.len(), services.len(), "{} lists duplicate services", provider_type);
+            assert!(services.iter().all(|(_, description)| !description.is_empty()));
+        }
+    }
+
+    #[test]
+    fn test_list_services_known_entries() {
+        let aws = create_provider(CloudProviderType::AWS).list_services();
+        assert!(aws.iter().any(|(name, _)| name == "ec2"));
+        assert!(aws.iter().any(|(name, _)| name == "s3"));
+
+        let azure = create_provider(CloudProviderType::Azure).list_services();
+        assert!(azure.iter().any(|(name, description)| name == "aks" && description.contains("Kubernetes")));
+    }
+
+    #[test]
+    fn test_capabilities_serialize() {
+        let capabilities = create_provider(CloudProviderType::GCP).capabilities();
+        let json = serde_json::to_value(&capabilities).unwrap();
+        assert_eq!(json["cli_binary"], "gcloud");
+        assert_eq!(json["json_output_flag"], "--format=json");
+        assert!(json["services"].as_array().unwrap().iter().any(|s| s == "compute"));
+    }
+}
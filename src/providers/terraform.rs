@@ -0,0 +1,134 @@
+//! Terraform provider implementation for CUC
+//!
+//! Terraform is not a cloud, but "plan my changes" style requests translate
+//! to its CLI the same way cloud requests do. Credentials belong to the
+//! cloud providers a configuration uses, so there is no login to check.
+
+use async_trait::async_trait;
+use crate::core::{CloudProvider, CloudProviderType, CommandSafety, ParsedCommand, is_binary_on_path, Result};
+
+/// Terraform provider
+pub struct TerraformProvider;
+
+impl TerraformProvider {
+    /// Create a new Terraform provider
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for TerraformProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl CloudProvider for TerraformProvider {
+    fn provider_type(&self) -> CloudProviderType {
+        CloudProviderType::Terraform
+    }
+
+    async fn is_cli_installed(&self) -> Result<bool> {
+        Ok(is_binary_on_path("terraform"))
+    }
+
+    async fn is_authenticated(&self) -> Result<bool> {
+        // Each configured cloud provider authenticates on its own
+        Ok(true)
+    }
+
+    fn get_rag_context(&self) -> String {
+        r#"Terraform CLI Commands:
+- terraform init: Initialize a working directory, downloading providers and modules
+- terraform plan: Show the changes needed to match the configuration
+- terraform apply: Create, update or destroy infrastructure to match the configuration
+- terraform destroy: Destroy all infrastructure managed by the configuration
+- terraform state: Inspect and modify the state, e.g. list, show, mv, rm
+- terraform validate: Check that the configuration is valid
+- terraform fmt: Rewrite configuration files to the canonical format
+- terraform output: Show output values from the state
+
+Common patterns:
+- Preview changes: terraform plan
+- Save a plan: terraform plan -out=tfplan
+- Apply a saved plan: terraform apply tfplan
+- List managed resources: terraform state list
+- Show one resource: terraform state show <address>
+- Preview a destroy: terraform plan -destroy
+"#.to_string()
+    }
+
+    fn validate(&self, command: &str) -> Result<CommandSafety> {
+        if ParsedCommand::parse(command)?.binary != "terraform" {
+            return Err(anyhow::anyhow!(
+                "Invalid Terraform command: must start with 'terraform'"
+            ).into());
+        }
+        Ok(CommandSafety::classify(command))
+    }
+
+    fn get_command_patterns(&self) -> Vec<String> {
+        vec![
+            "terraform init".to_string(),
+            "terraform plan".to_string(),
+            "terraform validate".to_string(),
+            "terraform state list".to_string(),
+            "terraform output".to_string(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_provider_type() {
+        let provider = TerraformProvider::new();
+        assert_eq!(provider.provider_type(), CloudProviderType::Terraform);
+        assert!(provider.is_authenticated().await.unwrap());
+    }
+
+    #[test]
+    fn test_validate_command() {
+        let provider = TerraformProvider::new();
+        assert!(provider.validate_command("terraform plan -out=tfplan").is_ok());
+        assert!(provider.validate_command("terraformx plan").is_err());
+        assert!(provider.validate_command("tf plan").is_err());
+        assert!(provider.validate_command("aws s3 ls").is_err());
+    }
+
+    #[test]
+    fn test_apply_and_destroy_are_destructive() {
+        let provider = TerraformProvider::new();
+        assert!(provider.validate("terraform apply").unwrap().destructive);
+        assert!(provider.validate("terraform apply tfplan").unwrap().destructive);
+        assert!(provider.validate("terraform destroy").unwrap().destructive);
+        assert!(!provider.validate("terraform plan").unwrap().destructive);
+        assert!(!provider.validate("terraform state list").unwrap().destructive);
+    }
+
+    #[test]
+    fn test_get_rag_context() {
+        let provider = TerraformProvider::new();
+        let context = provider.get_rag_context();
+        for subcommand in ["init", "plan", "apply", "destroy", "state"] {
+            assert!(context.contains(&format!("- terraform {}:", subcommand)), "{} missing", subcommand);
+        }
+    }
+
+    #[test]
+    fn test_default_provider() {
+        let provider = TerraformProvider;
+        assert_eq!(provider.provider_type(), CloudProviderType::Terraform);
+    }
+
+    #[test]
+    fn test_command_patterns() {
+        let provider = TerraformProvider::new();
+        let patterns = provider.get_command_patterns();
+        assert!(patterns.iter().any(|p| p == "terraform plan"));
+        assert!(provider.capabilities().services.contains(&"state".to_string()));
+    }
+}
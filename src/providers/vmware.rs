@@ -1,7 +1,7 @@
 //! VMware vSphere provider implementation for CUC
 
 use async_trait::async_trait;
-use crate::core::{CloudProvider, CloudProviderType, Result};
+use crate::core::{CloudProvider, CloudProviderType, CommandSafety, ParsedCommand, CommandRunner, SystemRunner, Result};
 use std::process::Command;
 
 /// VMware vSphere provider
@@ -10,7 +10,7 @@ pub struct VMwareProvider {
 }
 
 /// VMware configuration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct VMwareConfig {
     /// vCenter URL (optional)
     pub vcenter_url: Option<String>,
@@ -18,15 +18,6 @@ pub struct VMwareConfig {
     pub username: Option<String>,
 }
 
-impl Default for VMwareConfig {
-    fn default() -> Self {
-        Self {
-            vcenter_url: None,
-            username: None,
-        }
-    }
-}
-
 impl VMwareProvider {
     /// Create a new VMware provider
     pub fn new() -> Self {
@@ -39,6 +30,30 @@ impl VMwareProvider {
     pub fn with_config(config: VMwareConfig) -> Self {
         Self { config }
     }
+
+    /// Environment variables passed to govc, built from the configuration
+    ///
+    /// The password is never stored in the config and is read from `GOVC_PASSWORD`.
+    fn govc_env(&self) -> Vec<(&'static str, String)> {
+        let mut vars = Vec::new();
+        if let Some(ref url) = self.config.vcenter_url {
+            vars.push(("GOVC_URL", url.clone()));
+        }
+        if let Some(ref username) = self.config.username {
+            vars.push(("GOVC_USERNAME", username.clone()));
+        }
+        if let Ok(password) = std::env::var("GOVC_PASSWORD") {
+            vars.push(("GOVC_PASSWORD", password));
+        }
+        vars
+    }
+
+    /// Build a govc invocation with the configured environment
+    fn govc_command(&self, args: &[&str]) -> Command {
+        let mut command = Command::new("govc");
+        command.args(args).envs(self.govc_env());
+        command
+    }
 }
 
 impl Default for VMwareProvider {
@@ -54,20 +69,17 @@ impl CloudProvider for VMwareProvider {
     }
 
     async fn is_cli_installed(&self) -> Result<bool> {
-        let output = Command::new("which")
-            .arg("govc")
-            .output();
+        let output = self.govc_command(&["version"]).output();
         
         Ok(output.is_ok() && output.unwrap().status.success())
     }
 
     async fn is_authenticated(&self) -> Result<bool> {
-        let output = Command::new("govc")
-            .args(["about"])
-            .output();
-        
-        match output {
-            Ok(result) => Ok(result.status.success()),
+        let env = self.govc_env();
+        let env: Vec<(&str, &str)> = env.iter().map(|(name, value)| (*name, value.as_str())).collect();
+
+        match SystemRunner.run("govc", &["about"], &env).await {
+            Ok(output) => Ok(output.success),
             Err(_) => Ok(false),
         }
     }
@@ -93,13 +105,13 @@ Common patterns:
 "#.to_string()
     }
 
-    fn validate_command(&self, command: &str) -> Result<()> {
-        if !command.starts_with("govc") {
+    fn validate(&self, command: &str) -> Result<CommandSafety> {
+        if ParsedCommand::parse(command)?.binary != "govc" {
             return Err(anyhow::anyhow!(
                 "Invalid VMware command: must start with 'govc'"
             ).into());
         }
-        Ok(())
+        Ok(CommandSafety::classify(command))
     }
 
     fn get_command_patterns(&self) -> Vec<String> {
@@ -148,6 +160,42 @@ mod tests {
         assert_eq!(provider.config.vcenter_url, config.vcenter_url);
     }
 
+    #[test]
+    fn test_govc_command_env_from_config() {
+        let config = VMwareConfig {
+            vcenter_url: Some("https://vcenter.example.com/sdk".to_string()),
+            username: Some("administrator@vsphere.local".to_string()),
+        };
+        let provider = VMwareProvider::with_config(config);
+        let command = provider.govc_command(&["about"]);
+
+        let envs: Vec<(String, Option<String>)> = command
+            .get_envs()
+            .map(|(k, v)| (
+                k.to_string_lossy().to_string(),
+                v.map(|v| v.to_string_lossy().to_string()),
+            ))
+            .collect();
+
+        assert!(envs.contains(&(
+            "GOVC_URL".to_string(),
+            Some("https://vcenter.example.com/sdk".to_string())
+        )));
+        assert!(envs.contains(&(
+            "GOVC_USERNAME".to_string(),
+            Some("administrator@vsphere.local".to_string())
+        )));
+        assert_eq!(command.get_program(), "govc");
+        assert_eq!(command.get_args().collect::<Vec<_>>(), vec!["about"]);
+    }
+
+    #[test]
+    fn test_govc_command_env_empty_config() {
+        let provider = VMwareProvider::new();
+        let command = provider.govc_command(&["about"]);
+        assert!(!command.get_envs().any(|(k, _)| k == "GOVC_URL" || k == "GOVC_USERNAME"));
+    }
+
     #[test]
     fn test_command_patterns() {
         let provider = VMwareProvider::new();
@@ -1,12 +1,13 @@
 //! Document indexer implementations
 
 use async_trait::async_trait;
+use scraper::{ElementRef, Html, Selector};
 use serde_json::json;
 use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::core::{
-    DocumentIndexer, Document, IndexingResult, IndexingConfig,
+    DocumentIndexer, Document, IndexingResult, IndexingConfig, ChunkConfig,
     VectorStore, VectorDocument,
     Error, Result,
 };
@@ -33,33 +34,12 @@ impl<V: VectorStore> LocalDocumentIndexer<V> {
             config,
         }
     }
-
-    /// Chunk a document into smaller pieces
-    fn chunk_document(&self, content: &str) -> Vec<String> {
-        let mut chunks = Vec::new();
-        let chars: Vec<char> = content.chars().collect();
-        let mut start = 0;
-
-        while start < chars.len() {
-            let end = (start + self.config.chunk_size).min(chars.len());
-            let chunk: String = chars[start..end].iter().collect();
-            chunks.push(chunk);
-
-            if end >= chars.len() {
-                break;
-            }
-
-            start = end - self.config.chunk_overlap;
-        }
-
-        chunks
-    }
 }
 
 #[async_trait]
 impl<V: VectorStore + 'static> DocumentIndexer for LocalDocumentIndexer<V> {
     async fn index_document(&self, document: Document) -> Result<IndexingResult> {
-        let chunks = self.chunk_document(&document.content);
+        let chunks = split_text_into_chunks(&document.content, &self.config.chunking);
         let mut documents_indexed = 0;
         let mut documents_failed = 0;
         let mut errors = Vec::new();
@@ -73,6 +53,11 @@ impl<V: VectorStore + 'static> DocumentIndexer for LocalDocumentIndexer<V> {
             if let Some(ref url) = document.url {
                 metadata["url"] = json!(url);
             }
+            if let Some(provider) = self.config.provider
+                && metadata.get("provider").is_none()
+            {
+                metadata["provider"] = json!(provider.id());
+            }
 
             let vector_doc = VectorDocument {
                 id: chunk_id,
@@ -158,7 +143,7 @@ impl<V: VectorStore + 'static> DocumentIndexer for LocalDocumentIndexer<V> {
     async fn index_from_file(&self, path: &str) -> Result<IndexingResult> {
         let content = tokio::fs::read_to_string(path)
             .await
-            .map_err(|e| Error::Io(e))?;
+            .map_err(Error::Io)?;
 
         let document = Document {
             id: Uuid::new_v4().to_string(),
@@ -178,12 +163,54 @@ impl<V: VectorStore + 'static> DocumentIndexer for LocalDocumentIndexer<V> {
         let count = self.vector_store.count().await?;
         Ok(json!({
             "total_documents": count,
-            "chunk_size": self.config.chunk_size,
-            "chunk_overlap": self.config.chunk_overlap,
+            "chunk_target_size": self.config.chunking.target_size,
+            "chunk_max_size": self.config.chunking.max_size,
+            "chunk_overlap": self.config.chunking.overlap,
         }))
     }
 }
 
+/// Split text into overlapping chunks of at most `config.max_size` characters
+///
+/// Each chunk ends at the first whitespace after `target_size`, or is cut at
+/// `max_size` when there is none, so words are rarely split. The next chunk
+/// starts `overlap` characters before the previous one ended.
+pub fn split_text_into_chunks(content: &str, config: &ChunkConfig) -> Vec<String> {
+    let chars: Vec<char> = content.chars().collect();
+    let max_size = config.max_size.max(1);
+    let target_size = config.target_size.clamp(1, max_size);
+    let mut chunks = Vec::new();
+    let mut start = 0;
+
+    while start < chars.len() {
+        let hard_end = (start + max_size).min(chars.len());
+        let end = if hard_end == chars.len() {
+            hard_end
+        } else {
+            create_chunk_end(&chars, start + target_size, hard_end)
+        };
+
+        chunks.push(chars[start..end].iter().collect());
+        if end == chars.len() {
+            break;
+        }
+
+        // Always move forward, even when the overlap covers the whole chunk
+        start = end.saturating_sub(config.overlap).max(start + 1);
+    }
+
+    chunks
+}
+
+/// Pick where a chunk ends: just after the first whitespace in `target..hard_end`
+fn create_chunk_end(chars: &[char], target: usize, hard_end: usize) -> usize {
+    chars[target..hard_end]
+        .iter()
+        .position(|c| c.is_whitespace())
+        .map(|offset| target + offset + 1)
+        .unwrap_or(hard_end)
+}
+
 /// Web document indexer with scraping capabilities
 pub struct WebDocumentIndexer<V: VectorStore> {
     local_indexer: LocalDocumentIndexer<V>,
@@ -195,6 +222,13 @@ impl<V: VectorStore> WebDocumentIndexer<V> {
             local_indexer: LocalDocumentIndexer::new(vector_store),
         }
     }
+
+    /// Create with custom configuration
+    pub fn with_config(vector_store: Arc<V>, config: IndexingConfig) -> Self {
+        Self {
+            local_indexer: LocalDocumentIndexer::with_config(vector_store, config),
+        }
+    }
 }
 
 #[async_trait]
@@ -208,13 +242,49 @@ impl<V: VectorStore + 'static> DocumentIndexer for WebDocumentIndexer<V> {
     }
 
     async fn index_from_url(&self, url: &str) -> Result<IndexingResult> {
-        // TODO: Implement web scraping with scraper crate
-        // For now, delegate to local indexer
-        self.local_indexer.index_from_url(url).await
+        let html = reqwest::get(url)
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| Error::Network(format!("Failed to fetch {}: {}", url, e)))?
+            .text()
+            .await
+            .map_err(|e| Error::Network(format!("Failed to read {}: {}", url, e)))?;
+
+        let (title, content) = extract_page_text(&html);
+        if content.is_empty() {
+            return Err(Error::DocumentIndexer(format!("No text content found at {}", url)));
+        }
+
+        let document = Document {
+            id: format!("web_{:x}", md5::compute(url.as_bytes())),
+            title: title.unwrap_or_else(|| url.to_string()),
+            content,
+            url: Some(url.to_string()),
+            metadata: json!({
+                "source": "web",
+                "type": "documentation",
+            }),
+        };
+
+        self.local_indexer.index_document(document).await
     }
 
     async fn index_from_urls(&self, urls: Vec<String>) -> Result<IndexingResult> {
-        self.local_indexer.index_from_urls(urls).await
+        let mut total = IndexingResult { documents_indexed: 0, documents_failed: 0, errors: Vec::new() };
+        for url in urls {
+            match self.index_from_url(&url).await {
+                Ok(result) => {
+                    total.documents_indexed += result.documents_indexed;
+                    total.documents_failed += result.documents_failed;
+                    total.errors.extend(result.errors);
+                }
+                Err(e) => {
+                    total.documents_failed += 1;
+                    total.errors.push(format!("Failed to index URL {}: {}", url, e));
+                }
+            }
+        }
+        Ok(total)
     }
 
     async fn index_from_file(&self, path: &str) -> Result<IndexingResult> {
@@ -226,10 +296,72 @@ impl<V: VectorStore + 'static> DocumentIndexer for WebDocumentIndexer<V> {
     }
 }
 
+/// Regions that hold a page's main content, in order of preference
+const MAIN_CONTENT_SELECTORS: &[&str] = &["main", "article", "[role=main]"];
+
+/// Elements inside the main content that are never documentation
+const BOILERPLATE_ELEMENTS: &[&str] = &["nav", "footer", "aside", "script", "style", "noscript"];
+
+/// Extract the page title and documentation text from an HTML document
+///
+/// Text comes from the page's main content region with navigation, footers
+/// and scripts removed. Pages without such a region fall back to the whole
+/// body text.
+fn extract_page_text(html: &str) -> (Option<String>, String) {
+    let document = Html::parse_document(html);
+
+    let title = Selector::parse("title").ok().and_then(|selector| {
+        document
+            .select(&selector)
+            .next()
+            .map(|element| element.text().collect::<String>().trim().to_string())
+            .filter(|title| !title.is_empty())
+    });
+
+    let main = MAIN_CONTENT_SELECTORS.iter().find_map(|selector| {
+        let selector = Selector::parse(selector).ok()?;
+        document.select(&selector).next()
+    });
+
+    let content = match main {
+        Some(region) => main_content_text(region),
+        None => Selector::parse("body")
+            .ok()
+            .and_then(|selector| document.select(&selector).next())
+            .map(|body| join_text(body.text()))
+            .unwrap_or_default(),
+    };
+
+    (title, content)
+}
+
+/// Text inside `region`, skipping anything within a boilerplate element
+fn main_content_text(region: ElementRef) -> String {
+    let text = region.descendants().filter_map(|node| {
+        let text = node.value().as_text()?;
+        let in_boilerplate = node
+            .ancestors()
+            .take_while(|ancestor| ancestor.id() != region.id())
+            .filter_map(|ancestor| ancestor.value().as_element())
+            .any(|element| BOILERPLATE_ELEMENTS.contains(&element.name()));
+        (!in_boilerplate).then_some(&**text)
+    });
+    join_text(text)
+}
+
+/// Join text fragments with single spaces, dropping empty ones
+fn join_text<'a>(fragments: impl Iterator<Item = &'a str>) -> String {
+    fragments
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::vector_store::LocalVectorStore;
+    use crate::rag::LocalVectorStore;
 
     #[tokio::test]
     async fn test_document_indexing() {
@@ -254,4 +386,86 @@ mod tests {
         let count = store.count().await.unwrap();
         assert!(count > 0);
     }
+
+    fn sample_text() -> String {
+        (0..200).map(|i| format!("word{} ", i)).collect()
+    }
+
+    #[test]
+    fn test_chunks_share_overlap() {
+        let config = ChunkConfig { target_size: 100, max_size: 150, overlap: 30 };
+        let chunks = split_text_into_chunks(&sample_text(), &config);
+        assert!(chunks.len() > 2);
+
+        for pair in chunks.windows(2) {
+            let previous: Vec<char> = pair[0].chars().collect();
+            let tail: String = previous[previous.len() - 30..].iter().collect();
+            assert!(pair[1].starts_with(&tail), "{:?} does not start with {:?}", pair[1], tail);
+        }
+    }
+
+    #[test]
+    fn test_chunks_respect_max_size() {
+        let config = ChunkConfig { target_size: 100, max_size: 120, overlap: 20 };
+        let spaced = split_text_into_chunks(&sample_text(), &config);
+        let unbroken = split_text_into_chunks(&"x".repeat(1000), &config);
+
+        for chunk in spaced.iter().chain(&unbroken) {
+            assert!(chunk.chars().count() <= 120);
+        }
+        assert_eq!(unbroken[0].len(), 120);
+    }
+
+    #[test]
+    fn test_chunking_terminates_with_large_overlap() {
+        let config = ChunkConfig { target_size: 10, max_size: 10, overlap: 50 };
+        let chunks = split_text_into_chunks(&"y".repeat(40), &config);
+        assert!(!chunks.is_empty());
+        assert!(chunks.last().unwrap().ends_with('y'));
+    }
+
+    #[test]
+    fn test_extract_page_text() {
+        let html = "<html><head><title> CLI Guide </title></head>\
+            <body><h1>Install</h1><p>Run <code>ibmcloud login</code> first.</p></body></html>";
+        let (title, content) = extract_page_text(html);
+        assert_eq!(title.as_deref(), Some("CLI Guide"));
+        assert!(content.contains("Install"));
+        assert!(content.contains("ibmcloud login"));
+    }
+
+    const DOCS_PAGE: &str = r#"<html><head><title>VPC Guide</title><script>var tracking = 1;</script></head>
+        <body>
+          <div class="cookie-banner">We use cookies</div>
+          <nav><ul><li>Home</li><li>Pricing</li></ul></nav>
+          <main>
+            <nav class="breadcrumbs">Docs / VPC</nav>
+            <h1>Create a VPC</h1>
+            <p>Run <code>ibmcloud is vpc-create my-vpc</code> to create a VPC.</p>
+            <aside>Related: Billing FAQ</aside>
+            <script>console.log("analytics")</script>
+          </main>
+          <footer>Copyright IBM Corp</footer>
+        </body></html>"#;
+
+    #[test]
+    fn test_extract_page_text_skips_boilerplate() {
+        let (_, content) = extract_page_text(DOCS_PAGE);
+        assert_eq!(content, "Create a VPC Run ibmcloud is vpc-create my-vpc to create a VPC.");
+
+        let chunks = split_text_into_chunks(&content, &ChunkConfig::default());
+        for boilerplate in ["cookies", "Pricing", "Docs / VPC", "Billing FAQ", "analytics", "Copyright"] {
+            assert!(chunks.iter().all(|chunk| !chunk.contains(boilerplate)), "{} leaked into chunks", boilerplate);
+        }
+    }
+
+    #[test]
+    fn test_extract_page_text_prefers_role_main() {
+        let html = r#"<body><nav>Menu</nav><div role="main"><p>Install the CLI</p><footer>Legal</footer></div></body>"#;
+        assert_eq!(extract_page_text(html).1, "Install the CLI");
+
+        // Without a main region, all body text is kept
+        let html = "<body><nav>Menu</nav><p>Install the CLI</p></body>";
+        assert_eq!(extract_page_text(html).1, "Menu Install the CLI");
+    }
 }
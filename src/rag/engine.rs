@@ -7,7 +7,7 @@ use std::sync::Arc;
 use crate::core::{
     RAGEngine, RAGQuery, RAGResult,
     VectorStore, VectorDocument, SearchConfig,
-    DocumentIndexer, Document,
+    DocumentIndexer, Document, CloudProvider, CloudProviderType,
     Error, Result,
 };
 
@@ -16,6 +16,10 @@ pub struct LocalRAGEngine<V: VectorStore, D: DocumentIndexer> {
     vector_store: Arc<V>,
     document_indexer: Arc<D>,
     initialized: bool,
+    /// Documentation URLs indexed on initialization
+    online_sources: Vec<String>,
+    /// Sources that failed to index during the last initialization
+    failed_sources: Vec<String>,
 }
 
 impl<V: VectorStore, D: DocumentIndexer> LocalRAGEngine<V, D> {
@@ -25,9 +29,22 @@ impl<V: VectorStore, D: DocumentIndexer> LocalRAGEngine<V, D> {
             vector_store,
             document_indexer,
             initialized: false,
+            online_sources: Vec::new(),
+            failed_sources: Vec::new(),
         }
     }
 
+    /// Also index these documentation URLs when initializing
+    pub fn with_online_sources(mut self, urls: Vec<String>) -> Self {
+        self.online_sources = urls;
+        self
+    }
+
+    /// Sources that failed to index during the last initialization
+    pub fn failed_sources(&self) -> &[String] {
+        &self.failed_sources
+    }
+
     /// Add basic IBM Cloud CLI knowledge
     pub async fn add_basic_knowledge(&self) -> Result<()> {
         let basic_knowledge = vec![
@@ -74,6 +91,7 @@ impl<V: VectorStore, D: DocumentIndexer> LocalRAGEngine<V, D> {
                 metadata: json!({
                     "category": category,
                     "type": "documentation",
+                    "provider": CloudProviderType::IBMCloud.id(),
                 }),
             })
             .collect();
@@ -81,6 +99,44 @@ impl<V: VectorStore, D: DocumentIndexer> LocalRAGEngine<V, D> {
         self.document_indexer.index_documents(documents).await?;
         Ok(())
     }
+
+    /// Index each provider's command patterns and CLI reference, tagged with the provider
+    ///
+    /// This curated knowledge keeps retrieval useful when no documentation
+    /// could be fetched. Document IDs are stable, so seeding again replaces
+    /// the earlier copies.
+    pub async fn seed_provider_knowledge(&self, providers: &[Box<dyn CloudProvider>]) -> Result<()> {
+        let documents: Vec<Document> = providers
+            .iter()
+            .flat_map(|provider| {
+                let provider_type = provider.provider_type();
+                let patterns = format!(
+                    "Common {} commands:\n{}",
+                    provider_type.display_name(),
+                    provider.get_command_patterns().join("\n")
+                );
+                [
+                    ("patterns", format!("{} Command Patterns", provider_type.display_name()), patterns),
+                    ("reference", format!("{} CLI Reference", provider_type.display_name()), provider.get_rag_context()),
+                ]
+                .into_iter()
+                .map(move |(kind, title, content)| Document {
+                    id: format!("provider_knowledge_{}_{}", provider_type.id(), kind),
+                    title,
+                    content,
+                    url: None,
+                    metadata: json!({
+                        "category": "provider_knowledge",
+                        "type": "documentation",
+                        "provider": provider_type.id(),
+                    }),
+                })
+            })
+            .collect();
+
+        self.document_indexer.index_documents(documents).await?;
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -90,8 +146,34 @@ impl<V: VectorStore + 'static, D: DocumentIndexer + 'static> RAGEngine for Local
             return Err(Error::RAGEngine("Vector store not connected".to_string()));
         }
 
-        // Add basic knowledge
-        self.add_basic_knowledge().await?;
+        // Each source is indexed independently so one failure does not
+        // leave the engine unusable
+        self.failed_sources.clear();
+        let mut indexed_any = false;
+
+        match self.add_basic_knowledge().await {
+            Ok(()) => indexed_any = true,
+            Err(e) => self.failed_sources.push(format!("basic knowledge: {}", e)),
+        }
+
+        if !self.online_sources.is_empty() {
+            match self.document_indexer.index_from_urls(self.online_sources.clone()).await {
+                Ok(result) => {
+                    indexed_any |= result.documents_indexed > 0;
+                    self.failed_sources.extend(result.errors);
+                }
+                Err(e) => self.failed_sources.push(format!("online sources: {}", e)),
+            }
+        }
+
+        // Documents loaded from a saved store are still usable
+        if !indexed_any && self.vector_store.count().await? == 0 {
+            self.initialized = false;
+            return Err(Error::RAGEngine(format!(
+                "No knowledge could be indexed: {}",
+                self.failed_sources.join("; ")
+            )));
+        }
 
         self.initialized = true;
         Ok(())
@@ -108,19 +190,25 @@ impl<V: VectorStore + 'static, D: DocumentIndexer + 'static> RAGEngine for Local
             filters: query.filters.as_ref().map(|f| {
                 json!(f.iter().map(|(k, v)| (k.clone(), v.clone())).collect::<Vec<_>>())
             }),
+            ..Default::default()
         };
 
-        let search_result = self.vector_store.search(&query.query, &search_config).await?;
-        let context = self.build_context(&search_result.documents);
+        let results = self.vector_store.search_results(&query.query, &search_config).await?;
+        // Documents keep their rank as their position and carry their score
+        let documents: Vec<VectorDocument> = results
+            .into_iter()
+            .map(|hit| VectorDocument { score: Some(hit.score), ..hit.document })
+            .collect();
+        let context = self.build_context(&documents);
 
         Ok(RAGResult {
-            documents: search_result.documents,
-            context,
             metadata: Some(json!({
                 "query": query.query,
                 "top_k": query.top_k,
-                "results_count": search_result.total,
+                "results_count": documents.len(),
             })),
+            documents,
+            context,
         })
     }
 
@@ -134,10 +222,10 @@ impl<V: VectorStore + 'static, D: DocumentIndexer + 'static> RAGEngine for Local
         for (i, doc) in documents.iter().enumerate() {
             context.push_str(&format!("{}. ", i + 1));
 
-            if let Some(title) = doc.metadata.get("title") {
-                if let Some(title_str) = title.as_str() {
-                    context.push_str(&format!("[{}] ", title_str));
-                }
+            if let Some(title) = doc.metadata.get("title")
+                && let Some(title_str) = title.as_str()
+            {
+                context.push_str(&format!("[{}] ", title_str));
             }
 
             context.push_str(&doc.content);
@@ -167,6 +255,7 @@ impl<V: VectorStore + 'static, D: DocumentIndexer + 'static> RAGEngine for Local
             "initialized": self.initialized,
             "vector_store_count": vector_count,
             "indexer_stats": indexer_stats,
+            "failed_sources": self.failed_sources,
         }))
     }
 
@@ -178,8 +267,7 @@ impl<V: VectorStore + 'static, D: DocumentIndexer + 'static> RAGEngine for Local
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::vector_store::LocalVectorStore;
-    use crate::document_indexer::LocalDocumentIndexer;
+    use crate::rag::{LocalDocumentIndexer, LocalVectorStore};
 
     #[tokio::test]
     async fn test_rag_engine() {
@@ -204,4 +292,64 @@ mod tests {
         assert!(!result.documents.is_empty());
         assert!(!result.context.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_seeded_provider_knowledge_is_retrievable() {
+        let mut store = LocalVectorStore::new();
+        store.connect().await.unwrap();
+        let store = Arc::new(store);
+
+        let indexer = Arc::new(LocalDocumentIndexer::new(store.clone()));
+        let mut engine = LocalRAGEngine::new(store.clone(), indexer)
+            .with_online_sources(vec!["https://docs.aws.amazon.com/cli/".to_string()]);
+        let providers: Vec<Box<dyn CloudProvider>> = CloudProviderType::all()
+            .into_iter()
+            .map(crate::providers::create_provider)
+            .collect();
+        engine.seed_provider_knowledge(&providers).await.unwrap();
+        engine.initialize().await.unwrap();
+
+        let query = RAGQuery {
+            query: "list ec2".to_string(),
+            top_k: 1,
+            score_threshold: Some(0.1),
+            filters: None,
+        };
+        let result = engine.retrieve(&query).await.unwrap();
+        assert_eq!(result.documents[0].metadata["provider"], "aws");
+        assert_eq!(result.documents[0].metadata["category"], "provider_knowledge");
+
+        // Seeding again replaces the same documents
+        let count = store.count().await.unwrap();
+        engine.seed_provider_knowledge(&providers).await.unwrap();
+        assert_eq!(store.count().await.unwrap(), count);
+    }
+
+    #[tokio::test]
+    async fn test_online_failure_keeps_local_knowledge() {
+        let mut store = LocalVectorStore::new();
+        store.connect().await.unwrap();
+        let store = Arc::new(store);
+
+        // The local indexer cannot fetch URLs, so the online source always fails
+        let indexer = Arc::new(LocalDocumentIndexer::new(store.clone()));
+        let mut engine = LocalRAGEngine::new(store, indexer)
+            .with_online_sources(vec!["https://cloud.ibm.com/docs/cli".to_string()]);
+
+        engine.initialize().await.unwrap();
+        assert!(engine.is_ready());
+        assert_eq!(engine.failed_sources().len(), 1);
+        assert!(engine.failed_sources()[0].contains("https://cloud.ibm.com/docs/cli"));
+
+        let stats = engine.stats().await.unwrap();
+        assert_eq!(stats["failed_sources"].as_array().unwrap().len(), 1);
+
+        let query = RAGQuery {
+            query: "IBM Cloud CLI commands".to_string(),
+            top_k: 3,
+            score_threshold: Some(0.1),
+            filters: None,
+        };
+        assert!(!engine.retrieve(&query).await.unwrap().documents.is_empty());
+    }
 }
@@ -7,15 +7,8 @@ mod engine;
 #[cfg(test)]
 mod tests;
 
-pub use vector_store::{LocalVectorStore, QdrantVectorStore};
-pub use document_indexer::{LocalDocumentIndexer, WebDocumentIndexer};
+pub use vector_store::LocalVectorStore;
+pub use document_indexer::WebDocumentIndexer;
 pub use engine::LocalRAGEngine;
-
-// Re-export core types for convenience
-pub use crate::core::{
-    RAGEngine, RAGQuery, RAGResult,
-    VectorStore, VectorDocument, SearchResult, SearchConfig,
-    DocumentIndexer, Document, IndexingResult, IndexingConfig,
-    Error, Result,
-};
-
+#[cfg(test)]
+pub use document_indexer::LocalDocumentIndexer;
@@ -2,7 +2,7 @@
 
 #[cfg(test)]
 mod snapshot_tests {
-    use super::{LocalVectorStore, LocalDocumentIndexer, LocalRAGEngine};
+    use crate::rag::{LocalVectorStore, LocalDocumentIndexer, LocalRAGEngine};
     use crate::core::{VectorStore, VectorDocument, SearchConfig, Document, RAGQuery, DocumentIndexer, RAGEngine};
     use serde_json::json;
     use std::sync::Arc;
@@ -27,6 +27,7 @@ mod snapshot_tests {
             top_k: 5,
             score_threshold: Some(0.1),
             filters: None,
+            ..Default::default()
         };
 
         let results = store.search("IBM Cloud CLI", &config).await.unwrap();
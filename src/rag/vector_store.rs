@@ -1,18 +1,91 @@
 //! Vector store implementations
 
 use async_trait::async_trait;
-use serde_json::json;
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 
 use crate::core::{
-    VectorStore, VectorDocument, SearchResult, SearchConfig,
+    VectorStore, VectorDocument, SearchResult, SearchConfig, SimilarityMetric,
     Error, Result,
 };
 
+/// Check a document's metadata against search filters
+///
+/// Filters are either an object of `key: value` pairs or an array of
+/// `[key, value]` pairs, as built by `RAGEngine::retrieve`. Every pair must
+/// match exactly, except that a document without the key matches any value:
+/// chunks indexed without a provider tag apply to every provider.
+fn matches_filters(metadata: &serde_json::Value, filters: Option<&serde_json::Value>) -> bool {
+    let matches = |key: &str, value: &serde_json::Value| metadata.get(key).is_none_or(|found| found == value);
+
+    match filters {
+        None => true,
+        Some(serde_json::Value::Object(pairs)) => pairs.iter().all(|(key, value)| matches(key, value)),
+        Some(serde_json::Value::Array(pairs)) => pairs.iter().all(|pair| match pair.as_array().map(Vec::as_slice) {
+            Some([serde_json::Value::String(key), value]) => matches(key, value),
+            _ => false,
+        }),
+        Some(_) => true,
+    }
+}
+
+/// A stored document with its embedding normalized once at index time
+struct StoredDocument {
+    document: VectorDocument,
+    /// Unit-length copy of the embedding, so search is a plain dot product
+    unit_embedding: Option<Vec<f32>>,
+}
+
+impl StoredDocument {
+    fn new(document: VectorDocument) -> Self {
+        let unit_embedding = document.embedding.as_deref().map(normalize);
+        Self { document, unit_embedding }
+    }
+}
+
+/// Scale a vector to unit length; the zero vector stays zero
+fn normalize(vector: &[f32]) -> Vec<f32> {
+    let magnitude = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if magnitude == 0.0 {
+        return vec![0.0; vector.len()];
+    }
+    vector.iter().map(|x| x / magnitude).collect()
+}
+
+/// Dot product of two vectors of equal length, 0.0 otherwise
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Euclidean distance between two vectors, `None` if their lengths differ
+fn euclidean_distance(a: &[f32], b: &[f32]) -> Option<f32> {
+    if a.len() != b.len() {
+        return None;
+    }
+    Some(a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum::<f32>().sqrt())
+}
+
+impl StoredDocument {
+    /// Score against a query under `metric`
+    ///
+    /// `unit_query` is the normalized query, used for cosine similarity.
+    /// Returns `None` for documents without an embedding, or whose embedding
+    /// cannot be compared under the metric.
+    fn score(&self, metric: SimilarityMetric, query: &[f32], unit_query: &[f32]) -> Option<f32> {
+        match metric {
+            SimilarityMetric::Cosine => Some(dot(unit_query, self.unit_embedding.as_ref()?)),
+            SimilarityMetric::DotProduct => Some(dot(query, self.document.embedding.as_ref()?)),
+            SimilarityMetric::Euclidean => euclidean_distance(query, self.document.embedding.as_ref()?),
+        }
+    }
+}
+
 /// Local in-memory vector store implementation
 pub struct LocalVectorStore {
-    documents: Arc<RwLock<HashMap<String, VectorDocument>>>,
+    documents: Arc<RwLock<HashMap<String, StoredDocument>>>,
     connected: bool,
 }
 
@@ -25,7 +98,38 @@ impl LocalVectorStore {
         }
     }
 
+    /// Load documents previously written by `save_to_file`
+    ///
+    /// Returns the number of documents loaded.
+    pub fn load_from_file(&self, path: &str) -> Result<usize> {
+        let content = std::fs::read_to_string(path)?;
+        let loaded: Vec<VectorDocument> = serde_json::from_str(&content)
+            .map_err(|e| Error::Serialization(e.to_string()))?;
+
+        let mut docs = self.documents.write()
+            .map_err(|e| Error::VectorStore(format!("Lock error: {}", e)))?;
+        let count = loaded.len();
+        for document in loaded {
+            docs.insert(document.id.clone(), StoredDocument::new(document));
+        }
+        Ok(count)
+    }
+
+    /// Persist all documents to a JSON file
+    pub fn save_to_file(&self, path: &str) -> Result<()> {
+        let docs = self.documents.read()
+            .map_err(|e| Error::VectorStore(format!("Lock error: {}", e)))?;
+        let mut documents: Vec<&VectorDocument> = docs.values().map(|stored| &stored.document).collect();
+        documents.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let json = serde_json::to_string_pretty(&documents)
+            .map_err(|e| Error::Serialization(e.to_string()))?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+
     /// Simple cosine similarity calculation
+    #[cfg(test)]
     fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
         if a.len() != b.len() {
             return 0.0;
@@ -81,7 +185,7 @@ impl VectorStore for LocalVectorStore {
         let id = document.id.clone();
         let mut docs = self.documents.write()
             .map_err(|e| Error::VectorStore(format!("Lock error: {}", e)))?;
-        docs.insert(id.clone(), document);
+        docs.insert(id.clone(), StoredDocument::new(document));
         Ok(id)
     }
 
@@ -92,7 +196,7 @@ impl VectorStore for LocalVectorStore {
 
         for document in documents {
             let id = document.id.clone();
-            docs.insert(id.clone(), document);
+            docs.insert(id.clone(), StoredDocument::new(document));
             ids.push(id);
         }
 
@@ -105,12 +209,14 @@ impl VectorStore for LocalVectorStore {
 
         let mut results: Vec<VectorDocument> = docs
             .values()
+            .map(|stored| &stored.document)
             .map(|doc| {
                 let score = Self::text_similarity(query, &doc.content);
                 let mut doc_with_score = doc.clone();
                 doc_with_score.score = Some(score);
                 doc_with_score
             })
+            .filter(|doc| matches_filters(&doc.metadata, config.filters.as_ref()))
             .filter(|doc| {
                 if let Some(threshold) = config.score_threshold {
                     doc.score.unwrap_or(0.0) >= threshold
@@ -120,8 +226,10 @@ impl VectorStore for LocalVectorStore {
             })
             .collect();
 
+        // Equal scores fall back to id order so results don't depend on map order
         results.sort_by(|a, b| {
             b.score.unwrap_or(0.0).partial_cmp(&a.score.unwrap_or(0.0)).unwrap()
+                .then_with(|| a.id.cmp(&b.id))
         });
 
         results.truncate(config.top_k);
@@ -138,29 +246,33 @@ impl VectorStore for LocalVectorStore {
         let docs = self.documents.read()
             .map_err(|e| Error::VectorStore(format!("Lock error: {}", e)))?;
 
+        // Embeddings are stored normalized, so cosine similarity is a dot product
+        let unit_query = normalize(&vector);
+        let ascending = config.metric.ranks_ascending();
         let mut results: Vec<VectorDocument> = docs
             .values()
-            .filter_map(|doc| {
-                if let Some(ref embedding) = doc.embedding {
-                    let score = Self::cosine_similarity(&vector, embedding);
-                    let mut doc_with_score = doc.clone();
-                    doc_with_score.score = Some(score);
-                    Some(doc_with_score)
-                } else {
-                    None
-                }
-            })
-            .filter(|doc| {
-                if let Some(threshold) = config.score_threshold {
-                    doc.score.unwrap_or(0.0) >= threshold
-                } else {
-                    true
+            .filter(|stored| matches_filters(&stored.document.metadata, config.filters.as_ref()))
+            .filter_map(|stored| {
+                let score = stored.score(config.metric, &vector, &unit_query)?;
+                let rejected = match config.score_threshold {
+                    Some(threshold) if ascending => score > threshold,
+                    Some(threshold) => score < threshold,
+                    None => false,
+                };
+                if rejected {
+                    return None;
                 }
+
+                let mut doc_with_score = stored.document.clone();
+                doc_with_score.score = Some(score);
+                Some(doc_with_score)
             })
             .collect();
 
         results.sort_by(|a, b| {
-            b.score.unwrap_or(0.0).partial_cmp(&a.score.unwrap_or(0.0)).unwrap()
+            let order = b.score.unwrap_or(0.0).total_cmp(&a.score.unwrap_or(0.0));
+            let order = if ascending { order.reverse() } else { order };
+            order.then_with(|| a.id.cmp(&b.id))
         });
 
         results.truncate(config.top_k);
@@ -176,7 +288,7 @@ impl VectorStore for LocalVectorStore {
     async fn get(&self, id: &str) -> Result<Option<VectorDocument>> {
         let docs = self.documents.read()
             .map_err(|e| Error::VectorStore(format!("Lock error: {}", e)))?;
-        Ok(docs.get(id).cloned())
+        Ok(docs.get(id).map(|stored| stored.document.clone()))
     }
 
     async fn delete(&self, id: &str) -> Result<bool> {
@@ -220,9 +332,79 @@ impl Default for QdrantVectorStore {
     }
 }
 
+/// Vector store kept in memory only, connected from creation and never persisted
+///
+/// Unlike `LocalVectorStore` it has no file methods, so code and tests using
+/// it cannot touch disk by accident.
+pub struct InMemoryVectorStore {
+    inner: LocalVectorStore,
+}
+
+impl InMemoryVectorStore {
+    /// Create an empty, connected store
+    pub fn new() -> Self {
+        Self {
+            inner: LocalVectorStore {
+                documents: Arc::new(RwLock::new(HashMap::new())),
+                connected: true,
+            },
+        }
+    }
+}
+
+impl Default for InMemoryVectorStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl VectorStore for InMemoryVectorStore {
+    async fn connect(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn store(&self, document: VectorDocument) -> Result<String> {
+        self.inner.store(document).await
+    }
+
+    async fn store_batch(&self, documents: Vec<VectorDocument>) -> Result<Vec<String>> {
+        self.inner.store_batch(documents).await
+    }
+
+    async fn search(&self, query: &str, config: &SearchConfig) -> Result<SearchResult> {
+        self.inner.search(query, config).await
+    }
+
+    async fn search_by_vector(&self, vector: Vec<f32>, config: &SearchConfig) -> Result<SearchResult> {
+        self.inner.search_by_vector(vector, config).await
+    }
+
+    async fn get(&self, id: &str) -> Result<Option<VectorDocument>> {
+        self.inner.get(id).await
+    }
+
+    async fn delete(&self, id: &str) -> Result<bool> {
+        self.inner.delete(id).await
+    }
+
+    async fn clear(&self) -> Result<()> {
+        self.inner.clear().await
+    }
+
+    async fn count(&self) -> Result<usize> {
+        self.inner.count().await
+    }
+
+    fn is_connected(&self) -> bool {
+        true
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serde_json::json;
 
     #[tokio::test]
     async fn test_local_vector_store() {
@@ -247,6 +429,140 @@ mod tests {
         assert!(retrieved.is_some());
     }
 
+    #[tokio::test]
+    async fn test_vector_search_matches_cosine_similarity() {
+        let store = LocalVectorStore::new();
+        let mut embeddings = Vec::new();
+
+        // Deterministic pseudo-random embeddings, including a zero vector
+        let mut seed: u32 = 42;
+        for i in 0..50 {
+            let embedding: Vec<f32> = (0..8)
+                .map(|_| {
+                    seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                    if i == 0 { 0.0 } else { (seed >> 16) as f32 / 65_536.0 - 0.5 }
+                })
+                .collect();
+            embeddings.push((format!("doc{}", i), embedding.clone()));
+            store.store(VectorDocument {
+                id: format!("doc{}", i),
+                content: String::new(),
+                embedding: Some(embedding),
+                metadata: json!({}),
+                score: None,
+            }).await.unwrap();
+        }
+
+        let query = vec![0.3, -0.1, 0.8, 0.05, -0.4, 0.2, 0.0, 0.6];
+        let config = SearchConfig {
+            top_k: 10,
+            score_threshold: None,
+            filters: None,
+            ..Default::default()
+        };
+        let result = store.search_by_vector(query.clone(), &config).await.unwrap();
+
+        // Reference: the naive per-document cosine similarity
+        let mut expected: Vec<(String, f32)> = embeddings
+            .iter()
+            .map(|(id, embedding)| (id.clone(), LocalVectorStore::cosine_similarity(&query, embedding)))
+            .collect();
+        expected.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+        expected.truncate(10);
+
+        assert_eq!(result.documents.len(), expected.len());
+        for (document, (id, score)) in result.documents.iter().zip(&expected) {
+            assert_eq!(&document.id, id);
+            assert!((document.score.unwrap() - score).abs() < 1e-5);
+        }
+    }
+
+    async fn ranked_ids(store: &LocalVectorStore, query: Vec<f32>, metric: SimilarityMetric) -> Vec<String> {
+        let config = SearchConfig {
+            top_k: 10,
+            score_threshold: None,
+            filters: None,
+            metric,
+        };
+        let result = store.search_by_vector(query, &config).await.unwrap();
+        result.documents.into_iter().map(|doc| doc.id).collect()
+    }
+
+    #[tokio::test]
+    async fn test_similarity_metrics_rank_differently() {
+        let store = LocalVectorStore::new();
+        for (id, embedding) in [
+            // Same direction as the query but far away
+            ("long", vec![10.0, 0.0]),
+            // Close to the query but at an angle
+            ("near", vec![1.0, 1.0]),
+            // Opposite direction
+            ("opposite", vec![-1.0, 0.0]),
+        ] {
+            store.store(VectorDocument {
+                id: id.to_string(),
+                content: String::new(),
+                embedding: Some(embedding),
+                metadata: json!({}),
+                score: None,
+            }).await.unwrap();
+        }
+
+        let query = vec![1.0, 0.0];
+        assert_eq!(ranked_ids(&store, query.clone(), SimilarityMetric::Cosine).await, ["long", "near", "opposite"]);
+        assert_eq!(ranked_ids(&store, query.clone(), SimilarityMetric::DotProduct).await, ["long", "near", "opposite"]);
+        assert_eq!(ranked_ids(&store, query, SimilarityMetric::Euclidean).await, ["near", "opposite", "long"]);
+
+        // Magnitude matters for the dot product but not for cosine
+        let tilted = vec![2.0, 2.5];
+        assert_eq!(ranked_ids(&store, tilted.clone(), SimilarityMetric::Cosine).await, ["near", "long", "opposite"]);
+        assert_eq!(ranked_ids(&store, tilted, SimilarityMetric::DotProduct).await, ["long", "near", "opposite"]);
+    }
+
+    #[tokio::test]
+    async fn test_euclidean_threshold_is_max_distance() {
+        let store = LocalVectorStore::new();
+        for (id, embedding) in [("close", vec![0.5, 0.0]), ("far", vec![5.0, 0.0])] {
+            store.store(VectorDocument {
+                id: id.to_string(),
+                content: String::new(),
+                embedding: Some(embedding),
+                metadata: json!({}),
+                score: None,
+            }).await.unwrap();
+        }
+
+        let config = SearchConfig {
+            top_k: 10,
+            score_threshold: Some(1.0),
+            filters: None,
+            metric: SimilarityMetric::Euclidean,
+        };
+        let result = store.search_by_vector(vec![0.0, 0.0], &config).await.unwrap();
+        assert_eq!(result.documents.len(), 1);
+        assert_eq!(result.documents[0].id, "close");
+    }
+
+    #[tokio::test]
+    async fn test_save_and_load_round_trip() {
+        let temp_file = tempfile::NamedTempFile::new().unwrap();
+        let path = temp_file.path().to_str().unwrap();
+
+        let store = LocalVectorStore::new();
+        store.store(VectorDocument {
+            id: "persisted".to_string(),
+            content: "aws s3 ls lists buckets".to_string(),
+            embedding: None,
+            metadata: json!({"source": "custom"}),
+            score: None,
+        }).await.unwrap();
+        store.save_to_file(path).unwrap();
+
+        let reloaded = LocalVectorStore::new();
+        assert_eq!(reloaded.load_from_file(path).unwrap(), 1);
+        assert!(reloaded.get("persisted").await.unwrap().is_some());
+    }
+
     #[tokio::test]
     async fn test_search() {
         let mut store = LocalVectorStore::new();
@@ -275,9 +591,86 @@ mod tests {
             top_k: 2,
             score_threshold: Some(0.1),
             filters: None,
+            ..Default::default()
         };
 
         let results = store.search("IBM Cloud CLI", &config).await.unwrap();
         assert!(!results.documents.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_search_results_are_ranked_by_score() {
+        let mut store = LocalVectorStore::new();
+        store.connect().await.unwrap();
+        for (id, content) in [
+            ("partial", "IBM Cloud account settings"),
+            ("full", "IBM Cloud CLI login commands"),
+            ("weak", "Cloud storage pricing"),
+            ("none", "Kubernetes pods"),
+        ] {
+            store.store(VectorDocument {
+                id: id.to_string(),
+                content: content.to_string(),
+                embedding: None,
+                metadata: json!({}),
+                score: None,
+            }).await.unwrap();
+        }
+
+        let config = SearchConfig { top_k: 10, score_threshold: Some(0.1), ..Default::default() };
+        let results = store.search_results("IBM Cloud CLI login", &config).await.unwrap();
+
+        let ranks: Vec<usize> = results.iter().map(|hit| hit.rank).collect();
+        assert_eq!(ranks, vec![0, 1, 2]);
+        assert!(results.windows(2).all(|pair| pair[0].score >= pair[1].score));
+        assert_eq!(results[0].document.id, "full");
+        assert_eq!(results[0].document.score, Some(results[0].score));
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_store_index_search_delete() {
+        use crate::core::{Document, DocumentIndexer};
+        use crate::rag::LocalDocumentIndexer;
+
+        let store = Arc::new(InMemoryVectorStore::new());
+        assert!(store.is_connected());
+
+        let indexer = LocalDocumentIndexer::new(store.clone());
+        let document = Document {
+            id: "s3-guide".to_string(),
+            title: "S3 Guide".to_string(),
+            content: "Use aws s3 ls to list buckets and aws s3 cp to copy objects.".to_string(),
+            url: None,
+            metadata: json!({"provider": "aws"}),
+        };
+        let result = indexer.index_document(document).await.unwrap();
+        assert_eq!(result.documents_failed, 0);
+        let count = store.count().await.unwrap();
+        assert_eq!(count, result.documents_indexed);
+
+        let config = SearchConfig {
+            top_k: 1,
+            score_threshold: Some(0.1),
+            filters: Some(json!({"provider": "aws"})),
+            ..Default::default()
+        };
+        let results = store.search("list buckets", &config).await.unwrap();
+        let hit = results.documents.first().expect("indexed chunk is found").id.clone();
+
+        assert!(store.delete(&hit).await.unwrap());
+        assert!(!store.delete(&hit).await.unwrap());
+        assert_eq!(store.count().await.unwrap(), count - 1);
+
+        store.clear().await.unwrap();
+        assert_eq!(store.count().await.unwrap(), 0);
+    }
+
+    #[test]
+    fn test_untagged_documents_match_any_filter() {
+        let filters = json!([["provider", "aws"]]);
+        assert!(matches_filters(&json!({"provider": "aws", "title": "S3"}), Some(&filters)));
+        assert!(matches_filters(&json!({"title": "Shared notes"}), Some(&filters)));
+        assert!(!matches_filters(&json!({"provider": "gcp"}), Some(&filters)));
+        assert!(!matches_filters(&json!({"provider": "gcp"}), Some(&json!({"provider": "aws"}))));
+    }
 }
@@ -0,0 +1,179 @@
+//! HTTP service mode for the `serve` subcommand
+//!
+//! Exposes `POST /translate` and `GET /health` over a shared
+//! `CommandTranslator`, so CUC can be embedded behind a small service.
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::cli::{CliError, CommandTranslator, QualityAnalyzer};
+use crate::core::{CloudProviderType, LLMProvider, RAGEngine};
+
+/// Shared state behind every request
+pub struct ServerState<L: LLMProvider, R: RAGEngine> {
+    translator: CommandTranslator<L, R>,
+    quality_analyzer: QualityAnalyzer,
+}
+
+impl<L: LLMProvider, R: RAGEngine> ServerState<L, R> {
+    pub fn new(translator: CommandTranslator<L, R>) -> Self {
+        Self {
+            translator,
+            quality_analyzer: QualityAnalyzer::new(),
+        }
+    }
+}
+
+/// Body of `POST /translate`
+#[derive(Debug, Deserialize)]
+pub struct TranslateRequest {
+    pub query: String,
+    /// Provider ID such as `aws`; any provider when absent
+    #[serde(default)]
+    pub provider: Option<String>,
+}
+
+/// Response of `POST /translate`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TranslateResponse {
+    pub command: String,
+    pub quality: f32,
+}
+
+/// Response of `GET /health`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HealthResponse {
+    pub status: String,
+    pub model: String,
+}
+
+/// Error body returned with a non-2xx status
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorResponse {
+    pub error: String,
+}
+
+type ApiError = (StatusCode, Json<ErrorResponse>);
+
+/// HTTP status for a translation error
+fn error_status(error: &CliError) -> StatusCode {
+    match error {
+        CliError::UnknownProvider(_) | CliError::ValidationFailed(_) => StatusCode::BAD_REQUEST,
+        CliError::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
+        CliError::NotAuthenticated(_) => StatusCode::BAD_GATEWAY,
+        CliError::TranslationFailed(_) | CliError::ExecutionFailed(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+fn api_error(error: CliError) -> ApiError {
+    (error_status(&error), Json(ErrorResponse { error: error.to_string() }))
+}
+
+/// Build the service's routes
+pub fn router<L, R>(state: Arc<ServerState<L, R>>) -> Router
+where
+    L: LLMProvider + 'static,
+    R: RAGEngine + 'static,
+{
+    Router::new()
+        .route("/health", get(health::<L, R>))
+        .route("/translate", post(translate::<L, R>))
+        .with_state(state)
+}
+
+/// Serve the routes on `addr` until the process is stopped
+pub async fn serve<L, R>(translator: CommandTranslator<L, R>, addr: &str) -> std::io::Result<()>
+where
+    L: LLMProvider + 'static,
+    R: RAGEngine + 'static,
+{
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    println!("🌐 Listening on http://{}", listener.local_addr()?);
+    axum::serve(listener, router(Arc::new(ServerState::new(translator)))).await
+}
+
+async fn health<L: LLMProvider, R: RAGEngine>(State(state): State<Arc<ServerState<L, R>>>) -> Json<HealthResponse> {
+    Json(HealthResponse {
+        status: "ok".to_string(),
+        model: state.translator.llm().model_id().to_string(),
+    })
+}
+
+async fn translate<L: LLMProvider, R: RAGEngine>(
+    State(state): State<Arc<ServerState<L, R>>>,
+    Json(request): Json<TranslateRequest>,
+) -> Result<Json<TranslateResponse>, ApiError> {
+    if request.query.trim().is_empty() {
+        return Err(api_error(CliError::ValidationFailed("query must not be empty".to_string())));
+    }
+
+    let command = match request.provider.as_deref() {
+        Some(id) => {
+            let provider = CloudProviderType::from_str(id).ok_or_else(|| api_error(CliError::UnknownProvider(id.to_string())))?;
+            state.translator.translate_for_provider(&request.query, provider).await
+        }
+        None => state.translator.translate(&request.query).await,
+    }
+    .map_err(api_error)?;
+
+    let quality = state
+        .quality_analyzer
+        .analyze_with_success_rate(&command, state.translator.get_command_success_rate(&command))
+        .score;
+    Ok(Json(TranslateResponse { command, quality }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::test_support::{MockLLM, TestRag};
+
+    fn state(llm: MockLLM) -> State<Arc<ServerState<MockLLM, TestRag>>> {
+        State(Arc::new(ServerState::new(CommandTranslator::new(llm))))
+    }
+
+    fn request(query: &str, provider: Option<&str>) -> Json<TranslateRequest> {
+        Json(TranslateRequest {
+            query: query.to_string(),
+            provider: provider.map(str::to_string),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_health() {
+        let Json(health) = health(state(MockLLM::replying("aws s3 ls"))).await;
+        assert_eq!(health.status, "ok");
+        assert_eq!(health.model, "mock");
+    }
+
+    #[tokio::test]
+    async fn test_translate_returns_command_and_quality() {
+        let state = state(MockLLM::replying("aws s3api list-buckets"));
+        let Json(response) = translate(state, request("which of my buckets are public", Some("aws"))).await.unwrap();
+        assert_eq!(response.command, "aws s3api list-buckets");
+        assert!(response.quality > 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_translate_rejects_bad_requests() {
+        let state = state(MockLLM::replying("aws s3 ls"));
+
+        let (status, Json(body)) = translate(state.clone(), request("list buckets", Some("oracle"))).await.unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body.error.contains("oracle"));
+
+        let (status, _) = translate(state, request("  ", None)).await.unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_translate_maps_llm_errors() {
+        let state = state(MockLLM::failing(|| crate::core::Error::Timeout("slow".to_string())));
+        let (status, _) = translate(state, request("list buckets", None)).await.unwrap_err();
+        assert_eq!(status, StatusCode::GATEWAY_TIMEOUT);
+    }
+}
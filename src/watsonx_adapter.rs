@@ -1,24 +1,226 @@
 //! Adapter to make watsonx-rs implement LLMProvider trait
 
 use async_trait::async_trait;
-use std::time::Duration;
+use chrono::Utc;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use regex::Regex;
+use serde::Deserialize;
+use tokio::sync::RwLock;
 use tokio::time::timeout;
 use std::env;
 
 use crate::core::{
     LLMProvider, GenerationConfig, GenerationResult, GenerationAttempt,
-    RetryConfig, Error, Result,
+    RetryConfig, CloudProviderType, Error, Result,
 };
 use watsonx_rs::{WatsonxClient, WatsonxConfig, GenerationConfig as WatxGenConfig};
 
+/// Lifetime of an IBM Cloud IAM access token when IAM does not say
+const TOKEN_LIFETIME_SECS: i64 = 3600;
+
+/// Refresh the token this long before it actually expires
+const TOKEN_REFRESH_MARGIN_SECS: i64 = 300;
+
+/// IAM host watsonx-rs uses when `WATSONX_IAM_URL` is not set
+const DEFAULT_IAM_HOST: &str = "iam.cloud.ibm.com";
+
+/// IBM Cloud regions hosting watsonx.ai
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WatsonxRegion {
+    #[default]
+    UsSouth,
+    EuDe,
+    EuGb,
+    JpTok,
+    AuSyd,
+    CaTor,
+}
+
+impl WatsonxRegion {
+    /// Parse a region code such as `eu-de`
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().replace('_', "-").as_str() {
+            "us-south" | "dallas" => Some(WatsonxRegion::UsSouth),
+            "eu-de" | "frankfurt" => Some(WatsonxRegion::EuDe),
+            "eu-gb" | "london" => Some(WatsonxRegion::EuGb),
+            "jp-tok" | "tokyo" => Some(WatsonxRegion::JpTok),
+            "au-syd" | "sydney" => Some(WatsonxRegion::AuSyd),
+            "ca-tor" | "toronto" => Some(WatsonxRegion::CaTor),
+            _ => None,
+        }
+    }
+
+    /// Region code used in the endpoint hostname
+    pub fn code(&self) -> &'static str {
+        match self {
+            WatsonxRegion::UsSouth => "us-south",
+            WatsonxRegion::EuDe => "eu-de",
+            WatsonxRegion::EuGb => "eu-gb",
+            WatsonxRegion::JpTok => "jp-tok",
+            WatsonxRegion::AuSyd => "au-syd",
+            WatsonxRegion::CaTor => "ca-tor",
+        }
+    }
+
+    /// Base URL of the watsonx.ai ML API in this region
+    pub fn api_url(&self) -> String {
+        format!("https://{}.ml.cloud.ibm.com", self.code())
+    }
+}
+
+/// Resolve the ML endpoint from `WATSONX_URL` and `WATSONX_REGION` values
+///
+/// An explicit URL wins over the region; with neither, us-south is used.
+fn resolve_endpoint(url: Option<String>, region: Option<String>) -> Result<String> {
+    if let Some(url) = url.filter(|url| !url.trim().is_empty()) {
+        return Ok(url.trim().trim_end_matches('/').to_string());
+    }
+
+    match region.filter(|region| !region.trim().is_empty()) {
+        Some(region) => WatsonxRegion::from_str(&region)
+            .map(|region| region.api_url())
+            .ok_or_else(|| Error::Configuration(format!("Unknown WATSONX_REGION: {}", region))),
+        None => Ok(WatsonxRegion::default().api_url()),
+    }
+}
+
+/// Reduce a configured IAM URL to the bare host the client expects
+///
+/// watsonx-rs builds `https://{iam_url}/identity/token` itself, so a scheme,
+/// trailing slash or the token path would otherwise be doubled. `None` for
+/// a blank value, leaving the client's default.
+fn normalize_iam_host(iam_url: &str) -> Option<String> {
+    let mut host = iam_url.trim();
+    for scheme in ["https://", "http://"] {
+        // `get` rather than slicing, since the cut may fall inside a multibyte character
+        if host.get(..scheme.len()).is_some_and(|prefix| prefix.eq_ignore_ascii_case(scheme)) {
+            host = &host[scheme.len()..];
+        }
+    }
+    let host = host.trim_end_matches('/');
+    let host = host.strip_suffix("/identity/token").unwrap_or(host).trim_end_matches('/');
+    (!host.is_empty()).then(|| host.to_string())
+}
+
+/// Credentials and endpoint needed to rebuild the client
+struct ClientSettings {
+    api_key: String,
+    project_id: String,
+    api_url: String,
+    /// IAM host from `WATSONX_IAM_URL`, normalized; the client default otherwise
+    iam_host: Option<String>,
+}
+
+/// Lifetime fields of an IAM token response
+#[derive(Debug, Deserialize)]
+struct IamToken {
+    /// Seconds the token is valid for
+    expires_in: Option<i64>,
+    /// Unix timestamp at which the token expires
+    expiration: Option<i64>,
+}
+
+/// Tracks when the access token held by the client expires
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TokenExpiry {
+    expires_at: i64,
+    /// Seconds a token lives, as last reported by IAM
+    lifetime: i64,
+}
+
+impl TokenExpiry {
+    /// Expiry for a token issued at the given unix timestamp
+    fn issued_at(timestamp: i64) -> Self {
+        Self {
+            expires_at: timestamp + TOKEN_LIFETIME_SECS,
+            lifetime: TOKEN_LIFETIME_SECS,
+        }
+    }
+
+    /// Expiry of the token in an IAM response received at `now`
+    ///
+    /// `expiration` wins over `expires_in`; with neither, the token is
+    /// assumed to live `TOKEN_LIFETIME_SECS`.
+    fn from_iam_token(token: &IamToken, now: i64) -> Self {
+        let lifetime = token
+            .expiration
+            .map(|expiration| expiration - now)
+            .or(token.expires_in)
+            .filter(|secs| *secs > 0)
+            .unwrap_or(TOKEN_LIFETIME_SECS);
+        Self {
+            expires_at: now + lifetime,
+            lifetime,
+        }
+    }
+
+    /// Expiry of a token issued at `now` with the same lifetime as this one
+    fn renewed_at(&self, now: i64) -> Self {
+        Self {
+            expires_at: now + self.lifetime,
+            lifetime: self.lifetime,
+        }
+    }
+
+    /// Whether the token expires within the refresh margin of `now`
+    fn needs_refresh(&self, now: i64) -> bool {
+        now + TOKEN_REFRESH_MARGIN_SECS >= self.expires_at
+    }
+}
+
 /// Thin wrapper around watsonx-rs client to implement LLMProvider
 pub struct WatsonxAdapter {
-    client: WatsonxClient,
+    client: RwLock<WatsonxClient>,
+    settings: Option<ClientSettings>,
+    token_expiry: Mutex<TokenExpiry>,
 }
 
 impl WatsonxAdapter {
     pub fn new(client: WatsonxClient) -> Self {
-        Self { client }
+        Self {
+            client: RwLock::new(client),
+            settings: None,
+            token_expiry: Mutex::new(TokenExpiry::issued_at(Utc::now().timestamp())),
+        }
+    }
+
+    /// Create an adapter that can rebuild its client when the token expires
+    pub fn from_credentials(api_key: String, project_id: String) -> Result<Self> {
+        Self::with_endpoint(api_key, project_id, WatsonxRegion::default().api_url(), None)
+    }
+
+    /// Like `from_credentials`, but against the ML API at `api_url`, and
+    /// getting tokens from `iam_url` when given
+    pub fn with_endpoint(api_key: String, project_id: String, api_url: String, iam_url: Option<String>) -> Result<Self> {
+        let iam_host = iam_url.as_deref().and_then(normalize_iam_host);
+        let settings = ClientSettings { api_key, project_id, api_url, iam_host };
+        let client = build_client(&settings)?;
+        let mut adapter = Self::new(client);
+        adapter.settings = Some(settings);
+        Ok(adapter)
+    }
+
+    /// Rebuild the client with a fresh token if the current one is about to expire
+    async fn refresh_if_expiring(&self) -> Result<()> {
+        let Some(ref settings) = self.settings else {
+            return Ok(());
+        };
+
+        let now = Utc::now().timestamp();
+        let expiry = *self.token_expiry.lock()
+            .map_err(|e| Error::LLMProvider(format!("Lock error: {}", e)))?;
+        if !expiry.needs_refresh(now) {
+            return Ok(());
+        }
+
+        let client = build_client(settings)?;
+        *self.client.write().await = client;
+        *self.token_expiry.lock()
+            .map_err(|e| Error::LLMProvider(format!("Lock error: {}", e)))? = expiry.renewed_at(now);
+        Ok(())
     }
 }
 
@@ -26,7 +228,15 @@ impl WatsonxAdapter {
 #[async_trait]
 impl LLMProvider for WatsonxAdapter {
     async fn connect(&mut self) -> Result<()> {
-        // watsonx-rs handles connection internally when calling generate methods
+        // watsonx-rs authenticates lazily, so the API key is checked against
+        // IAM here, which also tells how long tokens live
+        if let Some(ref settings) = self.settings {
+            let token = fetch_iam_token(settings).await?;
+            *self.client.get_mut() = build_client(settings)?;
+            *self.token_expiry.get_mut()
+                .map_err(|e| Error::LLMProvider(format!("Lock error: {}", e)))? =
+                TokenExpiry::from_iam_token(&token, Utc::now().timestamp());
+        }
         Ok(())
     }
 
@@ -47,10 +257,13 @@ impl LLMProvider for WatsonxAdapter {
             .with_top_k(config.top_k.unwrap_or(50))
             .with_stop_sequences(config.stop_sequences.clone());
 
+        self.refresh_if_expiring().await?;
+
         let generation_future: std::pin::Pin<Box<dyn std::future::Future<Output = Result<String>> + Send>> = Box::pin(async {
             // Use generate_text_stream as per user requirement
             // Note: generate_text_stream requires a callback for streaming, using generate_text for now
-            let watx_result = self.client.generate_text(prompt, &watx_config).await
+            let client = self.client.read().await;
+            let watx_result = client.generate_text(prompt, &watx_config).await
                 .map_err(|e| Error::LLMProvider(format!("WatsonX generation failed: {}", e)))?;
             Ok::<String, Error>(watx_result.text)
         });
@@ -60,30 +273,8 @@ impl LLMProvider for WatsonxAdapter {
             Err(_) => return Err(Error::Timeout("Request timed out".to_string())),
         };
 
-        // Clean up the response
-        let mut cleaned_answer = text.trim().to_string();
-
-        if cleaned_answer.starts_with("Answer:") {
-            cleaned_answer = cleaned_answer
-                .strip_prefix("Answer:")
-                .unwrap_or(&cleaned_answer)
-                .trim()
-                .to_string();
-        }
-
-        if let Some(query_pos) = cleaned_answer.find("Query:") {
-            cleaned_answer = cleaned_answer[..query_pos].trim().to_string();
-        }
-
-        let final_answer = cleaned_answer
-            .lines()
-            .next()
-            .unwrap_or(&cleaned_answer)
-            .trim()
-            .to_string();
-
         Ok(GenerationResult {
-            text: final_answer,
+            text: response_text(&text, config),
             model_id: config.model_id.clone(),
             tokens_used: None,
             quality_score: None,
@@ -98,48 +289,20 @@ impl LLMProvider for WatsonxAdapter {
         retry_config: Option<RetryConfig>,
     ) -> Result<GenerationAttempt> {
         let retry_cfg = retry_config.unwrap_or_default();
-        let mut best_attempt: Option<GenerationAttempt> = None;
-
-        for attempt in 1..=retry_cfg.max_attempts {
-            let enhanced_prompt = enhance_prompt_with_feedback(
-                base_prompt,
-                previous_failures,
-                attempt,
-            );
-
-            let timeout_duration = retry_cfg.base_timeout + Duration::from_secs((attempt - 1) as u64 * 10);
-
-            let mut attempt_config = config.clone();
-            attempt_config.timeout = timeout_duration;
-
-            match self.generate_with_config(&enhanced_prompt, &attempt_config).await {
-                Ok(result) => {
-                    let quality_score = assess_quality(&result.text, base_prompt);
-
-                    let current_attempt = GenerationAttempt {
-                        prompt: enhanced_prompt,
-                        result: result.text.clone(),
-                        quality_score,
-                        attempt_number: attempt,
-                    };
-
-                    if quality_score >= retry_cfg.quality_threshold {
-                        return Ok(current_attempt);
-                    }
-
-                    if best_attempt.as_ref().map_or(true, |best| quality_score > best.quality_score) {
-                        best_attempt = Some(current_attempt);
-                    }
-                }
-                Err(e) => {
-                    if attempt == retry_cfg.max_attempts {
-                        return Err(e);
-                    }
-                }
-            }
-        }
+        generate_with_retries(self, base_prompt, config, previous_failures, &retry_cfg, &mut Vec::new()).await
+    }
 
-        best_attempt.ok_or_else(|| Error::LLMProvider("All generation attempts failed".to_string()))
+    async fn generate_with_feedback_traced(
+        &self,
+        base_prompt: &str,
+        config: &GenerationConfig,
+        previous_failures: &[String],
+        retry_config: Option<RetryConfig>,
+    ) -> Result<(GenerationAttempt, Vec<GenerationAttempt>)> {
+        let retry_cfg = retry_config.unwrap_or_default();
+        let mut attempts = Vec::new();
+        let best = generate_with_retries(self, base_prompt, config, previous_failures, &retry_cfg, &mut attempts).await?;
+        Ok((best, attempts))
     }
 
     async fn generate_stream(
@@ -161,6 +324,242 @@ impl LLMProvider for WatsonxAdapter {
     }
 }
 
+/// Turn raw model output into a single command line
+///
+/// Applies `trim_response` and then `extract_command`, exactly as every
+/// generation does.
+pub(crate) fn clean_response(text: &str, stop_sequences: &[String]) -> String {
+    extract_command(&trim_response(text, stop_sequences))
+}
+
+/// The text a generation returns for raw model output
+///
+/// Raw generations are only trimmed; all others are cleaned to one command.
+fn response_text(text: &str, config: &GenerationConfig) -> String {
+    if config.raw {
+        text.trim().to_string()
+    } else {
+        clean_response(text, &config.stop_sequences)
+    }
+}
+
+/// Words models commonly put in front of a stop marker, e.g. "User Query:"
+const STOP_MARKER_PREFIXES: &[&str] = &["user", "human", "next", "new", "another"];
+
+/// Trim model output at the earliest stop sequence and drop trailing explanations
+///
+/// Stop sequences are matched case-insensitively and tolerate variants such as
+/// `Query :` or `User Query:`. They only match at the start of a line or after
+/// whitespace, so a marker inside a token such as `/Query:/` is kept as part of
+/// the command. A leading `Answer:` is removed. When a paragraph
+/// contains a CLI command, any paragraphs after it are treated as explanation.
+fn trim_response(text: &str, stop_sequences: &[String]) -> String {
+    let mut text = text.trim();
+
+    if text.get(..7).is_some_and(|prefix| prefix.eq_ignore_ascii_case("answer:")) {
+        text = text[7..].trim_start();
+    }
+
+    let prefixes = STOP_MARKER_PREFIXES.join("|");
+    let mut cut = text.len();
+    for stop in stop_sequences {
+        let word = stop.trim().trim_end_matches(':').trim();
+        if word.is_empty() {
+            continue;
+        }
+
+        let pattern = format!(r"(?i)(?:^|\s)((?:(?:{})\s+)?{}\s*:)", prefixes, regex::escape(word));
+        if let Ok(stop_regex) = Regex::new(&pattern) {
+            // A marker at the very start carries no command before it, so skip it
+            let found = stop_regex
+                .captures_iter(text)
+                .filter_map(|captures| captures.get(1))
+                .find(|marker| marker.start() > 0);
+            if let Some(marker) = found {
+                cut = cut.min(marker.start());
+            }
+        }
+    }
+    let text = text[..cut].trim();
+
+    let paragraphs: Vec<&str> = text
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|paragraph| !paragraph.is_empty())
+        .collect();
+    let cli_commands: Vec<&str> = CloudProviderType::all()
+        .iter()
+        .map(|provider| provider.cli_command())
+        .collect();
+    let command_paragraph = paragraphs.iter().position(|paragraph| {
+        paragraph.lines().any(|line| {
+            let first = strip_line_prefix(line).split_whitespace().next().unwrap_or("");
+            cli_commands.contains(&first)
+        })
+    });
+
+    match command_paragraph {
+        Some(last) => paragraphs[..=last].join("\n\n"),
+        None => text.to_string(),
+    }
+}
+
+/// Extract a single-line command from raw model output
+///
+/// Strips markdown code fences, `$ ` prompts and list numbering, joins
+/// backslash-continued lines, and prefers the first line that starts with a
+/// known cloud CLI. Falls back to the first non-empty line.
+fn extract_command(text: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut continued: Option<String> = None;
+
+    for raw_line in text.lines() {
+        let line = raw_line.trim();
+        if line.starts_with("```") {
+            continue;
+        }
+
+        let line = strip_line_prefix(line);
+        let line = match continued.take() {
+            Some(previous) => format!("{} {}", previous, line.trim_start()),
+            None => line.to_string(),
+        };
+
+        if let Some(head) = line.strip_suffix('\\') {
+            continued = Some(head.trim_end().to_string());
+            continue;
+        }
+
+        if !line.trim().is_empty() {
+            lines.push(line.trim().to_string());
+        }
+    }
+
+    if let Some(rest) = continued {
+        lines.push(rest);
+    }
+
+    let cli_commands: Vec<&str> = CloudProviderType::all()
+        .iter()
+        .map(|provider| provider.cli_command())
+        .collect();
+
+    lines
+        .iter()
+        .find(|line| {
+            let first = line.split_whitespace().next().unwrap_or("");
+            cli_commands.contains(&first)
+        })
+        .or_else(|| lines.first())
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Remove shell prompts, list markers and inline-code backticks from a line
+fn strip_line_prefix(line: &str) -> &str {
+    let mut line = line.trim();
+
+    // Numbered ("1." / "1)") and bulleted ("-" / "*") list markers
+    let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits > 0 {
+        let rest = &line[digits..];
+        if let Some(rest) = rest.strip_prefix('.').or_else(|| rest.strip_prefix(')')) {
+            line = rest.trim_start();
+        }
+    } else if let Some(rest) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+        line = rest.trim_start();
+    }
+
+    if let Some(rest) = line.strip_prefix("$ ").or_else(|| line.strip_prefix("> ")) {
+        line = rest.trim_start();
+    }
+
+    line.trim_matches('`')
+}
+
+/// Run generation attempts with progressive prompts until one meets the quality threshold
+///
+/// Stops early once `max_total_duration` is spent and returns the best attempt so far.
+/// Every scored attempt is appended to `attempts` in order; requests that
+/// error out are not.
+async fn generate_with_retries<L: LLMProvider + ?Sized>(
+    llm: &L,
+    base_prompt: &str,
+    config: &GenerationConfig,
+    previous_failures: &[String],
+    retry_cfg: &RetryConfig,
+    attempts: &mut Vec<GenerationAttempt>,
+) -> Result<GenerationAttempt> {
+    let started = Instant::now();
+    let mut best_attempt: Option<GenerationAttempt> = None;
+    let mut last_error: Option<Error> = None;
+
+    for attempt in 1..=retry_cfg.max_attempts {
+        let Some(remaining) = retry_cfg.max_total_duration.checked_sub(started.elapsed()) else {
+            break;
+        };
+        if remaining.is_zero() {
+            break;
+        }
+
+        let enhanced_prompt = enhance_prompt_with_feedback(
+            base_prompt,
+            previous_failures,
+            attempt,
+        );
+
+        let timeout_duration = retry_cfg.base_timeout
+            + Duration::from_secs((attempt - 1) as u64 * 10)
+            + retry_jitter(retry_cfg.max_jitter);
+
+        let mut attempt_config = config.clone();
+        attempt_config.timeout = timeout_duration.min(remaining);
+
+        // The overall budget is enforced here as well, in case the provider ignores the config timeout
+        let outcome = match timeout(remaining, llm.generate_with_config(&enhanced_prompt, &attempt_config)).await {
+            Ok(outcome) => outcome,
+            Err(_) => Err(Error::Timeout("Retry time budget exhausted".to_string())),
+        };
+
+        match outcome {
+            Ok(result) => {
+                let quality_score = assess_quality(&result.text, base_prompt);
+
+                let current_attempt = GenerationAttempt {
+                    prompt: enhanced_prompt,
+                    result: result.text.clone(),
+                    quality_score,
+                    attempt_number: attempt,
+                };
+                attempts.push(current_attempt.clone());
+
+                if quality_score >= retry_cfg.quality_threshold {
+                    return Ok(current_attempt);
+                }
+
+                if best_attempt.as_ref().is_none_or(|best| quality_score > best.quality_score) {
+                    best_attempt = Some(current_attempt);
+                }
+            }
+            Err(e) => last_error = Some(e),
+        }
+    }
+
+    best_attempt.ok_or_else(|| {
+        last_error.unwrap_or_else(|| Error::LLMProvider("All generation attempts failed".to_string()))
+    })
+}
+
+/// Random jitter in `[0, max)` so concurrent retries don't line up
+fn retry_jitter(max: Duration) -> Duration {
+    let max_millis = max.as_millis() as u64;
+    if max_millis == 0 {
+        return Duration::ZERO;
+    }
+    let random = RandomState::new().build_hasher().finish();
+    Duration::from_millis(random % max_millis)
+}
+
 /// Enhance prompt with feedback from previous failures
 fn enhance_prompt_with_feedback(
     base_prompt: &str,
@@ -208,8 +607,8 @@ fn assess_quality(text: &str, _prompt: &str) -> f32 {
 
     // Check if result starts with a valid cloud CLI command
     max_score += 0.3;
-    let cli_commands = ["ibmcloud", "aws", "gcloud", "az", "govc"];
-    if cli_commands.iter().any(|cmd| text.trim().starts_with(cmd)) {
+    let first = text.split_whitespace().next().unwrap_or("");
+    if CloudProviderType::all().iter().any(|provider| provider.cli_command() == first) {
         score += 0.3;
     }
 
@@ -248,10 +647,41 @@ fn assess_quality(text: &str, _prompt: &str) -> f32 {
     }
 }
 
+/// Check whether WatsonX credentials are present in the environment
+pub fn has_watsonx_credentials() -> bool {
+    let has_key = env::var("WATSONX_API_KEY").or_else(|_| env::var("API_KEY")).is_ok();
+    let has_project = env::var("WATSONX_PROJECT_ID").or_else(|_| env::var("PROJECT_ID")).is_ok();
+    has_key && has_project
+}
+
 /// Create WatsonX adapter from environment variables
 pub fn create_watsonx_client() -> Result<WatsonxAdapter> {
     dotenvy::dotenv().ok();
 
+    let (api_key, project_id) = watsonx_credentials()?;
+    let api_url = resolve_endpoint(env::var("WATSONX_URL").ok(), env::var("WATSONX_REGION").ok())?;
+
+    WatsonxAdapter::with_endpoint(api_key, project_id, api_url, env::var("WATSONX_IAM_URL").ok())
+}
+
+/// Create a second adapter from `WATSONX_FALLBACK_URL` or `WATSONX_FALLBACK_REGION`
+///
+/// Uses the same credentials as the primary client. Returns `None` when
+/// no fallback endpoint is configured.
+pub fn create_watsonx_fallback_client() -> Result<Option<WatsonxAdapter>> {
+    let url = env::var("WATSONX_FALLBACK_URL").ok().filter(|url| !url.trim().is_empty());
+    let region = env::var("WATSONX_FALLBACK_REGION").ok().filter(|region| !region.trim().is_empty());
+    if url.is_none() && region.is_none() {
+        return Ok(None);
+    }
+
+    let (api_key, project_id) = watsonx_credentials()?;
+    let api_url = resolve_endpoint(url, region)?;
+    WatsonxAdapter::with_endpoint(api_key, project_id, api_url, env::var("WATSONX_IAM_URL").ok()).map(Some)
+}
+
+/// API key and project ID from the environment
+fn watsonx_credentials() -> Result<(String, String)> {
     let api_key = env::var("WATSONX_API_KEY")
         .or_else(|_| env::var("API_KEY"))
         .map_err(|_| Error::Configuration(
@@ -264,9 +694,338 @@ pub fn create_watsonx_client() -> Result<WatsonxAdapter> {
             "WATSONX_PROJECT_ID or PROJECT_ID environment variable not found".to_string()
         ))?;
 
-    let config = WatsonxConfig::new(api_key, project_id);
-    let client = WatsonxClient::new(config)
-        .map_err(|e| Error::Configuration(format!("Failed to create WatsonX client: {}", e)))?;
-    Ok(WatsonxAdapter::new(client))
+    Ok((api_key, project_id))
+}
+
+/// Token endpoint of the IAM host in `settings`
+fn iam_token_url(settings: &ClientSettings) -> String {
+    let host = settings.iam_host.as_deref().unwrap_or(DEFAULT_IAM_HOST);
+    format!("https://{}/identity/token", host)
+}
+
+/// Exchange the API key for an IAM token, failing if IAM is unreachable or
+/// rejects the key
+async fn fetch_iam_token(settings: &ClientSettings) -> Result<IamToken> {
+    let response = reqwest::Client::new()
+        .post(iam_token_url(settings))
+        .form(&[
+            ("grant_type", "urn:ibm:params:oauth:grant-type:apikey"),
+            ("apikey", settings.api_key.as_str()),
+        ])
+        .send()
+        .await
+        .map_err(|e| Error::Network(format!("Cannot reach IBM Cloud IAM: {}", e)))?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(Error::Authentication(format!("IBM Cloud IAM rejected the API key ({})", status)));
+    }
+    response
+        .json()
+        .await
+        .map_err(|e| Error::Serialization(format!("Unreadable IAM token response: {}", e)))
+}
+
+/// Build a watsonx-rs client from credentials
+fn build_client(settings: &ClientSettings) -> Result<WatsonxClient> {
+    let mut config = WatsonxConfig::new(settings.api_key.clone(), settings.project_id.clone());
+    config.api_url = settings.api_url.clone();
+    if let Some(ref iam_host) = settings.iam_host {
+        config.iam_url = iam_host.clone();
+    }
+    WatsonxClient::new(config)
+        .map_err(|e| Error::Configuration(format!("Failed to create WatsonX client: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::test_support::MockLLM;
+
+    #[test]
+    fn test_region_urls() {
+        assert_eq!(WatsonxRegion::default().api_url(), "https://us-south.ml.cloud.ibm.com");
+        assert_eq!(WatsonxRegion::EuDe.api_url(), "https://eu-de.ml.cloud.ibm.com");
+        assert_eq!(WatsonxRegion::from_str("JP_TOK"), Some(WatsonxRegion::JpTok));
+        assert_eq!(WatsonxRegion::from_str("london"), Some(WatsonxRegion::EuGb));
+        assert_eq!(WatsonxRegion::from_str("mars-1"), None);
+    }
+
+    #[test]
+    fn test_resolve_endpoint_from_env_values() {
+        assert_eq!(resolve_endpoint(None, None).unwrap(), "https://us-south.ml.cloud.ibm.com");
+        assert_eq!(resolve_endpoint(None, Some("eu-gb".to_string())).unwrap(), "https://eu-gb.ml.cloud.ibm.com");
+        // An explicit URL wins over the region
+        assert_eq!(
+            resolve_endpoint(Some("https://private.example.com/".to_string()), Some("eu-de".to_string())).unwrap(),
+            "https://private.example.com"
+        );
+        assert_eq!(resolve_endpoint(Some(" ".to_string()), None).unwrap(), "https://us-south.ml.cloud.ibm.com");
+        assert!(matches!(resolve_endpoint(None, Some("mars-1".to_string())), Err(Error::Configuration(_))));
+    }
+
+    #[test]
+    fn test_fresh_token_does_not_need_refresh() {
+        let expiry = TokenExpiry::issued_at(1_000);
+        assert!(!expiry.needs_refresh(1_000));
+        assert!(!expiry.needs_refresh(1_000 + TOKEN_LIFETIME_SECS - TOKEN_REFRESH_MARGIN_SECS - 1));
+    }
+
+    #[test]
+    fn test_token_lifetime_comes_from_iam() {
+        let token: IamToken = serde_json::from_str(
+            r#"{"access_token":"x","token_type":"Bearer","expires_in":1200,"expiration":2200}"#,
+        )
+        .unwrap();
+        let expiry = TokenExpiry::from_iam_token(&token, 1_000);
+        assert_eq!(expiry.expires_at, 2_200);
+        assert_eq!(expiry.renewed_at(5_000).expires_at, 6_200);
+
+        let token: IamToken = serde_json::from_str(r#"{"access_token":"x","expires_in":1800}"#).unwrap();
+        assert_eq!(TokenExpiry::from_iam_token(&token, 1_000).expires_at, 2_800);
+
+        // Without either field the default lifetime is assumed
+        let token: IamToken = serde_json::from_str(r#"{"access_token":"x"}"#).unwrap();
+        assert_eq!(TokenExpiry::from_iam_token(&token, 1_000), TokenExpiry::issued_at(1_000));
+    }
+
+    #[test]
+    fn test_token_near_expiry_needs_refresh() {
+        let expiry = TokenExpiry::issued_at(1_000);
+        assert!(expiry.needs_refresh(1_000 + TOKEN_LIFETIME_SECS - TOKEN_REFRESH_MARGIN_SECS));
+        assert!(expiry.needs_refresh(1_000 + TOKEN_LIFETIME_SECS));
+    }
+
+    #[test]
+    fn test_extract_command_from_fenced_output() {
+        let output = "Here is the command:\n```bash\naws s3 ls\n```\nThis lists buckets.";
+        assert_eq!(extract_command(output), "aws s3 ls");
+    }
+
+    #[test]
+    fn test_extract_command_strips_prompt_and_numbering() {
+        assert_eq!(extract_command("$ ibmcloud resource groups"), "ibmcloud resource groups");
+        assert_eq!(
+            extract_command("1. gcloud compute instances list\n2. gcloud config list"),
+            "gcloud compute instances list"
+        );
+        assert_eq!(extract_command("`az group list`"), "az group list");
+    }
+
+    #[test]
+    fn test_extract_command_joins_backslash_continuation() {
+        let output = "```\naws ec2 describe-instances \\\n  --region us-east-1 \\\n  --output json\n```";
+        assert_eq!(
+            extract_command(output),
+            "aws ec2 describe-instances --region us-east-1 --output json"
+        );
+    }
+
+    #[test]
+    fn test_extract_command_plain_single_line() {
+        assert_eq!(extract_command("ibmcloud ks clusters"), "ibmcloud ks clusters");
+        assert_eq!(extract_command(""), "");
+    }
+
+    fn default_stops() -> Vec<String> {
+        GenerationConfig::default().stop_sequences
+    }
+
+    #[test]
+    fn test_trim_response_at_query_variants() {
+        let stops = default_stops();
+        assert_eq!(
+            trim_response("ibmcloud resource groups\nQuery: list apps", &stops),
+            "ibmcloud resource groups"
+        );
+        assert_eq!(
+            trim_response("ibmcloud resource groups\nQuery : list apps", &stops),
+            "ibmcloud resource groups"
+        );
+        assert_eq!(
+            trim_response("aws s3 ls\nUser Query: show buckets\nCommand: aws s3 ls", &stops),
+            "aws s3 ls"
+        );
+        assert_eq!(
+            trim_response("aws s3 ls  query: again", &stops),
+            "aws s3 ls"
+        );
+    }
+
+    #[test]
+    fn test_trim_response_ignores_stop_inside_token() {
+        let stops = default_stops();
+        let command = r#"aws logs start-query --log-group-name app --query-string "filter @message like /Query:/""#;
+        assert_eq!(trim_response(command, &stops), command);
+        assert_eq!(trim_response("az monitor log-analytics query --analytics-query:Perf", &stops), "az monitor log-analytics query --analytics-query:Perf");
+
+        // The same marker after whitespace still ends the command
+        assert_eq!(trim_response(&format!("{}\nQuery: next", command), &stops), command);
+    }
+
+    #[test]
+    fn test_trim_response_earliest_stop_wins() {
+        let stops = default_stops();
+        assert_eq!(
+            trim_response("gcloud compute instances list\nHuman: thanks\nAssistant: welcome\nQuery: x", &stops),
+            "gcloud compute instances list"
+        );
+    }
+
+    #[test]
+    fn test_trim_response_strips_answer_prefix_and_explanations() {
+        let stops = default_stops();
+        let output = "Answer: az vm list --output table\n\nThis command lists all virtual machines.\n\nYou can add --resource-group to filter.";
+        assert_eq!(trim_response(output, &stops), "az vm list --output table");
+    }
+
+    #[test]
+    fn test_trim_response_keeps_intro_before_fenced_command() {
+        let stops = default_stops();
+        let output = "Here is the command:\n\n```bash\naws s3 ls\n```\n\nIt lists buckets.";
+        let trimmed = trim_response(output, &stops);
+        assert_eq!(extract_command(&trimmed), "aws s3 ls");
+        assert!(!trimmed.contains("It lists buckets"));
+    }
+
+    #[test]
+    fn test_retry_jitter_is_bounded() {
+        assert_eq!(retry_jitter(Duration::ZERO), Duration::ZERO);
+        for _ in 0..20 {
+            assert!(retry_jitter(Duration::from_millis(50)) < Duration::from_millis(50));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retries_stop_when_total_budget_exceeded() {
+        // Low-quality answers force retries; each one takes 100ms
+        let llm = MockLLM::replying("no idea").with_delay(Duration::from_millis(100));
+        let retry_cfg = RetryConfig {
+            max_attempts: 10,
+            max_total_duration: Duration::from_millis(250),
+            max_jitter: Duration::ZERO,
+            ..Default::default()
+        };
+
+        let started = Instant::now();
+        let attempt = generate_with_retries(&llm, "list buckets", &GenerationConfig::default(), &[], &retry_cfg, &mut Vec::new())
+            .await
+            .unwrap();
+
+        assert!(started.elapsed() < Duration::from_millis(600));
+        assert!(attempt.attempt_number < 10);
+        assert_eq!(attempt.result, "no idea");
+    }
+
+    #[tokio::test]
+    async fn test_retries_return_first_good_attempt() {
+        let llm = MockLLM::replying("aws s3 ls");
+        let attempt = generate_with_retries(
+            &llm,
+            "list buckets",
+            &GenerationConfig::default(),
+            &[],
+            &RetryConfig::default(),
+            &mut Vec::new(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(attempt.attempt_number, 1);
+    }
+
+    #[tokio::test]
+    async fn test_retries_capture_every_attempt_in_order() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // Each answer scores better than the one before
+        let replies = ["no idea", "try the s3 tool", "aws s3 ls"];
+        let calls = AtomicUsize::new(0);
+        let llm = MockLLM::new(move |_| Ok(replies[calls.fetch_add(1, Ordering::SeqCst)].to_string()));
+        let retry_cfg = RetryConfig {
+            max_jitter: Duration::ZERO,
+            ..Default::default()
+        };
+
+        let mut attempts = Vec::new();
+        let best = generate_with_retries(&llm, "list buckets", &GenerationConfig::default(), &[], &retry_cfg, &mut attempts)
+            .await
+            .unwrap();
+
+        let results: Vec<&str> = attempts.iter().map(|attempt| attempt.result.as_str()).collect();
+        assert_eq!(results, replies);
+        let numbers: Vec<u32> = attempts.iter().map(|attempt| attempt.attempt_number).collect();
+        assert_eq!(numbers, vec![1, 2, 3]);
+        assert!(attempts[0].quality_score < attempts[1].quality_score);
+        assert!(attempts[1].quality_score < attempts[2].quality_score);
+        assert_eq!(best.result, "aws s3 ls");
+    }
+
+    #[test]
+    fn test_quality_recognizes_every_provider_cli() {
+        let aws = assess_quality("aws s3api list-buckets --output json", "list buckets");
+        assert_eq!(assess_quality("gh repo list --limit 10", "list repos"), aws);
+        assert_eq!(assess_quality("terraform state list -id=web", "list resources"), aws);
+        assert!(assess_quality("ghost repo list --limit 10", "list repos") < aws);
+    }
+
+    #[test]
+    fn test_expired_token_needs_refresh() {
+        let expiry = TokenExpiry::issued_at(1_000);
+        assert!(expiry.needs_refresh(1_000 + TOKEN_LIFETIME_SECS * 2));
+    }
+
+    #[test]
+    fn test_normalize_iam_host() {
+        for configured in [
+            "iam.cloud.ibm.com",
+            "https://iam.cloud.ibm.com",
+            "HTTPS://iam.cloud.ibm.com/",
+            "http://iam.cloud.ibm.com//",
+            " https://iam.cloud.ibm.com/identity/token ",
+        ] {
+            let host = normalize_iam_host(configured).unwrap();
+            assert_eq!(format!("https://{}/identity/token", host), "https://iam.cloud.ibm.com/identity/token", "{:?}", configured);
+        }
+        assert_eq!(normalize_iam_host("iam.test.cloud.ibm.com/").as_deref(), Some("iam.test.cloud.ibm.com"));
+        assert_eq!(normalize_iam_host(" "), None);
+        assert_eq!(normalize_iam_host("https://"), None);
+        // Hosts whose 7th or 8th byte falls inside a character, as long as the schemes
+        assert_eq!(normalize_iam_host("iam.téé.example.com").as_deref(), Some("iam.téé.example.com"));
+        assert_eq!(normalize_iam_host("iäm-tëst.example").as_deref(), Some("iäm-tëst.example"));
+        assert_eq!(normalize_iam_host("HTTPS://iäm-tëst.example/").as_deref(), Some("iäm-tëst.example"));
+    }
+
+    #[test]
+    fn test_iam_token_url() {
+        let settings = |iam_host: Option<&str>| ClientSettings {
+            api_key: "key".to_string(),
+            project_id: "project".to_string(),
+            api_url: WatsonxRegion::default().api_url(),
+            iam_host: iam_host.map(str::to_string),
+        };
+        assert_eq!(iam_token_url(&settings(None)), "https://iam.cloud.ibm.com/identity/token");
+        assert_eq!(iam_token_url(&settings(Some("iam.test.cloud.ibm.com"))), "https://iam.test.cloud.ibm.com/identity/token");
+    }
+
+    #[tokio::test]
+    async fn test_connect_fails_when_iam_is_unreachable() {
+        let mut adapter = WatsonxAdapter::with_endpoint(
+            "key".to_string(),
+            "project".to_string(),
+            WatsonxRegion::default().api_url(),
+            Some("127.0.0.1:1".to_string()),
+        )
+        .unwrap();
+        assert!(matches!(adapter.connect().await, Err(Error::Network(_))));
+    }
+
+    #[test]
+    fn test_raw_generation_skips_command_extraction() {
+        let output = "Problem: You are not logged in.\n\nFix:\n$ ibmcloud login\n$ ibmcloud target -g default";
+        let config = GenerationConfig::default();
+        assert_eq!(response_text(output, &config), "ibmcloud login");
+
+        let raw = GenerationConfig { raw: true, ..Default::default() };
+        assert_eq!(response_text(&format!("\n{}\n", output), &raw), output);
+    }
 }
 